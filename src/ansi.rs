@@ -0,0 +1,161 @@
+use std::borrow::Cow;
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Span;
+
+const ESC: char = '\x1b';
+
+enum Token<'a> {
+    Text(&'a str),
+    Sgr(&'a str),
+}
+
+/// Split a line into plain text runs and SGR parameter strings. Other escape
+/// sequences (cursor movement, OSC titles, ...) are dropped, as is an
+/// unterminated sequence at the end of a partial line.
+fn tokenize(line: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let bytes = line.as_bytes();
+    let mut text_start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] != ESC as u8 {
+            i += 1;
+            continue;
+        }
+        if i > text_start {
+            tokens.push(Token::Text(&line[text_start..i]));
+        }
+        i += 1;
+        match bytes.get(i) {
+            // CSI: ESC [ params intermediates final
+            Some(b'[') => {
+                i += 1;
+                let params_start = i;
+                while i < bytes.len() && !(0x40..=0x7e).contains(&bytes[i]) {
+                    i += 1;
+                }
+                if i < bytes.len() {
+                    if bytes[i] == b'm' {
+                        tokens.push(Token::Sgr(&line[params_start..i]));
+                    }
+                    i += 1;
+                }
+            }
+            // OSC: ESC ] ... terminated by BEL or ESC \
+            Some(b']') => {
+                i += 1;
+                while i < bytes.len() {
+                    if bytes[i] == 0x07 {
+                        i += 1;
+                        break;
+                    }
+                    if bytes[i] == ESC as u8 && bytes.get(i + 1) == Some(&b'\\') {
+                        i += 2;
+                        break;
+                    }
+                    i += 1;
+                }
+            }
+            // Two-character escape; skip the whole (possibly multi-byte) char
+            Some(_) => {
+                i += line[i..].chars().next().map_or(1, |c| c.len_utf8());
+            }
+            None => {}
+        }
+        text_start = i;
+    }
+    if text_start < bytes.len() {
+        tokens.push(Token::Text(&line[text_start..]));
+    }
+    tokens
+}
+
+/// Convert a line containing ANSI escape sequences into styled spans.
+pub fn spans(line: &str) -> Vec<Span<'_>> {
+    if !line.contains(ESC) {
+        return vec![Span::raw(line)];
+    }
+    let mut style = Style::default();
+    let mut spans = Vec::new();
+    for token in tokenize(line) {
+        match token {
+            Token::Text(text) => spans.push(Span::styled(text, style)),
+            Token::Sgr(params) => style = apply_sgr(style, params),
+        }
+    }
+    spans
+}
+
+/// Remove all escape sequences, leaving the text as it appears on screen.
+pub fn strip(line: &str) -> Cow<'_, str> {
+    if !line.contains(ESC) {
+        return Cow::Borrowed(line);
+    }
+    let mut out = String::with_capacity(line.len());
+    for token in tokenize(line) {
+        if let Token::Text(text) = token {
+            out.push_str(text);
+        }
+    }
+    Cow::Owned(out)
+}
+
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    let codes: Vec<u16> = params
+        .split([';', ':'])
+        .map(|p| p.parse().unwrap_or(0))
+        .collect();
+    let mut i = 0;
+    while i < codes.len() {
+        match codes[i] {
+            0 => style = Style::default(),
+            1 => style = style.add_modifier(Modifier::BOLD),
+            2 => style = style.add_modifier(Modifier::DIM),
+            3 => style = style.add_modifier(Modifier::ITALIC),
+            4 => style = style.add_modifier(Modifier::UNDERLINED),
+            5 | 6 => style = style.add_modifier(Modifier::SLOW_BLINK),
+            7 => style = style.add_modifier(Modifier::REVERSED),
+            8 => style = style.add_modifier(Modifier::HIDDEN),
+            9 => style = style.add_modifier(Modifier::CROSSED_OUT),
+            22 => style = style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style = style.remove_modifier(Modifier::ITALIC),
+            24 => style = style.remove_modifier(Modifier::UNDERLINED),
+            25 => style = style.remove_modifier(Modifier::SLOW_BLINK),
+            27 => style = style.remove_modifier(Modifier::REVERSED),
+            28 => style = style.remove_modifier(Modifier::HIDDEN),
+            29 => style = style.remove_modifier(Modifier::CROSSED_OUT),
+            n @ 30..=37 => style = style.fg(Color::Indexed((n - 30) as u8)),
+            n @ 90..=97 => style = style.fg(Color::Indexed((n - 90 + 8) as u8)),
+            n @ 40..=47 => style = style.bg(Color::Indexed((n - 40) as u8)),
+            n @ 100..=107 => style = style.bg(Color::Indexed((n - 100 + 8) as u8)),
+            39 => style.fg = None,
+            49 => style.bg = None,
+            n @ (38 | 48) => {
+                let (color, used) = extended_color(&codes[i + 1..]);
+                if let Some(color) = color {
+                    style = if n == 38 {
+                        style.fg(color)
+                    } else {
+                        style.bg(color)
+                    };
+                }
+                i += used;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    style
+}
+
+/// Parse the arguments of a 38/48 extended color: `5;n` or `2;r;g;b`.
+/// Returns the color and how many codes were consumed.
+fn extended_color(args: &[u16]) -> (Option<Color>, usize) {
+    match args {
+        [5, n, ..] => (Some(Color::Indexed(*n as u8)), 2),
+        [2, r, g, b, ..] => (Some(Color::Rgb(*r as u8, *g as u8, *b as u8)), 4),
+        _ => (None, args.len()),
+    }
+}
@@ -1,17 +1,46 @@
+use std::ops::Range;
+use std::path::PathBuf;
 use std::sync::mpsc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::Rect;
+use regex::Regex;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+
+use crate::checksum;
+use crate::config::{
+    BellSetting, Config, GridSetting, Profile, ProfileCommand, SplitSetting, StatsFormat,
+    ViewSetting,
+};
+use crate::control::{self, ControlRequest};
+use crate::diff::DiffLine;
+use crate::export::{self, ExportFormat, ExportRange, ExportSettings};
+use crate::hex::ArrayFormat;
+use crate::highlight::{Highlighter, LineFilter};
+use crate::i18n;
+use crate::keymap::{Action, Keymap};
+use crate::menu::{self, MenuCommand};
 use crate::message::Message;
-use crate::serial::{Connection, DisplayMode, SerialEvent};
+use crate::paths::{self, Completion};
+use crate::script::{Script, ScriptAction};
+use crate::serial::{
+    self, ArmedCapture, AuditLog, CaptureLimit, Connection, DisplayMode, FieldTable, Generator,
+    LineControl, LinkTest, NetAddress, Pacing, Pattern, PatternKind, Protocol, ReadSettings,
+    SerialEvent, SessionStats, ShareServer, TransferKind, AUTO_BAUD_RATES, DECODERS,
+};
+use crate::session::Session;
+use crate::theme::Theme;
+use crate::trigger::{TriggerRule, Triggers};
 
 pub const BAUD_RATES: &[u32] = &[
     300, 1200, 2400, 4800, 9600, 19200, 38400, 57600, 115200, 230400, 460800, 921600,
 ];
 
 pub const PARITY_OPTIONS: &[(&str, serialport::Parity)] = &[
-    ("None", serialport::Parity::None),
-    ("Odd", serialport::Parity::Odd),
-    ("Even", serialport::Parity::Even),
+    ("screen.parity_none", serialport::Parity::None),
+    ("screen.parity_odd", serialport::Parity::Odd),
+    ("screen.parity_even", serialport::Parity::Even),
 ];
 
 pub const DATA_BITS_OPTIONS: &[(&str, serialport::DataBits)] = &[
@@ -27,18 +56,27 @@ pub const STOP_BITS_OPTIONS: &[(&str, serialport::StopBits)] = &[
 ];
 
 pub const DISPLAY_MODE_OPTIONS: &[(&str, DisplayMode)] = &[
-    ("Text (UTF-8)", DisplayMode::Text),
-    ("Hex Dump", DisplayMode::HexDump),
+    ("screen.mode_text", DisplayMode::Text),
+    ("screen.mode_hex", DisplayMode::HexDump),
+];
+
+pub const PACING_OPTIONS: &[(&str, Pacing)] = &[
+    ("screen.pacing_none", Pacing::NONE),
+    ("screen.pacing_fast_pc", Pacing::FAST_PC),
+    ("screen.pacing_slow_mcu", Pacing::SLOW_MCU),
+    ("screen.pacing_paper_tape", Pacing::PAPER_TAPE),
 ];
 
 #[derive(Clone, Copy, PartialEq)]
 pub enum Screen {
+    ProfileSelect,
     PortSelect,
     BaudSelect,
     DataBitsSelect,
     ParitySelect,
     StopBitsSelect,
     DisplayModeSelect,
+    PacingSelect,
     Connected,
 }
 
@@ -46,6 +84,258 @@ pub enum Screen {
 pub enum ViewMode {
     Tabs,
     Grid,
+    Split,
+}
+
+impl ViewMode {
+    fn from_setting(setting: ViewSetting) -> Self {
+        match setting {
+            ViewSetting::Tabs => ViewMode::Tabs,
+            ViewSetting::Grid => ViewMode::Grid,
+            ViewSetting::Split => ViewMode::Split,
+        }
+    }
+
+    fn setting(self) -> ViewSetting {
+        match self {
+            ViewMode::Tabs => ViewSetting::Tabs,
+            ViewMode::Grid => ViewSetting::Grid,
+            ViewMode::Split => ViewSetting::Split,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SplitDirection {
+    /// Panes side by side
+    Horizontal,
+    /// Panes stacked
+    Vertical,
+}
+
+impl SplitDirection {
+    fn from_setting(setting: SplitSetting) -> Self {
+        match setting {
+            SplitSetting::Horizontal => SplitDirection::Horizontal,
+            SplitSetting::Vertical => SplitDirection::Vertical,
+        }
+    }
+
+    fn setting(self) -> SplitSetting {
+        match self {
+            SplitDirection::Horizontal => SplitSetting::Horizontal,
+            SplitDirection::Vertical => SplitSetting::Vertical,
+        }
+    }
+}
+
+/// How the grid view arranges its cells.
+#[derive(Clone, Copy, PartialEq)]
+pub enum GridShape {
+    /// Near-square: ceil(sqrt(n)) columns
+    Auto,
+    Columns(usize),
+    Rows(usize),
+}
+
+impl GridShape {
+    /// The choices offered by View → Grid Shape.
+    pub const ALL: [GridShape; 7] = [
+        GridShape::Auto,
+        GridShape::Columns(1),
+        GridShape::Columns(2),
+        GridShape::Columns(3),
+        GridShape::Rows(1),
+        GridShape::Rows(2),
+        GridShape::Rows(3),
+    ];
+
+    /// (columns, rows) for a grid of `total` cells.
+    pub fn dims(self, total: usize) -> (usize, usize) {
+        let cols = match self {
+            GridShape::Auto => (total as f64).sqrt().ceil() as usize,
+            GridShape::Columns(n) => n,
+            GridShape::Rows(n) => total.div_ceil(n),
+        };
+        // Never more columns than cells, so no column stays empty
+        let cols = cols.clamp(1, total.max(1));
+        (cols, total.div_ceil(cols))
+    }
+
+    fn from_setting(setting: Option<GridSetting>) -> Self {
+        match setting {
+            None => GridShape::Auto,
+            Some(GridSetting::Columns(n)) => GridShape::Columns(n.max(1) as usize),
+            Some(GridSetting::Rows(n)) => GridShape::Rows(n.max(1) as usize),
+        }
+    }
+
+    fn setting(self) -> Option<GridSetting> {
+        match self {
+            GridShape::Auto => None,
+            GridShape::Columns(n) => Some(GridSetting::Columns(n as u16)),
+            GridShape::Rows(n) => Some(GridSetting::Rows(n as u16)),
+        }
+    }
+}
+
+/// How often the UI ticks and how connections read their ports, from the
+/// config or Tools → Preferences.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Preferences {
+    pub tick_ms: u64,
+    pub read_buffer: usize,
+    pub read_timeout_ms: u64,
+}
+
+impl Preferences {
+    /// The values the Preferences dialog steps through; settings from the
+    /// config are kept within the first and last.
+    pub const TICK_MS: [u64; 7] = [10, 20, 50, 100, 250, 500, 1000];
+    pub const READ_BUFFERS: [usize; 7] = [256, 1024, 4096, 16384, 65536, 262144, 1048576];
+    pub const READ_TIMEOUT_MS: [u64; 7] = [1, 5, 10, 25, 50, 100, 250];
+    /// Rows of the dialog, one per setting
+    pub const ROWS: usize = 3;
+
+    fn from_settings(session: &Session, config: &Config) -> Self {
+        let defaults = ReadSettings::DEFAULT;
+        Self {
+            tick_ms: within(
+                &Self::TICK_MS,
+                session.tick_ms.or(config.tick_ms).unwrap_or(50),
+            ),
+            read_buffer: within(
+                &Self::READ_BUFFERS,
+                session
+                    .read_buffer_bytes
+                    .or(config.read_buffer_bytes)
+                    .unwrap_or(defaults.buffer),
+            ),
+            read_timeout_ms: within(
+                &Self::READ_TIMEOUT_MS,
+                session
+                    .read_timeout_ms
+                    .or(config.read_timeout_ms)
+                    .unwrap_or(defaults.timeout.as_millis() as u64),
+            ),
+        }
+    }
+
+    /// How often `App::tick()` runs.
+    pub fn tick(self) -> Duration {
+        Duration::from_millis(self.tick_ms)
+    }
+
+    /// What new connections read their ports with.
+    pub fn read(self) -> ReadSettings {
+        ReadSettings {
+            buffer: self.read_buffer,
+            timeout: Duration::from_millis(self.read_timeout_ms),
+        }
+    }
+
+    /// Move the setting in dialog row `row` to the next larger or smaller
+    /// choice.
+    fn step(&mut self, row: usize, up: bool) {
+        match row {
+            0 => self.tick_ms = step(&Self::TICK_MS, self.tick_ms, up),
+            1 => self.read_buffer = step(&Self::READ_BUFFERS, self.read_buffer, up),
+            _ => self.read_timeout_ms = step(&Self::READ_TIMEOUT_MS, self.read_timeout_ms, up),
+        }
+    }
+}
+
+/// Line settings being picked for an open connection in Connection → Line
+/// Setup.
+#[derive(Clone, Copy)]
+pub struct LineSetup {
+    pub baud_rate: u32,
+    pub data_bits: serialport::DataBits,
+    pub parity: serialport::Parity,
+    pub stop_bits: serialport::StopBits,
+}
+
+impl LineSetup {
+    /// Rows of the dialog, one per setting
+    pub const ROWS: usize = 4;
+
+    fn of(conn: &Connection) -> Self {
+        Self {
+            baud_rate: conn.baud_rate,
+            data_bits: conn.data_bits,
+            parity: conn.parity,
+            stop_bits: conn.stop_bits,
+        }
+    }
+
+    /// Move the setting in dialog row `row` to the next or previous choice.
+    /// A baud rate that isn't in the list steps to its neighbours.
+    fn step(&mut self, row: usize, up: bool) {
+        match row {
+            0 => self.baud_rate = step(BAUD_RATES, self.baud_rate, up),
+            1 => self.data_bits = step_option(DATA_BITS_OPTIONS, self.data_bits, up),
+            2 => self.parity = step_option(PARITY_OPTIONS, self.parity, up),
+            _ => self.stop_bits = step_option(STOP_BITS_OPTIONS, self.stop_bits, up),
+        }
+    }
+}
+
+/// The option after `value` in a wizard list, or before it with `!up`;
+/// `value` itself at either end.
+fn step_option<T: Copy + PartialEq>(options: &[(&str, T)], value: T, up: bool) -> T {
+    let idx = options.iter().position(|&(_, v)| v == value).unwrap_or(0);
+    let idx = if up {
+        (idx + 1).min(options.len() - 1)
+    } else {
+        idx.saturating_sub(1)
+    };
+    options[idx].1
+}
+
+/// The label of `value` in a wizard list.
+pub fn option_label<T: PartialEq>(options: &[(&'static str, T)], value: T) -> &'static str {
+    options
+        .iter()
+        .find(|(_, v)| *v == value)
+        .map_or("", |(label, _)| label)
+}
+
+/// Where the word before byte `pos` of `text` starts, skipping the
+/// non-word characters in between, like readline's Alt+B. Words are runs of
+/// letters and digits.
+fn word_start(text: &str, pos: usize) -> usize {
+    let mut chars = text[..pos].char_indices().rev().peekable();
+    while chars.next_if(|(_, c)| !c.is_alphanumeric()).is_some() {}
+    let mut start = chars.peek().map_or(0, |&(i, c)| i + c.len_utf8());
+    while let Some((i, _)) = chars.next_if(|(_, c)| c.is_alphanumeric()) {
+        start = i;
+    }
+    start
+}
+
+/// Where the word at or after byte `pos` of `text` ends, like readline's
+/// Alt+F.
+fn word_end(text: &str, pos: usize) -> usize {
+    let mut chars = text[pos..].char_indices().peekable();
+    while chars.next_if(|(_, c)| !c.is_alphanumeric()).is_some() {}
+    while chars.next_if(|(_, c)| c.is_alphanumeric()).is_some() {}
+    pos + chars.peek().map_or(text.len() - pos, |&(i, _)| i)
+}
+
+/// `value` clamped to the range of `choices`, which are in ascending order.
+fn within<T: Copy + Ord>(choices: &[T], value: T) -> T {
+    value.clamp(choices[0], choices[choices.len() - 1])
+}
+
+/// The choice after `value`, or before it with `!up`; `value` itself at
+/// either end.
+fn step<T: Copy + PartialOrd>(choices: &[T], value: T, up: bool) -> T {
+    let next = if up {
+        choices.iter().find(|&&c| c > value)
+    } else {
+        choices.iter().rev().find(|&&c| c < value)
+    };
+    next.copied().unwrap_or(value)
 }
 
 #[derive(Clone, Copy, PartialEq)]
@@ -53,6 +343,13 @@ pub enum OpenMenu {
     File,
     Connection,
     View,
+    Tools,
+    Help,
+    /// Right-click menu for the tab of connection `idx`, drawn at column `x`
+    Tab {
+        idx: usize,
+        x: u16,
+    },
 }
 
 #[derive(Clone, Copy, PartialEq)]
@@ -63,6 +360,7 @@ pub enum PendingScreen {
     ParitySelect,
     StopBitsSelect,
     DisplayModeSelect,
+    PacingSelect,
 }
 
 #[derive(Clone)]
@@ -73,8 +371,270 @@ pub enum Dialog {
         connection_idx: usize,
         filename: String,
         cursor_pos: usize,
+        action: FileAction,
         after: AfterSave,
     },
+    // Pick the folder to export a connection into, then its filename: the
+    // folder itself is row 0, its subfolders follow
+    FolderBrowser {
+        connection_idx: usize,
+        folder: PathBuf,
+        subfolders: Vec<String>,
+        selected: usize,
+    },
+    SearchPrompt {
+        query: String,
+        cursor_pos: usize,
+    },
+    FilterPrompt {
+        query: String,
+        cursor_pos: usize,
+    },
+    // Regex whose matches raise an alert on the active connection
+    WatchPrompt {
+        query: String,
+        cursor_pos: usize,
+    },
+    // Regex whose named groups fill the active connection's field table
+    FieldsPrompt {
+        query: String,
+        cursor_pos: usize,
+    },
+    // How long the link test on a connection sends, e.g. `10s`
+    LinkTestPrompt {
+        connection_idx: usize,
+        duration: String,
+        cursor_pos: usize,
+    },
+    // Note to mark the active connection's scrollback and live stream with
+    NotePrompt {
+        text: String,
+        cursor_pos: usize,
+    },
+    // Host and port for a network connection, from the port list
+    HostPrompt {
+        host: String,
+        cursor_pos: usize,
+    },
+    // A device path that isn't in the port list, e.g. a PTY
+    PathPrompt {
+        path: String,
+        cursor_pos: usize,
+    },
+    // Listen address for sharing the active connection over TCP
+    SharePrompt {
+        address: String,
+        cursor_pos: usize,
+    },
+    // Hex bytes to checksum, with the results shown live below
+    ChecksumPrompt {
+        query: String,
+        cursor_pos: usize,
+    },
+    CommandMenu {
+        connection_idx: usize,
+        commands: Vec<ProfileCommand>,
+        selected: usize,
+    },
+    ProtocolMenu {
+        connection_idx: usize,
+        upload: bool,
+        selected: usize,
+    },
+    // First launch without a config file: key bindings and an offer to
+    // write a starter config
+    Welcome,
+    // The device started a ZMODEM send on the connection with this id
+    ConfirmZmodemReceive {
+        connection_id: usize,
+    },
+    // Progress of a file transfer on the connection with this id
+    Transfer {
+        connection_id: usize,
+    },
+    // Pick how many of a connection's recent Send bar entries to resend
+    ResendMenu {
+        connection_idx: usize,
+        selected: usize,
+    },
+    // Pick where a connection's received data goes: (connection id, both ways)
+    // With `sniff`, the pick opens a sniffer tab on both instead
+    BridgeMenu {
+        connection_idx: usize,
+        targets: Vec<(usize, bool)>,
+        selected: usize,
+        sniff: bool,
+    },
+    // Copy a scrollback frame to the clipboard as source code
+    CopyAs {
+        bytes: Vec<u8>,
+        selected: usize,
+    },
+    // Pick the grid view's shape from `GridShape::ALL`
+    GridMenu {
+        selected: usize,
+    },
+    // Tick and read settings being edited, a row per setting
+    Preferences {
+        selected: usize,
+        prefs: Preferences,
+    },
+    // New line settings for an open connection, a row per setting
+    LineSetup {
+        connection_idx: usize,
+        selected: usize,
+        line: LineSetup,
+    },
+    // Pick the decoder for a connection's received data from `DECODERS`
+    DecoderMenu {
+        connection_idx: usize,
+        selected: usize,
+    },
+    // The trigger rules, then an entry for adding one
+    TriggerList {
+        selected: usize,
+    },
+    // One field of a trigger rule being added (`index` None) or edited; the
+    // fields are asked for in turn, and Esc goes back to the list
+    TriggerPrompt {
+        index: Option<usize>,
+        rule: TriggerRule,
+        field: TriggerField,
+        text: String,
+        cursor_pos: usize,
+    },
+    // One field of a capture being armed on a connection; the fields are
+    // asked for in turn
+    CapturePrompt {
+        connection_idx: usize,
+        capture: CaptureSettings,
+        field: CaptureField,
+        text: String,
+        cursor_pos: usize,
+    },
+    // Pick the test pattern to send on a connection
+    GeneratorMenu {
+        connection_idx: usize,
+        selected: usize,
+    },
+    // The bytes or count of a test pattern for a connection; the fields are
+    // asked for in turn
+    GeneratorPrompt {
+        connection_idx: usize,
+        generator: GeneratorSettings,
+        field: GeneratorField,
+        text: String,
+        cursor_pos: usize,
+    },
+    // Key bindings and mouse gestures, scrolled down this many lines
+    Help {
+        scroll: usize,
+    },
+    // ASCII codes, Ctrl chords and the bytes of special keys, scrolled down
+    // this many lines
+    AsciiTable {
+        scroll: usize,
+    },
+    // Pick what to compare a connection's scrollback with: the other
+    // connections (by id), then an entry for a saved log file
+    DiffMenu {
+        connection_idx: usize,
+        targets: Vec<usize>,
+        selected: usize,
+    },
+    // A unified diff from `old` to `new`, scrolled down this many lines
+    Diff {
+        old: String,
+        new: String,
+        lines: Vec<DiffLine>,
+        scroll: usize,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum TriggerField {
+    Pattern,
+    Send,
+    Delay,
+}
+
+/// An armed capture's fields as entered, checked once the last is in.
+#[derive(Clone, Default)]
+pub struct CaptureSettings {
+    pub start: String,
+    pub end: String,
+    pub limit: String,
+    pub file: String,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CaptureField {
+    Start,
+    End,
+    Limit,
+    File,
+}
+
+/// A test pattern's fields as entered, checked as each is taken.
+#[derive(Clone)]
+pub struct GeneratorSettings {
+    pub kind: PatternKind,
+    /// The constant byte or the frame, in hex
+    pub bytes: String,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum GeneratorField {
+    Bytes,
+    Count,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+pub struct StatusMessage {
+    pub text: String,
+    pub severity: Severity,
+    // When it stops being shown; None stays until dismissed
+    until: Option<Instant>,
+}
+
+#[derive(Clone)]
+pub struct SearchState {
+    pub query: String,
+    // (connection id, line number) of the match the view is currently on. The
+    // line number counts evicted lines too, so it stays put as the scrollback
+    // rolls over.
+    pub current: Option<(usize, usize)>,
+}
+
+impl SearchState {
+    /// Index of the current match in `conn`'s scrollback, unless it has been evicted.
+    pub fn current_line(&self, conn: &Connection) -> Option<usize> {
+        match self.current {
+            Some((id, line)) if id == conn.id => line.checked_sub(conn.evicted_lines()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FileAction {
+    Export(ExportSettings),
+    ExportStats,
+    LiveStream,
+    /// Compare a saved log with a connection's scrollback
+    Diff,
+    /// Play a recorded live stream back at this many times its speed
+    Replay(u32),
+    SendFile,
+    Upload(Protocol),
+    Download(Protocol),
 }
 
 #[derive(Clone)]
@@ -84,26 +644,161 @@ pub enum AfterSave {
     QuitNext { remaining: Vec<usize> },
 }
 
-// Menu bar layout constants — must match menu_bar.rs rendering
-pub const MENU_FILE_X: u16 = 1;
-pub const MENU_FILE_W: u16 = 6; // " File "
-pub const MENU_CONN_X: u16 = 7;
-pub const MENU_CONN_W: u16 = 12; // " Connection "
-pub const MENU_VIEW_X: u16 = 19;
-pub const MENU_VIEW_W: u16 = 6; // " View "
+/// Speeds Up/Down step through when choosing a recording to replay.
+const REPLAY_SPEEDS: [u32; 5] = [1, 2, 5, 10, 100];
+
+/// Function keys of the connected screen: key number, label and what the
+/// key does. The keys work whether or not `function_key_bar` shows them.
+const FUNCTION_KEYS: [(u8, &str, Message); 9] = [
+    (1, "fkey.help", Message::OpenHelp),
+    (2, "fkey.new", Message::NewConnection),
+    (3, "fkey.find", Message::OpenSearch),
+    (4, "fkey.filter", Message::OpenFilter),
+    (5, "fkey.export", Message::ExportScrollback),
+    (6, "fkey.view", Message::ToggleViewMode),
+    (7, "fkey.pause", Message::TogglePause),
+    (8, "fkey.close", Message::CloseConnection),
+    (10, "fkey.quit", Message::Quit),
+];
+
+/// Key numbers, labels and messages of the function keys, for the bar and
+/// the help. A `[keymap]` binding of a bare F-key takes its place.
+pub fn function_keys(keymap: &Keymap) -> impl Iterator<Item = (u8, &'static str, Message)> + '_ {
+    (1..=12).filter_map(|key| {
+        match keymap.action(KeyEvent::new(KeyCode::F(key), KeyModifiers::NONE)) {
+            Some(action) => Some((key, action.bar_label(), action.message())),
+            None => FUNCTION_KEYS.into_iter().find(|(k, _, _)| *k == key),
+        }
+    })
+}
+
+pub fn function_key(keymap: &Keymap, key: u8) -> Option<Message> {
+    function_keys(keymap)
+        .find(|(k, _, _)| *k == key)
+        .map(|(_, _, msg)| msg)
+}
+
+/// Function key bar segments (key number, "New ", columns) that fit in
+/// `width` columns, the key drawn as "F2" before the label. status_bar.rs
+/// renders from the same layout.
+pub fn function_key_bar(keymap: &Keymap, width: u16) -> Vec<(u8, String, Range<u16>)> {
+    let mut x = 0;
+    let mut segments = Vec::new();
+    for (key, label, _) in function_keys(keymap) {
+        let label = format!("{} ", i18n::t(label));
+        let end = x + (format!("F{}", key).len() + label.chars().count()) as u16;
+        if end > width {
+            break;
+        }
+        segments.push((key, label, x..end));
+        x = end;
+    }
+    segments
+}
 
+#[derive(Clone)]
 pub struct PortInfo {
     pub name: String,
     pub description: String,
+    // USB adapters only; the serial number tells identical ones apart
+    pub vid_pid: Option<(u16, u16)>,
+    pub manufacturer: Option<String>,
+    pub serial_number: Option<String>,
+}
+
+impl PortInfo {
+    /// VID:PID, manufacturer and serial number, for the port list's second
+    /// column; empty for anything but a USB adapter.
+    pub fn details(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some((vid, pid)) = self.vid_pid {
+            parts.push(format!("{:04x}:{:04x}", vid, pid));
+        }
+        if let Some(manufacturer) = &self.manufacturer {
+            parts.push(manufacturer.clone());
+        }
+        if let Some(serial_number) = &self.serial_number {
+            parts.push(format!("S/N {}", serial_number));
+        }
+        parts.join("  ")
+    }
+}
+
+/// The port list entry to keep selected when the list changes: a port, or
+/// the n-th entry after the ports ("Network…", "Device path…").
+enum PortSelection {
+    Port(String),
+    Entry(usize),
+}
+
+/// How well `port` matches a typed filter, ignoring case: 0 when its name
+/// contains the filter, 1 when its description or USB details do, 2 when the
+/// filter's characters appear in order across them all, and `None` otherwise.
+fn port_match(port: &PortInfo, filter: &str) -> Option<u8> {
+    let filter = filter.to_lowercase();
+    let name = port.name.to_lowercase();
+    let description = format!("{} {}", port.description, port.details()).to_lowercase();
+    if name.contains(&filter) {
+        return Some(0);
+    }
+    if description.contains(&filter) {
+        return Some(1);
+    }
+    let mut text = name.chars().chain(description.chars());
+    filter.chars().all(|c| text.any(|t| t == c)).then_some(2)
+}
+
+/// Settings of a connection being duplicated; the copy opens with them as
+/// soon as a port is picked.
+struct DuplicateSource {
+    port_name: String,
+    baud_rate: u32,
+    data_bits: serialport::DataBits,
+    parity: serialport::Parity,
+    stop_bits: serialport::StopBits,
+    display_mode: DisplayMode,
+    pacing: Pacing,
+    // Where the connection is shared; picking its own port connects there
+    shared_at: Option<std::net::SocketAddr>,
 }
 
 pub struct App {
     pub screen: Screen,
     pub should_quit: bool,
+    // Whether something shown has changed since the last frame; `run()` only
+    // draws then, and clears it
+    pub dirty: bool,
+    // A received BEL is to be rung on the terminal; `run()` writes it out
+    pub ring_bell: bool,
+    // The screen shows inverted for a received BEL until then
+    pub flash_until: Option<Instant>,
 
-    // Port selection
+    // User configuration (profiles, ...)
+    pub config: Config,
+
+    // Profile selection — availability is rechecked whenever ports are refreshed
+    pub selected_profile_index: usize,
+    pub profile_available: Vec<bool>,
+
+    // Port selection; the ports matching `port_filter`, best first, are
+    // followed by "Network…" and "Device path…"
+    pub detected_ports: Vec<PortInfo>,
     pub available_ports: Vec<PortInfo>,
+    pub port_filter: String,
     pub selected_port_index: usize,
+    // Listed first, by port name or USB serial number
+    pub favorite_ports: Vec<String>,
+    // Ports plugged in while a port list was open, marked "(new)"
+    pub new_ports: Vec<String>,
+    // When the ports were last scanned; the first scan finds nothing new
+    ports_checked: Option<Instant>,
+    // A background rescan is running; it answers with `PortsScanned`
+    scanning_ports: bool,
+    // Terminal server or device path typed in instead of picking a port; for
+    // raw TCP and telnet the wizard then skips the line settings
+    pub typed_target: Option<String>,
+    // Set while the port list picks the port for a duplicate
+    duplicating: Option<DuplicateSource>,
 
     // Baud selection
     pub selected_baud_index: usize,
@@ -119,18 +814,27 @@ pub struct App {
 
     // Display mode selection
     pub selected_display_mode_index: usize,
+    pub selected_pacing_index: usize,
 
     // Connections
     pub connections: Vec<Connection>,
     pub active_connection: usize,
     pub view_mode: ViewMode,
 
+    // Split view: active connection plus a pinned one (by connection id)
+    pub pinned_connection: Option<usize>,
+    pub split_direction: SplitDirection,
+    pub grid_shape: GridShape,
+    pub prefs: Preferences,
+
     // Input
     pub input_buffer: String,
+    // Byte offset of the Send bar's cursor in `input_buffer`
+    pub input_cursor: usize,
 
     // Serial channel
-    pub serial_tx: mpsc::Sender<SerialEvent>,
-    pub serial_rx: mpsc::Receiver<SerialEvent>,
+    pub serial_tx: UnboundedSender<SerialEvent>,
+    pub serial_rx: UnboundedReceiver<SerialEvent>,
 
     // ID counter
     next_connection_id: usize,
@@ -138,8 +842,8 @@ pub struct App {
     // Inline new-connection flow (shown as a tab/grid cell)
     pub pending_connection: Option<PendingScreen>,
 
-    // Status message (shown briefly in status bar)
-    pub status_message: Option<(String, Instant)>,
+    // Status message (shown briefly in status bar, errors until dismissed)
+    pub status_message: Option<StatusMessage>,
 
     // Menu
     pub open_menu: Option<OpenMenu>,
@@ -147,29 +851,253 @@ pub struct App {
     // Dialog
     pub dialog: Option<Dialog>,
 
-    // Terminal size (updated each frame for click calculations)
+    // Send bar interprets input as hex byte pairs instead of text
+    pub hex_input: bool,
+    // Send bar text goes out as typed, without `\n`, `\xNN`, ... escapes
+    pub literal_input: bool,
+
+    // Scrollback search (active after a query is confirmed, cleared with Esc)
+    pub search: Option<SearchState>,
+
+    // Selected scrollback line as (connection id, line number counting evicted
+    // lines), moved with Shift+Up/Down and cleared with Esc
+    line_cursor: Option<(usize, usize)>,
+
+    // Terminal size (updated each frame and on resize for click calculations)
     pub terminal_cols: u16,
     pub terminal_rows: u16,
+
+    // Height of the Send box in rows (including borders), adjustable by dragging
+    // its top border or with Ctrl+Up/Down; kept for the rest of the session
+    pub input_height: u16,
+    dragging_divider: bool,
+    // Scrollbar being dragged: the connection index and its scrollback's area
+    dragging_scrollbar: Option<(usize, Rect)>,
+
+    // Transmit audit log from the config, handed to every new connection
+    audit: Option<AuditLog>,
+
+    // Scrollback highlight rules from the config
+    pub highlighter: Highlighter,
+
+    // UI colors from the `theme` and `[colors]` config
+    pub theme: Theme,
+
+    // Key chords for commands, from the defaults and the `[keymap]` config
+    pub keymap: Keymap,
+
+    // `[stats] patterns`, counted on every new connection
+    stat_patterns: Vec<Regex>,
+
+    // Commands from control socket clients, when `control_port` is set
+    control_rx: Option<mpsc::Receiver<ControlRequest>>,
+
+    // State from the last run, updated and saved on the way out
+    session: Session,
+
+    // Hooks from the `script` config
+    script: Option<Script>,
+
+    // Rules answering received lines, and answers waiting out their delay
+    pub triggers: Triggers,
+    pending_responses: Vec<PendingResponse>,
+}
+
+/// A trigger response to send once `at` has passed.
+struct PendingResponse {
+    at: Instant,
+    id: usize,
+    data: Vec<u8>,
+}
+
+pub const MIN_INPUT_HEIGHT: u16 = 3;
+
+/// Sharing suggests this TCP port plus the connection id.
+const SHARE_BASE_PORT: u16 = 7000;
+
+/// How long the screen shows inverted for a received BEL.
+const BELL_FLASH: Duration = Duration::from_millis(150);
+
+/// How often a port list on screen is rescanned for plugged in adapters.
+const PORT_POLL: Duration = Duration::from_secs(1);
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl App {
     pub fn new() -> Self {
-        let (serial_tx, serial_rx) = mpsc::channel();
+        let (config, mut load_error) = match Config::load() {
+            Ok(config) => (config, None),
+            Err(e) => (Config::default(), Some(format!("{:#}", e))),
+        };
+        if let Err(e) = i18n::init(config.language.as_deref()) {
+            load_error = Some(e);
+        }
+        // Worded once the language is known
+        let mut config_error = load_error.map(|e| i18n::tf("status.config_error", &[&e]));
+        let triggers = Triggers::load().unwrap_or_else(|e| {
+            config_error = Some(i18n::tf("status.triggers", &[&format!("{:#}", e)]));
+            Triggers::default()
+        });
+        let session = if config.remembers_session() {
+            Session::load().unwrap_or_else(|e| {
+                config_error = Some(i18n::tf("status.session", &[&format!("{:#}", e)]));
+                Session::default()
+            })
+        } else {
+            Session::default()
+        };
+
+        let mut app = Self::from_parts(config, session, triggers, &mut config_error);
+        app.audit = app.config.audit_log.as_deref().and_then(|path| {
+            match AuditLog::open(path, app.config.log_durability()) {
+                Ok(audit) => Some(audit),
+                Err(e) => {
+                    config_error = Some(i18n::tf("status.audit_log_open", &[&path, &e]));
+                    None
+                }
+            }
+        });
+        app.script = app
+            .config
+            .script
+            .as_deref()
+            .and_then(|path| match Script::load(path) {
+                Ok(script) => Some(script),
+                Err(e) => {
+                    config_error = Some(format!("{:#}", e));
+                    None
+                }
+            });
+        app.control_rx = app.config.control_port.and_then(|port| {
+            let (tx, rx) = mpsc::channel();
+            match control::listen(port, tx) {
+                Ok(()) => Some(rx),
+                Err(e) => {
+                    config_error = Some(i18n::tf("status.control_port", &[&port, &e]));
+                    None
+                }
+            }
+        });
+        app.refresh_ports();
+        app.select_last_used();
+        if Config::is_missing() {
+            app.dialog = Some(Dialog::Welcome);
+        }
+        if let Some(err) = config_error {
+            app.set_status(Severity::Error, err);
+        }
+        app
+    }
+
+    /// An app running on `config` as given: unlike `new()` it reads no
+    /// files, opens no control port or audit log and doesn't scan the
+    /// ports, so tests and embedders can drive `update()` directly.
+    pub fn with_config(config: Config) -> Self {
+        let mut config_error = None;
+        let mut app = Self::from_parts(
+            config,
+            Session::default(),
+            Triggers::default(),
+            &mut config_error,
+        );
+        if let Some(err) = config_error {
+            app.set_status(Severity::Error, err);
+        }
+        app
+    }
+
+    /// The app state `config` and the loaded session and triggers make up,
+    /// before anything outside the process is touched. Problems with the
+    /// config go to `config_error`.
+    fn from_parts(
+        config: Config,
+        session: Session,
+        triggers: Triggers,
+        config_error: &mut Option<String>,
+    ) -> Self {
+        let (serial_tx, serial_rx) = tokio::sync::mpsc::unbounded_channel();
+
+        let highlighter = Highlighter::new(&config.highlights).unwrap_or_else(|e| {
+            *config_error = Some(i18n::tf("status.config_error", &[&e]));
+            Highlighter::default()
+        });
+        let theme = Theme::new(config.theme, &config.colors).unwrap_or_else(|e| {
+            *config_error = Some(i18n::tf("status.config_error", &[&e]));
+            Theme::DARK
+        });
+        let keymap = Keymap::new(&config.keymap).unwrap_or_else(|e| {
+            *config_error = Some(i18n::tf("status.config_error", &[&e]));
+            Keymap::default()
+        });
+        let stat_patterns = config
+            .stats
+            .patterns
+            .iter()
+            .filter_map(|pattern| match Regex::new(pattern) {
+                Ok(re) => Some(re),
+                Err(e) => {
+                    *config_error = Some(i18n::tf("status.stats_pattern", &[&e]));
+                    None
+                }
+            })
+            .collect();
+        let screen = if config.profiles.is_empty() {
+            Screen::PortSelect
+        } else {
+            Screen::ProfileSelect
+        };
+        let view_mode = ViewMode::from_setting(session.view.unwrap_or(config.default_view));
+        let split_direction = session
+            .split
+            .map_or(SplitDirection::Horizontal, SplitDirection::from_setting);
+        let grid_shape = GridShape::from_setting(session.grid);
+        let prefs = Preferences::from_settings(&session, &config);
+        let favorite_ports = session
+            .favorite_ports
+            .clone()
+            .unwrap_or_else(|| config.favorite_ports.clone());
+        let input_height = session
+            .input_height
+            .map_or(MIN_INPUT_HEIGHT, |h| h.max(MIN_INPUT_HEIGHT));
 
-        let mut app = Self {
-            screen: Screen::PortSelect,
+        Self {
+            screen,
             should_quit: false,
+            dirty: true,
+            ring_bell: false,
+            flash_until: None,
+            config,
+            selected_profile_index: 0,
+            profile_available: Vec::new(),
+            detected_ports: Vec::new(),
             available_ports: Vec::new(),
+            port_filter: String::new(),
             selected_port_index: 0,
+            favorite_ports,
+            new_ports: Vec::new(),
+            ports_checked: None,
+            scanning_ports: false,
+            typed_target: None,
+            duplicating: None,
             selected_baud_index: 4, // 9600 default
             selected_data_bits_index: 3, // Eight
             selected_parity_index: 0,    // None
             selected_stop_bits_index: 0, // One
             selected_display_mode_index: 0, // Text
+            selected_pacing_index: 0,       // None
             connections: Vec::new(),
             active_connection: 0,
-            view_mode: ViewMode::Tabs,
+            view_mode,
+            pinned_connection: None,
+            split_direction,
+            grid_shape,
+            prefs,
             input_buffer: String::new(),
+            input_cursor: 0,
             serial_tx,
             serial_rx,
             next_connection_id: 0,
@@ -177,67 +1105,848 @@ impl App {
             status_message: None,
             open_menu: None,
             dialog: None,
+            hex_input: false,
+            literal_input: false,
+            search: None,
+            line_cursor: None,
             terminal_cols: 80,
             terminal_rows: 24,
-        };
-        app.refresh_ports();
-        app
+            input_height,
+            dragging_divider: false,
+            dragging_scrollbar: None,
+            audit: None,
+            highlighter,
+            theme,
+            keymap,
+            stat_patterns,
+            control_rx: None,
+            session,
+            script: None,
+            triggers,
+            pending_responses: Vec::new(),
+        }
     }
 
-    pub fn refresh_ports(&mut self) {
-        self.available_ports = match serialport::available_ports() {
-            Ok(ports) => ports
-                .into_iter()
-                .map(|p| {
-                    let description = match &p.port_type {
-                        serialport::SerialPortType::UsbPort(info) => {
-                            info.product.clone().unwrap_or_else(|| "USB Serial".into())
-                        }
-                        serialport::SerialPortType::BluetoothPort => "Bluetooth".into(),
-                        serialport::SerialPortType::PciPort => "PCI".into(),
-                        serialport::SerialPortType::Unknown => String::new(),
-                    };
-                    PortInfo {
-                        name: p.port_name,
-                        description,
-                    }
-                })
-                .collect(),
-            Err(_) => Vec::new(),
-        };
-        if self.selected_port_index >= self.available_ports.len() {
-            self.selected_port_index = 0;
+    /// Start the profile and port lists on the ones used last time.
+    fn select_last_used(&mut self) {
+        if let Some(idx) = self
+            .session
+            .active_profile
+            .as_ref()
+            .and_then(|name| self.config.profiles.iter().position(|p| &p.name == name))
+        {
+            self.selected_profile_index = idx;
+        }
+        if let Some(idx) = self
+            .session
+            .active_port
+            .as_ref()
+            .and_then(|name| self.available_ports.iter().position(|p| &p.name == name))
+        {
+            self.selected_port_index = idx;
         }
     }
 
-    pub fn drain_serial_events(&mut self) {
-        while let Ok(event) = self.serial_rx.try_recv() {
-            match event {
-                SerialEvent::Data { id, data } => {
-                    if let Some(conn) = self.connection_by_id(id) {
-                        conn.push_data(&data);
-                    }
-                }
-                SerialEvent::Error { id, err } => {
+    /// Record the view and the active connection for the next run.
+    fn save_session(&mut self) -> Result<(), String> {
+        if !self.config.remembers_session() {
+            return Ok(());
+        }
+        self.session.view = Some(self.view_mode.setting());
+        self.session.split = Some(self.split_direction.setting());
+        self.session.grid = self.grid_shape.setting();
+        self.session.tick_ms = Some(self.prefs.tick_ms);
+        self.session.read_buffer_bytes = Some(self.prefs.read_buffer);
+        self.session.read_timeout_ms = Some(self.prefs.read_timeout_ms);
+        self.session.favorite_ports = Some(self.favorite_ports.clone());
+        self.session.input_height = Some(self.input_height);
+        if let Some(conn) = self.connections.get(self.active_connection) {
+            if !conn.read_only {
+                self.session.active_port = Some(conn.port_name.clone());
+                self.session.active_profile = conn.profile_name.clone();
+            }
+        }
+        self.session.save().map_err(|e| format!("{:#}", e))
+    }
+
+    /// Rescan the ports, keeping the selected entry selected unless a port
+    /// was plugged in, which then gets selected. Returns whether the ports
+    /// changed.
+    pub fn refresh_ports(&mut self) -> bool {
+        let ports = serialport::available_ports().unwrap_or_default();
+        self.ports_found(ports)
+    }
+
+    /// Take over the ports a scan found; see `refresh_ports()`.
+    fn ports_found(&mut self, ports: Vec<serialport::SerialPortInfo>) -> bool {
+        let selection = self.port_selection();
+        let previous: Vec<String> = self.detected_ports.iter().map(|p| p.name.clone()).collect();
+        self.detected_ports = ports
+            .into_iter()
+            .map(|p| {
+                let mut port = PortInfo {
+                    name: p.port_name,
+                    description: String::new(),
+                    vid_pid: None,
+                    manufacturer: None,
+                    serial_number: None,
+                };
+                match p.port_type {
+                    serialport::SerialPortType::UsbPort(info) => {
+                        port.description = info.product.unwrap_or_else(|| "USB Serial".into());
+                        port.vid_pid = Some((info.vid, info.pid));
+                        port.manufacturer = info.manufacturer;
+                        port.serial_number = info.serial_number;
+                    }
+                    serialport::SerialPortType::BluetoothPort => {
+                        port.description = "Bluetooth".into();
+                    }
+                    serialport::SerialPortType::PciPort => port.description = "PCI".into(),
+                    serialport::SerialPortType::Unknown => {}
+                }
+                port
+            })
+            .collect();
+        self.filter_ports();
+        let appeared: Vec<String> = self
+            .detected_ports
+            .iter()
+            .filter(|p| self.ports_checked.is_some() && !previous.contains(&p.name))
+            .map(|p| p.name.clone())
+            .collect();
+        let changed = !appeared.is_empty() || self.detected_ports.len() != previous.len();
+        match self
+            .available_ports
+            .iter()
+            .position(|p| appeared.contains(&p.name))
+        {
+            Some(idx) => self.selected_port_index = idx,
+            None => self.restore_port_selection(selection),
+        }
+        if !appeared.is_empty() {
+            self.new_ports = appeared;
+        }
+        self.new_ports
+            .retain(|name| self.detected_ports.iter().any(|p| &p.name == name));
+        self.ports_checked = Some(Instant::now());
+        self.check_profiles();
+        changed
+    }
+
+    /// Rescan the ports while a port or profile list is on screen, so
+    /// adapters show up (and profiles turn available) as they're plugged in.
+    /// The scan can take a while, so it runs on a blocking task and reports
+    /// back with `PortsScanned`.
+    fn poll_ports(&mut self) {
+        let listing = matches!(self.screen, Screen::PortSelect | Screen::ProfileSelect)
+            || self.pending_connection == Some(PendingScreen::PortSelect);
+        if listing
+            && !self.scanning_ports
+            && self
+                .ports_checked
+                .is_none_or(|checked| checked.elapsed() >= PORT_POLL)
+        {
+            self.scanning_ports = true;
+            let serial_tx = self.serial_tx.clone();
+            tokio::task::spawn_blocking(move || {
+                let ports = serialport::available_ports().unwrap_or_default();
+                let _ = serial_tx.send(SerialEvent::PortsScanned { ports });
+            });
+        }
+    }
+
+    fn port_selection(&self) -> PortSelection {
+        match self.available_ports.get(self.selected_port_index) {
+            Some(port) => PortSelection::Port(port.name.clone()),
+            None => PortSelection::Entry(self.selected_port_index - self.available_ports.len()),
+        }
+    }
+
+    /// Select `selection` again in a changed list, or the first entry if its
+    /// port is gone.
+    fn restore_port_selection(&mut self, selection: PortSelection) {
+        self.selected_port_index = match selection {
+            PortSelection::Port(name) => self
+                .available_ports
+                .iter()
+                .position(|p| p.name == name)
+                .unwrap_or(0),
+            PortSelection::Entry(n) => self.available_ports.len() + n,
+        };
+    }
+
+    /// Narrow the port list to the detected ports matching `port_filter`:
+    /// favorites first, in the order they were marked, then best matches
+    /// first.
+    fn filter_ports(&mut self) {
+        let mut matches: Vec<(usize, u8, &PortInfo)> = self
+            .detected_ports
+            .iter()
+            .filter_map(|p| {
+                let favorite = self.favorite_position(p).unwrap_or(usize::MAX);
+                port_match(p, &self.port_filter).map(|rank| (favorite, rank, p))
+            })
+            .collect();
+        matches.sort_by_key(|&(favorite, rank, _)| (favorite, rank));
+        self.available_ports = matches.into_iter().map(|(_, _, p)| p.clone()).collect();
+    }
+
+    /// Where `port` is among the favorites, by its name or USB serial number.
+    fn favorite_position(&self, port: &PortInfo) -> Option<usize> {
+        self.favorite_ports
+            .iter()
+            .position(|f| *f == port.name || port.serial_number.as_ref() == Some(f))
+    }
+
+    pub fn is_favorite(&self, port: &PortInfo) -> bool {
+        self.favorite_position(port).is_some()
+    }
+
+    /// Mark or unmark the selected port as a favorite. A USB adapter is
+    /// remembered by its serial number, so it stays a favorite on another
+    /// USB socket.
+    fn toggle_favorite_port(&mut self) {
+        let Some(port) = self.available_ports.get(self.selected_port_index).cloned() else {
+            return;
+        };
+        if self.is_favorite(&port) {
+            self.favorite_ports
+                .retain(|f| *f != port.name && port.serial_number.as_ref() != Some(f));
+            self.set_status(
+                Severity::Info,
+                i18n::tf("status.favorite_removed", &[&port.name]),
+            );
+        } else {
+            self.favorite_ports.push(
+                port.serial_number
+                    .clone()
+                    .unwrap_or_else(|| port.name.clone()),
+            );
+            self.set_status(
+                Severity::Info,
+                i18n::tf("status.favorite_added", &[&port.name]),
+            );
+        }
+        let selection = self.port_selection();
+        self.filter_ports();
+        self.restore_port_selection(selection);
+    }
+
+    /// Go on with the `n`-th favorite in the port list, as if it was picked.
+    fn pick_favorite_port(&mut self, n: usize) {
+        // Favorites come first
+        if self
+            .available_ports
+            .get(n)
+            .is_some_and(|p| self.is_favorite(p))
+        {
+            self.selected_port_index = n;
+            self.choose_port();
+        }
+    }
+
+    /// Type into the port filter; the best match gets selected.
+    fn edit_port_filter(&mut self, c: Option<char>) {
+        match c {
+            Some(c) => self.port_filter.push(c),
+            None => {
+                self.port_filter.pop();
+            }
+        }
+        self.filter_ports();
+        self.selected_port_index = 0;
+    }
+
+    /// Show all ports again, keeping the selected entry selected.
+    fn clear_port_filter(&mut self) {
+        if self.port_filter.is_empty() {
+            return;
+        }
+        let selection = self.port_selection();
+        self.port_filter.clear();
+        self.filter_ports();
+        self.restore_port_selection(selection);
+    }
+
+    /// Mark each saved profile as available if its port is currently present.
+    fn check_profiles(&mut self) {
+        self.profile_available = self
+            .config
+            .profiles
+            .iter()
+            .map(|p| {
+                // A terminal server can only be checked by connecting
+                self.detected_ports.iter().any(|port| port.name == p.port)
+                    || std::path::Path::new(&p.port).exists()
+                    || NetAddress::parse(&p.port).is_some()
+                    || serial::is_demo(&p.port)
+            })
+            .collect();
+    }
+
+    /// Handle `first` and the serial events already queued behind it.
+    pub fn drain_serial_events(&mut self, first: SerialEvent) {
+        // Scrolled-back views stay on the lines they show
+        let view_ends: Vec<(usize, usize)> = self
+            .connections
+            .iter()
+            .filter(|c| !c.follow)
+            .map(|c| (c.id, c.view_end()))
+            .collect();
+
+        let mut next = Some(first);
+        while let Some(event) = next {
+            // A rescan that found the same ports leaves the screen as it is
+            self.dirty |= !matches!(event, SerialEvent::PortsScanned { .. });
+            match event {
+                SerialEvent::Data { id, data, time } => {
+                    let mut zmodem_start = false;
+                    let mut bridge_to = None;
+                    let mut new_lines = Vec::new();
+                    let mut partial = String::new();
+                    let mut port = String::new();
+                    let mut bells = 0;
+                    let on_line = self.script.as_ref().is_some_and(|s| s.has_hook("on_line"));
+                    let wants_lines = on_line || !self.triggers.is_empty();
+                    if let Some(conn) = self.connection_by_id(id) {
+                        let before = conn.evicted_lines() + conn.scrollback.len();
+                        conn.receive(&data, time);
+                        bells = conn.take_bells();
+                        bridge_to = conn.bridge_to;
+                        // Hex rows aren't lines of text
+                        if (wants_lines || conn.watch.is_some() || conn.armed.is_some())
+                            && conn.display_mode == DisplayMode::Text
+                        {
+                            new_lines = conn
+                                .received_lines_since(before)
+                                .map(|line| crate::ansi::strip(line).into_owned())
+                                .collect();
+                            partial = crate::ansi::strip(conn.partial_line()).into_owned();
+                            port = conn.port_name.clone();
+                        }
+                        zmodem_start = !conn.read_only
+                            && !conn.dry_run
+                            && conn.busy.is_none()
+                            && conn.detect_zmodem_start(&data);
+                    }
+                    if zmodem_start && self.dialog.is_none() {
+                        self.dialog = Some(Dialog::ConfirmZmodemReceive { connection_id: id });
+                    }
+                    if bells > 0 {
+                        self.bell(id);
+                    }
+                    self.check_watch(id, &new_lines);
+                    self.check_armed(id, &new_lines);
+                    self.check_triggers(id, &new_lines, &partial);
+                    if on_line {
+                        for line in new_lines {
+                            self.run_script(|script| script.on_line(id, &port, &line));
+                        }
+                    }
+                    self.sniff(id, &data);
+                    if let Some(peer) = bridge_to.and_then(|peer| self.connection_by_id(peer)) {
+                        // Like share input, it waits for nothing: a transfer
+                        // or paced send owning the peer drops it
+                        if peer.alive && peer.can_send() && peer.busy.is_none() {
+                            peer.send(&data);
+                        }
+                    }
+                }
+                SerialEvent::Error { id, err } => {
                     if let Some(conn) = self.connection_by_id(id) {
                         conn.push_data(format!("\n[ERROR: {}]\n", err).as_bytes());
-                        conn.alive = false;
+                        conn.stats.errors += 1;
+                        conn.modem_lines = None;
+                        let was_alive = std::mem::replace(&mut conn.alive, false);
+                        let msg = format!("{}: {}", conn.port_name, err);
+                        self.alert(id, Severity::Error, msg);
+                        if was_alive {
+                            self.connection_ended(id);
+                        }
                     }
                 }
                 SerialEvent::Disconnected { id } => {
                     if let Some(conn) = self.connection_by_id(id) {
                         conn.push_data(b"\n[DISCONNECTED]\n");
-                        conn.alive = false;
+                        conn.modem_lines = None;
+                        let was_alive = std::mem::replace(&mut conn.alive, false);
+                        let msg = i18n::tf("status.disconnected", &[&conn.port_name]);
+                        self.alert(id, Severity::Warning, msg);
+                        if was_alive {
+                            self.connection_ended(id);
+                        }
+                    }
+                }
+                SerialEvent::Info { id, text } => match self.connection_by_id(id) {
+                    Some(conn) => conn.push_line(text),
+                    // e.g. a post_close hook of a connection that is gone
+                    None => self.set_status(Severity::Info, text),
+                },
+                SerialEvent::Progress { id, bytes, total } => {
+                    if let Some(conn) = self.connection_by_id(id) {
+                        conn.set_busy_bytes(bytes, total);
+                    }
+                }
+                SerialEvent::PowerCycleDone { id, result } => {
+                    let serial_tx = self.serial_tx.clone();
+                    if let Some(conn) = self.connection_by_id(id) {
+                        conn.end_busy();
+                        let (severity, msg) = match result {
+                            Ok(()) => (
+                                Severity::Success,
+                                i18n::tf("status.power_cycled", &[&conn.port_name]),
+                            ),
+                            Err(e) => (Severity::Error, e),
+                        };
+                        conn.push_line(format!("--- {} ---", msg));
+                        // Reopens only if the cycle released the port
+                        conn.reopen(serial_tx);
+                        self.alert(id, severity, msg);
+                    }
+                }
+                SerialEvent::LineSettingsDone { id, result } => {
+                    if let Some(conn) = self.connection_by_id(id) {
+                        let (severity, msg) = match result {
+                            Ok(line) => {
+                                conn.line_settings_applied(line);
+                                (
+                                    Severity::Info,
+                                    i18n::tf("status.line_settings_changed", &[&conn.label()]),
+                                )
+                            }
+                            Err(e) => (
+                                Severity::Error,
+                                i18n::tf("status.line_settings_failed", &[&e]),
+                            ),
+                        };
+                        conn.push_line(format!("--- {} ---", msg));
+                        self.alert(id, severity, msg);
+                    }
+                }
+                SerialEvent::PortsScanned { ports } => {
+                    self.scanning_ports = false;
+                    self.dirty |= self.ports_found(ports);
+                }
+                SerialEvent::ShareInput { id, data } => {
+                    if let Some(conn) = self.connection_by_id(id) {
+                        // A transfer or paced send owns the port meanwhile
+                        if conn.can_send() && conn.busy.is_none() {
+                            conn.send(&data);
+                        }
+                    }
+                }
+                SerialEvent::ModemLines { id, lines } => {
+                    if let Some(conn) = self.connection_by_id(id) {
+                        conn.modem_lines = Some(lines);
+                    }
+                }
+                SerialEvent::TransferDone { id, result } => {
+                    if matches!(self.dialog, Some(Dialog::Transfer { connection_id }) if connection_id == id)
+                    {
+                        self.dialog = None;
+                    }
+                    let (severity, msg) = match result {
+                        Ok(msg) => (Severity::Success, msg),
+                        Err(e) => (Severity::Error, i18n::tf("status.transfer_failed", &[&e])),
+                    };
+                    if let Some(conn) = self.connection_by_id(id) {
+                        conn.end_busy();
+                        conn.push_line(format!("--- {} ---", msg));
                     }
+                    self.alert(id, severity, msg);
+                }
+            }
+            next = self.serial_rx.try_recv().ok();
+        }
+
+        for (id, end) in view_ends {
+            if let Some(conn) = self.connection_by_id(id) {
+                conn.keep_view(end);
+            }
+        }
+    }
+
+    /// Surface an event on a connection in the status bar and flag its tab if
+    /// it's in the background. Muted connections stay quiet.
+    fn alert(&mut self, id: usize, severity: Severity, msg: String) {
+        let Some(idx) = self.connections.iter().position(|c| c.id == id) else {
+            return;
+        };
+        if self.connections[idx].muted {
+            return;
+        }
+        if idx != self.active_connection {
+            self.connections[idx].alert = true;
+        }
+        self.set_status(severity, msg);
+    }
+
+    /// Act on a BEL received on a connection, as the `bell` setting says. A
+    /// background tab is flagged instead of flashing the screen.
+    fn bell(&mut self, id: usize) {
+        let Some(idx) = self.connections.iter().position(|c| c.id == id) else {
+            return;
+        };
+        if self.connections[idx].muted {
+            return;
+        }
+        match self.config.bell {
+            BellSetting::Off => {}
+            BellSetting::Flash if idx != self.active_connection => {
+                self.connections[idx].alert = true;
+            }
+            BellSetting::Flash => self.flash_until = Some(Instant::now() + BELL_FLASH),
+            BellSetting::Ring => self.ring_bell = true,
+        }
+    }
+
+    /// Step the export format (keeping the extension in step) or the replay
+    /// speed in a filename prompt. Only text exports take a range.
+    fn cycle_file_option(&mut self, forward: bool) {
+        let Some(Dialog::FileNamePrompt {
+            filename,
+            cursor_pos,
+            action,
+            ..
+        }) = &mut self.dialog
+        else {
+            return;
+        };
+        match action {
+            FileAction::Export(export) => {
+                let format = export.format;
+                let next = if forward {
+                    format.next()
+                } else {
+                    format.prev()
+                };
+                // `.gz` stays at the end
+                let (name, gz) = match filename.strip_suffix(".gz") {
+                    Some(name) => (name, ".gz"),
+                    None => (filename.as_str(), ""),
+                };
+                let renamed = name
+                    .strip_suffix(&format!(".{}", format.extension()))
+                    .map(|stem| format!("{}.{}{}", stem, next.extension(), gz));
+                if let Some(renamed) = renamed {
+                    *filename = renamed;
+                    *cursor_pos = filename.len();
+                }
+                export.format = next;
+                if next != ExportFormat::Text {
+                    export.range = ExportRange::All;
+                }
+            }
+            FileAction::Replay(speed) => {
+                let len = REPLAY_SPEEDS.len();
+                let pos = REPLAY_SPEEDS.iter().position(|s| s == speed);
+                let next = match pos {
+                    Some(pos) if forward => (pos + 1) % len,
+                    Some(pos) => (pos + len - 1) % len,
+                    None => 0,
+                };
+                *speed = REPLAY_SPEEDS[next];
+            }
+            _ => {}
+        }
+    }
+
+    /// Step the range of a text export in its filename prompt.
+    fn cycle_export_range(&mut self, forward: bool) {
+        if let Some(Dialog::FileNamePrompt {
+            action: FileAction::Export(export),
+            ..
+        }) = &mut self.dialog
+        {
+            if export.format == ExportFormat::Text {
+                let range = export.range;
+                export.range = if forward { range.next() } else { range.prev() };
+            }
+        }
+    }
+
+    /// Switch a connection between transmitting and previewing its sends.
+    fn toggle_dry_run(&mut self, idx: usize) {
+        let Some(conn) = self.connections.get_mut(idx) else {
+            return;
+        };
+        if let Some(text) = conn.busy_text() {
+            self.set_status(Severity::Warning, i18n::tf("status.busy", &[&text]));
+            return;
+        }
+        conn.dry_run = !conn.dry_run;
+        let msg = if conn.dry_run {
+            i18n::t("status.dry_run")
+        } else {
+            i18n::t("status.dry_run_ended")
+        };
+        conn.push_line(format!("--- {} ---", msg));
+        self.set_status(Severity::Info, msg.into());
+    }
+
+    /// Stop sharing the active connection, or ask where to share it.
+    fn toggle_share(&mut self) {
+        let Some(conn) = self.connections.get_mut(self.active_connection) else {
+            return;
+        };
+        if let Some(share) = conn.share.take() {
+            let msg = i18n::tf("status.share_stopped", &[&share.address]);
+            conn.push_line(format!("--- {} ---", msg));
+            self.set_status(Severity::Info, msg);
+            return;
+        }
+        let address = format!("127.0.0.1:{}", SHARE_BASE_PORT + conn.id as u16);
+        self.dialog = Some(Dialog::SharePrompt {
+            cursor_pos: address.len(),
+            address,
+        });
+    }
+
+    /// Offer the active connection to TCP clients on `address`.
+    fn start_share(&mut self, address: &str) {
+        let serial_tx = self.serial_tx.clone();
+        let Some(conn) = self.connections.get_mut(self.active_connection) else {
+            return;
+        };
+        match ShareServer::start(conn.id, address.trim(), serial_tx) {
+            Ok(share) => {
+                let msg = i18n::tf("status.sharing", &[&share.address]);
+                conn.push_line(format!("--- {} ---", msg));
+                conn.share = Some(share);
+                self.set_status(Severity::Success, msg);
+            }
+            Err(e) => self.set_status(Severity::Error, i18n::tf("status.share_failed", &[&e])),
+        }
+    }
+
+    /// Pick another connection to bridge the active one to, or end its bridge.
+    fn toggle_bridge(&mut self) {
+        let Some(conn) = self.connections.get(self.active_connection) else {
+            return;
+        };
+        if let Some(peer) = conn.bridge_to {
+            self.end_bridge(conn.id, peer);
+            return;
+        }
+        self.open_peer_menu(false);
+    }
+
+    /// List the other connections to bridge or sniff the active one with,
+    /// each once for one way (two taps) and once for both ways (passthrough).
+    fn open_peer_menu(&mut self, sniff: bool) {
+        let Some(conn) = self.connections.get(self.active_connection) else {
+            return;
+        };
+        if conn.taps.is_some() {
+            return;
+        }
+        let targets: Vec<(usize, bool)> = self
+            .connections
+            .iter()
+            .filter(|c| c.id != conn.id && c.taps.is_none())
+            .flat_map(|c| [(c.id, false), (c.id, true)])
+            .collect();
+        if targets.is_empty() {
+            let msg = if sniff {
+                i18n::t("status.sniff_needs_peer")
+            } else {
+                i18n::t("status.bridge_needs_peer")
+            };
+            self.set_status(Severity::Warning, msg.into());
+            return;
+        }
+        self.dialog = Some(Dialog::BridgeMenu {
+            connection_idx: self.active_connection,
+            targets,
+            selected: 0,
+            sniff,
+        });
+    }
+
+    /// Open a sniffer tab interleaving what connection `idx` and `peer` (an
+    /// id) receive; with `passthrough` they are also bridged both ways.
+    fn start_sniffer(&mut self, idx: usize, peer: usize, passthrough: bool) {
+        let Some(conn) = self.connections.get(idx) else {
+            return;
+        };
+        let Some(peer_conn) = self.connections.iter().find(|c| c.id == peer) else {
+            return;
+        };
+        let name = format!("{} + {}", conn.port_name, peer_conn.port_name);
+        let taps = [conn.id, peer];
+        let display_mode = conn.display_mode;
+        if passthrough {
+            self.start_bridge(idx, peer, true);
+            if self.connections[idx].bridge_to != Some(peer) {
+                // Refused, with the reason in the status bar
+                return;
+            }
+        }
+        let id = self.next_connection_id;
+        self.next_connection_id += 1;
+        let mut sniffer = Connection::sniffer(id, name.clone(), taps, display_mode);
+        sniffer.stats = SessionStats::new(&self.stat_patterns);
+        sniffer.max_scrollback = self.config.scrollback_limit();
+        sniffer.push_line(format!("--- Sniffing {} ---", name));
+        self.connections.push(sniffer);
+        self.active_connection = self.connections.len() - 1;
+        self.set_status(Severity::Success, i18n::tf("status.sniffing", &[&name]));
+    }
+
+    /// Copy received data into the sniffer tabs tapping connection `id`.
+    fn sniff(&mut self, id: usize, data: &[u8]) {
+        let tapped = |c: &Connection| c.taps.is_some_and(|taps| taps.contains(&id));
+        if !self.connections.iter().any(tapped) {
+            return;
+        }
+        let Some(port_name) = self
+            .connections
+            .iter()
+            .find(|c| c.id == id)
+            .map(|c| c.port_name.clone())
+        else {
+            return;
+        };
+        for sniffer in self.connections.iter_mut().filter(|c| tapped(c)) {
+            sniffer.sniffed(&port_name, data);
+        }
+    }
+
+    /// Write what connection `idx` receives to connection `peer` (an id), and
+    /// the other way round too with `both_ways`.
+    fn start_bridge(&mut self, idx: usize, peer: usize, both_ways: bool) {
+        let Some(conn) = self.connections.get(idx) else {
+            return;
+        };
+        let (id, port_name, can_send) = (conn.id, conn.port_name.clone(), conn.can_send());
+        let Some(peer_conn) = self.connection_by_id(peer) else {
+            return;
+        };
+        let read_only = if !peer_conn.can_send() {
+            Some(peer_conn.port_name.clone())
+        } else if both_ways && !can_send {
+            Some(port_name.clone())
+        } else {
+            None
+        };
+        if let Some(name) = read_only {
+            self.set_status(Severity::Warning, i18n::tf("status.read_only", &[&name]));
+            return;
+        }
+        // The reverse direction replaces a bridge the peer had elsewhere
+        if both_ways && peer_conn.bridge_to.replace(id).is_some_and(|old| old != id) {
+            peer_conn.push_line("--- Bridge ended ---".into());
+        }
+        let peer_name = peer_conn.port_name.clone();
+        let arrow = if both_ways { "⇄" } else { "←" };
+        peer_conn.push_line(format!("--- Bridged {} {} ---", arrow, port_name));
+        let conn = &mut self.connections[idx];
+        conn.bridge_to = Some(peer);
+        let arrow = if both_ways { "⇄" } else { "→" };
+        conn.push_line(format!("--- Bridged {} {} ---", arrow, peer_name));
+        self.set_status(
+            Severity::Success,
+            i18n::tf("status.bridged", &[&port_name, &arrow, &peer_name]),
+        );
+    }
+
+    /// End the bridge from connection `id` to `peer`, and back if it runs
+    /// both ways.
+    fn end_bridge(&mut self, id: usize, peer: usize) {
+        for (from, to) in [(id, peer), (peer, id)] {
+            if let Some(conn) = self.connection_by_id(from) {
+                if conn.bridge_to == Some(to) {
+                    conn.bridge_to = None;
+                    conn.push_line("--- Bridge ended ---".into());
                 }
             }
         }
+        self.set_status(Severity::Info, i18n::t("status.bridge_ended").into());
+    }
+
+    /// Toggle DTR (0) or RTS (1), or send a break (2), on the active connection.
+    fn line_control(&mut self, item: u16) {
+        let Some(conn) = self.connections.get_mut(self.active_connection) else {
+            return;
+        };
+        if conn.read_only || !conn.alive {
+            return;
+        }
+        let (control, text) = match item {
+            0 => (
+                LineControl::Dtr(!conn.dtr),
+                i18n::tf("status.dtr", &[&on_off(!conn.dtr)]),
+            ),
+            1 => (
+                LineControl::Rts(!conn.rts),
+                i18n::tf("status.rts", &[&on_off(!conn.rts)]),
+            ),
+            _ => (LineControl::Break, i18n::t("status.break_sent").to_string()),
+        };
+        conn.control(control);
+        self.set_status(Severity::Info, text);
+    }
+
+    /// Offer new line settings for the active connection, starting from
+    /// its current ones.
+    fn open_line_setup(&mut self) {
+        let Some(conn) = self.connections.get(self.active_connection) else {
+            return;
+        };
+        if !conn.can_change_line_settings() {
+            let text = if conn.alive {
+                i18n::tf("status.no_line_settings", &[&conn.port_name])
+            } else {
+                i18n::tf("status.not_connected", &[&conn.port_name])
+            };
+            self.set_status(Severity::Warning, text);
+            return;
+        }
+        self.dialog = Some(Dialog::LineSetup {
+            connection_idx: self.active_connection,
+            selected: 0,
+            line: LineSetup::of(conn),
+        });
+    }
+
+    fn toggle_mute(&mut self, idx: usize) {
+        let Some(conn) = self.connections.get_mut(idx) else {
+            return;
+        };
+        conn.muted = !conn.muted;
+        conn.alert = false;
+        let msg = if conn.muted {
+            i18n::tf("status.muted", &[&conn.port_name])
+        } else {
+            i18n::tf("status.unmuted", &[&conn.port_name])
+        };
+        self.set_status(Severity::Info, msg);
     }
 
     pub fn is_pending_active(&self) -> bool {
         self.pending_connection.is_some() && self.active_connection == self.connections.len()
     }
 
+    /// Whether the status bar shows the function key bar rather than hints.
+    /// Modes with their own hints (passthrough, hex input, search) keep them.
+    pub fn shows_function_keys(&self) -> bool {
+        self.config.function_key_bar
+            && self.screen == Screen::Connected
+            && !self.connections.is_empty()
+            && !self.is_pending_active()
+            && !self.is_passthrough_active()
+            && !self.hex_input
+            && self.search.is_none()
+            && self.status().is_none()
+            && self.active_busy_text().is_none()
+    }
+
+    pub fn is_passthrough_active(&self) -> bool {
+        self.connections
+            .get(self.active_connection)
+            .is_some_and(|c| c.passthrough)
+    }
+
     fn handle_pending_message(&mut self, msg: &Message) -> bool {
         let pending = match self.pending_connection {
             Some(p) => p,
@@ -276,20 +1985,23 @@ impl App {
                             self.selected_display_mode_index -= 1;
                         }
                     }
+                    PendingScreen::PacingSelect => {
+                        if self.selected_pacing_index > 0 {
+                            self.selected_pacing_index -= 1;
+                        }
+                    }
                 }
                 true
             }
             Message::Down => {
                 match pending {
                     PendingScreen::PortSelect => {
-                        if !self.available_ports.is_empty()
-                            && self.selected_port_index < self.available_ports.len() - 1
-                        {
+                        if self.selected_port_index < self.port_list_len() - 1 {
                             self.selected_port_index += 1;
                         }
                     }
                     PendingScreen::BaudSelect => {
-                        if self.selected_baud_index < BAUD_RATES.len() - 1 {
+                        if self.selected_baud_index < BAUD_RATES.len() {
                             self.selected_baud_index += 1;
                         }
                     }
@@ -313,15 +2025,18 @@ impl App {
                             self.selected_display_mode_index += 1;
                         }
                     }
+                    PendingScreen::PacingSelect => {
+                        if self.selected_pacing_index < PACING_OPTIONS.len() - 1 {
+                            self.selected_pacing_index += 1;
+                        }
+                    }
                 }
                 true
             }
             Message::Select => {
                 match pending {
                     PendingScreen::PortSelect => {
-                        if !self.available_ports.is_empty() {
-                            self.pending_connection = Some(PendingScreen::BaudSelect);
-                        }
+                        self.choose_port();
                     }
                     PendingScreen::BaudSelect => {
                         self.pending_connection = Some(PendingScreen::DataBitsSelect);
@@ -336,20 +2051,31 @@ impl App {
                         self.pending_connection = Some(PendingScreen::DisplayModeSelect);
                     }
                     PendingScreen::DisplayModeSelect => {
+                        self.choose_display_mode();
+                    }
+                    PendingScreen::PacingSelect => {
                         self.connect_selected();
                     }
                 }
                 true
             }
+            Message::Back
+                if pending == PendingScreen::PortSelect && !self.port_filter.is_empty() =>
+            {
+                self.clear_port_filter();
+                true
+            }
             Message::Back => {
                 match pending {
                     PendingScreen::PortSelect => {
                         self.pending_connection = None;
+                        self.duplicating = None;
                         if !self.connections.is_empty() {
                             self.active_connection = self.connections.len() - 1;
                         }
                     }
                     PendingScreen::BaudSelect => {
+                        self.typed_target = None;
                         self.pending_connection = Some(PendingScreen::PortSelect);
                     }
                     PendingScreen::DataBitsSelect => {
@@ -362,7 +2088,15 @@ impl App {
                         self.pending_connection = Some(PendingScreen::ParitySelect);
                     }
                     PendingScreen::DisplayModeSelect => {
-                        self.pending_connection = Some(PendingScreen::StopBitsSelect);
+                        self.pending_connection = if self.skips_line_settings() {
+                            self.typed_target = None;
+                            Some(PendingScreen::PortSelect)
+                        } else {
+                            Some(PendingScreen::StopBitsSelect)
+                        };
+                    }
+                    PendingScreen::PacingSelect => {
+                        self.pending_connection = Some(PendingScreen::DisplayModeSelect);
                     }
                 }
                 true
@@ -371,11 +2105,28 @@ impl App {
                 self.refresh_ports();
                 true
             }
+            Message::PortFilterInput(c) if pending == PendingScreen::PortSelect => {
+                self.edit_port_filter(Some(*c));
+                true
+            }
+            Message::PortFilterBackspace if pending == PendingScreen::PortSelect => {
+                self.edit_port_filter(None);
+                true
+            }
+            Message::ToggleFavoritePort if pending == PendingScreen::PortSelect => {
+                self.toggle_favorite_port();
+                true
+            }
+            Message::PickFavoritePort(n) if pending == PendingScreen::PortSelect => {
+                self.pick_favorite_port(*n);
+                true
+            }
             _ => false,
         }
     }
 
     pub fn update(&mut self, msg: Message) {
+        self.dirty = true;
         if self.is_pending_active() && self.handle_pending_message(&msg) {
             return;
         }
@@ -389,6 +2140,9 @@ impl App {
             }
 
             Message::Up => match self.screen {
+                Screen::ProfileSelect if self.selected_profile_index > 0 => {
+                    self.selected_profile_index -= 1;
+                }
                 Screen::PortSelect => {
                     if self.selected_port_index > 0 {
                         self.selected_port_index -= 1;
@@ -419,21 +2173,24 @@ impl App {
                         self.selected_display_mode_index -= 1;
                     }
                 }
+                Screen::PacingSelect if self.selected_pacing_index > 0 => {
+                    self.selected_pacing_index -= 1;
+                }
                 _ => {}
             },
 
             Message::Down => match self.screen {
-                Screen::PortSelect => {
-                    if !self.available_ports.is_empty()
-                        && self.selected_port_index < self.available_ports.len() - 1
-                    {
-                        self.selected_port_index += 1;
-                    }
+                // Last entry is "Manual connection…"
+                Screen::ProfileSelect
+                    if self.selected_profile_index < self.config.profiles.len() =>
+                {
+                    self.selected_profile_index += 1;
                 }
-                Screen::BaudSelect => {
-                    if self.selected_baud_index < BAUD_RATES.len() - 1 {
-                        self.selected_baud_index += 1;
-                    }
+                Screen::PortSelect if self.selected_port_index < self.port_list_len() - 1 => {
+                    self.selected_port_index += 1;
+                }
+                Screen::BaudSelect if self.selected_baud_index < BAUD_RATES.len() => {
+                    self.selected_baud_index += 1;
                 }
                 Screen::DataBitsSelect => {
                     if self.selected_data_bits_index < DATA_BITS_OPTIONS.len() - 1 {
@@ -455,14 +2212,18 @@ impl App {
                         self.selected_display_mode_index += 1;
                     }
                 }
+                Screen::PacingSelect if self.selected_pacing_index < PACING_OPTIONS.len() - 1 => {
+                    self.selected_pacing_index += 1;
+                }
                 _ => {}
             },
 
             Message::Select => match self.screen {
+                Screen::ProfileSelect => {
+                    self.select_profile(self.selected_profile_index);
+                }
                 Screen::PortSelect => {
-                    if !self.available_ports.is_empty() {
-                        self.screen = Screen::BaudSelect;
-                    }
+                    self.choose_port();
                 }
                 Screen::BaudSelect => {
                     self.screen = Screen::DataBitsSelect;
@@ -477,18 +2238,31 @@ impl App {
                     self.screen = Screen::DisplayModeSelect;
                 }
                 Screen::DisplayModeSelect => {
+                    self.choose_display_mode();
+                }
+                Screen::PacingSelect => {
                     self.connect_selected();
                 }
                 _ => {}
             },
 
+            Message::Back if self.screen == Screen::PortSelect && !self.port_filter.is_empty() => {
+                self.clear_port_filter();
+            }
             Message::Back => match self.screen {
+                Screen::ProfileSelect if self.connections.is_empty() => {
+                    self.should_quit = true;
+                }
+                Screen::PortSelect if !self.config.profiles.is_empty() => {
+                    self.screen = Screen::ProfileSelect;
+                }
                 Screen::PortSelect => {
                     if self.connections.is_empty() {
                         self.should_quit = true;
                     }
                 }
                 Screen::BaudSelect => {
+                    self.typed_target = None;
                     self.screen = Screen::PortSelect;
                 }
                 Screen::DataBitsSelect => {
@@ -501,7 +2275,15 @@ impl App {
                     self.screen = Screen::ParitySelect;
                 }
                 Screen::DisplayModeSelect => {
-                    self.screen = Screen::StopBitsSelect;
+                    self.screen = if self.skips_line_settings() {
+                        self.typed_target = None;
+                        Screen::PortSelect
+                    } else {
+                        Screen::StopBitsSelect
+                    };
+                }
+                Screen::PacingSelect => {
+                    self.screen = Screen::DisplayModeSelect;
                 }
                 _ => {}
             },
@@ -509,13 +2291,21 @@ impl App {
             Message::RefreshPorts => {
                 self.refresh_ports();
             }
+            Message::PortFilterInput(c) => {
+                self.edit_port_filter(Some(c));
+            }
+            Message::PortFilterBackspace => {
+                self.edit_port_filter(None);
+            }
+            Message::ToggleFavoritePort => {
+                self.toggle_favorite_port();
+            }
+            Message::PickFavoritePort(n) => {
+                self.pick_favorite_port(n);
+            }
 
             Message::NewConnection => {
-                if self.screen == Screen::Connected && self.pending_connection.is_none() {
-                    self.pending_connection = Some(PendingScreen::PortSelect);
-                    self.refresh_ports();
-                    self.active_connection = self.connections.len();
-                }
+                self.new_connection();
             }
 
             Message::CloseConnection => {
@@ -567,16 +2357,151 @@ impl App {
             Message::ToggleViewMode => {
                 self.view_mode = match self.view_mode {
                     ViewMode::Tabs => ViewMode::Grid,
-                    ViewMode::Grid => ViewMode::Tabs,
+                    ViewMode::Grid => ViewMode::Split,
+                    ViewMode::Split => ViewMode::Tabs,
+                };
+            }
+
+            Message::PinConnection => {
+                if let Some(conn) = self.connections.get(self.active_connection) {
+                    self.pinned_connection = Some(conn.id);
+                    self.set_status(Severity::Info, i18n::tf("status.pinned", &[&conn.label()]));
+                }
+            }
+
+            Message::SwapPanes => {
+                self.swap_split_panes();
+            }
+
+            Message::ToggleSplitDirection => {
+                self.split_direction = match self.split_direction {
+                    SplitDirection::Horizontal => SplitDirection::Vertical,
+                    SplitDirection::Vertical => SplitDirection::Horizontal,
                 };
             }
 
+            Message::CharInput(_)
+            | Message::Backspace
+            | Message::DeleteForward
+            | Message::DeleteWord
+            | Message::ClearInput
+            | Message::SendInput
+            | Message::SendRaw(_)
+            | Message::Paste(_)
+            | Message::PasteClipboard
+            | Message::EditCursorLine
+                if self.input_locked() => {}
+
+            Message::ResendLast => {
+                self.resend(self.active_connection, 1);
+            }
+
             Message::CharInput(c) => {
-                self.input_buffer.push(c);
+                self.input_buffer.insert(self.input_cursor, c);
+                self.input_cursor += c.len_utf8();
             }
 
             Message::Backspace => {
-                self.input_buffer.pop();
+                if let Some(c) = self.input_buffer[..self.input_cursor].chars().next_back() {
+                    self.input_cursor -= c.len_utf8();
+                    self.input_buffer.remove(self.input_cursor);
+                }
+            }
+
+            Message::DeleteForward => {
+                if self.input_cursor < self.input_buffer.len() {
+                    self.input_buffer.remove(self.input_cursor);
+                }
+            }
+
+            Message::InputLeft => {
+                if let Some(c) = self.input_buffer[..self.input_cursor].chars().next_back() {
+                    self.input_cursor -= c.len_utf8();
+                }
+            }
+
+            Message::InputRight => {
+                if let Some(c) = self.input_buffer[self.input_cursor..].chars().next() {
+                    self.input_cursor += c.len_utf8();
+                }
+            }
+
+            Message::InputHome => self.input_cursor = 0,
+
+            Message::InputEnd => self.input_cursor = self.input_buffer.len(),
+
+            Message::InputWordLeft => {
+                self.input_cursor = word_start(&self.input_buffer, self.input_cursor);
+            }
+
+            Message::InputWordRight => {
+                self.input_cursor = word_end(&self.input_buffer, self.input_cursor);
+            }
+
+            Message::DeleteWord => {
+                let start = word_start(&self.input_buffer, self.input_cursor);
+                self.input_buffer
+                    .replace_range(start..self.input_cursor, "");
+                self.input_cursor = start;
+            }
+
+            Message::ClearInput => {
+                self.input_buffer.clear();
+                self.input_cursor = 0;
+            }
+
+            Message::Paste(text) => {
+                self.paste(&text);
+            }
+
+            Message::PasteClipboard => {
+                match arboard::Clipboard::new().and_then(|mut c| c.get_text()) {
+                    Ok(text) => self.paste(&text),
+                    Err(e) => {
+                        self.set_status(Severity::Error, i18n::tf("status.clipboard", &[&e]));
+                    }
+                }
+            }
+
+            Message::ToggleHexInput => {
+                self.hex_input = !self.hex_input;
+            }
+
+            Message::ToggleLiteralInput => {
+                self.literal_input = !self.literal_input;
+            }
+
+            Message::TogglePassthrough => {
+                if let Some(conn) = self.connections.get_mut(self.active_connection) {
+                    if !conn.can_send() {
+                        return;
+                    }
+                    conn.passthrough = !conn.passthrough;
+                    let msg = if conn.passthrough {
+                        i18n::tf(
+                            "status.passthrough_on",
+                            &[&self.keymap.label(Action::Passthrough).unwrap_or_default()],
+                        )
+                    } else {
+                        i18n::t("status.passthrough_off").to_string()
+                    };
+                    self.set_status(Severity::Info, msg);
+                }
+            }
+
+            Message::SendRaw(data) => {
+                if let Some(conn) = self.connections.get_mut(self.active_connection) {
+                    conn.scroll_to(0);
+                    conn.send(&data);
+                }
+            }
+
+            Message::AbortBusy => {
+                if let Some(conn) = self.connections.get_mut(self.active_connection) {
+                    if let Some(label) = conn.abort_busy() {
+                        self.set_status(Severity::Warning, i18n::tf("status.aborted", &[&label]));
+                    }
+                }
             }
 
             Message::SendInput => {
@@ -584,37 +2509,171 @@ impl App {
                     && !self.connections.is_empty()
                     && self.active_connection < self.connections.len()
                 {
-                    let data = format!("{}\r\n", self.input_buffer);
-                    self.connections[self.active_connection].send(data.as_bytes());
-                    self.input_buffer.clear();
+                    if !self.connections[self.active_connection].can_send() {
+                        self.set_status(
+                            Severity::Warning,
+                            i18n::t("status.connection_read_only").into(),
+                        );
+                        return;
+                    }
+                    let data = if self.hex_input {
+                        match crate::hex::parse(&self.input_buffer) {
+                            Ok(bytes) => bytes,
+                            Err(e) => {
+                                self.set_status(Severity::Warning, e);
+                                return;
+                            }
+                        }
+                    } else {
+                        let mut data = self.text_bytes(&self.input_buffer);
+                        data.extend_from_slice(b"\r\n");
+                        data
+                    };
+                    let text = std::mem::take(&mut self.input_buffer);
+                    self.input_cursor = 0;
+                    self.connections[self.active_connection].send_input(text, data);
                 }
             }
 
             Message::ExportScrollback => {
                 if !self.connections.is_empty() && self.active_connection < self.connections.len() {
-                    let filename = self.generate_filename(self.active_connection);
+                    let filename = self
+                        .generate_filename(self.active_connection, ExportFormat::Text.extension());
                     let cursor_pos = filename.len();
                     self.dialog = Some(Dialog::FileNamePrompt {
                         connection_idx: self.active_connection,
                         filename,
                         cursor_pos,
+                        action: FileAction::Export(ExportSettings::default()),
                         after: AfterSave::Nothing,
                     });
                 }
             }
 
+            Message::OpenCommandMenu => {
+                self.open_command_menu();
+            }
+
+            Message::ToggleLiveStream => {
+                self.toggle_live_stream();
+            }
+
+            Message::OpenSearch => {
+                if !self.connections.is_empty() && self.active_connection < self.connections.len() {
+                    let query = self
+                        .search
+                        .as_ref()
+                        .map(|s| s.query.clone())
+                        .unwrap_or_default();
+                    let cursor_pos = query.len();
+                    self.dialog = Some(Dialog::SearchPrompt { query, cursor_pos });
+                }
+            }
+
+            Message::SearchNext => {
+                self.search_step(true);
+            }
+
+            Message::SearchPrev => {
+                self.search_step(false);
+            }
+
+            Message::ClearSearch => {
+                self.search = None;
+            }
+
+            Message::OpenFilter => {
+                self.open_filter();
+            }
+            Message::OpenNote => {
+                self.open_note();
+            }
+
+            Message::TogglePause => {
+                self.toggle_pause();
+            }
+
+            Message::ToggleWrap => {
+                self.toggle_wrap();
+            }
+
+            Message::CursorUp => {
+                self.move_line_cursor(true);
+            }
+
+            Message::CursorDown => {
+                self.move_line_cursor(false);
+            }
+
+            Message::ClearCursor => {
+                self.line_cursor = None;
+            }
+
+            Message::DismissStatus => {
+                self.status_message = None;
+            }
+
+            Message::OpenCopyAs => {
+                if let Some(bytes) = self.cursor_bytes() {
+                    self.dialog = Some(Dialog::CopyAs { bytes, selected: 0 });
+                }
+            }
+
+            Message::EditCursorLine => {
+                let read_only = self
+                    .connections
+                    .get(self.active_connection)
+                    .map(|c| !c.can_send());
+                if read_only == Some(true) {
+                    self.set_status(
+                        Severity::Warning,
+                        i18n::t("status.connection_read_only").into(),
+                    );
+                } else if let Some(bytes) = self.cursor_bytes() {
+                    self.hex_input = true;
+                    self.input_buffer = crate::hex::format(&bytes);
+                    self.input_cursor = self.input_buffer.len();
+                    self.set_status(
+                        Severity::Info,
+                        i18n::tf("status.edit_bytes", &[&bytes.len()]),
+                    );
+                }
+            }
+
             Message::ScrollUp => {
                 if !self.connections.is_empty() && self.active_connection < self.connections.len() {
                     let conn = &mut self.connections[self.active_connection];
                     let total = conn.scrollback.len();
-                    conn.scroll_offset = (conn.scroll_offset + 5).min(total);
+                    conn.scroll_to((conn.scroll_offset + 5).min(total));
+                }
+            }
+
+            Message::ScrollToTop => {
+                let rows = self.scrollback_rows();
+                if let Some(conn) = self.connections.get_mut(self.active_connection) {
+                    // The oldest line at the top of the view, so scrolling
+                    // down moves right away
+                    let top = conn.shown_lines().len().saturating_sub(rows);
+                    conn.scroll_to(top);
+                }
+            }
+
+            Message::ScrollToBottom => {
+                if let Some(conn) = self.connections.get_mut(self.active_connection) {
+                    conn.scroll_to(0);
                 }
             }
 
             Message::ScrollDown => {
                 if !self.connections.is_empty() && self.active_connection < self.connections.len() {
                     let conn = &mut self.connections[self.active_connection];
-                    conn.scroll_offset = conn.scroll_offset.saturating_sub(5);
+                    conn.scroll_to(conn.scroll_offset.saturating_sub(5));
+                }
+            }
+
+            Message::ScrollLeft | Message::ScrollRight => {
+                if let Some(conn) = self.connections.get_mut(self.active_connection) {
+                    conn.scroll_sideways(matches!(msg, Message::ScrollRight));
                 }
             }
 
@@ -626,6 +2685,38 @@ impl App {
                 self.handle_menu_click(col, row);
             }
 
+            Message::MouseDrag(row) => {
+                if self.dragging_divider {
+                    // The divider is the Send box's top border; status bar sits below the box
+                    let height = self.terminal_rows.saturating_sub(1).saturating_sub(row);
+                    self.set_input_height(height);
+                } else if let Some((idx, area)) = self.dragging_scrollbar {
+                    self.drag_scrollbar(idx, area, row);
+                }
+            }
+
+            Message::RightClick(col, row) => {
+                self.handle_right_click(col, row);
+            }
+
+            Message::MouseUp => {
+                self.dragging_divider = false;
+                self.dragging_scrollbar = None;
+            }
+
+            Message::MouseMove(col, row) => {
+                self.handle_mouse_move(col, row);
+            }
+
+            Message::Resize(cols, rows) => {
+                self.terminal_cols = cols;
+                self.terminal_rows = rows;
+            }
+
+            Message::ResizeInput(delta) => {
+                self.set_input_height(self.input_height.saturating_add_signed(delta));
+            }
+
             Message::DialogYes => {
                 self.handle_dialog_yes();
             }
@@ -634,183 +2725,692 @@ impl App {
                 self.handle_dialog_no();
             }
 
-            Message::DialogCancel => {
-                self.dialog = None;
+            Message::DialogCancel => match self.dialog.take() {
+                Some(Dialog::Transfer { connection_id }) => {
+                    if let Some(conn) = self.connection_by_id(connection_id) {
+                        conn.abort_busy();
+                    }
+                }
+                Some(Dialog::TriggerPrompt { index, .. }) => {
+                    let selected = index.unwrap_or(self.triggers.rules.len());
+                    self.dialog = Some(Dialog::TriggerList { selected });
+                }
+                _ => {}
+            },
+
+            Message::DialogDelete => {
+                if let Some(Dialog::TriggerList { selected }) = &mut self.dialog {
+                    if *selected < self.triggers.rules.len() {
+                        self.triggers.remove(*selected);
+                        *selected = (*selected).min(self.triggers.rules.len());
+                        self.save_triggers("status.trigger_removed");
+                    }
+                }
             }
 
             Message::DialogConfirm => {
                 self.handle_dialog_confirm();
             }
 
-            Message::DialogCharInput(c) => {
-                if let Some(Dialog::FileNamePrompt {
-                    filename,
-                    cursor_pos,
-                    ..
-                }) = &mut self.dialog
+            Message::DialogUp => {
+                if let Some(Dialog::FileNamePrompt { .. }) = &self.dialog {
+                    self.cycle_file_option(false);
+                } else if let Some(
+                    Dialog::Help { scroll }
+                    | Dialog::AsciiTable { scroll }
+                    | Dialog::Diff { scroll, .. },
+                ) = &mut self.dialog
                 {
-                    filename.insert(*cursor_pos, c);
-                    *cursor_pos += 1;
+                    *scroll = scroll.saturating_sub(1);
+                } else if let Some(
+                    Dialog::CommandMenu { selected, .. }
+                    | Dialog::ProtocolMenu { selected, .. }
+                    | Dialog::GeneratorMenu { selected, .. }
+                    | Dialog::FolderBrowser { selected, .. }
+                    | Dialog::CopyAs { selected, .. }
+                    | Dialog::ResendMenu { selected, .. }
+                    | Dialog::BridgeMenu { selected, .. }
+                    | Dialog::DiffMenu { selected, .. }
+                    | Dialog::TriggerList { selected }
+                    | Dialog::GridMenu { selected }
+                    | Dialog::Preferences { selected, .. }
+                    | Dialog::LineSetup { selected, .. }
+                    | Dialog::DecoderMenu { selected, .. },
+                ) = &mut self.dialog
+                {
+                    *selected = selected.saturating_sub(1);
                 }
             }
 
-            Message::DialogBackspace => {
-                if let Some(Dialog::FileNamePrompt {
-                    filename,
-                    cursor_pos,
-                    ..
+            Message::DialogDown => {
+                let max_scroll = crate::ui::help_max_scroll(self);
+                let ascii_max_scroll = crate::ui::ascii_max_scroll(self);
+                let diff_max_scroll = crate::ui::diff_max_scroll(self);
+                if let Some(Dialog::FileNamePrompt { .. }) = &self.dialog {
+                    self.cycle_file_option(true);
+                } else if let Some(Dialog::Help { scroll }) = &mut self.dialog {
+                    *scroll = (*scroll + 1).min(max_scroll);
+                } else if let Some(Dialog::AsciiTable { scroll }) = &mut self.dialog {
+                    *scroll = (*scroll + 1).min(ascii_max_scroll);
+                } else if let Some(Dialog::Diff { scroll, .. }) = &mut self.dialog {
+                    *scroll = (*scroll + 1).min(diff_max_scroll);
+                } else if let Some(Dialog::DiffMenu {
+                    selected, targets, ..
                 }) = &mut self.dialog
                 {
-                    if *cursor_pos > 0 {
-                        filename.remove(*cursor_pos - 1);
-                        *cursor_pos -= 1;
+                    // The last entry asks for a log file
+                    if *selected < targets.len() {
+                        *selected += 1;
                     }
-                }
-            }
-
-            Message::DialogCursorLeft => {
-                if let Some(Dialog::FileNamePrompt { cursor_pos, .. }) = &mut self.dialog {
-                    if *cursor_pos > 0 {
-                        *cursor_pos -= 1;
+                } else if let Some(Dialog::CommandMenu {
+                    selected, commands, ..
+                }) = &mut self.dialog
+                {
+                    if *selected + 1 < commands.len() {
+                        *selected += 1;
                     }
-                }
-            }
-
-            Message::DialogCursorRight => {
-                if let Some(Dialog::FileNamePrompt {
-                    filename,
-                    cursor_pos,
+                } else if let Some(Dialog::ProtocolMenu { selected, .. }) = &mut self.dialog {
+                    if *selected + 1 < Protocol::ALL.len() {
+                        *selected += 1;
+                    }
+                } else if let Some(Dialog::GeneratorMenu { selected, .. }) = &mut self.dialog {
+                    if *selected + 1 < PatternKind::ALL.len() {
+                        *selected += 1;
+                    }
+                } else if let Some(Dialog::FolderBrowser {
+                    selected,
+                    subfolders,
                     ..
                 }) = &mut self.dialog
                 {
-                    if *cursor_pos < filename.len() {
-                        *cursor_pos += 1;
+                    if *selected < subfolders.len() {
+                        *selected += 1;
+                    }
+                } else if let Some(Dialog::CopyAs { selected, .. }) = &mut self.dialog {
+                    if *selected + 1 < ArrayFormat::ALL.len() {
+                        *selected += 1;
+                    }
+                } else if let Some(Dialog::GridMenu { selected }) = &mut self.dialog {
+                    if *selected + 1 < GridShape::ALL.len() {
+                        *selected += 1;
+                    }
+                } else if let Some(Dialog::Preferences { selected, .. }) = &mut self.dialog {
+                    if *selected + 1 < Preferences::ROWS {
+                        *selected += 1;
+                    }
+                } else if let Some(Dialog::LineSetup { selected, .. }) = &mut self.dialog {
+                    if *selected + 1 < LineSetup::ROWS {
+                        *selected += 1;
+                    }
+                } else if let Some(Dialog::DecoderMenu { selected, .. }) = &mut self.dialog {
+                    if *selected + 1 < DECODERS.len() {
+                        *selected += 1;
+                    }
+                } else if let Some(Dialog::TriggerList { selected }) = &mut self.dialog {
+                    // The last entry adds a rule
+                    if *selected < self.triggers.rules.len() {
+                        *selected += 1;
+                    }
+                } else if let Some(Dialog::BridgeMenu {
+                    selected, targets, ..
+                }) = &mut self.dialog
+                {
+                    if *selected + 1 < targets.len() {
+                        *selected += 1;
+                    }
+                } else if let Some(Dialog::ResendMenu {
+                    connection_idx,
+                    selected,
+                }) = &mut self.dialog
+                {
+                    let len = self
+                        .connections
+                        .get(*connection_idx)
+                        .map_or(0, |c| c.history.len());
+                    if *selected + 1 < len {
+                        *selected += 1;
                     }
                 }
             }
-        }
-    }
 
-    fn handle_menu_click(&mut self, col: u16, row: u16) {
-        let file_range = MENU_FILE_X..MENU_FILE_X + MENU_FILE_W;
-        let conn_range = MENU_CONN_X..MENU_CONN_X + MENU_CONN_W;
-        let view_range = MENU_VIEW_X..MENU_VIEW_X + MENU_VIEW_W;
+            Message::DialogPageUp | Message::DialogPageDown
+                if matches!(self.dialog, Some(Dialog::FileNamePrompt { .. })) =>
+            {
+                self.cycle_export_range(matches!(msg, Message::DialogPageDown));
+            }
+            Message::DialogPageUp | Message::DialogPageDown => {
+                let max_scroll = match self.dialog {
+                    Some(Dialog::Diff { .. }) => crate::ui::diff_max_scroll(self),
+                    Some(Dialog::AsciiTable { .. }) => crate::ui::ascii_max_scroll(self),
+                    _ => crate::ui::help_max_scroll(self),
+                };
+                let page = self.terminal_rows.saturating_sub(6).max(1) as usize;
+                if let Some(
+                    Dialog::Help { scroll }
+                    | Dialog::AsciiTable { scroll }
+                    | Dialog::Diff { scroll, .. },
+                ) = &mut self.dialog
+                {
+                    *scroll = if matches!(msg, Message::DialogPageUp) {
+                        scroll.saturating_sub(page)
+                    } else {
+                        (*scroll + page).min(max_scroll)
+                    };
+                }
+            }
 
-        if row == 0 {
-            // Clicking on the menu bar itself — toggle menus
-            let new_menu = if file_range.contains(&col) {
-                Some(OpenMenu::File)
-            } else if conn_range.contains(&col) {
-                Some(OpenMenu::Connection)
-            } else if view_range.contains(&col) {
-                Some(OpenMenu::View)
-            } else {
-                None
-            };
-            if new_menu == self.open_menu {
+            Message::OpenHelp => {
                 self.open_menu = None;
-            } else {
-                self.open_menu = new_menu;
+                self.dialog = Some(Dialog::Help { scroll: 0 });
             }
-            return;
-        }
 
-        // Clicking on an open dropdown
-        let Some(menu) = self.open_menu else {
-            // No menu open — check for content area clicks
-            self.handle_content_click(col, row);
-            return;
-        };
+            Message::OpenAsciiTable => {
+                self.open_menu = None;
+                self.dialog = Some(Dialog::AsciiTable { scroll: 0 });
+            }
 
-        let drop_w = 0..16_u16; // dropdown is 16 chars wide
-        let handled = match menu {
-            OpenMenu::File => {
-                let drop_col = col.wrapping_sub(MENU_FILE_X);
-                if row == 2 && drop_w.contains(&drop_col) {
-                    // Export
-                    self.open_menu = None;
-                    if !self.connections.is_empty() {
-                        let filename = self.generate_filename(self.active_connection);
-                        let cursor_pos = filename.len();
-                        self.dialog = Some(Dialog::FileNamePrompt {
-                            connection_idx: self.active_connection,
-                            filename,
-                            cursor_pos,
-                            after: AfterSave::Nothing,
-                        });
+            Message::DialogCharInput(c) => {
+                if let Some(
+                    Dialog::FileNamePrompt {
+                        filename,
+                        cursor_pos,
+                        ..
                     }
-                    true
-                } else if row == 3 && drop_w.contains(&drop_col) {
-                    // Quit
-                    self.open_menu = None;
-                    if self.connections.is_empty() {
-                        self.should_quit = true;
-                    } else {
-                        self.dialog = Some(Dialog::ConfirmQuit);
+                    | Dialog::SearchPrompt {
+                        query: filename,
+                        cursor_pos,
                     }
-                    true
-                } else {
-                    false
-                }
-            }
-            OpenMenu::Connection => {
-                let drop_col = col.wrapping_sub(MENU_CONN_X);
-                if row == 2 && drop_w.contains(&drop_col) {
-                    self.open_menu = None;
-                    if self.screen == Screen::Connected && self.pending_connection.is_none() {
-                        self.pending_connection = Some(PendingScreen::PortSelect);
-                        self.refresh_ports();
-                        self.active_connection = self.connections.len();
+                    | Dialog::ChecksumPrompt {
+                        query: filename,
+                        cursor_pos,
                     }
-                    true
-                } else if row == 3 && drop_w.contains(&drop_col) {
-                    // Close
-                    self.open_menu = None;
-                    if !self.connections.is_empty() {
-                        self.dialog = Some(Dialog::ConfirmCloseConnection);
+                    | Dialog::FilterPrompt {
+                        query: filename,
+                        cursor_pos,
                     }
-                    true
-                } else {
-                    false
-                }
-            }
-            OpenMenu::View => {
-                let drop_col = col.wrapping_sub(MENU_VIEW_X);
-                if row == 2 && drop_w.contains(&drop_col) {
-                    self.open_menu = None;
-                    self.view_mode = ViewMode::Tabs;
-                    true
-                } else if row == 3 && drop_w.contains(&drop_col) {
-                    self.open_menu = None;
-                    self.view_mode = ViewMode::Grid;
-                    true
-                } else {
-                    false
+                    | Dialog::HostPrompt {
+                        host: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::PathPrompt {
+                        path: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::SharePrompt {
+                        address: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::TriggerPrompt {
+                        text: filename,
+                        cursor_pos,
+                        ..
+                    }
+                    | Dialog::WatchPrompt {
+                        query: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::FieldsPrompt {
+                        query: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::LinkTestPrompt {
+                        duration: filename,
+                        cursor_pos,
+                        ..
+                    }
+                    | Dialog::NotePrompt {
+                        text: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::CapturePrompt {
+                        text: filename,
+                        cursor_pos,
+                        ..
+                    }
+                    | Dialog::GeneratorPrompt {
+                        text: filename,
+                        cursor_pos,
+                        ..
+                    },
+                ) = &mut self.dialog
+                {
+                    filename.insert(*cursor_pos, c);
+                    *cursor_pos += c.len_utf8();
                 }
             }
-        };
-        if !handled {
-            self.open_menu = None;
-        }
-    }
 
-    fn handle_content_click(&mut self, col: u16, row: u16) {
-        match self.screen {
-            Screen::PortSelect => {
-                // Layout: row 0 = menu bar, row 1 = top border, rows 2+ = items,
-                // bottom = bottom border + status bar
+            Message::DialogBackspace => {
+                if let Some(
+                    Dialog::FileNamePrompt {
+                        filename,
+                        cursor_pos,
+                        ..
+                    }
+                    | Dialog::SearchPrompt {
+                        query: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::ChecksumPrompt {
+                        query: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::FilterPrompt {
+                        query: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::HostPrompt {
+                        host: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::PathPrompt {
+                        path: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::SharePrompt {
+                        address: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::TriggerPrompt {
+                        text: filename,
+                        cursor_pos,
+                        ..
+                    }
+                    | Dialog::WatchPrompt {
+                        query: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::FieldsPrompt {
+                        query: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::LinkTestPrompt {
+                        duration: filename,
+                        cursor_pos,
+                        ..
+                    }
+                    | Dialog::NotePrompt {
+                        text: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::CapturePrompt {
+                        text: filename,
+                        cursor_pos,
+                        ..
+                    }
+                    | Dialog::GeneratorPrompt {
+                        text: filename,
+                        cursor_pos,
+                        ..
+                    },
+                ) = &mut self.dialog
+                {
+                    if let Some(c) = filename[..*cursor_pos].chars().next_back() {
+                        *cursor_pos -= c.len_utf8();
+                        filename.remove(*cursor_pos);
+                    }
+                }
+            }
+
+            Message::DialogCursorLeft => {
+                if let Some(Dialog::FolderBrowser {
+                    connection_idx,
+                    folder,
+                    ..
+                }) = &self.dialog
+                {
+                    // Up to the parent, with the folder just left selected
+                    let from = folder.file_name().map(|n| n.to_string_lossy().into_owned());
+                    if let Some(parent) = folder.parent() {
+                        self.browse_folder(*connection_idx, parent.to_path_buf(), from);
+                    }
+                } else if let Some(Dialog::Preferences { selected, prefs }) = &mut self.dialog {
+                    prefs.step(*selected, false);
+                } else if let Some(Dialog::LineSetup { selected, line, .. }) = &mut self.dialog {
+                    line.step(*selected, false);
+                } else if let Some(
+                    Dialog::FileNamePrompt {
+                        filename,
+                        cursor_pos,
+                        ..
+                    }
+                    | Dialog::SearchPrompt {
+                        query: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::ChecksumPrompt {
+                        query: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::FilterPrompt {
+                        query: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::HostPrompt {
+                        host: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::PathPrompt {
+                        path: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::SharePrompt {
+                        address: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::TriggerPrompt {
+                        text: filename,
+                        cursor_pos,
+                        ..
+                    }
+                    | Dialog::WatchPrompt {
+                        query: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::FieldsPrompt {
+                        query: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::LinkTestPrompt {
+                        duration: filename,
+                        cursor_pos,
+                        ..
+                    }
+                    | Dialog::NotePrompt {
+                        text: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::CapturePrompt {
+                        text: filename,
+                        cursor_pos,
+                        ..
+                    }
+                    | Dialog::GeneratorPrompt {
+                        text: filename,
+                        cursor_pos,
+                        ..
+                    },
+                ) = &mut self.dialog
+                {
+                    if let Some(c) = filename[..*cursor_pos].chars().next_back() {
+                        *cursor_pos -= c.len_utf8();
+                    }
+                }
+            }
+
+            Message::DialogCursorRight => {
+                if let Some(Dialog::FolderBrowser {
+                    connection_idx,
+                    folder,
+                    subfolders,
+                    selected,
+                }) = &self.dialog
+                {
+                    if let Some(name) = selected.checked_sub(1).map(|i| &subfolders[i]) {
+                        self.browse_folder(*connection_idx, folder.join(name), None);
+                    }
+                } else if let Some(Dialog::Preferences { selected, prefs }) = &mut self.dialog {
+                    prefs.step(*selected, true);
+                } else if let Some(Dialog::LineSetup { selected, line, .. }) = &mut self.dialog {
+                    line.step(*selected, true);
+                } else if let Some(
+                    Dialog::FileNamePrompt {
+                        filename,
+                        cursor_pos,
+                        ..
+                    }
+                    | Dialog::SearchPrompt {
+                        query: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::ChecksumPrompt {
+                        query: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::FilterPrompt {
+                        query: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::HostPrompt {
+                        host: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::PathPrompt {
+                        path: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::SharePrompt {
+                        address: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::TriggerPrompt {
+                        text: filename,
+                        cursor_pos,
+                        ..
+                    }
+                    | Dialog::WatchPrompt {
+                        query: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::FieldsPrompt {
+                        query: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::LinkTestPrompt {
+                        duration: filename,
+                        cursor_pos,
+                        ..
+                    }
+                    | Dialog::NotePrompt {
+                        text: filename,
+                        cursor_pos,
+                    }
+                    | Dialog::CapturePrompt {
+                        text: filename,
+                        cursor_pos,
+                        ..
+                    }
+                    | Dialog::GeneratorPrompt {
+                        text: filename,
+                        cursor_pos,
+                        ..
+                    },
+                ) = &mut self.dialog
+                {
+                    if let Some(c) = filename[*cursor_pos..].chars().next() {
+                        *cursor_pos += c.len_utf8();
+                    }
+                }
+            }
+
+            Message::DialogToggle => {
+                // Whether CSV and JSON lines exports carry the times
+                if let Some(Dialog::FileNamePrompt {
+                    action: FileAction::Export(export),
+                    ..
+                }) = &mut self.dialog
+                {
+                    if export.format.has_timestamps() {
+                        export.timestamps = !export.timestamps;
+                    }
+                }
+            }
+
+            Message::DialogTab => {
+                // Complete the path before the cursor from the filesystem
+                let mut listed = None;
+                if let Some(Dialog::FileNamePrompt {
+                    filename,
+                    cursor_pos,
+                    ..
+                }) = &mut self.dialog
+                {
+                    let rest = filename.split_off(*cursor_pos);
+                    match paths::complete(filename) {
+                        Completion::Extended(path) => *filename = path,
+                        Completion::Ambiguous(names) => listed = Some(names.join("  ")),
+                        Completion::None => {}
+                    }
+                    *cursor_pos = filename.len();
+                    filename.push_str(&rest);
+                }
+                if let Some(names) = listed {
+                    self.set_status(Severity::Info, names);
+                }
+            }
+        }
+    }
+
+    fn handle_menu_click(&mut self, col: u16, row: u16) {
+        if row == 0 {
+            // Clicking on the menu bar itself — toggle menus
+            let new_menu = menu::titles()
+                .into_iter()
+                .find(|(_, title, x)| (*x..*x + title.chars().count() as u16).contains(&col))
+                .map(|(menu, _, _)| menu);
+            if new_menu == self.open_menu {
+                self.open_menu = None;
+            } else {
+                self.open_menu = new_menu;
+            }
+            return;
+        }
+
+        // Clicking on an open dropdown
+        let Some(open) = self.open_menu else {
+            // No menu open — check for content area clicks
+            self.handle_content_click(col, row);
+            return;
+        };
+
+        // Items start on the row below the dropdown's top border; a click
+        // anywhere else just closes the menu
+        let (x, y) = menu::dropdown_origin(open);
+        let command = if row > y && col.wrapping_sub(x) < menu::DROPDOWN_WIDTH {
+            menu::items(self, open)
+                .get((row - y - 1) as usize)
+                .map(|item| item.command)
+        } else {
+            None
+        };
+        self.open_menu = None;
+        if let Some(command) = command {
+            self.run_menu_command(command);
+        }
+    }
+
+    fn run_menu_command(&mut self, command: MenuCommand) {
+        match command {
+            MenuCommand::Export => self.update(Message::ExportScrollback),
+            MenuCommand::ExportTo => self.open_folder_browser(),
+            MenuCommand::ExportStats => self.open_stats_prompt(),
+            MenuCommand::Quit => self.update(Message::Quit),
+            MenuCommand::New => self.new_connection(),
+            MenuCommand::Duplicate(idx) => self.duplicate_connection(idx),
+            MenuCommand::Close(idx) => {
+                if idx < self.connections.len() {
+                    self.active_connection = idx;
+                    self.dialog = Some(Dialog::ConfirmCloseConnection);
+                }
+            }
+            MenuCommand::RunCommand => self.open_command_menu(),
+            MenuCommand::Mute(idx) => self.toggle_mute(idx),
+            MenuCommand::PowerCycle => self.power_cycle(self.active_connection),
+            MenuCommand::Resend => self.open_resend_menu(),
+            MenuCommand::DryRun => self.toggle_dry_run(self.active_connection),
+            MenuCommand::Share => self.toggle_share(),
+            MenuCommand::Bridge => self.toggle_bridge(),
+            MenuCommand::Sniff => self.open_peer_menu(true),
+            MenuCommand::Triggers => self.dialog = Some(Dialog::TriggerList { selected: 0 }),
+            MenuCommand::Watch => self.open_watch(),
+            MenuCommand::ArmCapture => self.toggle_armed_capture(),
+            MenuCommand::Note => self.open_note(),
+            MenuCommand::ToggleDtr => self.line_control(0),
+            MenuCommand::ToggleRts => self.line_control(1),
+            MenuCommand::SendBreak => self.line_control(2),
+            MenuCommand::TabView => self.view_mode = ViewMode::Tabs,
+            MenuCommand::GridView => self.view_mode = ViewMode::Grid,
+            MenuCommand::SplitView => self.view_mode = ViewMode::Split,
+            MenuCommand::Filter => self.open_filter(),
+            MenuCommand::Pause => self.toggle_pause(),
+            MenuCommand::Wrap => self.toggle_wrap(),
+            MenuCommand::HexPane => self.toggle_hex_pane(),
+            MenuCommand::Plot => self.toggle_plot(),
+            MenuCommand::Fields => self.open_fields(),
+            MenuCommand::GridShape => {
+                let selected = GridShape::ALL
+                    .iter()
+                    .position(|&shape| shape == self.grid_shape)
+                    .unwrap_or(0);
+                self.dialog = Some(Dialog::GridMenu { selected });
+            }
+            MenuCommand::Preferences => {
+                self.dialog = Some(Dialog::Preferences {
+                    selected: 0,
+                    prefs: self.prefs,
+                });
+            }
+            MenuCommand::LineSetup => self.open_line_setup(),
+            MenuCommand::Decoder => {
+                if let Some(conn) = self.connections.get(self.active_connection) {
+                    let selected = DECODERS
+                        .iter()
+                        .position(|d| d.name == conn.decoding.name)
+                        .unwrap_or(0);
+                    self.dialog = Some(Dialog::DecoderMenu {
+                        connection_idx: self.active_connection,
+                        selected,
+                    });
+                }
+            }
+            MenuCommand::SendFile => {
+                self.open_transfer_prompt(self.active_connection, FileAction::SendFile)
+            }
+            MenuCommand::Generator => self.open_generator_menu(),
+            MenuCommand::Upload => self.open_protocol_menu(true),
+            MenuCommand::Download => self.open_protocol_menu(false),
+            MenuCommand::LinkTest => self.open_link_test(),
+            MenuCommand::Checksum => self.open_checksum(),
+            MenuCommand::Diff => self.open_diff_menu(),
+            MenuCommand::LiveStream => self.toggle_live_stream(),
+            MenuCommand::Replay => self.open_replay(),
+            MenuCommand::KeyHelp => self.update(Message::OpenHelp),
+            MenuCommand::AsciiTable => self.update(Message::OpenAsciiTable),
+        }
+    }
+
+    fn handle_content_click(&mut self, col: u16, row: u16) {
+        match self.screen {
+            Screen::ProfileSelect => {
+                let inner_top = 2_u16;
+                let inner_bottom = self.terminal_rows.saturating_sub(2);
+                if row >= inner_top && row < inner_bottom {
+                    let visible_height = (inner_bottom - inner_top) as usize;
+                    let visual_row = (row - inner_top) as usize;
+                    let count = self.config.profiles.len() + 1;
+                    let offset =
+                        list_scroll_offset(self.selected_profile_index, visible_height, count);
+                    let item_index = offset + visual_row;
+                    if item_index < count {
+                        self.selected_profile_index = item_index;
+                        self.select_profile(item_index);
+                    }
+                }
+            }
+            Screen::PortSelect => {
+                // Layout: row 0 = menu bar, row 1 = top border, rows 2+ = items,
+                // bottom = bottom border + status bar
                 let inner_top = 2_u16;
                 let inner_bottom = self.terminal_rows.saturating_sub(2); // status(1) + border(1)
                 if row >= inner_top && row < inner_bottom {
                     let visible_height = (inner_bottom - inner_top) as usize;
                     let visual_row = (row - inner_top) as usize;
-                    let count = self.available_ports.len();
+                    let count = self.port_list_len();
                     let offset =
                         list_scroll_offset(self.selected_port_index, visible_height, count);
                     let item_index = offset + visual_row;
                     if item_index < count {
                         self.selected_port_index = item_index;
-                        self.screen = Screen::BaudSelect;
+                        self.choose_port();
                     }
                 }
             }
@@ -820,7 +3420,7 @@ impl App {
                 if row >= inner_top && row < inner_bottom {
                     let visible_height = (inner_bottom - inner_top) as usize;
                     let visual_row = (row - inner_top) as usize;
-                    let count = BAUD_RATES.len();
+                    let count = BAUD_RATES.len() + 1;
                     let offset =
                         list_scroll_offset(self.selected_baud_index, visible_height, count);
                     let item_index = offset + visual_row;
@@ -890,6 +3490,22 @@ impl App {
                     let item_index = offset + visual_row;
                     if item_index < count {
                         self.selected_display_mode_index = item_index;
+                        self.choose_display_mode();
+                    }
+                }
+            }
+            Screen::PacingSelect => {
+                let inner_top = 2_u16;
+                let inner_bottom = self.terminal_rows.saturating_sub(2);
+                if row >= inner_top && row < inner_bottom {
+                    let visible_height = (inner_bottom - inner_top) as usize;
+                    let visual_row = (row - inner_top) as usize;
+                    let count = PACING_OPTIONS.len();
+                    let offset =
+                        list_scroll_offset(self.selected_pacing_index, visible_height, count);
+                    let item_index = offset + visual_row;
+                    if item_index < count {
+                        self.selected_pacing_index = item_index;
                         self.connect_selected();
                     }
                 }
@@ -900,11 +3516,39 @@ impl App {
                 }
 
                 // Layout: row 0 = menu bar, row 1+ = content area
-                // Content splits into: main_area, input_area(3 rows), status_bar(1 row)
+                // Content splits into: main_area, input_area(input_height rows), status_bar(1 row)
                 let content_top = 1_u16;
-                let status_and_input = 4_u16;
+                let status_and_input = self.input_height + 1;
                 let main_bottom = self.terminal_rows.saturating_sub(status_and_input);
 
+                if row == main_bottom {
+                    self.dragging_divider = true;
+                    return;
+                }
+
+                let scrollbar = crate::ui::scrollback_areas(self)
+                    .into_iter()
+                    .find(|&(idx, area)| self.on_scrollbar(idx, area, col, row));
+                if let Some((idx, area)) = scrollbar {
+                    self.dragging_scrollbar = Some((idx, area));
+                    self.drag_scrollbar(idx, area, row);
+                    return;
+                }
+
+                if row == self.terminal_rows.saturating_sub(1) && self.shows_function_keys() {
+                    let width = self
+                        .terminal_cols
+                        .saturating_sub(crate::ui::status_indicators_width(self));
+                    let key = function_key_bar(&self.keymap, width)
+                        .into_iter()
+                        .find(|(_, _, cols)| cols.contains(&col))
+                        .and_then(|(key, _, _)| function_key(&self.keymap, key));
+                    if let Some(msg) = key {
+                        self.update(msg);
+                    }
+                    return;
+                }
+
                 match self.view_mode {
                     ViewMode::Tabs => {
                         if row == content_top {
@@ -919,15 +3563,38 @@ impl App {
                             self.handle_grid_click(col, row, content_top, main_bottom);
                         }
                     }
+                    ViewMode::Split => {
+                        if row >= content_top && row < main_bottom {
+                            self.handle_split_click(col, row, content_top, main_bottom);
+                        }
+                    }
                 }
             }
         }
     }
 
+    /// Open the context menu of the tab under a right click.
+    fn handle_right_click(&mut self, col: u16, row: u16) {
+        self.open_menu = None;
+        // Tab bar sits right below the menu bar
+        if self.screen != Screen::Connected || self.view_mode != ViewMode::Tabs || row != 1 {
+            return;
+        }
+        let mut x = 0_u16;
+        for (i, conn) in self.connections.iter().enumerate() {
+            let label_width = tab_width(conn);
+            if col >= x && col < x + label_width {
+                self.open_menu = Some(OpenMenu::Tab { idx: i, x });
+                return;
+            }
+            x += label_width;
+        }
+    }
+
     fn handle_tab_bar_click(&mut self, col: u16) {
         let mut x = 0_u16;
         for (i, conn) in self.connections.iter().enumerate() {
-            let label_width = conn.label().len() as u16 + 2; // " label "
+            let label_width = tab_width(conn);
             if col >= x && col < x + label_width {
                 self.active_connection = i;
                 return;
@@ -944,14 +3611,31 @@ impl App {
             x += new_label_width;
         }
         // Check [+] button (only shown when no pending)
-        if self.pending_connection.is_none() && col >= x && col < x + 5 {
-            self.pending_connection = Some(PendingScreen::PortSelect);
-            self.refresh_ports();
-            self.active_connection = self.connections.len();
+        if col >= x && col < x + 5 {
+            self.new_connection();
         }
     }
 
     fn handle_grid_click(&mut self, col: u16, row: u16, grid_top: u16, grid_bottom: u16) {
+        let Some((idx, cell_top, cell_bottom)) = self.grid_cell_at(col, row, grid_top, grid_bottom)
+        else {
+            return;
+        };
+        self.active_connection = idx;
+        if idx == self.connections.len() {
+            self.handle_pending_click(row, cell_top, cell_bottom);
+        }
+    }
+
+    /// The grid cell under a screen position: its index (the pending cell
+    /// comes after the connections) and its top and bottom rows.
+    fn grid_cell_at(
+        &self,
+        col: u16,
+        row: u16,
+        grid_top: u16,
+        grid_bottom: u16,
+    ) -> Option<(usize, u16, u16)> {
         let total = self.connections.len()
             + if self.pending_connection.is_some() {
                 1
@@ -959,59 +3643,157 @@ impl App {
                 0
             };
         if total == 0 {
-            return;
+            return None;
         }
 
         let grid_height = grid_bottom - grid_top;
         let grid_width = self.terminal_cols;
 
-        let grid_cols = (total as f64).sqrt().ceil() as usize;
-        let grid_rows = total.div_ceil(grid_cols);
+        let (grid_cols, grid_rows) = self.grid_shape.dims(total);
 
         let cell_h = grid_height as usize / grid_rows;
         let cell_w = grid_width as usize / grid_cols;
 
         if cell_h == 0 || cell_w == 0 {
-            return;
+            return None;
         }
 
         let r = (row - grid_top) as usize / cell_h;
         let c = col as usize / cell_w;
         let idx = r * grid_cols + c;
+        if r >= grid_rows || c >= grid_cols || idx >= total {
+            return None;
+        }
+        let cell_top = grid_top + (r as u16) * (cell_h as u16);
+        Some((idx, cell_top, cell_top + cell_h as u16))
+    }
 
-        if idx < self.connections.len() {
+    /// With `focus_follows_mouse`, the grid cell under the pointer becomes
+    /// the active connection.
+    fn handle_mouse_move(&mut self, col: u16, row: u16) {
+        if !self.config.focus_follows_mouse
+            || self.screen != Screen::Connected
+            || self.view_mode != ViewMode::Grid
+            || self.open_menu.is_some()
+            || self.dragging_divider
+        {
+            return;
+        }
+        // Same layout as a click: menu bar on row 0, Send box and status bar below the grid
+        let grid_top = 1_u16;
+        let grid_bottom = self.terminal_rows.saturating_sub(self.input_height + 1);
+        if row < grid_top || row >= grid_bottom {
+            return;
+        }
+        if let Some((idx, _, _)) = self.grid_cell_at(col, row, grid_top, grid_bottom) {
             self.active_connection = idx;
-        } else if idx == self.connections.len() && self.pending_connection.is_some() {
-            self.active_connection = self.connections.len();
-            let cell_top = grid_top + (r as u16) * (cell_h as u16);
-            let cell_bottom = cell_top + cell_h as u16;
-            self.handle_pending_click(row, cell_top, cell_bottom);
         }
     }
 
-    fn handle_pending_click(&mut self, row: u16, cell_top: u16, cell_bottom: u16) {
-        // Cell has Block with Borders::ALL — inner content is 1 row inside each edge
-        let inner_top = cell_top + 1;
-        let inner_bottom = cell_bottom.saturating_sub(1);
-        if row < inner_top || row >= inner_bottom {
+    /// Whether (col, row) is on the scrollbar of connection `idx`, drawn
+    /// over the right border of `area` between its arrows. There is none
+    /// while every line fits.
+    fn on_scrollbar(&self, idx: usize, area: Rect, col: u16, row: u16) -> bool {
+        let Some(conn) = self.connections.get(idx) else {
+            return false;
+        };
+        let visible = area.height.saturating_sub(2) as usize;
+        col + 1 == area.right()
+            && row > area.y
+            && row + 1 < area.bottom()
+            && conn.shown_lines().len() > visible
+    }
+
+    /// Scroll connection `idx` in proportion to where `row` falls on its
+    /// scrollbar: the top row shows the oldest lines, the bottom one follows.
+    fn drag_scrollbar(&mut self, idx: usize, area: Rect, row: u16) {
+        let Some(conn) = self.connections.get_mut(idx) else {
             return;
-        }
+        };
+        let visible = area.height.saturating_sub(2) as usize;
+        let range = conn.shown_lines().len().saturating_sub(visible);
+        // Thumb positions run from below the ↑ arrow to above the ↓ arrow
+        let track = area.height.saturating_sub(3).max(1) as usize;
+        let pos = (row.saturating_sub(area.y + 1) as usize).min(track);
+        conn.scroll_to(range - (range * pos + track / 2) / track);
+    }
 
-        let visible_height = (inner_bottom - inner_top) as usize;
-        let visual_row = (row - inner_top) as usize;
+    /// Clamp and apply a new Send box height, leaving the scrollback at least
+    /// a few rows.
+    fn set_input_height(&mut self, height: u16) {
+        // menu bar + status bar + minimal main area
+        let max = self.terminal_rows.saturating_sub(1 + 1 + 4);
+        self.input_height = height.min(max).max(MIN_INPUT_HEIGHT);
+    }
 
-        match self.pending_connection {
-            Some(PendingScreen::PortSelect) => {
-                let count = self.available_ports.len();
-                let offset = list_scroll_offset(self.selected_port_index, visible_height, count);
-                let item_index = offset + visual_row;
-                if item_index < count {
-                    self.selected_port_index = item_index;
-                    self.pending_connection = Some(PendingScreen::BaudSelect);
+    fn handle_split_click(&mut self, col: u16, row: u16, top: u16, bottom: u16) {
+        if self.split_secondary().is_none() {
+            if self.is_pending_active() {
+                self.handle_pending_click(row, top, bottom);
+            }
+            return;
+        }
+        let (in_secondary, pane_top, pane_bottom) = match self.split_direction {
+            SplitDirection::Horizontal => (col >= self.terminal_cols / 2, top, bottom),
+            SplitDirection::Vertical => {
+                let mid = top + (bottom - top) / 2;
+                if row >= mid {
+                    (true, mid, bottom)
+                } else {
+                    (false, top, mid)
+                }
+            }
+        };
+        if in_secondary {
+            self.swap_split_panes();
+        } else if self.is_pending_active() {
+            self.handle_pending_click(row, pane_top, pane_bottom);
+        }
+    }
+
+    /// Index of the connection shown in the split view's second pane: the pinned
+    /// connection, or the first other connection if nothing (else) is pinned.
+    pub fn split_secondary(&self) -> Option<usize> {
+        self.pinned_connection
+            .and_then(|id| self.connections.iter().position(|c| c.id == id))
+            .filter(|&i| i != self.active_connection)
+            .or_else(|| (0..self.connections.len()).find(|&i| i != self.active_connection))
+    }
+
+    fn swap_split_panes(&mut self) {
+        let Some(secondary) = self.split_secondary() else {
+            return;
+        };
+        if let Some(conn) = self.connections.get(self.active_connection) {
+            self.pinned_connection = Some(conn.id);
+        }
+        self.active_connection = secondary;
+    }
+
+    fn handle_pending_click(&mut self, row: u16, cell_top: u16, cell_bottom: u16) {
+        // Cell has Block with Borders::ALL — inner content is 1 row inside each edge
+        let inner_top = cell_top + 1;
+        let inner_bottom = cell_bottom.saturating_sub(1);
+        if row < inner_top || row >= inner_bottom {
+            return;
+        }
+
+        let visible_height = (inner_bottom - inner_top) as usize;
+        let visual_row = (row - inner_top) as usize;
+
+        match self.pending_connection {
+            Some(PendingScreen::PortSelect) => {
+                let count = self.port_list_len();
+                let offset = list_scroll_offset(self.selected_port_index, visible_height, count);
+                let item_index = offset + visual_row;
+                if item_index < count {
+                    self.selected_port_index = item_index;
+                    self.choose_port();
                 }
             }
             Some(PendingScreen::BaudSelect) => {
-                let count = BAUD_RATES.len();
+                // The rates and "Auto"
+                let count = BAUD_RATES.len() + 1;
                 let offset = list_scroll_offset(self.selected_baud_index, visible_height, count);
                 let item_index = offset + visual_row;
                 if item_index < count {
@@ -1056,6 +3838,15 @@ impl App {
                 let item_index = offset + visual_row;
                 if item_index < count {
                     self.selected_display_mode_index = item_index;
+                    self.choose_display_mode();
+                }
+            }
+            Some(PendingScreen::PacingSelect) => {
+                let count = PACING_OPTIONS.len();
+                let offset = list_scroll_offset(self.selected_pacing_index, visible_height, count);
+                let item_index = offset + visual_row;
+                if item_index < count {
+                    self.selected_pacing_index = item_index;
                     self.connect_selected();
                 }
             }
@@ -1067,23 +3858,65 @@ impl App {
         match self.dialog.take() {
             Some(Dialog::ConfirmCloseConnection) => {
                 let idx = self.active_connection;
-                let filename = self.generate_filename(idx);
+                let filename = self.generate_filename(idx, ExportFormat::Text.extension());
                 let cursor_pos = filename.len();
                 self.dialog = Some(Dialog::FileNamePrompt {
                     connection_idx: idx,
                     filename,
                     cursor_pos,
+                    action: FileAction::Export(ExportSettings::default()),
                     after: AfterSave::CloseConnection,
                 });
             }
+            Some(Dialog::ConfirmZmodemReceive { connection_id }) => {
+                if let Some(idx) = self.connections.iter().position(|c| c.id == connection_id) {
+                    self.download(idx, ".".to_string(), Protocol::Zmodem);
+                }
+            }
             Some(Dialog::ConfirmQuit) => {
                 let indices: Vec<usize> = (0..self.connections.len()).collect();
                 self.start_save_chain(indices);
             }
+            Some(Dialog::Welcome) => {
+                self.create_starter_config();
+            }
             _ => {}
         }
     }
 
+    /// Write the starter config and switch to it, so its sample profile shows
+    /// up on the profile list straight away.
+    fn create_starter_config(&mut self) {
+        let path = match Config::write_starter() {
+            Ok(path) => path,
+            Err(e) => {
+                self.set_status(
+                    Severity::Error,
+                    i18n::tf("status.starter_config_failed", &[&format!("{:#}", e)]),
+                );
+                return;
+            }
+        };
+        match Config::load() {
+            Ok(config) => {
+                self.highlighter = Highlighter::new(&config.highlights).unwrap_or_default();
+                self.config = config;
+                if self.screen == Screen::PortSelect && !self.config.profiles.is_empty() {
+                    self.screen = Screen::ProfileSelect;
+                }
+                self.refresh_ports();
+                self.set_status(
+                    Severity::Success,
+                    i18n::tf("status.starter_config", &[&path.display()]),
+                );
+            }
+            Err(e) => self.set_status(
+                Severity::Error,
+                i18n::tf("status.config_error", &[&format!("{:#}", e)]),
+            ),
+        }
+    }
+
     fn handle_dialog_no(&mut self) {
         match self.dialog.take() {
             Some(Dialog::ConfirmCloseConnection) => {
@@ -1092,134 +3925,2349 @@ impl App {
             Some(Dialog::ConfirmQuit) => {
                 self.should_quit = true;
             }
+            Some(Dialog::ConfirmZmodemReceive { connection_id }) => {
+                // Tell the sender to give up rather than let it time out
+                if let Some(conn) = self.connection_by_id(connection_id) {
+                    let mut abort = vec![0x18; 8];
+                    abort.extend_from_slice(&[0x08; 8]);
+                    conn.send(&abort);
+                }
+            }
             _ => {}
         }
     }
 
     fn handle_dialog_confirm(&mut self) {
-        if let Some(Dialog::FileNamePrompt {
-            connection_idx,
-            filename,
-            after,
-            ..
-        }) = self.dialog.take()
-        {
-            self.export_connection(connection_idx, &filename);
-            match after {
-                AfterSave::Nothing => {}
-                AfterSave::CloseConnection => {
-                    self.do_close_active_connection();
+        match self.dialog.take() {
+            Some(Dialog::FileNamePrompt {
+                connection_idx,
+                filename,
+                action: FileAction::LiveStream,
+                ..
+            }) => {
+                self.start_live_stream(connection_idx, &filename);
+            }
+            Some(Dialog::FileNamePrompt {
+                filename,
+                action: FileAction::Replay(speed),
+                ..
+            }) => {
+                self.follow_stream(filename, Some(speed));
+            }
+            Some(Dialog::FileNamePrompt {
+                connection_idx,
+                filename,
+                action: FileAction::Diff,
+                ..
+            }) => {
+                self.diff_with_log(connection_idx, &filename);
+            }
+            Some(Dialog::DiffMenu {
+                connection_idx,
+                targets,
+                selected,
+            }) => match targets.get(selected) {
+                Some(&peer) => self.diff_with_connection(connection_idx, peer),
+                None => {
+                    self.dialog = Some(Dialog::FileNamePrompt {
+                        connection_idx,
+                        filename: String::new(),
+                        cursor_pos: 0,
+                        action: FileAction::Diff,
+                        after: AfterSave::Nothing,
+                    });
+                }
+            },
+            Some(Dialog::FileNamePrompt {
+                connection_idx,
+                filename,
+                action: FileAction::SendFile,
+                ..
+            }) => {
+                self.send_file(connection_idx, &filename);
+            }
+            Some(Dialog::FileNamePrompt {
+                connection_idx,
+                filename,
+                action: FileAction::Upload(protocol),
+                ..
+            }) => {
+                self.upload(connection_idx, &filename, protocol);
+            }
+            Some(Dialog::FileNamePrompt {
+                connection_idx,
+                filename,
+                action: FileAction::Download(protocol),
+                ..
+            }) => {
+                self.download(connection_idx, filename, protocol);
+            }
+            Some(Dialog::ResendMenu {
+                connection_idx,
+                selected,
+            }) => {
+                self.resend(connection_idx, selected + 1);
+            }
+            Some(Dialog::BridgeMenu {
+                connection_idx,
+                targets,
+                selected,
+                sniff,
+            }) => {
+                let (peer, both_ways) = targets[selected];
+                if sniff {
+                    self.start_sniffer(connection_idx, peer, both_ways);
+                } else {
+                    self.start_bridge(connection_idx, peer, both_ways);
+                }
+            }
+            Some(Dialog::CopyAs { bytes, selected }) => {
+                let format = ArrayFormat::ALL[selected];
+                let text = format.format(&bytes);
+                match arboard::Clipboard::new().and_then(|mut c| c.set_text(text)) {
+                    Ok(()) => self.set_status(
+                        Severity::Success,
+                        i18n::tf("status.copied_as", &[&bytes.len(), &format.name()]),
+                    ),
+                    Err(e) => self.set_status(Severity::Error, i18n::tf("status.clipboard", &[&e])),
+                }
+            }
+            Some(Dialog::FolderBrowser {
+                connection_idx,
+                folder,
+                subfolders,
+                selected,
+            }) => {
+                let folder = match selected.checked_sub(1) {
+                    Some(i) => folder.join(&subfolders[i]),
+                    None => folder,
+                };
+                let name = self.generate_filename(connection_idx, ExportFormat::Text.extension());
+                let filename = folder.join(name).to_string_lossy().into_owned();
+                let cursor_pos = filename.len();
+                self.dialog = Some(Dialog::FileNamePrompt {
+                    connection_idx,
+                    filename,
+                    cursor_pos,
+                    action: FileAction::Export(ExportSettings::default()),
+                    after: AfterSave::Nothing,
+                });
+            }
+            Some(Dialog::GeneratorMenu {
+                connection_idx,
+                selected,
+            }) => {
+                let kind = PatternKind::ALL[selected];
+                let generator = GeneratorSettings {
+                    kind,
+                    bytes: String::new(),
+                };
+                let field = if kind.takes_bytes() {
+                    GeneratorField::Bytes
+                } else {
+                    GeneratorField::Count
+                };
+                self.open_generator_prompt(connection_idx, generator, field);
+            }
+            Some(Dialog::GeneratorPrompt {
+                connection_idx,
+                generator,
+                field,
+                text,
+                cursor_pos,
+            }) => self.generator_field(connection_idx, generator, field, text, cursor_pos),
+            Some(Dialog::ProtocolMenu {
+                connection_idx,
+                upload,
+                selected,
+            }) => {
+                let protocol = Protocol::ALL[selected];
+                let action = if upload {
+                    FileAction::Upload(protocol)
+                } else {
+                    FileAction::Download(protocol)
+                };
+                self.open_transfer_prompt(connection_idx, action);
+            }
+            Some(Dialog::FileNamePrompt {
+                connection_idx,
+                filename,
+                action: FileAction::ExportStats,
+                ..
+            }) => match self.write_stats(connection_idx, &filename) {
+                Ok(()) => self.set_status(
+                    Severity::Success,
+                    i18n::tf("status.stats_written", &[&filename]),
+                ),
+                Err(e) => self.set_status(Severity::Error, i18n::tf("status.stats_failed", &[&e])),
+            },
+            Some(Dialog::FileNamePrompt {
+                connection_idx,
+                filename,
+                action,
+                after,
+                ..
+            }) => {
+                let export = match action {
+                    FileAction::Export(export) => export,
+                    _ => ExportSettings::default(),
+                };
+                self.export_connection(connection_idx, &filename, export);
+                match after {
+                    AfterSave::Nothing => {}
+                    AfterSave::CloseConnection => {
+                        self.do_close_active_connection();
+                    }
+                    AfterSave::QuitNext { remaining } => {
+                        self.start_save_chain(remaining);
+                    }
+                }
+            }
+            Some(Dialog::CommandMenu {
+                connection_idx,
+                commands,
+                selected,
+            }) => {
+                if let (Some(cmd), Some(conn)) =
+                    (commands.get(selected), self.connections.get(connection_idx))
+                {
+                    crate::command::spawn(
+                        conn.id,
+                        cmd.name.clone(),
+                        cmd.run.clone(),
+                        conn.command_vars(),
+                        self.serial_tx.clone(),
+                    );
+                }
+            }
+            Some(Dialog::FilterPrompt { query, .. }) => {
+                let Some(conn) = self.connections.get_mut(self.active_connection) else {
+                    return;
+                };
+                if query.is_empty() {
+                    conn.filter = None;
+                    return;
+                }
+                match LineFilter::parse(&query) {
+                    Ok(filter) => {
+                        conn.filter = Some(filter);
+                        conn.scroll_to(0);
+                    }
+                    Err(e) => self.set_status(Severity::Warning, e),
+                }
+            }
+            Some(Dialog::WatchPrompt { query, .. }) => {
+                let Some(conn) = self.connections.get_mut(self.active_connection) else {
+                    return;
+                };
+                if query.is_empty() {
+                    conn.watch = None;
+                    return;
+                }
+                match Regex::new(&query) {
+                    Ok(watch) => conn.watch = Some(watch),
+                    Err(e) => self
+                        .set_status(Severity::Warning, i18n::tf("status.invalid_pattern", &[&e])),
+                }
+            }
+            Some(Dialog::FieldsPrompt { query, cursor_pos }) => {
+                let Some(conn) = self.connections.get_mut(self.active_connection) else {
+                    return;
+                };
+                if query.is_empty() {
+                    conn.fields = None;
+                    return;
+                }
+                match FieldTable::new(&query) {
+                    Ok(table) => {
+                        conn.plot = None;
+                        conn.fields = Some(table);
+                    }
+                    Err(e) => {
+                        self.dialog = Some(Dialog::FieldsPrompt { query, cursor_pos });
+                        self.set_status(
+                            Severity::Warning,
+                            i18n::tf("status.invalid_pattern", &[&e]),
+                        );
+                    }
+                }
+            }
+            Some(Dialog::LinkTestPrompt {
+                connection_idx,
+                duration,
+                cursor_pos,
+            }) => {
+                let Some(conn) = self.connections.get_mut(connection_idx) else {
+                    return;
+                };
+                if conn.busy.is_some() {
+                    return;
+                }
+                match LinkTest::parse_duration(&duration) {
+                    Some(duration) => {
+                        conn.start_link_test(duration);
+                        self.dialog = Some(Dialog::Transfer {
+                            connection_id: conn.id,
+                        });
+                    }
+                    None => {
+                        self.dialog = Some(Dialog::LinkTestPrompt {
+                            connection_idx,
+                            duration,
+                            cursor_pos,
+                        });
+                        self.set_status(
+                            Severity::Warning,
+                            i18n::t("status.link_test_duration").into(),
+                        );
+                    }
+                }
+            }
+            Some(Dialog::NotePrompt { text, .. }) => {
+                if let Some(conn) = self.connections.get_mut(self.active_connection) {
+                    conn.annotate(&text);
+                }
+            }
+            Some(Dialog::HostPrompt { host, cursor_pos }) => {
+                self.choose_host(host, cursor_pos);
+            }
+            Some(Dialog::PathPrompt { path, cursor_pos }) => {
+                self.choose_path(path, cursor_pos);
+            }
+            Some(Dialog::SharePrompt { address, .. }) => {
+                self.start_share(&address);
+            }
+            Some(Dialog::ChecksumPrompt { query, .. }) => {
+                let bytes = match crate::hex::parse(&query) {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        self.set_status(Severity::Warning, e);
+                        return;
+                    }
+                };
+                let text = checksum::all(&bytes)
+                    .into_iter()
+                    .map(|(name, value)| format!("{}: {}", name, value))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                match arboard::Clipboard::new().and_then(|mut c| c.set_text(text)) {
+                    Ok(()) => self.set_status(
+                        Severity::Success,
+                        i18n::tf("status.copied_checksums", &[&bytes.len()]),
+                    ),
+                    Err(e) => self.set_status(Severity::Error, i18n::tf("status.clipboard", &[&e])),
+                }
+            }
+            Some(Dialog::GridMenu { selected }) => {
+                self.grid_shape = GridShape::ALL[selected];
+                self.view_mode = ViewMode::Grid;
+            }
+            Some(Dialog::Preferences { prefs, .. }) => {
+                // Open connections keep reading as they were opened
+                let changed = prefs.read() != self.prefs.read() && !self.connections.is_empty();
+                self.prefs = prefs;
+                if changed {
+                    self.set_status(Severity::Info, i18n::t("status.read_settings").to_string());
+                }
+            }
+            Some(Dialog::LineSetup {
+                connection_idx,
+                line,
+                ..
+            }) => {
+                if let Some(conn) = self.connections.get_mut(connection_idx) {
+                    conn.set_line_settings(
+                        line.baud_rate,
+                        line.data_bits,
+                        line.parity,
+                        line.stop_bits,
+                    );
+                }
+            }
+            Some(Dialog::DecoderMenu {
+                connection_idx,
+                selected,
+            }) => {
+                let info = &DECODERS[selected];
+                if let Some(conn) = self.connections.get_mut(connection_idx) {
+                    if conn.decoding.name != info.name {
+                        conn.set_decoder(info.name);
+                        let text = i18n::tf("status.decoding_as", &[&conn.port_name, &info.label]);
+                        self.set_status(Severity::Info, text);
+                    }
                 }
-                AfterSave::QuitNext { remaining } => {
-                    self.start_save_chain(remaining);
+            }
+            Some(Dialog::TriggerList { selected }) => {
+                let index = (selected < self.triggers.rules.len()).then_some(selected);
+                let rule =
+                    index.map_or_else(TriggerRule::default, |i| self.triggers.rules[i].clone());
+                self.open_trigger_prompt(index, rule, TriggerField::Pattern);
+            }
+            Some(Dialog::TriggerPrompt {
+                index,
+                rule,
+                field,
+                text,
+                cursor_pos,
+            }) => {
+                self.trigger_field(index, rule, field, text, cursor_pos);
+            }
+            Some(Dialog::CapturePrompt {
+                connection_idx,
+                capture,
+                field,
+                text,
+                cursor_pos,
+            }) => {
+                self.capture_field(connection_idx, capture, field, text, cursor_pos);
+            }
+            Some(Dialog::SearchPrompt { query, .. }) => {
+                if query.is_empty() {
+                    self.search = None;
+                } else {
+                    self.search = Some(SearchState {
+                        query,
+                        current: None,
+                    });
+                    self.search_step(true);
                 }
             }
+            _ => {}
         }
     }
 
-    fn start_save_chain(&mut self, mut indices: Vec<usize>) {
-        if let Some(idx) = indices.first().copied() {
-            indices.remove(0);
-            let filename = self.generate_filename(idx);
-            let cursor_pos = filename.len();
-            self.dialog = Some(Dialog::FileNamePrompt {
-                connection_idx: idx,
-                filename,
-                cursor_pos,
-                after: AfterSave::QuitNext { remaining: indices },
-            });
-        } else {
-            self.should_quit = true;
+    /// List the active connection's Send bar history, newest first, to resend
+    /// the last N entries.
+    fn open_resend_menu(&mut self) {
+        let Some(conn) = self.connections.get(self.active_connection) else {
+            return;
+        };
+        if conn.history.is_empty() {
+            self.set_status(Severity::Warning, i18n::t("status.nothing_sent").into());
+            return;
         }
+        self.dialog = Some(Dialog::ResendMenu {
+            connection_idx: self.active_connection,
+            selected: 0,
+        });
     }
 
-    fn do_close_active_connection(&mut self) {
-        if self.connections.is_empty() {
+    /// Resend a connection's last `count` Send bar entries.
+    fn resend(&mut self, idx: usize, count: usize) {
+        let Some(conn) = self.connections.get_mut(idx) else {
+            return;
+        };
+        if !conn.can_send() {
+            self.set_status(
+                Severity::Warning,
+                i18n::t("status.connection_read_only").into(),
+            );
             return;
         }
-        let idx = self.active_connection;
-        self.connections[idx].close();
-        self.connections.remove(idx);
-        if self.connections.is_empty() {
-            self.screen = Screen::PortSelect;
-            self.pending_connection = None;
-            self.refresh_ports();
-        } else if self.active_connection >= self.connections.len() {
-            self.active_connection = self.connections.len() - 1;
+        if let Some(text) = conn.busy_text() {
+            self.set_status(Severity::Warning, i18n::tf("status.busy", &[&text]));
+            return;
+        }
+        conn.scroll_to(0);
+        match conn.resend(count) {
+            Ok(msg) => self.set_status(Severity::Success, msg),
+            Err(e) => self.set_status(Severity::Warning, e),
         }
     }
 
-    fn connect_selected(&mut self) {
-        if self.available_ports.is_empty() {
-            return;
+    /// Freeze or resume the active connection's view.
+    fn toggle_pause(&mut self) {
+        if let Some(conn) = self.connections.get_mut(self.active_connection) {
+            conn.toggle_pause();
         }
-        let port_name = self.available_ports[self.selected_port_index].name.clone();
-        let baud_rate = BAUD_RATES[self.selected_baud_index];
-        let data_bits = DATA_BITS_OPTIONS[self.selected_data_bits_index].1;
-        let parity = PARITY_OPTIONS[self.selected_parity_index].1;
-        let stop_bits = STOP_BITS_OPTIONS[self.selected_stop_bits_index].1;
-        let display_mode = DISPLAY_MODE_OPTIONS[self.selected_display_mode_index].1;
-        let id = self.next_connection_id;
-        self.next_connection_id += 1;
+    }
 
-        let conn = Connection::new(
-            id,
-            port_name,
-            baud_rate,
-            data_bits,
-            parity,
-            stop_bits,
-            display_mode,
-            self.serial_tx.clone(),
-        );
-        self.connections.push(conn);
-        self.active_connection = self.connections.len() - 1;
-        self.pending_connection = None;
-        self.screen = Screen::Connected;
+    fn toggle_wrap(&mut self) {
+        if let Some(conn) = self.connections.get_mut(self.active_connection) {
+            conn.toggle_wrap();
+        }
     }
 
-    fn generate_filename(&self, connection_idx: usize) -> String {
-        let conn = &self.connections[connection_idx];
-        let safe_name = conn.port_name.replace(['/', '\\', ':'], "_");
-        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-        format!("{}_{}_{}.txt", safe_name, conn.baud_rate, timestamp)
+    /// Show the active connection's lines in hex beside the text, or stop.
+    fn toggle_hex_pane(&mut self) {
+        let Some(conn) = self.connections.get_mut(self.active_connection) else {
+            return;
+        };
+        if !conn.hex_pane && conn.display_mode == DisplayMode::HexDump {
+            self.set_status(Severity::Info, i18n::t("status.hex_pane_needs_text").into());
+            return;
+        }
+        conn.hex_pane = !conn.hex_pane;
     }
 
-    fn export_connection(&mut self, connection_idx: usize, filename: &str) {
-        if connection_idx >= self.connections.len() {
+    /// Chart the numbers the active connection receives instead of its
+    /// text, or go back to the text.
+    fn toggle_plot(&mut self) {
+        let Some(conn) = self.connections.get_mut(self.active_connection) else {
+            return;
+        };
+        if conn.plot.is_none() && conn.display_mode == DisplayMode::HexDump {
+            self.set_status(Severity::Info, i18n::t("status.plot_needs_text").into());
             return;
         }
-        let conn = &self.connections[connection_idx];
-        let content: String = conn
-            .scrollback_with_partial()
-            .collect::<Vec<_>>()
-            .join("\n");
+        conn.toggle_plot();
+        if conn.plot.is_some() {
+            conn.fields = None;
+        }
+    }
 
-        match std::fs::write(filename, &content) {
-            Ok(()) => {
-                self.status_message = Some((format!("Exported to {}", filename), Instant::now()));
-            }
-            Err(e) => {
-                self.status_message = Some((format!("Export failed: {}", e), Instant::now()));
-            }
+    /// Prompt for the active connection's line filter, pre-filled with the
+    /// current one. Confirming an empty filter removes it.
+    fn open_filter(&mut self) {
+        if let Some(conn) = self.connections.get(self.active_connection) {
+            let query = conn.filter.as_ref().map(|f| f.text()).unwrap_or_default();
+            let cursor_pos = query.len();
+            self.dialog = Some(Dialog::FilterPrompt { query, cursor_pos });
         }
     }
 
-    pub fn status_text(&self) -> Option<&str> {
-        if let Some((msg, time)) = &self.status_message {
-            if time.elapsed().as_secs() < 3 {
-                return Some(msg);
+    /// List what the active connection's scrollback can be compared with.
+    fn open_diff_menu(&mut self) {
+        let Some(conn) = self.connections.get(self.active_connection) else {
+            return;
+        };
+        let targets = self
+            .connections
+            .iter()
+            .filter(|c| c.id != conn.id)
+            .map(|c| c.id)
+            .collect();
+        self.dialog = Some(Dialog::DiffMenu {
+            connection_idx: self.active_connection,
+            targets,
+            selected: 0,
+        });
+    }
+
+    /// Diff connection `idx`'s scrollback against the connection with id `peer`.
+    fn diff_with_connection(&mut self, idx: usize, peer: usize) {
+        let Some(conn) = self.connections.get(idx) else {
+            return;
+        };
+        let Some(peer_conn) = self.connections.iter().find(|c| c.id == peer) else {
+            return;
+        };
+        let (old, new) = (plain_scrollback(conn), plain_scrollback(peer_conn));
+        self.show_diff(conn.label(), peer_conn.label(), &old, &new);
+    }
+
+    /// Diff a saved log file against connection `idx`'s scrollback.
+    fn diff_with_log(&mut self, idx: usize, filename: &str) {
+        let Some(conn) = self.connections.get(idx) else {
+            return;
+        };
+        let log = match std::fs::read(filename) {
+            Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+            Err(e) => {
+                self.set_status(
+                    Severity::Error,
+                    i18n::tf("status.cant_read", &[&filename, &e]),
+                );
+                return;
             }
+        };
+        let old: Vec<String> = log
+            .lines()
+            .map(|l| crate::ansi::strip(l).into_owned())
+            .collect();
+        let new = plain_scrollback(conn);
+        self.show_diff(filename.to_string(), conn.label(), &old, &new);
+    }
+
+    /// Open the diff view from `old` to `new`, or just say they're the same.
+    fn show_diff(&mut self, old: String, new: String, old_lines: &[String], new_lines: &[String]) {
+        let old_lines: Vec<&str> = old_lines.iter().map(String::as_str).collect();
+        let new_lines: Vec<&str> = new_lines.iter().map(String::as_str).collect();
+        let lines = crate::diff::unified(&old_lines, &new_lines);
+        if lines.is_empty() {
+            self.set_status(Severity::Info, i18n::tf("status.same", &[&old, &new]));
+            return;
         }
-        None
+        self.dialog = Some(Dialog::Diff {
+            old,
+            new,
+            lines,
+            scroll: 0,
+        });
     }
 
-    fn connection_by_id(&mut self, id: usize) -> Option<&mut Connection> {
-        self.connections.iter_mut().find(|c| c.id == id)
+    /// Checksum dialog, filled with the selected line's bytes if there is one.
+    fn open_checksum(&mut self) {
+        let query = self
+            .cursor_bytes()
+            .map(|bytes| crate::hex::format(&bytes))
+            .unwrap_or_default();
+        let cursor_pos = query.len();
+        self.dialog = Some(Dialog::ChecksumPrompt { query, cursor_pos });
     }
-}
 
-/// Compute the scroll offset ratatui's List widget uses when `ListState` starts at offset 0.
+    fn open_watch(&mut self) {
+        if let Some(conn) = self.connections.get(self.active_connection) {
+            let query = conn
+                .watch
+                .as_ref()
+                .map(|w| w.as_str().to_string())
+                .unwrap_or_default();
+            let cursor_pos = query.len();
+            self.dialog = Some(Dialog::WatchPrompt { query, cursor_pos });
+        }
+    }
+
+    /// Ask for the regex filling the active connection's field table,
+    /// pre-filled with the current one.
+    fn open_fields(&mut self) {
+        let Some(conn) = self.connections.get(self.active_connection) else {
+            return;
+        };
+        if conn.display_mode == DisplayMode::HexDump {
+            self.set_status(Severity::Info, i18n::t("status.fields_need_text").into());
+            return;
+        }
+        let query = conn
+            .fields
+            .as_ref()
+            .map(|f| f.pattern().to_string())
+            .unwrap_or_default();
+        let cursor_pos = query.len();
+        self.dialog = Some(Dialog::FieldsPrompt { query, cursor_pos });
+    }
+
+    /// Ask for a note to mark the active connection with; an empty one
+    /// marks just the time.
+    fn open_note(&mut self) {
+        if self.connections.get(self.active_connection).is_some() {
+            self.dialog = Some(Dialog::NotePrompt {
+                text: String::new(),
+                cursor_pos: 0,
+            });
+        }
+    }
+
+    /// Index of the selected line in `conn`'s scrollback, unless it has been
+    /// evicted or belongs to another connection.
+    pub fn cursor_line(&self, conn: &Connection) -> Option<usize> {
+        match self.line_cursor {
+            Some((id, line)) if id == conn.id => line.checked_sub(conn.evicted_lines()),
+            _ => None,
+        }
+    }
+
+    /// Rows of scrollback visible in the tab view, for keeping the cursor on screen.
+    fn scrollback_rows(&self) -> usize {
+        // menu bar + tab bar + block borders + status bar
+        self.terminal_rows
+            .saturating_sub(1 + 1 + 2 + 1 + self.input_height)
+            .max(1) as usize
+    }
+
+    /// Move the line cursor, starting from the bottom visible line, and scroll
+    /// to keep it in view.
+    fn move_line_cursor(&mut self, up: bool) {
+        let rows = self.scrollback_rows();
+        let Some(conn) = self.connections.get(self.active_connection) else {
+            return;
+        };
+        // Positions count the lines the filter shows
+        let shown = conn.shown_lines();
+        let total = shown.len();
+        if total == 0 {
+            return;
+        }
+        let bottom = total - 1 - conn.scroll_offset.min(total - 1);
+        let pos = match self.cursor_line(conn) {
+            None => bottom,
+            Some(line) => {
+                // The first shown line at or after the cursor
+                let next = shown.partition_point(|&i| i < line);
+                if up {
+                    next.saturating_sub(1)
+                } else if shown.get(next) == Some(&line) {
+                    (next + 1).min(total - 1)
+                } else {
+                    next.min(total - 1)
+                }
+            }
+        };
+        self.line_cursor = Some((conn.id, conn.evicted_lines() + shown[pos]));
+
+        let conn = &mut self.connections[self.active_connection];
+        if pos > bottom {
+            conn.scroll_to(total - 1 - pos);
+        } else if pos + rows <= bottom {
+            conn.scroll_to(total - pos - rows);
+        }
+    }
+
+    /// Bytes of the selected line, with a hint in the status bar if there are none.
+    fn cursor_bytes(&mut self) -> Option<Vec<u8>> {
+        let conn = self.connections.get(self.active_connection)?;
+        let bytes = self
+            .cursor_line(conn)
+            .and_then(|line| conn.line_bytes(line))
+            .filter(|bytes| !bytes.is_empty());
+        if bytes.is_none() {
+            let msg = if self.cursor_line(conn).is_none() {
+                i18n::t("status.select_line")
+            } else {
+                i18n::t("status.line_empty")
+            };
+            self.set_status(Severity::Info, msg.into());
+        }
+        bytes
+    }
+
+    /// Move to the next match in the active connection's scrollback.
+    /// `older` searches upwards (towards the start of the scrollback); a fresh
+    /// search starts from the bottom.
+    fn search_step(&mut self, older: bool) {
+        let Some(search) = &mut self.search else {
+            return;
+        };
+        if self.active_connection >= self.connections.len() {
+            return;
+        }
+        let conn = &mut self.connections[self.active_connection];
+        let needle = search.query.to_ascii_lowercase();
+        let lines: Vec<&str> = conn.scrollback_with_partial().collect();
+        // Only lines the filter shows can be stepped to; positions index `shown`
+        let shown = conn.shown_lines();
+        let total = shown.len();
+        let is_hit = |pos: &usize| {
+            crate::ansi::strip(lines[shown[*pos]])
+                .to_ascii_lowercase()
+                .contains(&needle)
+        };
+
+        let found = match (search.current_line(conn), older) {
+            (Some(line), true) => (0..shown.partition_point(|&i| i < line)).rev().find(is_hit),
+            (Some(line), false) => (shown.partition_point(|&i| i <= line)..total).find(is_hit),
+            (None, _) => (0..total).rev().find(is_hit),
+        };
+
+        match found {
+            Some(pos) => {
+                search.current = Some((conn.id, conn.evicted_lines() + shown[pos]));
+                // Put the match on the bottom row of the view
+                conn.scroll_to(total - 1 - pos);
+            }
+            None => {
+                let msg = if search.current_line(conn).is_some() {
+                    i18n::tf("status.no_more_matches", &[&search.query])
+                } else {
+                    i18n::tf("status.no_matches", &[&search.query])
+                };
+                self.set_status(Severity::Info, msg);
+            }
+        }
+    }
+
+    fn start_save_chain(&mut self, mut indices: Vec<usize>) {
+        if let Some(idx) = indices.first().copied() {
+            indices.remove(0);
+            let filename = self.generate_filename(idx, ExportFormat::Text.extension());
+            let cursor_pos = filename.len();
+            self.dialog = Some(Dialog::FileNamePrompt {
+                connection_idx: idx,
+                filename,
+                cursor_pos,
+                action: FileAction::Export(ExportSettings::default()),
+                after: AfterSave::QuitNext { remaining: indices },
+            });
+        } else {
+            self.should_quit = true;
+        }
+    }
+
+    fn do_close_active_connection(&mut self) {
+        if self.connections.is_empty() {
+            return;
+        }
+        let idx = self.active_connection;
+        if self.connections[idx].alive {
+            self.connection_ended(self.connections[idx].id);
+        }
+        self.connections[idx].close();
+        let conn = self.connections.remove(idx);
+        for other in &mut self.connections {
+            if other.bridge_to == Some(conn.id) {
+                other.bridge_to = None;
+                other.push_line(format!("--- Bridge to {} ended ---", conn.port_name));
+            }
+            if other.taps.is_some_and(|taps| taps.contains(&conn.id)) {
+                other.push_line(format!("--- {} closed ---", conn.port_name));
+            }
+        }
+        // Its output has no tab to go to any more; Info for a closed connection
+        // ends up in the status bar
+        if let Some(hook) = self.post_close_hook(&conn) {
+            crate::command::spawn(
+                conn.id,
+                "post_close".into(),
+                hook,
+                conn.command_vars(),
+                self.serial_tx.clone(),
+            );
+        }
+        if self.connections.is_empty() {
+            self.screen = if self.config.profiles.is_empty() {
+                Screen::PortSelect
+            } else {
+                Screen::ProfileSelect
+            };
+            self.pending_connection = None;
+            self.refresh_ports();
+        } else if self.active_connection >= self.connections.len() {
+            self.active_connection = self.connections.len() - 1;
+        }
+    }
+
+    /// Connect using a saved profile, or start the manual wizard for the trailing entry.
+    fn select_profile(&mut self, idx: usize) {
+        let Some(profile) = self.config.profiles.get(idx).cloned() else {
+            self.screen = Screen::PortSelect;
+            return;
+        };
+        if !self.profile_available.get(idx).copied().unwrap_or(false) {
+            self.set_status(
+                Severity::Warning,
+                i18n::tf("status.not_present", &[&profile.port]),
+            );
+        }
+        self.open_connection(
+            profile.port.clone(),
+            profile.baud,
+            profile.data_bits(),
+            profile.parity(),
+            profile.stop_bits(),
+            profile.display_mode(),
+            profile.pacing(),
+            Some(profile),
+        );
+    }
+
+    /// Start the inline new-connection flow in a "New" tab.
+    fn new_connection(&mut self) {
+        if self.screen == Screen::Connected && self.pending_connection.is_none() {
+            self.pending_connection = Some(PendingScreen::PortSelect);
+            self.typed_target = None;
+            self.duplicating = None;
+            self.port_filter.clear();
+            self.new_ports.clear();
+            self.refresh_ports();
+            self.active_connection = self.connections.len();
+        }
+    }
+
+    /// Start a new connection with the settings of connection `idx`: the port
+    /// list comes up on the first port not already open, and picking one
+    /// connects right away.
+    fn duplicate_connection(&mut self, idx: usize) {
+        let Some(conn) = self.connections.get(idx) else {
+            return;
+        };
+        if conn.read_only {
+            self.set_status(Severity::Warning, i18n::t("status.replay_duplicate").into());
+            return;
+        }
+        let source = DuplicateSource {
+            port_name: conn.port_name.clone(),
+            baud_rate: conn.baud_rate,
+            data_bits: conn.data_bits,
+            parity: conn.parity,
+            stop_bits: conn.stop_bits,
+            display_mode: conn.display_mode,
+            pacing: conn.pacing,
+            shared_at: conn.share.as_ref().map(|s| s.address),
+        };
+        self.new_connection();
+        if self.pending_connection.is_none() {
+            return;
+        }
+        // A terminal server connection most likely gets its copy on the same
+        // server, so start on the "Network…" entry
+        self.selected_port_index = if NetAddress::parse(&source.port_name).is_some() {
+            self.available_ports.len()
+        } else {
+            self.available_ports
+                .iter()
+                .position(|p| !self.port_in_use(&p.name))
+                .unwrap_or(0)
+        };
+        self.set_status(
+            Severity::Info,
+            i18n::tf("status.pick_duplicate_port", &[&source.port_name]),
+        );
+        self.duplicating = Some(source);
+    }
+
+    /// A connection has `port_name` open.
+    pub fn port_in_use(&self, port_name: &str) -> bool {
+        self.connections
+            .iter()
+            .any(|c| c.alive && !c.read_only && c.port_name == port_name)
+    }
+
+    /// Entries in the port list: the detected ports, "Network…" and
+    /// "Device path…".
+    fn port_list_len(&self) -> usize {
+        self.available_ports.len() + 2
+    }
+
+    /// Go on from the port list: to the line settings for a port, or to a
+    /// prompt for the "Network…" and "Device path…" entries. A duplicate
+    /// connects right away.
+    fn choose_port(&mut self) {
+        // Going back from the next step shows the whole list
+        self.clear_port_filter();
+        if self.selected_port_index > self.available_ports.len() {
+            self.dialog = Some(Dialog::PathPrompt {
+                path: String::new(),
+                cursor_pos: 0,
+            });
+            return;
+        }
+        if self.selected_port_index == self.available_ports.len() {
+            let host = match &self.duplicating {
+                Some(source) if NetAddress::parse(&source.port_name).is_some() => {
+                    source.port_name.clone()
+                }
+                _ => String::new(),
+            };
+            self.dialog = Some(Dialog::HostPrompt {
+                cursor_pos: host.len(),
+                host,
+            });
+            return;
+        }
+        self.typed_target = None;
+        if let Some(source) = &self.duplicating {
+            let mut port_name = self.available_ports[self.selected_port_index].name.clone();
+            if let Some(mut address) = source.shared_at.filter(|_| port_name == source.port_name) {
+                // The port itself is taken, but its share isn't
+                if address.ip().is_unspecified() {
+                    address.set_ip(std::net::Ipv4Addr::LOCALHOST.into());
+                }
+                port_name = format!("tcp://{}", address);
+            }
+            self.connect_duplicate(port_name);
+            return;
+        }
+        if self.pending_connection.is_some() {
+            self.pending_connection = Some(PendingScreen::BaudSelect);
+        } else {
+            self.screen = Screen::BaudSelect;
+        }
+    }
+
+    /// Pacing is only asked for serial lines; a raw TCP or telnet connection
+    /// opens once the display mode is picked.
+    fn choose_display_mode(&mut self) {
+        if self.skips_line_settings() {
+            self.connect_selected();
+        } else if self.pending_connection.is_some() {
+            self.pending_connection = Some(PendingScreen::PacingSelect);
+        } else {
+            self.screen = Screen::PacingSelect;
+        }
+    }
+
+    fn choose_host(&mut self, host: String, cursor_pos: usize) {
+        match serial::network_port_name(&host) {
+            Ok(name) if self.duplicating.is_some() => self.connect_duplicate(name),
+            Ok(name) => {
+                self.typed_target = Some(name);
+                let (screen, pending) = if self.skips_line_settings() {
+                    (Screen::DisplayModeSelect, PendingScreen::DisplayModeSelect)
+                } else {
+                    (Screen::BaudSelect, PendingScreen::BaudSelect)
+                };
+                if self.pending_connection.is_some() {
+                    self.pending_connection = Some(pending);
+                } else {
+                    self.screen = screen;
+                }
+            }
+            Err(e) => {
+                self.set_status(Severity::Warning, e);
+                self.dialog = Some(Dialog::HostPrompt { host, cursor_pos });
+            }
+        }
+    }
+
+    /// Go on with a device that isn't in the port list, e.g. a PTY or a
+    /// simulated `demo://` device.
+    fn choose_path(&mut self, path: String, cursor_pos: usize) {
+        let path = path.trim().to_string();
+        if !std::path::Path::new(&path).exists() && !serial::is_demo(&path) {
+            self.set_status(
+                Severity::Warning,
+                i18n::tf("status.no_such_device", &[&path]),
+            );
+            self.dialog = Some(Dialog::PathPrompt { path, cursor_pos });
+            return;
+        }
+        if self.duplicating.is_some() {
+            self.connect_duplicate(path);
+            return;
+        }
+        self.typed_target = Some(path);
+        if self.pending_connection.is_some() {
+            self.pending_connection = Some(PendingScreen::BaudSelect);
+        } else {
+            self.screen = Screen::BaudSelect;
+        }
+    }
+
+    /// The wizard is setting up a raw TCP or telnet connection, which has no
+    /// baud rate, parity etc. to ask for.
+    fn skips_line_settings(&self) -> bool {
+        self.typed_target
+            .as_deref()
+            .and_then(NetAddress::parse)
+            .is_some_and(|a| !a.has_line_settings())
+    }
+
+    /// The port, device or terminal server the wizard is setting up, for titles.
+    pub fn wizard_port_name(&self) -> &str {
+        match &self.typed_target {
+            Some(name) => name,
+            None => self
+                .available_ports
+                .get(self.selected_port_index)
+                .map(|p| p.name.as_str())
+                .unwrap_or("?"),
+        }
+    }
+
+    fn connect_selected(&mut self) {
+        let display_mode = DISPLAY_MODE_OPTIONS[self.selected_display_mode_index].1;
+        if self.skips_line_settings() {
+            let name = self.typed_target.take().unwrap_or_default();
+            self.open_connection(
+                name,
+                0,
+                serialport::DataBits::Eight,
+                serialport::Parity::None,
+                serialport::StopBits::One,
+                display_mode,
+                Pacing::NONE,
+                None,
+            );
+            return;
+        }
+        let port_name = match self.typed_target.take() {
+            Some(name) => name,
+            None => match self.available_ports.get(self.selected_port_index) {
+                Some(port) => port.name.clone(),
+                None => return,
+            },
+        };
+        // Past the rates is "Auto", which starts at the first rate it tries
+        let auto_baud = self.selected_baud_index == BAUD_RATES.len();
+        let baud_rate = BAUD_RATES
+            .get(self.selected_baud_index)
+            .copied()
+            .unwrap_or(AUTO_BAUD_RATES[0]);
+        let data_bits = DATA_BITS_OPTIONS[self.selected_data_bits_index].1;
+        let parity = PARITY_OPTIONS[self.selected_parity_index].1;
+        let stop_bits = STOP_BITS_OPTIONS[self.selected_stop_bits_index].1;
+        let pacing = PACING_OPTIONS[self.selected_pacing_index].1;
+        self.open_connection(
+            port_name,
+            baud_rate,
+            data_bits,
+            parity,
+            stop_bits,
+            display_mode,
+            pacing,
+            None,
+        );
+        if auto_baud {
+            self.connections[self.active_connection].start_auto_baud();
+        }
+    }
+
+    /// Open the copy of the connection being duplicated on `port_name`.
+    fn connect_duplicate(&mut self, port_name: String) {
+        let Some(source) = self.duplicating.take() else {
+            return;
+        };
+        // Like the wizard, a raw TCP or telnet connection gets no baud rate
+        let baud_rate = if NetAddress::parse(&port_name).is_none_or(|a| a.has_line_settings()) {
+            source.baud_rate
+        } else {
+            0
+        };
+        self.open_connection(
+            port_name,
+            baud_rate,
+            source.data_bits,
+            source.parity,
+            source.stop_bits,
+            source.display_mode,
+            source.pacing,
+            None,
+        );
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn open_connection(
+        &mut self,
+        port_name: String,
+        baud_rate: u32,
+        data_bits: serialport::DataBits,
+        parity: serialport::Parity,
+        stop_bits: serialport::StopBits,
+        display_mode: DisplayMode,
+        pacing: Pacing,
+        profile: Option<Profile>,
+    ) {
+        let id = self.next_connection_id;
+        self.next_connection_id += 1;
+
+        let mut conn = Connection::new(
+            id,
+            port_name,
+            baud_rate,
+            data_bits,
+            parity,
+            stop_bits,
+            display_mode,
+            pacing,
+            self.prefs.read(),
+            self.serial_tx.clone(),
+            profile.as_ref().map(|p| p.name.clone()),
+            profile.as_ref().and_then(|p| p.pre_open.clone()),
+        );
+        conn.partial_flush = self.config.partial_flush();
+        conn.idle_marker = self.config.idle_marker(profile.as_ref());
+        conn.max_scrollback = self.config.scrollback_limit();
+        conn.resend_cooldown = self.config.resend_cooldown(profile.as_ref());
+        conn.log_rotation = self.config.log_rotation(profile.as_ref());
+        conn.audit = self.audit.clone();
+        conn.stats = SessionStats::new(&self.stat_patterns);
+        match self.config.framing(profile.as_ref()) {
+            Ok(framing) => conn.set_framing(framing),
+            Err(e) => self.set_status(Severity::Error, i18n::tf("status.config_error", &[&e])),
+        }
+        match profile.as_ref().map(Profile::watch).transpose() {
+            Ok(watch) => conn.watch = watch.flatten(),
+            Err(e) => self.set_status(Severity::Error, i18n::tf("status.config_error", &[&e])),
+        }
+        let port_name = conn.port_name.clone();
+        self.connections.push(conn);
+        self.active_connection = self.connections.len() - 1;
+        self.pending_connection = None;
+        self.screen = Screen::Connected;
+        self.run_script(|script| script.on_connect(id, &port_name));
+    }
+
+    /// File → Export To: browse for the folder to export the active
+    /// connection into, starting in the current directory.
+    fn open_folder_browser(&mut self) {
+        if self.active_connection >= self.connections.len() {
+            return;
+        }
+        match std::env::current_dir() {
+            Ok(folder) => self.browse_folder(self.active_connection, folder, None),
+            Err(e) => self.set_status(Severity::Error, i18n::tf("status.export", &[&e])),
+        }
+    }
+
+    /// Show `folder` in the folder browser, with the subfolder `from` (the
+    /// one just left) selected. A folder that can't be read keeps the
+    /// browser where it was.
+    fn browse_folder(&mut self, connection_idx: usize, folder: PathBuf, from: Option<String>) {
+        match paths::subfolders(&folder) {
+            Ok(subfolders) => {
+                let selected = from
+                    .and_then(|name| subfolders.iter().position(|s| *s == name))
+                    .map_or(0, |i| i + 1);
+                self.dialog = Some(Dialog::FolderBrowser {
+                    connection_idx,
+                    folder,
+                    subfolders,
+                    selected,
+                });
+            }
+            Err(e) => self.set_status(Severity::Error, format!("{}: {}", folder.display(), e)),
+        }
+    }
+
+    fn generate_filename(&self, connection_idx: usize, ext: &str) -> String {
+        let conn = &self.connections[connection_idx];
+        let safe_name = conn.port_name.replace(['/', '\\', ':'], "_");
+        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+        format!("{}_{}_{}.{}", safe_name, conn.baud_rate, timestamp, ext)
+    }
+
+    fn export_connection(&mut self, connection_idx: usize, filename: &str, export: ExportSettings) {
+        if connection_idx >= self.connections.len() {
+            return;
+        }
+        let conn = &self.connections[connection_idx];
+        let content = match export.range {
+            ExportRange::All => export.format.render(conn, export.timestamps),
+            range => match range.lines(conn, self.pane_rows(connection_idx)) {
+                Ok(lines) => export::text(conn, &lines),
+                Err(msg) => {
+                    self.set_status(Severity::Warning, i18n::tf("status.export", &[&msg]));
+                    return;
+                }
+            },
+        };
+
+        match export::write(filename, &content) {
+            Ok(()) => {
+                self.set_status(Severity::Success, i18n::tf("status.exported", &[&filename]));
+            }
+            Err(e) => {
+                self.set_status(Severity::Error, i18n::tf("status.export_failed", &[&e]));
+            }
+        }
+    }
+
+    /// Rows of scrollback connection `idx`'s pane shows in any view, as
+    /// `terminal_view` lays it out; None when it isn't on screen.
+    fn pane_rows(&self, idx: usize) -> Option<usize> {
+        let (_, area) = crate::ui::scrollback_areas(self)
+            .into_iter()
+            .find(|&(i, _)| i == idx)?;
+        // Inside the borders, less a paused view's banner
+        let rows = usize::from(area.height.saturating_sub(2));
+        Some(match self.connections[idx].paused_new_lines() {
+            Some(_) if rows > 1 => rows - 1,
+            _ => rows,
+        })
+    }
+
+    pub fn active_busy_text(&self) -> Option<String> {
+        self.connections
+            .get(self.active_connection)
+            .and_then(|c| c.busy_text())
+    }
+
+    /// Insert pasted text into the Send bar at the cursor. Each complete line
+    /// of a multi-line paste is sent as if typed and Enter pressed, paced at
+    /// the line rate; text after the last newline stays in the Send bar.
+    fn paste(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        let Some((lines, rest)) = text.rsplit_once('\n') else {
+            self.input_buffer.insert_str(self.input_cursor, &text);
+            self.input_cursor += text.len();
+            return;
+        };
+        let Some(conn) = self.connections.get(self.active_connection) else {
+            return;
+        };
+        if !conn.can_send() {
+            self.set_status(
+                Severity::Warning,
+                i18n::t("status.connection_read_only").into(),
+            );
+            return;
+        }
+
+        let mut data = Vec::new();
+        let mut count = 0;
+        for (i, line) in lines.split('\n').enumerate() {
+            // The first line continues whatever was typed before the cursor
+            let line = if i == 0 {
+                format!("{}{}", &self.input_buffer[..self.input_cursor], line)
+            } else {
+                line.to_string()
+            };
+            if self.hex_input {
+                match crate::hex::parse(&line) {
+                    Ok(bytes) => data.extend(bytes),
+                    Err(e) => {
+                        self.set_status(Severity::Warning, e);
+                        return;
+                    }
+                }
+            } else {
+                data.extend(self.text_bytes(&line));
+                data.extend_from_slice(b"\r\n");
+            }
+            count += 1;
+        }
+        // Whatever was after the cursor follows the unfinished last line
+        self.input_buffer = format!("{}{}", rest, &self.input_buffer[self.input_cursor..]);
+        self.input_cursor = rest.len();
+        let conn = &mut self.connections[self.active_connection];
+        conn.scroll_to(0);
+        if !data.is_empty() {
+            conn.start_send_job(&format!("paste {} lines", count), data);
+        }
+    }
+
+    /// The bytes of a Send bar text line: with its `\r`, `\n`, `\t`, `\xNN`
+    /// and `\\` escapes turned into bytes, or as typed in literal mode.
+    fn text_bytes(&self, text: &str) -> Vec<u8> {
+        if self.literal_input {
+            text.as_bytes().to_vec()
+        } else {
+            serial::unescape(text)
+        }
+    }
+
+    /// Manual input is locked while a job drives the active connection.
+    fn input_locked(&mut self) -> bool {
+        match self.active_busy_text() {
+            Some(text) => {
+                self.set_status(Severity::Warning, i18n::tf("status.busy", &[&text]));
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn open_command_menu(&mut self) {
+        let Some(conn) = self.connections.get(self.active_connection) else {
+            return;
+        };
+        let commands = conn
+            .profile_name
+            .as_ref()
+            .and_then(|name| self.config.profiles.iter().find(|p| &p.name == name))
+            .map(|p| p.commands.clone())
+            .unwrap_or_default();
+        if commands.is_empty() {
+            self.set_status(Severity::Warning, i18n::t("status.no_commands").into());
+            return;
+        }
+        self.dialog = Some(Dialog::CommandMenu {
+            connection_idx: self.active_connection,
+            commands,
+            selected: 0,
+        });
+    }
+
+    /// The saved profile a connection was opened from.
+    fn profile_of(&self, conn: &Connection) -> Option<&Profile> {
+        let name = conn.profile_name.as_ref()?;
+        self.config.profiles.iter().find(|p| &p.name == name)
+    }
+
+    fn post_close_hook(&self, conn: &Connection) -> Option<String> {
+        self.profile_of(conn)?.post_close.clone()
+    }
+
+    /// Run the profile's power off/on commands. With `reconnect` the port is
+    /// released for the cycle and reopened once it reappears.
+    fn power_cycle(&mut self, idx: usize) {
+        let Some(conn) = self.connections.get(idx) else {
+            return;
+        };
+        if conn.read_only || conn.busy.is_some() {
+            return;
+        }
+        let Some(power) = self.profile_of(conn).and_then(|p| p.power.clone()) else {
+            self.set_status(
+                Severity::Warning,
+                i18n::t("status.no_power_commands").into(),
+            );
+            return;
+        };
+        let vars = conn.command_vars();
+        let conn = &mut self.connections[idx];
+        if power.reconnect {
+            conn.close();
+        }
+        conn.push_line("--- Power cycling target ---".into());
+        let cancel = conn.begin_busy("power cycle");
+        crate::command::power_cycle(
+            conn.id,
+            power,
+            conn.port_name.clone(),
+            vars,
+            cancel,
+            self.serial_tx.clone(),
+        );
+    }
+
+    /// Close every connection on the way out, running post_close hooks to
+    /// completion. Returns the hooks' output for printing after the UI is gone.
+    pub fn shutdown(&mut self) -> Vec<String> {
+        let mut output = Vec::new();
+        if let Err(e) = self.save_session() {
+            output.push(format!("Session state not saved: {}", e));
+        }
+        for idx in 0..self.connections.len() {
+            if self.connections[idx].alive {
+                if let Some(Ok(path)) = self.write_auto_stats(idx) {
+                    output.push(format!("Stats written to {}", path));
+                }
+            }
+        }
+        for mut conn in std::mem::take(&mut self.connections) {
+            conn.close();
+            let Some(hook) = self.post_close_hook(&conn) else {
+                continue;
+            };
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+            if let Err(e) =
+                crate::command::run(conn.id, "post_close", &hook, &conn.command_vars(), &tx)
+            {
+                output.push(format!("[post_close] failed to start: {}", e));
+            }
+            drop(tx);
+            output.extend(std::iter::from_fn(|| rx.try_recv().ok()).filter_map(
+                |event| match event {
+                    SerialEvent::Info { text, .. } => Some(text),
+                    _ => None,
+                },
+            ));
+        }
+        output
+    }
+
+    /// Periodic work that isn't driven by input or serial events.
+    pub fn tick(&mut self) {
+        // Data that came in before the tick counts against the idle checks
+        // below, so a packet isn't ended at a gap that wasn't there
+        if let Ok(event) = self.serial_rx.try_recv() {
+            self.drain_serial_events(event);
+        }
+        if let Some(conn) = self.connections.get_mut(self.active_connection) {
+            conn.alert = false;
+        }
+        let mut sent = None;
+        let mut detected = None;
+        let mut captured = None;
+        for conn in &mut self.connections {
+            // Rates are recomputed about once a second, which also keeps a
+            // stalled partial line's idle time counting; busy connections
+            // redraw every tick for their progress
+            let changed = conn.sync_live_stream()
+                | conn.flush_stale_partial()
+                | conn.end_idle_packet()
+                | conn.update_rates();
+            if changed || conn.busy.is_some() {
+                self.dirty = true;
+            }
+            if let Some(total) = conn.pump_send_job() {
+                sent = Some(total);
+            }
+            if let Some(result) = conn.pump_auto_baud() {
+                detected = Some(result);
+            }
+            if let Some(result) = conn.pump_armed() {
+                captured = Some(result);
+                self.dirty = true;
+            }
+        }
+        match captured {
+            Some(Ok(msg)) => self.set_status(Severity::Success, msg),
+            Some(Err(msg)) => self.set_status(Severity::Error, msg),
+            None => {}
+        }
+        match detected {
+            Some(Ok(msg)) => self.set_status(Severity::Success, msg),
+            Some(Err(msg)) => self.set_status(Severity::Warning, msg),
+            None => {}
+        }
+        if let Some(total) = sent {
+            self.set_status(Severity::Success, i18n::tf("status.sent", &[&total]));
+        }
+        self.poll_ports();
+        let now = Instant::now();
+        let (due, waiting) = std::mem::take(&mut self.pending_responses)
+            .into_iter()
+            .partition(|r| r.at <= now);
+        self.pending_responses = waiting;
+        if self
+            .status_message
+            .as_ref()
+            .is_some_and(|s| s.until.is_some_and(|until| until <= now))
+        {
+            self.status_message = None;
+            self.dirty = true;
+        }
+        if self.flash_until.is_some_and(|until| until <= now) {
+            self.flash_until = None;
+            self.dirty = true;
+        }
+        for response in due {
+            self.dirty = true;
+            self.send_response(response.id, &response.data);
+        }
+        if let Some(Err(e)) = self.audit.as_ref().map(|a| a.sync_due()) {
+            self.set_status(Severity::Error, i18n::tf("status.audit_log", &[&e]));
+        }
+        while let Some(request) = self.control_rx.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            let reply = self.control_command(&request.command);
+            let _ = request.reply.send(reply);
+        }
+    }
+
+    /// Answer a control socket command with a line of JSON.
+    fn control_command(&self, command: &str) -> String {
+        let mut words = command.split_whitespace();
+        match words.next() {
+            Some("stats") => {
+                let connections: Vec<_> = self
+                    .connections
+                    .iter()
+                    .map(|c| {
+                        serde_json::json!({
+                            "id": c.id,
+                            "port": c.port_name,
+                            "settings": c.label(),
+                            "profile": c.profile_name,
+                            "alive": c.alive,
+                            "read_only": c.read_only,
+                            "rx_bytes": c.rx_bytes,
+                            "tx_bytes": c.tx_bytes,
+                            "rx_rate": c.rx_rate,
+                            "tx_rate": c.tx_rate,
+                            "lines": c.scrollback.len(),
+                            "live_stream": c.live_stream_path(),
+                            "busy": c.busy_text(),
+                            "share": c.share.as_ref().map(|s| serde_json::json!({
+                                "address": s.address.to_string(),
+                                "clients": s.client_count(),
+                            })),
+                            "bridge_to": c.bridge_to,
+                        })
+                    })
+                    .collect();
+                control::ok(serde_json::json!({ "connections": connections }))
+            }
+            Some("export") => {
+                let Some(conn) = words
+                    .next()
+                    .and_then(|id| id.parse::<usize>().ok())
+                    .and_then(|id| self.connections.iter().find(|c| c.id == id))
+                else {
+                    return control::error("usage: export <connection id> [file]");
+                };
+                let lines: Vec<&str> = conn.scrollback_with_partial().collect();
+                // Without a file the scrollback comes back in the reply
+                let Some(name) = words.next() else {
+                    return control::ok(serde_json::json!({ "id": conn.id, "lines": lines }));
+                };
+                let path = match control::export_path(name) {
+                    Ok(path) => path.to_string_lossy().into_owned(),
+                    Err(e) => return control::error(e),
+                };
+                match export::write(&path, lines.join("\n").as_bytes()) {
+                    Ok(()) => control::ok(serde_json::json!({
+                        "id": conn.id,
+                        "path": path,
+                        "line_count": lines.len(),
+                    })),
+                    Err(e) => control::error(format!("writing {}: {}", path, e)),
+                }
+            }
+            Some("screenshot") => control::ok(serde_json::json!({
+                "cols": self.terminal_cols,
+                "rows": self.terminal_rows,
+                "lines": crate::ui::screenshot(self),
+            })),
+            _ => control::error(format!(
+                "unknown command {:?} (stats, export <id> [file], screenshot)",
+                command
+            )),
+        }
+    }
+
+    fn open_protocol_menu(&mut self, upload: bool) {
+        let Some(conn) = self.connections.get(self.active_connection) else {
+            return;
+        };
+        if conn.read_only || conn.busy.is_some() {
+            return;
+        }
+        if conn.dry_run {
+            self.set_status(
+                Severity::Warning,
+                i18n::t("status.dry_run_transfers").into(),
+            );
+            return;
+        }
+        self.dialog = Some(Dialog::ProtocolMenu {
+            connection_idx: self.active_connection,
+            upload,
+            selected: 0,
+        });
+    }
+
+    fn open_generator_menu(&mut self) {
+        let Some(conn) = self.connections.get(self.active_connection) else {
+            return;
+        };
+        if !conn.can_send() || conn.busy.is_some() {
+            return;
+        }
+        if conn.dry_run {
+            self.set_status(
+                Severity::Warning,
+                i18n::t("status.dry_run_generator").into(),
+            );
+            return;
+        }
+        self.dialog = Some(Dialog::GeneratorMenu {
+            connection_idx: self.active_connection,
+            selected: 0,
+        });
+    }
+
+    fn open_generator_prompt(
+        &mut self,
+        connection_idx: usize,
+        generator: GeneratorSettings,
+        field: GeneratorField,
+    ) {
+        let text = match field {
+            GeneratorField::Bytes => generator.bytes.clone(),
+            GeneratorField::Count => String::new(),
+        };
+        self.dialog = Some(Dialog::GeneratorPrompt {
+            connection_idx,
+            generator,
+            field,
+            cursor_pos: text.len(),
+            text,
+        });
+    }
+
+    /// Take the test pattern's bytes and ask for the count, or take the
+    /// count and start sending.
+    fn generator_field(
+        &mut self,
+        connection_idx: usize,
+        mut generator: GeneratorSettings,
+        field: GeneratorField,
+        text: String,
+        cursor_pos: usize,
+    ) {
+        let bytes = crate::hex::parse(&text);
+        let invalid = match field {
+            GeneratorField::Bytes => match &bytes {
+                Err(e) => Some(e.clone()),
+                Ok(b) if b.is_empty() => Some(i18n::t("status.enter_hex").into()),
+                Ok(b) if generator.kind == PatternKind::Constant && b.len() > 1 => {
+                    Some(i18n::t("status.enter_byte").into())
+                }
+                Ok(_) => None,
+            },
+            GeneratorField::Count
+                if !text.trim().is_empty()
+                    && text.trim().parse::<u64>().ok().filter(|&n| n > 0).is_none() =>
+            {
+                Some(i18n::t("status.enter_count").into())
+            }
+            GeneratorField::Count => None,
+        };
+        if let Some(e) = invalid {
+            self.dialog = Some(Dialog::GeneratorPrompt {
+                connection_idx,
+                generator,
+                field,
+                text,
+                cursor_pos,
+            });
+            self.set_status(Severity::Warning, e);
+            return;
+        }
+        if field == GeneratorField::Bytes {
+            generator.bytes = text;
+            self.open_generator_prompt(connection_idx, generator, GeneratorField::Count);
+            return;
+        }
+        let Some(conn) = self.connections.get_mut(connection_idx) else {
+            return;
+        };
+        if conn.busy.is_some() {
+            return;
+        }
+        // The bytes were checked as they were entered
+        let bytes = crate::hex::parse(&generator.bytes).unwrap_or_default();
+        let pattern = match generator.kind {
+            PatternKind::Incrementing => Pattern::Incrementing,
+            PatternKind::Constant => Pattern::Constant(bytes.first().copied().unwrap_or(0)),
+            PatternKind::Random => Pattern::Random,
+            PatternKind::Frame => Pattern::Frame(bytes),
+        };
+        conn.start_generator(
+            &format!("send {} pattern", generator.kind.name()),
+            Generator::new(pattern, text.trim().parse().ok()),
+        );
+    }
+
+    /// Ask how long to run a link test on the active connection.
+    fn open_link_test(&mut self) {
+        let Some(conn) = self.connections.get(self.active_connection) else {
+            return;
+        };
+        if conn.read_only || conn.busy.is_some() {
+            return;
+        }
+        if conn.dry_run {
+            self.set_status(
+                Severity::Warning,
+                i18n::t("status.dry_run_link_test").into(),
+            );
+            return;
+        }
+        let duration = "10s".to_string();
+        let cursor_pos = duration.len();
+        self.dialog = Some(Dialog::LinkTestPrompt {
+            connection_idx: self.active_connection,
+            duration,
+            cursor_pos,
+        });
+    }
+
+    /// Prompt for the file to send (or receive into) on a connection.
+    fn open_transfer_prompt(&mut self, connection_idx: usize, action: FileAction) {
+        let Some(conn) = self.connections.get(connection_idx) else {
+            return;
+        };
+        if !conn.can_send() || conn.busy.is_some() {
+            return;
+        }
+        let filename = match action {
+            FileAction::Download(p) if p.receives_into_folder() => ".".to_string(),
+            FileAction::Download(_) => self.generate_filename(connection_idx, "bin"),
+            _ => String::new(),
+        };
+        let cursor_pos = filename.len();
+        self.dialog = Some(Dialog::FileNamePrompt {
+            connection_idx,
+            filename,
+            cursor_pos,
+            action,
+            after: AfterSave::Nothing,
+        });
+    }
+
+    fn send_file(&mut self, connection_idx: usize, filename: &str) {
+        let Some(conn) = self.connections.get_mut(connection_idx) else {
+            return;
+        };
+        match std::fs::read(filename) {
+            Ok(data) if data.is_empty() => {
+                self.set_status(
+                    Severity::Warning,
+                    i18n::tf("status.empty_file", &[&filename]),
+                );
+            }
+            Ok(data) => {
+                let name = std::path::Path::new(filename)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| filename.to_string());
+                conn.start_send_job(&format!("Send {}", name), data);
+            }
+            Err(e) => {
+                self.set_status(Severity::Error, i18n::tf("status.send_failed", &[&e]));
+            }
+        }
+    }
+
+    fn upload(&mut self, connection_idx: usize, filename: &str, protocol: Protocol) {
+        let Some(conn) = self.connections.get_mut(connection_idx) else {
+            return;
+        };
+        match std::fs::read(filename) {
+            Ok(data) if data.is_empty() => {
+                self.set_status(
+                    Severity::Warning,
+                    i18n::tf("status.empty_file", &[&filename]),
+                );
+            }
+            Ok(data) => {
+                let name = std::path::Path::new(filename)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| filename.to_string());
+                conn.start_transfer(
+                    &format!("{} send {}", protocol.name(), name),
+                    protocol,
+                    TransferKind::Send { name, data },
+                );
+                self.dialog = Some(Dialog::Transfer {
+                    connection_id: conn.id,
+                });
+            }
+            Err(e) => {
+                self.set_status(Severity::Error, i18n::tf("status.send_failed", &[&e]));
+            }
+        }
+    }
+
+    fn download(&mut self, connection_idx: usize, path: String, protocol: Protocol) {
+        let Some(conn) = self.connections.get_mut(connection_idx) else {
+            return;
+        };
+        conn.start_transfer(
+            &format!("{} receive into {}", protocol.name(), path),
+            protocol,
+            TransferKind::Receive(path),
+        );
+        self.dialog = Some(Dialog::Transfer {
+            connection_id: conn.id,
+        });
+    }
+
+    fn open_stats_prompt(&mut self) {
+        if self.connections.is_empty() || self.active_connection >= self.connections.len() {
+            return;
+        }
+        let filename = self.generate_filename(self.active_connection, "json");
+        let cursor_pos = filename.len();
+        self.dialog = Some(Dialog::FileNamePrompt {
+            connection_idx: self.active_connection,
+            filename,
+            cursor_pos,
+            action: FileAction::ExportStats,
+            after: AfterSave::Nothing,
+        });
+    }
+
+    /// Write a connection's stats report, as CSV for `.csv` files, JSON
+    /// otherwise, compressed if the name ends in `.gz`.
+    fn write_stats(&self, connection_idx: usize, path: &str) -> Result<(), String> {
+        let Some(conn) = self.connections.get(connection_idx) else {
+            return Err("no such connection".into());
+        };
+        let report = conn.stats_report();
+        let text = match StatsFormat::for_path(path) {
+            StatsFormat::Json => report.to_json(),
+            StatsFormat::Csv => report.to_csv(),
+        };
+        export::write(path, text.as_bytes()).map_err(|e| e.to_string())
+    }
+
+    /// Write the `[stats] on_close` report, if configured, under a generated
+    /// name. Returns the path or the error.
+    fn write_auto_stats(&self, connection_idx: usize) -> Option<Result<String, String>> {
+        let format = self.config.stats.on_close?;
+        let path = self.generate_filename(connection_idx, format.extension());
+        Some(self.write_stats(connection_idx, &path).map(|()| path))
+    }
+
+    /// The connection with this id closed or dropped: write its automatic
+    /// stats report and tell the script.
+    fn connection_ended(&mut self, id: usize) {
+        self.auto_stats(id);
+        let Some(port) = self
+            .connections
+            .iter()
+            .find(|c| c.id == id)
+            .map(|c| c.port_name.clone())
+        else {
+            return;
+        };
+        self.run_script(|script| script.on_disconnect(id, &port));
+    }
+
+    fn open_trigger_prompt(
+        &mut self,
+        index: Option<usize>,
+        rule: TriggerRule,
+        field: TriggerField,
+    ) {
+        let text = match field {
+            TriggerField::Pattern => rule.pattern.clone(),
+            TriggerField::Send => rule.send.clone(),
+            TriggerField::Delay => rule.delay_ms.to_string(),
+        };
+        self.dialog = Some(Dialog::TriggerPrompt {
+            index,
+            rule,
+            field,
+            cursor_pos: text.len(),
+            text,
+        });
+    }
+
+    /// Take one field of the rule being edited and ask for the next, saving
+    /// the rule after the last.
+    fn trigger_field(
+        &mut self,
+        index: Option<usize>,
+        mut rule: TriggerRule,
+        field: TriggerField,
+        text: String,
+        cursor_pos: usize,
+    ) {
+        let delay = match text.trim() {
+            "" => Some(0),
+            ms => ms.parse::<u64>().ok(),
+        };
+        let invalid = match field {
+            // An empty pattern would answer every line
+            TriggerField::Pattern if text.is_empty() => {
+                Some(i18n::t("status.enter_pattern").to_string())
+            }
+            TriggerField::Pattern => Regex::new(&text)
+                .err()
+                .map(|e| i18n::tf("status.invalid_pattern", &[&e])),
+            TriggerField::Delay if delay.is_none() => {
+                Some(i18n::t("status.enter_delay").to_string())
+            }
+            _ => None,
+        };
+        if let Some(e) = invalid {
+            self.dialog = Some(Dialog::TriggerPrompt {
+                index,
+                rule,
+                field,
+                text,
+                cursor_pos,
+            });
+            self.set_status(Severity::Warning, e);
+            return;
+        }
+        match field {
+            TriggerField::Pattern => {
+                rule.pattern = text;
+                self.open_trigger_prompt(index, rule, TriggerField::Send);
+            }
+            TriggerField::Send => {
+                rule.send = text;
+                self.open_trigger_prompt(index, rule, TriggerField::Delay);
+            }
+            TriggerField::Delay => {
+                rule.delay_ms = delay.unwrap_or(0);
+                if let Err(e) = self.triggers.set(index, rule) {
+                    self.set_status(Severity::Warning, e);
+                    return;
+                }
+                let selected = index.unwrap_or(self.triggers.rules.len() - 1);
+                self.dialog = Some(Dialog::TriggerList { selected });
+                self.save_triggers("status.trigger_saved");
+            }
+        }
+    }
+
+    /// Stop the active connection's armed capture, or start asking for one.
+    fn toggle_armed_capture(&mut self) {
+        let idx = self.active_connection;
+        let Some(conn) = self.connections.get_mut(idx) else {
+            return;
+        };
+        if let Some(msg) = conn.disarm() {
+            self.set_status(Severity::Info, msg);
+            return;
+        }
+        if conn.display_mode != DisplayMode::Text {
+            self.set_status(Severity::Info, i18n::t("status.capture_needs_text").into());
+            return;
+        }
+        let capture = CaptureSettings {
+            file: self.generate_filename(idx, "txt"),
+            ..CaptureSettings::default()
+        };
+        self.open_capture_prompt(idx, capture, CaptureField::Start);
+    }
+
+    fn open_capture_prompt(
+        &mut self,
+        connection_idx: usize,
+        capture: CaptureSettings,
+        field: CaptureField,
+    ) {
+        let text = match field {
+            CaptureField::Start => capture.start.clone(),
+            CaptureField::End => capture.end.clone(),
+            CaptureField::Limit => capture.limit.clone(),
+            CaptureField::File => capture.file.clone(),
+        };
+        self.dialog = Some(Dialog::CapturePrompt {
+            connection_idx,
+            capture,
+            field,
+            cursor_pos: text.len(),
+            text,
+        });
+    }
+
+    /// Take one field of the capture being armed and ask for the next,
+    /// arming it after the last.
+    fn capture_field(
+        &mut self,
+        connection_idx: usize,
+        mut capture: CaptureSettings,
+        field: CaptureField,
+        text: String,
+        cursor_pos: usize,
+    ) {
+        let invalid = match field {
+            CaptureField::Start if text.is_empty() => {
+                Some(i18n::t("status.enter_start_pattern").into())
+            }
+            CaptureField::Start | CaptureField::End => Regex::new(&text)
+                .err()
+                .map(|e| i18n::tf("status.invalid_pattern", &[&e])),
+            CaptureField::Limit
+                if !text.trim().is_empty() && CaptureLimit::parse(&text).is_none() =>
+            {
+                Some(i18n::t("status.enter_limit").into())
+            }
+            CaptureField::File if text.trim().is_empty() => {
+                Some(i18n::t("status.enter_file_name").into())
+            }
+            _ => None,
+        };
+        if let Some(e) = invalid {
+            self.dialog = Some(Dialog::CapturePrompt {
+                connection_idx,
+                capture,
+                field,
+                text,
+                cursor_pos,
+            });
+            self.set_status(Severity::Warning, e);
+            return;
+        }
+        match field {
+            CaptureField::Start => {
+                capture.start = text;
+                self.open_capture_prompt(connection_idx, capture, CaptureField::End);
+            }
+            CaptureField::End => {
+                capture.end = text;
+                self.open_capture_prompt(connection_idx, capture, CaptureField::Limit);
+            }
+            CaptureField::Limit => {
+                capture.limit = text;
+                self.open_capture_prompt(connection_idx, capture, CaptureField::File);
+            }
+            CaptureField::File => {
+                capture.file = text;
+                self.arm_capture(connection_idx, capture);
+            }
+        }
+    }
+
+    fn arm_capture(&mut self, connection_idx: usize, capture: CaptureSettings) {
+        let durability = self.config.log_durability();
+        let Some(conn) = self.connections.get_mut(connection_idx) else {
+            return;
+        };
+        // Both patterns were checked as they were entered
+        let (Ok(start), Ok(end)) = (
+            Regex::new(&capture.start),
+            (!capture.end.is_empty())
+                .then(|| Regex::new(&capture.end))
+                .transpose(),
+        ) else {
+            return;
+        };
+        conn.armed = Some(ArmedCapture::new(
+            capture.file.clone(),
+            start,
+            end,
+            CaptureLimit::parse(&capture.limit),
+            durability,
+        ));
+        conn.push_line(format!(
+            "--- Capture armed: waiting for /{}/ ---",
+            capture.start
+        ));
+        self.set_status(
+            Severity::Info,
+            i18n::tf("status.capture_armed", &[&capture.file]),
+        );
+    }
+
+    /// Hand lines just completed to the connection's armed capture.
+    fn check_armed(&mut self, id: usize, lines: &[String]) {
+        let Some(conn) = self.connection_by_id(id) else {
+            return;
+        };
+        match conn.feed_armed(lines) {
+            Some(Ok(msg)) => self.set_status(Severity::Success, msg),
+            Some(Err(msg)) => self.set_status(Severity::Error, msg),
+            None => {}
+        }
+    }
+
+    fn save_triggers(&mut self, done: &'static str) {
+        match self.triggers.save() {
+            Ok(()) => self.set_status(Severity::Success, i18n::t(done).to_string()),
+            Err(e) => self.set_status(
+                Severity::Error,
+                i18n::tf("status.triggers_save_failed", &[&format!("{:#}", e)]),
+            ),
+        }
+    }
+
+    /// Mark lines matching the connection's watch pattern and raise an alert,
+    /// whichever tab is active.
+    fn check_watch(&mut self, id: usize, lines: &[String]) {
+        let Some(conn) = self.connection_by_id(id) else {
+            return;
+        };
+        let Some(watch) = &conn.watch else {
+            return;
+        };
+        let hits: Vec<String> = lines
+            .iter()
+            .filter(|line| watch.is_match(line))
+            .cloned()
+            .collect();
+        let Some(last) = hits.last() else {
+            return;
+        };
+        let msg = format!("{}: {}", conn.port_name, last.trim());
+        for line in &hits {
+            conn.push_line(format!("--- ⚠ Watch: {} ---", line.trim()));
+        }
+        self.alert(id, Severity::Warning, msg);
+    }
+
+    /// Answer trigger rules matching the lines just completed or the partial
+    /// line, e.g. a `login:` prompt that has no line ending.
+    fn check_triggers(&mut self, id: usize, lines: &[String], partial: &str) {
+        let Some(conn) = self.connections.iter_mut().find(|c| c.id == id) else {
+            return;
+        };
+        let mut due = Vec::new();
+        let mut fired = std::mem::take(&mut conn.fired_triggers);
+        for line in lines {
+            // Rules that answered this line while it was partial are done with it
+            due.extend(self.triggers.matching(line).filter(|i| !fired.contains(i)));
+            fired.clear();
+        }
+        for i in self.triggers.matching(partial) {
+            if !fired.contains(&i) {
+                fired.push(i);
+                due.push(i);
+            }
+        }
+        conn.fired_triggers = fired;
+
+        for i in due {
+            let rule = &self.triggers.rules[i];
+            let data = crate::serial::unescape(&rule.send);
+            if rule.delay_ms == 0 {
+                self.send_response(id, &data);
+            } else {
+                self.pending_responses.push(PendingResponse {
+                    at: Instant::now() + Duration::from_millis(rule.delay_ms),
+                    id,
+                    data,
+                });
+            }
+        }
+    }
+
+    fn send_response(&mut self, id: usize, data: &[u8]) {
+        let Some(conn) = self.connection_by_id(id) else {
+            return;
+        };
+        // Like share input, a running job owns the port
+        if conn.alive && conn.can_send() && conn.busy.is_none() {
+            conn.push_line(format!(
+                "--- Trigger sent {} ---",
+                crate::serial::escape(data)
+            ));
+            conn.send(data);
+        }
+    }
+
+    /// Call a script hook, if a script is loaded, and carry out what it asked for.
+    fn run_script(&mut self, hook: impl FnOnce(&mut Script) -> Result<Vec<ScriptAction>, String>) {
+        let Some(script) = &mut self.script else {
+            return;
+        };
+        match hook(script) {
+            Ok(actions) => {
+                for action in actions {
+                    self.script_action(action);
+                }
+            }
+            Err(e) => self.set_status(Severity::Error, e),
+        }
+    }
+
+    fn script_action(&mut self, action: ScriptAction) {
+        match action {
+            ScriptAction::Send { id, data } => {
+                if let Some(conn) = self.connection_by_id(id) {
+                    // Like share input, a running job owns the port
+                    if conn.alive && conn.can_send() && conn.busy.is_none() {
+                        conn.send(&data);
+                    }
+                }
+            }
+            ScriptAction::Status(text) => self.set_status(Severity::Info, text),
+            ScriptAction::Open { port, baud } => self.open_connection(
+                port,
+                baud,
+                serialport::DataBits::Eight,
+                serialport::Parity::None,
+                serialport::StopBits::One,
+                DisplayMode::Text,
+                Pacing::NONE,
+                None,
+            ),
+        }
+    }
+
+    /// Write the automatic stats report for the connection with this id as it
+    /// closes or drops.
+    fn auto_stats(&mut self, id: usize) {
+        let Some(idx) = self.connections.iter().position(|c| c.id == id) else {
+            return;
+        };
+        match self.write_auto_stats(idx) {
+            Some(Ok(path)) => {
+                self.set_status(Severity::Info, i18n::tf("status.stats_written", &[&path]))
+            }
+            Some(Err(e)) => {
+                self.set_status(Severity::Error, i18n::tf("status.stats_failed", &[&e]))
+            }
+            None => {}
+        }
+    }
+
+    fn toggle_live_stream(&mut self) {
+        if self.connections.is_empty() || self.active_connection >= self.connections.len() {
+            return;
+        }
+        let idx = self.active_connection;
+        if let Some(path) = self.connections[idx].stop_live_stream() {
+            self.set_status(
+                Severity::Info,
+                i18n::tf("status.live_stream_stopped", &[&path]),
+            );
+            return;
+        }
+        let filename = self.generate_filename(idx, "stream");
+        let cursor_pos = filename.len();
+        self.dialog = Some(Dialog::FileNamePrompt {
+            connection_idx: idx,
+            filename,
+            cursor_pos,
+            action: FileAction::LiveStream,
+            after: AfterSave::Nothing,
+        });
+    }
+
+    /// Prompt for a recorded live stream to replay, starting with the newest
+    /// one in the current directory, compressed or not.
+    fn open_replay(&mut self) {
+        let newest = std::fs::read_dir(".")
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|e| {
+                let name = e.file_name().to_string_lossy().into_owned();
+                name.ends_with(".stream") || name.ends_with(".stream.gz")
+            })
+            .max_by_key(|e| e.metadata().and_then(|m| m.modified()).ok());
+        let filename = newest.map_or_else(String::new, |e| {
+            e.file_name().to_string_lossy().into_owned()
+        });
+        let cursor_pos = filename.len();
+        self.dialog = Some(Dialog::FileNamePrompt {
+            connection_idx: self.active_connection,
+            filename,
+            cursor_pos,
+            action: FileAction::Replay(REPLAY_SPEEDS[0]),
+            after: AfterSave::Nothing,
+        });
+    }
+
+    fn start_live_stream(&mut self, connection_idx: usize, filename: &str) {
+        if connection_idx >= self.connections.len() {
+            return;
+        }
+        let durability = self.config.log_durability();
+        let conn = &mut self.connections[connection_idx];
+        match conn.start_live_stream(filename, durability) {
+            Ok(()) => {
+                // With daily rotation the file name carries the date
+                let path = conn.live_stream_path().unwrap_or(filename).to_string();
+                self.set_status(
+                    Severity::Success,
+                    i18n::tf("status.live_streaming", &[&path]),
+                )
+            }
+            Err(e) => self.set_status(
+                Severity::Error,
+                i18n::tf("status.live_stream_failed", &[&e]),
+            ),
+        }
+    }
+
+    /// Open a read-only tab following a live stream file written by another
+    /// instance, or replaying a recorded one at `replay` times its speed.
+    pub fn follow_stream(&mut self, path: String, replay: Option<u32>) {
+        let id = self.next_connection_id;
+        self.next_connection_id += 1;
+        let display_mode = DISPLAY_MODE_OPTIONS[self.selected_display_mode_index].1;
+        let mut conn = Connection::follow(id, path, replay, display_mode, self.serial_tx.clone());
+        conn.stats = SessionStats::new(&self.stat_patterns);
+        conn.partial_flush = self.config.partial_flush();
+        conn.idle_marker = self.config.idle_marker(None);
+        conn.max_scrollback = self.config.scrollback_limit();
+        self.connections.push(conn);
+        self.active_connection = self.connections.len() - 1;
+        self.screen = Screen::Connected;
+    }
+
+    /// Open a tab on the simulated device (`serialtui --demo`), for trying
+    /// things out without hardware.
+    pub fn open_demo(&mut self) {
+        let display_mode = DISPLAY_MODE_OPTIONS[self.selected_display_mode_index].1;
+        self.open_connection(
+            serial::DEMO_SCHEME.to_string(),
+            115200,
+            serialport::DataBits::Eight,
+            serialport::Parity::None,
+            serialport::StopBits::One,
+            display_mode,
+            Pacing::NONE,
+            None,
+        );
+    }
+
+    fn set_status(&mut self, severity: Severity, text: String) {
+        let durations = &self.config.status;
+        let secs = match severity {
+            Severity::Info => durations.info_secs,
+            Severity::Success => durations.success_secs,
+            Severity::Warning => durations.warning_secs,
+            Severity::Error => durations.error_secs,
+        };
+        self.status_message = Some(StatusMessage {
+            text,
+            severity,
+            until: (secs > 0).then(|| Instant::now() + Duration::from_secs(secs)),
+        });
+        self.dirty = true;
+    }
+
+    /// The status message, while it's still due to be shown.
+    pub fn status(&self) -> Option<&StatusMessage> {
+        self.status_message
+            .as_ref()
+            .filter(|s| s.until.is_none_or(|until| Instant::now() < until))
+    }
+
+    /// Whether a sticky status message is waiting to be dismissed.
+    pub fn status_is_sticky(&self) -> bool {
+        self.status().is_some_and(|s| s.until.is_none())
+    }
+
+    fn connection_by_id(&mut self, id: usize) -> Option<&mut Connection> {
+        self.connections.iter_mut().find(|c| c.id == id)
+    }
+}
+
+/// Compute the scroll offset ratatui's List widget uses when `ListState` starts at offset 0.
 fn list_scroll_offset(selected: usize, visible_height: usize, _count: usize) -> usize {
     if visible_height == 0 {
         return 0;
@@ -1230,3 +6278,92 @@ fn list_scroll_offset(selected: usize, visible_height: usize, _count: usize) ->
         0
     }
 }
+
+/// `on` or `off`, for status messages.
+fn on_off(on: bool) -> &'static str {
+    if on {
+        i18n::t("status.on")
+    } else {
+        i18n::t("status.off")
+    }
+}
+
+/// On-screen width of a connection's tab, matching the tab bar rendering.
+fn tab_width(conn: &Connection) -> u16 {
+    ratatui::text::Span::raw(conn.tab_title()).width() as u16
+}
+
+/// A connection's scrollback without colour codes, as compared by the diff view.
+fn plain_scrollback(conn: &Connection) -> Vec<String> {
+    conn.scrollback_with_partial()
+        .map(|line| crate::ansi::strip(line).into_owned())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn send_bar_edits_whole_characters() {
+        let mut app = App::with_config(Config::default());
+        for c in "aüb".chars() {
+            app.update(Message::CharInput(c));
+        }
+        app.update(Message::InputLeft);
+        app.update(Message::Backspace);
+        assert_eq!(app.input_buffer, "ab");
+        assert_eq!(app.input_cursor, 1);
+    }
+
+    #[test]
+    fn literal_input_sends_backslashes_as_typed() {
+        let mut app = App::with_config(Config::default());
+        assert_eq!(app.text_bytes(r"\x15\n"), [0x15, b'\n']);
+        app.update(Message::ToggleLiteralInput);
+        assert_eq!(app.text_bytes(r"\x15\n"), b"\\x15\\n");
+    }
+
+    #[test]
+    fn idle_marker_is_not_received_data() {
+        let mut app = App::with_config(Config::default());
+        let mut conn = Connection::sniffer(0, "test".into(), [1, 2], DisplayMode::Text);
+        conn.idle_marker = Some(Duration::from_millis(100));
+        conn.watch = Some(Regex::new("idle").unwrap());
+        app.connections.push(conn);
+        let start = Instant::now();
+        for (data, time) in [("a\n", start), ("b\n", start + Duration::from_secs(2))] {
+            app.drain_serial_events(SerialEvent::Data {
+                id: 0,
+                data: data.as_bytes().to_vec(),
+                time,
+            });
+        }
+        let scrollback = &app.connections[0].scrollback;
+        assert!(scrollback.iter().any(|line| line.contains("2.0 s idle")));
+        assert!(!scrollback.iter().any(|line| line.contains("Watch")));
+    }
+
+    #[test]
+    fn rebound_function_key_takes_over_its_bar_slot() {
+        let mut config = Config::default();
+        config.keymap.insert("f5".into(), "wrap".into());
+        let app = App::with_config(config);
+        let f5 = function_key_bar(&app.keymap, 200)
+            .into_iter()
+            .find(|(key, _, _)| *key == 5);
+        assert_eq!(f5.map(|(_, label, _)| label), Some("fkey.wrap ".into()));
+        assert!(matches!(
+            function_key(&app.keymap, 5),
+            Some(Message::ToggleWrap)
+        ));
+    }
+
+    #[test]
+    fn quit_without_connections_needs_no_confirmation() {
+        let mut app = App::with_config(Config::default());
+        app.update(Message::Quit);
+        assert!(app.should_quit);
+        assert!(app.dialog.is_none());
+    }
+}
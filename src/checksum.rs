@@ -0,0 +1,84 @@
+/// The checksums the Tools → Checksum dialog shows, as (name, value) with
+/// the value in hex at the checksum's width.
+pub fn all(data: &[u8]) -> Vec<(&'static str, String)> {
+    vec![
+        ("Sum-8", format!("{:02X}", sum8(data))),
+        ("XOR-8", format!("{:02X}", xor8(data))),
+        ("CRC-8", format!("{:02X}", crc8(data))),
+        ("CRC-16/MODBUS", format!("{:04X}", crc16_modbus(data))),
+        ("CRC-16/CCITT-FALSE", format!("{:04X}", crc16_ccitt(data))),
+        ("CRC-32", format!("{:08X}", crc32(data))),
+    ]
+}
+
+fn sum8(data: &[u8]) -> u8 {
+    data.iter().fold(0, |sum, b| sum.wrapping_add(*b))
+}
+
+fn xor8(data: &[u8]) -> u8 {
+    data.iter().fold(0, |x, b| x ^ b)
+}
+
+/// Polynomial 0x07, no reflection, init 0.
+fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for b in data {
+        crc ^= b;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Reflected polynomial 0xA001, init 0xFFFF; sent low byte first.
+fn crc16_modbus(data: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for b in data {
+        crc ^= *b as u16;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xA001
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+/// Polynomial 0x1021, no reflection, init 0xFFFF.
+fn crc16_ccitt(data: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for b in data {
+        crc ^= (*b as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// The zlib/Ethernet CRC: reflected polynomial 0xEDB88320.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for b in data {
+        crc ^= *b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
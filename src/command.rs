@@ -0,0 +1,158 @@
+use std::io::{BufRead, BufReader};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc;
+
+use crate::config::PowerCommands;
+use crate::serial::SerialEvent;
+
+// How long a power cycle waits for a released port to come back
+const PORT_WAIT: Duration = Duration::from_secs(30);
+
+/// Substitute `${VAR}` references in `template` with values from `vars`.
+/// Unknown variables are left untouched.
+pub fn expand(template: &str, vars: &[(String, String)]) -> String {
+    let mut out = template.to_string();
+    for (name, value) in vars {
+        out = out.replace(&format!("${{{}}}", name), value);
+    }
+    out
+}
+
+fn shell(command_line: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command_line]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command_line]);
+        cmd
+    }
+}
+
+/// Run an external command through the platform shell with `vars` substituted
+/// and exported, forwarding each output line to the connection's scrollback.
+pub fn run(
+    id: usize,
+    name: &str,
+    template: &str,
+    vars: &[(String, String)],
+    serial_tx: &mpsc::UnboundedSender<SerialEvent>,
+) -> std::io::Result<ExitStatus> {
+    let info = |text: String| {
+        let _ = serial_tx.send(SerialEvent::Info { id, text });
+    };
+    let command_line = expand(template, vars);
+    info(format!("[{}] $ {}", name, command_line));
+
+    let mut child = shell(&command_line)
+        .envs(vars.iter().map(|(k, v)| (k, v)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let stderr_thread = child.stderr.take().map(|stderr| {
+        let tx = serial_tx.clone();
+        let prefix = format!("[{}] ", name);
+        thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                let _ = tx.send(SerialEvent::Info {
+                    id,
+                    text: format!("{}{}", prefix, line),
+                });
+            }
+        })
+    });
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            info(format!("[{}] {}", name, line));
+        }
+    }
+    if let Some(handle) = stderr_thread {
+        let _ = handle.join();
+    }
+
+    let status = child.wait()?;
+    info(format!("[{}] {}", name, status));
+    Ok(status)
+}
+
+/// Run a command on a background thread.
+pub fn spawn(
+    id: usize,
+    name: String,
+    template: String,
+    vars: Vec<(String, String)>,
+    serial_tx: mpsc::UnboundedSender<SerialEvent>,
+) {
+    thread::spawn(move || {
+        if let Err(e) = run(id, &name, &template, &vars, &serial_tx) {
+            let _ = serial_tx.send(SerialEvent::Info {
+                id,
+                text: format!("[{}] failed to start: {}", name, e),
+            });
+        }
+    });
+}
+
+/// Run a profile's power off/on commands on a background thread, then (with
+/// `reconnect`) wait for `port` to reappear. Reports `PowerCycleDone`; the
+/// caller releases and reopens the port.
+pub fn power_cycle(
+    id: usize,
+    power: PowerCommands,
+    port: String,
+    vars: Vec<(String, String)>,
+    cancel: Arc<AtomicBool>,
+    serial_tx: mpsc::UnboundedSender<SerialEvent>,
+) {
+    thread::spawn(move || {
+        let result = (|| {
+            let step = |name: &str, template: &str| match run(id, name, template, &vars, &serial_tx)
+            {
+                Ok(status) if status.success() => Ok(()),
+                Ok(status) => Err(format!("power {} failed ({})", name, status)),
+                Err(e) => Err(format!("power {} failed to start: {}", name, e)),
+            };
+            step("off", &power.off)?;
+            sleep_unless_cancelled(Duration::from_millis(power.delay_ms), &cancel)?;
+            step("on", &power.on)?;
+            if power.reconnect {
+                wait_for_port(&port, &cancel)?;
+            }
+            Ok(())
+        })();
+        let _ = serial_tx.send(SerialEvent::PowerCycleDone { id, result });
+    });
+}
+
+fn sleep_unless_cancelled(duration: Duration, cancel: &AtomicBool) -> Result<(), String> {
+    let deadline = Instant::now() + duration;
+    while Instant::now() < deadline {
+        if cancel.load(Ordering::Relaxed) {
+            return Err("power cycle cancelled".into());
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    Ok(())
+}
+
+fn wait_for_port(port: &str, cancel: &AtomicBool) -> Result<(), String> {
+    let deadline = Instant::now() + PORT_WAIT;
+    while Instant::now() < deadline {
+        let present = serialport::available_ports()
+            .map(|ports| ports.iter().any(|p| p.port_name == port))
+            .unwrap_or(false);
+        if present {
+            return Ok(());
+        }
+        sleep_unless_cancelled(Duration::from_millis(250), cancel)?;
+    }
+    Err(format!("{} did not reappear", port))
+}
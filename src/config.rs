@@ -0,0 +1,573 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::serial::{DisplayMode, Durability, Framing, Pacing, Rotation};
+
+pub const DEFAULT_SCROLLBACK_LINES: usize = 100_000;
+
+/// Commented config with a sample profile, offered on first launch.
+const STARTER: &str = include_str!("../config.example.toml");
+
+/// User configuration, read from `<config dir>/serialtui/config.toml`.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct Config {
+    /// Move a partial line into the scrollback after this many milliseconds
+    /// without data, so prompts without a newline show up. Unset: wait for
+    /// the newline.
+    pub partial_flush_ms: Option<u64>,
+    /// Lines of scrollback kept per connection before the oldest are dropped.
+    /// Unset: `DEFAULT_SCROLLBACK_LINES`; 0: unlimited.
+    pub scrollback_lines: Option<usize>,
+    /// Append a record of everything transmitted, on any connection, to this file.
+    pub audit_log: Option<String>,
+    /// How live streams and the audit log reach the disk.
+    pub log_durability: LogDurability,
+    /// With `fsync` durability, the longest a record waits to be synced;
+    /// 0 syncs every record.
+    pub log_fsync_interval_ms: u64,
+    /// Mark a pause in received data at least this long with a line in the
+    /// scrollback.
+    pub idle_marker_ms: Option<u64>,
+    /// Start a new live stream file once the current one reaches this many
+    /// megabytes.
+    pub log_rotate_mb: Option<u64>,
+    /// Start a new live stream file every day, with the date in its name.
+    pub log_rotate_daily: bool,
+    /// Refuse a resend this soon after the previous Send bar input.
+    pub resend_cooldown_ms: Option<u64>,
+    /// Hex dump packet framing: a packet ends after this byte sequence,
+    /// written as hex (`"7E"`, `"0D 0A"`).
+    pub frame_delimiter: Option<String>,
+    /// Hex dump packet framing: a packet ends after this long without data.
+    pub frame_gap_ms: Option<u64>,
+    /// How often timers, status messages and idle checks run, in
+    /// milliseconds. Unset: 50; longer polls wake the CPU less often.
+    pub tick_ms: Option<u64>,
+    /// Bytes taken from a port per read. Unset: 1024; high-rate captures
+    /// keep up better with more.
+    pub read_buffer_bytes: Option<usize>,
+    /// How long a file transfer's read waits for data, in milliseconds.
+    /// Unset: 10.
+    pub read_timeout_ms: Option<u64>,
+    /// Ports listed first in the port list, with Alt+1–9 picking them, by
+    /// port name or USB serial number.
+    pub favorite_ports: Vec<String>,
+    /// Answer control commands (`stats`, `export`, `screenshot`) on this
+    /// localhost TCP port.
+    pub control_port: Option<u16>,
+    /// UI language code (`en`, `de`). Unset: taken from `LANG`.
+    pub language: Option<String>,
+    /// In grid view, the cell under the mouse pointer becomes the active
+    /// connection without a click.
+    pub focus_follows_mouse: bool,
+    /// Rhai script whose `on_connect`, `on_line` and `on_disconnect` hooks
+    /// run on every connection.
+    pub script: Option<String>,
+    /// Show a clickable function key bar (F2 New, F3 Find, ...) in place of
+    /// the key hints on the connected screen.
+    pub function_key_bar: bool,
+    /// What a received BEL (0x07) does: nothing, flash the screen (or flag
+    /// a background tab) or ring the terminal's bell.
+    pub bell: BellSetting,
+    /// View for connections on the first run, or on every run without
+    /// `remember_session`.
+    pub default_view: ViewSetting,
+    /// Start with the view, Send bar height and port of the last run. Unset:
+    /// on.
+    pub remember_session: Option<bool>,
+    /// UI color scheme, for dark or light terminal backgrounds.
+    pub theme: ThemeSetting,
+    /// Override single colors of the theme.
+    pub colors: ThemeColors,
+    /// Key chords for connected-screen commands, e.g. `"alt+w" = "close"`,
+    /// or `"none"` to free a chord.
+    pub keymap: BTreeMap<String, String>,
+    /// How long status bar messages stay up, by severity.
+    pub status: StatusDurations,
+    /// Session statistics: extra counters and automatic reports.
+    pub stats: StatsConfig,
+    #[serde(rename = "profile")]
+    pub profiles: Vec<Profile>,
+    #[serde(rename = "highlight")]
+    pub highlights: Vec<HighlightRule>,
+}
+
+/// Seconds a status message of each severity is shown; 0 keeps it up until
+/// it's dismissed with Esc.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct StatusDurations {
+    pub info_secs: u64,
+    pub success_secs: u64,
+    pub warning_secs: u64,
+    pub error_secs: u64,
+}
+
+impl Default for StatusDurations {
+    fn default() -> Self {
+        Self {
+            info_secs: 3,
+            success_secs: 3,
+            warning_secs: 5,
+            error_secs: 0,
+        }
+    }
+}
+
+/// Theme colors by slot, each a color name, index or `#rrggbb` as in
+/// `[[highlight]]`. Unset slots keep the theme's color.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeColors {
+    pub text: Option<String>,
+    pub dim: Option<String>,
+    pub accent: Option<String>,
+    pub selected_fg: Option<String>,
+    pub selected_bg: Option<String>,
+    pub menu_fg: Option<String>,
+    pub menu_bg: Option<String>,
+    pub menu_open_fg: Option<String>,
+    pub menu_open_bg: Option<String>,
+    pub dialog_border: Option<String>,
+    pub alert: Option<String>,
+    pub error: Option<String>,
+    pub success: Option<String>,
+    pub passthrough: Option<String>,
+    pub matched: Option<String>,
+    pub current_match: Option<String>,
+    pub on_accent: Option<String>,
+    /// Plot series colors, up to six
+    pub series: Option<Vec<String>>,
+}
+
+/// Session statistics settings.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct StatsConfig {
+    /// Count the lines matching each of these regexes
+    pub patterns: Vec<String>,
+    /// Write a report in this format whenever a connection closes or drops
+    pub on_close: Option<StatsFormat>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsFormat {
+    Json,
+    Csv,
+}
+
+impl StatsFormat {
+    /// CSV for `.csv` (or `.csv.gz`) files, JSON otherwise.
+    pub fn for_path(path: &str) -> Self {
+        let path = path.strip_suffix(".gz").unwrap_or(path);
+        if path.to_ascii_lowercase().ends_with(".csv") {
+            StatsFormat::Csv
+        } else {
+            StatsFormat::Json
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            StatsFormat::Json => "json",
+            StatsFormat::Csv => "csv",
+        }
+    }
+}
+
+/// Style applied to scrollback text matching a regex, e.g. `ERROR` in red.
+#[derive(Deserialize, Clone)]
+pub struct HighlightRule {
+    pub pattern: String,
+    /// Color name (`red`, `lightyellow`, ...), index or `#rrggbb`
+    pub fg: Option<String>,
+    pub bg: Option<String>,
+    #[serde(default)]
+    pub bold: bool,
+    /// Style the whole line instead of just the match
+    #[serde(default)]
+    pub line: bool,
+}
+
+/// A saved set of connection settings for a known device.
+#[derive(Deserialize, Clone)]
+pub struct Profile {
+    pub name: String,
+    pub port: String,
+    #[serde(default = "default_baud")]
+    pub baud: u32,
+    #[serde(default = "default_data_bits")]
+    pub data_bits: u8,
+    #[serde(default)]
+    pub parity: ParitySetting,
+    #[serde(default = "default_stop_bits")]
+    pub stop_bits: u8,
+    #[serde(default)]
+    pub display: DisplaySetting,
+    /// Pacing preset; the three fields after it override parts of it.
+    #[serde(default)]
+    pub pacing: PacingSetting,
+    pub char_delay_ms: Option<u64>,
+    pub line_delay_ms: Option<u64>,
+    pub flow_control: Option<FlowControlSetting>,
+    #[serde(default, rename = "command")]
+    pub commands: Vec<ProfileCommand>,
+    /// Run before the port is opened; the connection is abandoned if it fails.
+    pub pre_open: Option<String>,
+    /// Run after the connection is closed.
+    pub post_close: Option<String>,
+    /// Off/on commands for Connection → Power Cycle.
+    pub power: Option<PowerCommands>,
+    /// Overrides the top-level `resend_cooldown_ms` for this device.
+    pub resend_cooldown_ms: Option<u64>,
+    /// Overrides the top-level `idle_marker_ms` for this device; 0 turns
+    /// the markers off.
+    pub idle_marker_ms: Option<u64>,
+    /// Override the top-level live stream rotation for this device.
+    pub log_rotate_mb: Option<u64>,
+    pub log_rotate_daily: Option<bool>,
+    /// Override the top-level packet framing for this device.
+    pub frame_delimiter: Option<String>,
+    pub frame_gap_ms: Option<u64>,
+    /// Regexes raising an alert when a received line matches.
+    #[serde(default)]
+    pub watch: Vec<String>,
+}
+
+/// How to power-cycle the target, e.g. through a network relay.
+#[derive(Deserialize, Clone)]
+pub struct PowerCommands {
+    pub off: String,
+    pub on: String,
+    /// Pause between the off and on commands
+    #[serde(default = "default_power_delay_ms")]
+    pub delay_ms: u64,
+    /// Release the port for the cycle, then wait for it to reappear and
+    /// reconnect (for adapters powered by the target). Otherwise the port
+    /// stays open to catch the boot messages.
+    #[serde(default)]
+    pub reconnect: bool,
+}
+
+/// An external command (flasher, upload hook, ...) runnable from a connection.
+/// `${PORT}`, `${BAUD}`, `${LOGFILE}` etc. are substituted at runtime.
+#[derive(Deserialize, Clone)]
+pub struct ProfileCommand {
+    pub name: String,
+    pub run: String,
+}
+
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ParitySetting {
+    #[default]
+    None,
+    Odd,
+    Even,
+}
+
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DisplaySetting {
+    #[default]
+    Text,
+    Hex,
+}
+
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum PacingSetting {
+    #[default]
+    None,
+    FastPc,
+    SlowMcu,
+    PaperTape,
+}
+
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeSetting {
+    #[default]
+    Dark,
+    Light,
+}
+
+#[derive(Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BellSetting {
+    #[default]
+    Off,
+    Flash,
+    Ring,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ViewSetting {
+    #[default]
+    Tabs,
+    Grid,
+    Split,
+}
+
+/// A grid view with a fixed number of columns or rows.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum GridSetting {
+    Columns(u16),
+    Rows(u16),
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum SplitSetting {
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogDurability {
+    Buffered,
+    #[default]
+    Line,
+    Fsync,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum FlowControlSetting {
+    None,
+    Software,
+    Hardware,
+}
+
+fn default_baud() -> u32 {
+    9600
+}
+
+fn default_data_bits() -> u8 {
+    8
+}
+
+fn default_stop_bits() -> u8 {
+    1
+}
+
+fn default_power_delay_ms() -> u64 {
+    1000
+}
+
+impl Config {
+    pub fn partial_flush(&self) -> Option<Duration> {
+        self.partial_flush_ms
+            .filter(|&ms| ms > 0)
+            .map(Duration::from_millis)
+    }
+
+    /// Resend cooldown for a connection, from its profile or the top level.
+    pub fn resend_cooldown(&self, profile: Option<&Profile>) -> Option<Duration> {
+        profile
+            .and_then(|p| p.resend_cooldown_ms)
+            .or(self.resend_cooldown_ms)
+            .filter(|&ms| ms > 0)
+            .map(Duration::from_millis)
+    }
+
+    /// The pause that gets an idle marker on a connection, from its profile
+    /// or the top level.
+    pub fn idle_marker(&self, profile: Option<&Profile>) -> Option<Duration> {
+        profile
+            .and_then(|p| p.idle_marker_ms)
+            .or(self.idle_marker_ms)
+            .filter(|&ms| ms > 0)
+            .map(Duration::from_millis)
+    }
+
+    /// Hex dump packet framing for a connection, from its profile or the top
+    /// level. None when neither a delimiter nor a gap is set.
+    pub fn framing(&self, profile: Option<&Profile>) -> Result<Option<Framing>, String> {
+        let delimiter = profile
+            .and_then(|p| p.frame_delimiter.as_deref())
+            .or(self.frame_delimiter.as_deref())
+            .map(crate::hex::parse)
+            .transpose()
+            .map_err(|e| format!("frame_delimiter: {}", e))?
+            .unwrap_or_default();
+        let gap = profile
+            .and_then(|p| p.frame_gap_ms)
+            .or(self.frame_gap_ms)
+            .filter(|&ms| ms > 0)
+            .map(Duration::from_millis);
+        if delimiter.is_empty() && gap.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(Framing { delimiter, gap }))
+    }
+
+    /// When a connection's live stream starts a new file, from its profile
+    /// or the top level.
+    pub fn log_rotation(&self, profile: Option<&Profile>) -> Rotation {
+        Rotation {
+            max_bytes: profile
+                .and_then(|p| p.log_rotate_mb)
+                .or(self.log_rotate_mb)
+                .filter(|&mb| mb > 0)
+                .map(|mb| mb * 1024 * 1024),
+            daily: profile
+                .and_then(|p| p.log_rotate_daily)
+                .unwrap_or(self.log_rotate_daily),
+        }
+    }
+
+    pub fn log_durability(&self) -> Durability {
+        match self.log_durability {
+            LogDurability::Buffered => Durability::Buffered,
+            LogDurability::Line => Durability::Line,
+            LogDurability::Fsync => Durability::Fsync {
+                interval: Duration::from_millis(self.log_fsync_interval_ms),
+            },
+        }
+    }
+
+    pub fn remembers_session(&self) -> bool {
+        self.remember_session.unwrap_or(true)
+    }
+
+    pub fn scrollback_limit(&self) -> usize {
+        match self.scrollback_lines {
+            None => DEFAULT_SCROLLBACK_LINES,
+            Some(0) => usize::MAX,
+            Some(lines) => lines,
+        }
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join("serialtui").join("config.toml"))
+    }
+
+    /// No config file yet, i.e. this is the first launch.
+    pub fn is_missing() -> bool {
+        Self::path().is_some_and(|path| !path.exists())
+    }
+
+    /// Write the starter config to `path()`. Never overwrites an existing file.
+    pub fn write_starter() -> Result<PathBuf> {
+        let path = Self::path().context("no config directory on this system")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .and_then(|mut f| f.write_all(STARTER.as_bytes()))
+            .with_context(|| format!("writing {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Load the config file; a missing file yields the defaults.
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("reading {}", path.display()))?;
+        toml::from_str(&text).with_context(|| format!("parsing {}", path.display()))
+    }
+}
+
+impl Profile {
+    /// The `watch` patterns as one regex; None without any.
+    pub fn watch(&self) -> Result<Option<regex::Regex>, String> {
+        if self.watch.is_empty() {
+            return Ok(None);
+        }
+        for pattern in &self.watch {
+            regex::Regex::new(pattern).map_err(|e| format!("watch \"{}\": {}", pattern, e))?;
+        }
+        regex::Regex::new(&self.watch.join("|"))
+            .map(Some)
+            .map_err(|e| format!("watch: {}", e))
+    }
+
+    pub fn data_bits(&self) -> serialport::DataBits {
+        match self.data_bits {
+            5 => serialport::DataBits::Five,
+            6 => serialport::DataBits::Six,
+            7 => serialport::DataBits::Seven,
+            _ => serialport::DataBits::Eight,
+        }
+    }
+
+    pub fn parity(&self) -> serialport::Parity {
+        match self.parity {
+            ParitySetting::None => serialport::Parity::None,
+            ParitySetting::Odd => serialport::Parity::Odd,
+            ParitySetting::Even => serialport::Parity::Even,
+        }
+    }
+
+    pub fn stop_bits(&self) -> serialport::StopBits {
+        match self.stop_bits {
+            2 => serialport::StopBits::Two,
+            _ => serialport::StopBits::One,
+        }
+    }
+
+    pub fn display_mode(&self) -> DisplayMode {
+        match self.display {
+            DisplaySetting::Text => DisplayMode::Text,
+            DisplaySetting::Hex => DisplayMode::HexDump,
+        }
+    }
+
+    pub fn pacing(&self) -> Pacing {
+        let mut pacing = match self.pacing {
+            PacingSetting::None => Pacing::NONE,
+            PacingSetting::FastPc => Pacing::FAST_PC,
+            PacingSetting::SlowMcu => Pacing::SLOW_MCU,
+            PacingSetting::PaperTape => Pacing::PAPER_TAPE,
+        };
+        if let Some(ms) = self.char_delay_ms {
+            pacing.char_delay = Duration::from_millis(ms);
+        }
+        if let Some(ms) = self.line_delay_ms {
+            pacing.line_delay = Duration::from_millis(ms);
+        }
+        if let Some(flow_control) = self.flow_control {
+            pacing.flow_control = match flow_control {
+                FlowControlSetting::None => serialport::FlowControl::None,
+                FlowControlSetting::Software => serialport::FlowControl::Software,
+                FlowControlSetting::Hardware => serialport::FlowControl::Hardware,
+            };
+        }
+        pacing
+    }
+
+    /// Short settings summary, e.g. `115200 8N1`.
+    pub fn summary(&self) -> String {
+        let parity = match self.parity {
+            ParitySetting::None => 'N',
+            ParitySetting::Odd => 'O',
+            ParitySetting::Even => 'E',
+        };
+        format!(
+            "{} {}{}{}",
+            self.baud,
+            self.data_bits.clamp(5, 8),
+            parity,
+            self.stop_bits.clamp(1, 2)
+        )
+    }
+}
@@ -0,0 +1,106 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+// Control socket protocol: one command per line (`stats`, `export <id> [file]`,
+// `screenshot`), answered with one line of JSON that always has an `ok`
+// field, plus `error` when it's false. Commands are answered by the UI thread
+// between frames, so replies reflect exactly what's on screen. Any local
+// process can connect, so exports only go to `export_dir()`.
+
+/// How long a client waits for the UI thread before giving up.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Clients served at once; more are turned away with an error.
+const MAX_CLIENTS: usize = 4;
+
+/// A command from a control client, answered through `reply`.
+pub struct ControlRequest {
+    pub command: String,
+    pub reply: mpsc::Sender<String>,
+}
+
+/// Accept control clients on `127.0.0.1:port`, forwarding their commands.
+pub fn listen(port: u16, requests: mpsc::Sender<ControlRequest>) -> io::Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    let clients = Arc::new(AtomicUsize::new(0));
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            if clients.fetch_add(1, Ordering::SeqCst) >= MAX_CLIENTS {
+                clients.fetch_sub(1, Ordering::SeqCst);
+                let _ = writeln!(stream, "{}", error("too many control clients"));
+                continue;
+            }
+            let requests = requests.clone();
+            let clients = Arc::clone(&clients);
+            thread::spawn(move || {
+                serve(stream, requests);
+                clients.fetch_sub(1, Ordering::SeqCst);
+            });
+        }
+    });
+    Ok(())
+}
+
+/// Where `export <id> <file>` writes: `exports` in serialtui's state folder.
+pub fn export_dir() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|d| d.join("serialtui").join("exports"))
+}
+
+/// The path in `export_dir()` for a client's file name, which must be a
+/// plain name: no folders, and not hidden.
+pub fn export_path(name: &str) -> Result<PathBuf, String> {
+    if name.starts_with('.') || Path::new(name).file_name() != Some(name.as_ref()) {
+        return Err(format!("{:?} is not a plain file name", name));
+    }
+    let dir = export_dir().ok_or("no state folder to export to")?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("creating {}: {}", dir.display(), e))?;
+    Ok(dir.join(name))
+}
+
+fn serve(stream: TcpStream, requests: mpsc::Sender<ControlRequest>) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let command = line.trim().to_string();
+        if command.is_empty() {
+            continue;
+        }
+        let (reply_tx, reply_rx) = mpsc::channel();
+        let request = ControlRequest {
+            command,
+            reply: reply_tx,
+        };
+        if requests.send(request).is_err() {
+            break;
+        }
+        let reply = reply_rx
+            .recv_timeout(REPLY_TIMEOUT)
+            .unwrap_or_else(|_| error("serialtui did not answer"));
+        if writeln!(writer, "{}", reply).is_err() {
+            break;
+        }
+    }
+}
+
+/// A successful reply: `body`'s fields plus `"ok": true`.
+pub fn ok(mut body: serde_json::Value) -> String {
+    if let Some(fields) = body.as_object_mut() {
+        fields.insert("ok".into(), true.into());
+    }
+    body.to_string()
+}
+
+pub fn error(message: impl std::fmt::Display) -> String {
+    serde_json::json!({ "ok": false, "error": message.to_string() }).to_string()
+}
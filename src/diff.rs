@@ -0,0 +1,158 @@
+/// One row of a unified diff.
+#[derive(Clone)]
+pub enum DiffLine {
+    /// `@@ -start,count +start,count @@`
+    Hunk(String),
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+/// Unchanged lines shown around each change.
+const CONTEXT: usize = 3;
+
+/// Differing lines the shortest edit is searched for at most. Past that the
+/// rest of the texts counts as replaced, which still shows where they part.
+const MAX_EDITS: usize = 1000;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Op {
+    Same,
+    Remove,
+    Add,
+}
+
+/// The changes from `old` to `new` as unified diff hunks; empty when they
+/// are the same.
+pub fn unified(old: &[&str], new: &[&str]) -> Vec<DiffLine> {
+    let ops = edits(old, new);
+
+    // Each op with the old and new line it starts at
+    let mut at = Vec::with_capacity(ops.len());
+    let (mut i, mut j) = (0, 0);
+    for &op in &ops {
+        at.push((op, i, j));
+        match op {
+            Op::Same => (i, j) = (i + 1, j + 1),
+            Op::Remove => i += 1,
+            Op::Add => j += 1,
+        }
+    }
+
+    let changes: Vec<usize> = (0..at.len()).filter(|&n| at[n].0 != Op::Same).collect();
+    let mut lines = Vec::new();
+    let mut n = 0;
+    while n < changes.len() {
+        // Changes this close together share a hunk
+        let mut last = n;
+        while last + 1 < changes.len() && changes[last + 1] - changes[last] <= 2 * CONTEXT + 1 {
+            last += 1;
+        }
+        let start = changes[n].saturating_sub(CONTEXT);
+        let end = (changes[last] + CONTEXT + 1).min(at.len());
+        let hunk = &at[start..end];
+
+        let old_count = hunk.iter().filter(|(op, ..)| *op != Op::Add).count();
+        let new_count = hunk.iter().filter(|(op, ..)| *op != Op::Remove).count();
+        let (_, i, j) = hunk[0];
+        lines.push(DiffLine::Hunk(format!(
+            "@@ -{},{} +{},{} @@",
+            i + usize::from(old_count > 0),
+            old_count,
+            j + usize::from(new_count > 0),
+            new_count
+        )));
+        lines.extend(hunk.iter().map(|&(op, i, j)| match op {
+            Op::Same => DiffLine::Same(old[i].to_string()),
+            Op::Remove => DiffLine::Removed(old[i].to_string()),
+            Op::Add => DiffLine::Added(new[j].to_string()),
+        }));
+        n = last + 1;
+    }
+    lines
+}
+
+/// The edit script from `old` to `new`, without searching the common start
+/// and end.
+fn edits(old: &[&str], new: &[&str]) -> Vec<Op> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut ops = vec![Op::Same; prefix];
+    let (a, b) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+    ops.extend(shortest_edit(a, b).unwrap_or_else(|| {
+        let mut replaced = vec![Op::Remove; a.len()];
+        replaced.extend(vec![Op::Add; b.len()]);
+        replaced
+    }));
+    ops.extend(vec![Op::Same; suffix]);
+    ops
+}
+
+/// Myers' shortest edit script, or None when it takes more than
+/// `MAX_EDITS` edits.
+fn shortest_edit(a: &[&str], b: &[&str]) -> Option<Vec<Op>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (a.len() + b.len()).min(MAX_EDITS) as isize;
+    // Furthest x reached on each diagonal k = x - y, indexed k + max + 1
+    let offset = max + 1;
+    let mut v = vec![0isize; 2 * max as usize + 3];
+    // v's diagonals -d..=d before each round d, for walking back
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    for d in 0..=max {
+        trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let down =
+                k == -d || (k != d && v[(offset + k - 1) as usize] < v[(offset + k + 1) as usize]);
+            let mut x = if down {
+                v[(offset + k + 1) as usize]
+            } else {
+                v[(offset + k - 1) as usize] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[(offset + k) as usize] = x;
+            if x >= n && y >= m {
+                return Some(backtrack(&trace, n, m));
+            }
+        }
+    }
+    None
+}
+
+fn backtrack(trace: &[Vec<isize>], n: isize, m: isize) -> Vec<Op> {
+    let mut ops = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let at = |k: isize| v[(k + d) as usize];
+        let k = x - y;
+        let down = k == -d || (k != d && at(k - 1) < at(k + 1));
+        let prev_k = if down { k + 1 } else { k - 1 };
+        let prev_x = if d == 0 { 0 } else { at(prev_k) };
+        let prev_y = if d == 0 { 0 } else { prev_x - prev_k };
+        while x > prev_x && y > prev_y {
+            ops.push(Op::Same);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            ops.push(if down { Op::Add } else { Op::Remove });
+        }
+        (x, y) = (prev_x, prev_y);
+    }
+    ops.reverse();
+    ops
+}
@@ -0,0 +1,221 @@
+use std::fs::File;
+use std::io::{self, Write};
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::serial::{self, Connection};
+
+/// What File → Export writes; Up/Down in the filename prompt step through
+/// these.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    /// The scrollback as shown
+    Text,
+    /// The received bytes as they came in
+    Raw,
+    /// A row per read or write: time, direction and data
+    Csv,
+    /// The same as JSON objects, one per line
+    JsonLines,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 4] = [
+        ExportFormat::Text,
+        ExportFormat::Raw,
+        ExportFormat::Csv,
+        ExportFormat::JsonLines,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ExportFormat::Text => "text",
+            ExportFormat::Raw => "raw binary",
+            ExportFormat::Csv => "CSV",
+            ExportFormat::JsonLines => "JSON lines",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Text => "txt",
+            ExportFormat::Raw => "bin",
+            ExportFormat::Csv => "csv",
+            ExportFormat::JsonLines => "jsonl",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let idx = Self::ALL.iter().position(|&f| f == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    pub fn prev(self) -> Self {
+        let idx = Self::ALL.iter().position(|&f| f == self).unwrap_or(0);
+        Self::ALL[(idx + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    /// Whether it records when each read or write happened.
+    pub fn has_timestamps(self) -> bool {
+        matches!(self, ExportFormat::Csv | ExportFormat::JsonLines)
+    }
+
+    /// The file contents for `conn`. CSV and JSON lines carry the data with
+    /// `\\` and unprintable bytes as `\xNN`, like live streams, and the time
+    /// of each read or write unless `timestamps` is off.
+    pub fn render(self, conn: &Connection, timestamps: bool) -> Vec<u8> {
+        match self {
+            ExportFormat::Text => conn
+                .scrollback_with_partial()
+                .collect::<Vec<_>>()
+                .join("\n")
+                .into_bytes(),
+            ExportFormat::Raw => conn.capture.received(),
+            ExportFormat::Csv => {
+                let mut out = String::from(match timestamps {
+                    true => "time,direction,data\n",
+                    false => "direction,data\n",
+                });
+                for chunk in conn.capture.chunks() {
+                    if timestamps {
+                        out.push_str(&format!("{},", chunk.time.format(TIME_FORMAT)));
+                    }
+                    out.push_str(&format!(
+                        "{},\"{}\"\n",
+                        chunk.direction,
+                        serial::escape(&chunk.data).replace('"', "\"\"")
+                    ));
+                }
+                out.into_bytes()
+            }
+            ExportFormat::JsonLines => {
+                let mut out = String::new();
+                for chunk in conn.capture.chunks() {
+                    let mut line = serde_json::json!({
+                        "direction": chunk.direction.name(),
+                        "data": serial::escape(&chunk.data),
+                    });
+                    if timestamps {
+                        line["time"] = chunk.time.format(TIME_FORMAT).to_string().into();
+                    }
+                    out.push_str(&line.to_string());
+                    out.push('\n');
+                }
+                out.into_bytes()
+            }
+        }
+    }
+}
+
+/// Write an export to `path`, gzip-compressed when it ends in `.gz`.
+pub fn write(path: &str, content: &[u8]) -> io::Result<()> {
+    if !serial::is_gzip(path) {
+        return std::fs::write(path, content);
+    }
+    let mut encoder = GzEncoder::new(File::create(path)?, Compression::default());
+    encoder.write_all(content)?;
+    encoder.finish()?;
+    Ok(())
+}
+
+/// How CSV and JSON lines exports write the time of a read or write.
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3f";
+
+/// What the export filename prompt has picked.
+#[derive(Clone, Copy, PartialEq)]
+pub struct ExportSettings {
+    pub format: ExportFormat,
+    /// Only text exports take a range
+    pub range: ExportRange,
+    /// Whether CSV and JSON lines record each read's or write's time;
+    /// toggled with Ctrl+T in the prompt
+    pub timestamps: bool,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            format: ExportFormat::Text,
+            range: ExportRange::All,
+            timestamps: true,
+        }
+    }
+}
+
+/// The part of the scrollback a text export covers; PgUp/PgDn in the
+/// filename prompt step through these.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ExportRange {
+    All,
+    /// The lines in the connection's pane
+    Screen,
+    /// From the next to last note through the last one, or from the only
+    /// note to the end
+    Notes,
+    /// The last this many lines
+    Last(usize),
+}
+
+impl ExportRange {
+    pub const ALL: [ExportRange; 6] = [
+        ExportRange::All,
+        ExportRange::Screen,
+        ExportRange::Notes,
+        ExportRange::Last(100),
+        ExportRange::Last(1000),
+        ExportRange::Last(10_000),
+    ];
+
+    pub fn next(self) -> Self {
+        let idx = Self::ALL.iter().position(|&r| r == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    pub fn prev(self) -> Self {
+        let idx = Self::ALL.iter().position(|&r| r == self).unwrap_or(0);
+        Self::ALL[(idx + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    /// Indices into `scrollback_with_partial()` of the lines it covers, with
+    /// the connection's pane `rows` tall (None when it's not on screen).
+    pub fn lines(self, conn: &Connection, rows: Option<usize>) -> Result<Vec<usize>, String> {
+        let count = conn.scrollback_with_partial().count();
+        match self {
+            ExportRange::All => Ok((0..count).collect()),
+            ExportRange::Screen => {
+                let rows = rows.ok_or("The connection isn't on screen")?;
+                let shown = conn.shown_lines();
+                Ok(shown[conn.view_window(shown.len(), rows)].to_vec())
+            }
+            ExportRange::Notes => {
+                let evicted = conn.evicted_lines();
+                let notes: Vec<usize> = conn
+                    .notes
+                    .iter()
+                    .filter_map(|line| line.checked_sub(evicted))
+                    .collect();
+                match notes.as_slice() {
+                    [] => Err(
+                        "No notes in the scrollback; add them with Connection → Add Note".into(),
+                    ),
+                    [only] => Ok((*only..count).collect()),
+                    [.., from, to] => Ok((*from..=*to).collect()),
+                }
+            }
+            ExportRange::Last(n) => Ok((count.saturating_sub(n)..count).collect()),
+        }
+    }
+}
+
+/// The scrollback lines at `lines` (indices into
+/// `scrollback_with_partial()`) as a text export.
+pub fn text(conn: &Connection, lines: &[usize]) -> Vec<u8> {
+    let scrollback: Vec<&str> = conn.scrollback_with_partial().collect();
+    lines
+        .iter()
+        .map(|&i| scrollback[i])
+        .collect::<Vec<_>>()
+        .join("\n")
+        .into_bytes()
+}
@@ -0,0 +1,150 @@
+use std::ops::Range;
+
+/// One whitespace/comma separated token of hex input and whether it parses.
+pub struct Token {
+    pub range: Range<usize>,
+    pub valid: bool,
+}
+
+/// Split hex input into tokens. A token is one or more byte pairs
+/// (`DE`, `DEAD`, `0xBE`); an odd digit count or a non-hex character makes it
+/// invalid.
+pub fn tokens(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        let separator = c.is_whitespace() || c == ',';
+        match (start, separator) {
+            (None, false) => start = Some(i),
+            (Some(s), true) => {
+                tokens.push(Token {
+                    range: s..i,
+                    valid: token_bytes(&text[s..i]).is_some(),
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+/// Parse hex input into bytes, or describe the first malformed token.
+pub fn parse(text: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    for token in tokens(text) {
+        let s = &text[token.range];
+        match token_bytes(s) {
+            Some(b) => bytes.extend(b),
+            None => return Err(format!("Invalid hex: {}", s)),
+        }
+    }
+    Ok(bytes)
+}
+
+/// Format bytes as space separated pairs (`DE AD BE EF`), the form `parse` reads.
+pub fn format(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn token_bytes(token: &str) -> Option<Vec<u8>> {
+    let digits = token
+        .strip_prefix("0x")
+        .or_else(|| token.strip_prefix("0X"))
+        .unwrap_or(token);
+    if digits.is_empty() || !digits.len().is_multiple_of(2) || !digits.is_ascii() {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Source-code forms a captured frame can be copied as.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ArrayFormat {
+    C,
+    Rust,
+}
+
+impl ArrayFormat {
+    pub const ALL: [ArrayFormat; 2] = [ArrayFormat::C, ArrayFormat::Rust];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ArrayFormat::C => "C array (uint8_t[])",
+            ArrayFormat::Rust => "Rust byte slice (&[u8])",
+        }
+    }
+
+    /// Format `bytes` as a declaration, 16 bytes per line for longer frames.
+    pub fn format(self, bytes: &[u8]) -> String {
+        let rows: Vec<String> = bytes
+            .chunks(16)
+            .map(|row| {
+                row.iter()
+                    .map(|b| format!("0x{:02X}", b))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect();
+        let body = if rows.len() > 1 {
+            format!("\n    {},\n", rows.join(",\n    "))
+        } else {
+            rows.concat()
+        };
+        match self {
+            ArrayFormat::C => format!("const uint8_t frame[{}] = {{{}}};", bytes.len(), body),
+            ArrayFormat::Rust => format!("const FRAME: &[u8] = &[{}];", body),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_pairs_runs_and_prefixes() {
+        assert_eq!(parse("DE ad,0xBE 0Xef"), Ok(vec![0xDE, 0xAD, 0xBE, 0xEF]));
+        assert_eq!(parse("DEADBEEF"), Ok(vec![0xDE, 0xAD, 0xBE, 0xEF]));
+        assert_eq!(parse("  01,, 02\t"), Ok(vec![0x01, 0x02]));
+        assert_eq!(parse(""), Ok(vec![]));
+    }
+
+    #[test]
+    fn parse_names_the_first_bad_token() {
+        assert_eq!(parse("DE ABC 12"), Err("Invalid hex: ABC".to_string()));
+        assert_eq!(parse("0x"), Err("Invalid hex: 0x".to_string()));
+        assert_eq!(parse("GG"), Err("Invalid hex: GG".to_string()));
+        assert_eq!(parse("ÄÖ"), Err("Invalid hex: ÄÖ".to_string()));
+    }
+
+    #[test]
+    fn tokens_mark_ranges_and_validity() {
+        let text = "DE 1 0xBE,zz";
+        let tokens: Vec<_> = tokens(text)
+            .into_iter()
+            .map(|t| (&text[t.range], t.valid))
+            .collect();
+        assert_eq!(
+            tokens,
+            [("DE", true), ("1", false), ("0xBE", true), ("zz", false)]
+        );
+    }
+
+    #[test]
+    fn format_is_read_back_by_parse() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        assert_eq!(parse(&format(&bytes)), Ok(bytes));
+        assert_eq!(format(&[0x0A, 0xFF]), "0A FF");
+    }
+}
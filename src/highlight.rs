@@ -0,0 +1,114 @@
+use std::str::FromStr;
+
+use ratatui::style::{Color, Modifier, Style};
+use regex::Regex;
+
+use crate::config::HighlightRule;
+
+/// Compiled `[[highlight]]` rules from the config.
+#[derive(Default)]
+pub struct Highlighter {
+    rules: Vec<Rule>,
+}
+
+struct Rule {
+    regex: Regex,
+    style: Style,
+    whole_line: bool,
+}
+
+impl Highlighter {
+    pub fn new(rules: &[HighlightRule]) -> Result<Self, String> {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                let regex = Regex::new(&rule.pattern)
+                    .map_err(|e| format!("highlight \"{}\": {}", rule.pattern, e))?;
+                let mut style = Style::default();
+                if let Some(fg) = &rule.fg {
+                    style = style.fg(parse_color(fg)?);
+                }
+                if let Some(bg) = &rule.bg {
+                    style = style.bg(parse_color(bg)?);
+                }
+                if rule.bold {
+                    style = style.add_modifier(Modifier::BOLD);
+                }
+                Ok(Rule {
+                    regex,
+                    style,
+                    whole_line: rule.line,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self { rules })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Styled byte ranges of `text` (a line with escapes stripped), one entry
+    /// per matching rule in config order, so later rules win. Each entry's
+    /// ranges are sorted and disjoint.
+    pub fn matches(&self, text: &str) -> Vec<(Vec<(usize, usize)>, Style)> {
+        self.rules
+            .iter()
+            .filter_map(|rule| {
+                let ranges: Vec<(usize, usize)> = if !rule.whole_line {
+                    rule.regex
+                        .find_iter(text)
+                        .filter(|m| !m.is_empty())
+                        .map(|m| (m.start(), m.end()))
+                        .collect()
+                } else if rule.regex.is_match(text) {
+                    vec![(0, text.len())]
+                } else {
+                    Vec::new()
+                };
+                (!ranges.is_empty()).then_some((ranges, rule.style))
+            })
+            .collect()
+    }
+}
+
+pub(crate) fn parse_color(name: &str) -> Result<Color, String> {
+    Color::from_str(name).map_err(|_| format!("unknown color \"{}\"", name))
+}
+
+/// A runtime view filter: show only lines matching `pattern`, or with
+/// `hide`, only lines that don't. Lines stay in the scrollback either way.
+pub struct LineFilter {
+    pub pattern: String,
+    pub hide: bool,
+    regex: Regex,
+}
+
+impl LineFilter {
+    /// Parse the filter prompt's text; a leading `!` hides matches.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (hide, pattern) = match text.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        let regex = Regex::new(pattern).map_err(|e| format!("Invalid filter: {}", e))?;
+        Ok(Self {
+            pattern: pattern.to_string(),
+            hide,
+            regex,
+        })
+    }
+
+    /// The text the filter prompt was given.
+    pub fn text(&self) -> String {
+        if self.hide {
+            format!("!{}", self.pattern)
+        } else {
+            self.pattern.clone()
+        }
+    }
+
+    pub fn shows(&self, line: &str) -> bool {
+        self.regex.is_match(&crate::ansi::strip(line)) != self.hide
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Locales built into the binary: (language code, strings file).
+const LOCALES: [(&str, &str); 2] = [
+    ("en", include_str!("../locales/en.toml")),
+    ("de", include_str!("../locales/de.toml")),
+];
+
+struct Strings {
+    selected: HashMap<String, String>,
+    english: HashMap<String, String>,
+}
+
+static STRINGS: OnceLock<Strings> = OnceLock::new();
+
+/// Pick the UI language: `language` from the config if set, otherwise the
+/// locale environment (`LC_ALL`, `LC_MESSAGES`, `LANG`). Unknown languages
+/// fall back to English. Returns an error naming a configured language that
+/// isn't available.
+pub fn init(language: Option<&str>) -> Result<(), String> {
+    let env = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|v| !v.is_empty());
+    let requested = language.map(str::to_string).or(env).unwrap_or_default();
+    // "de_DE.UTF-8" -> "de"
+    let code = requested
+        .split(['_', '.', '-', '@'])
+        .next()
+        .unwrap_or("")
+        .to_ascii_lowercase();
+
+    let found = LOCALES.iter().find(|(c, _)| *c == code);
+    let _ = STRINGS.set(Strings {
+        selected: found.map(|(_, src)| parse(src)).unwrap_or_default(),
+        english: parse(LOCALES[0].1),
+    });
+    match (language, found) {
+        (Some(l), None) => Err(format!("Unknown language {:?}, using English", l)),
+        _ => Ok(()),
+    }
+}
+
+/// The UI string for `key` (`section.name`) in the current language.
+pub fn t(key: &'static str) -> &'static str {
+    let Some(strings) = STRINGS.get() else {
+        return key;
+    };
+    strings
+        .selected
+        .get(key)
+        .or_else(|| strings.english.get(key))
+        .map(String::as_str)
+        .unwrap_or(key)
+}
+
+/// `t(key)` with each `{}` replaced by the next of `args`.
+pub fn tf(key: &'static str, args: &[&dyn std::fmt::Display]) -> String {
+    let mut out = String::new();
+    let mut args = args.iter();
+    let mut parts = t(key).split("{}").peekable();
+    while let Some(part) = parts.next() {
+        out.push_str(part);
+        if parts.peek().is_some() {
+            if let Some(arg) = args.next() {
+                out.push_str(&arg.to_string());
+            }
+        }
+    }
+    out
+}
+
+/// Flatten `[section] name = "..."` tables into `section.name` keys.
+fn parse(src: &str) -> HashMap<String, String> {
+    let mut strings = HashMap::new();
+    let Ok(table) = src.parse::<toml::Table>() else {
+        return strings;
+    };
+    for (section, value) in table {
+        let Some(entries) = value.as_table() else {
+            continue;
+        };
+        for (name, text) in entries {
+            if let Some(text) = text.as_str() {
+                strings.insert(format!("{}.{}", section, name), text.to_string());
+            }
+        }
+    }
+    strings
+}
@@ -1,19 +1,12 @@
-use std::time::Duration;
-
 use ratatui::crossterm::event::{
     self, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEventKind,
 };
 
 use crate::app::{App, Dialog, PendingScreen, Screen};
+use crate::keymap::Action;
 use crate::message::Message;
 
-pub fn poll_event(app: &App) -> Option<Message> {
-    if !event::poll(Duration::from_millis(50)).ok()? {
-        return None;
-    }
-
-    let event = event::read().ok()?;
-
+pub fn map_event(app: &App, event: Event) -> Option<Message> {
     match event {
         Event::Key(key) => {
             if key.kind != event::KeyEventKind::Press {
@@ -29,22 +22,51 @@ pub fn poll_event(app: &App) -> Option<Message> {
                 return Some(Message::CloseMenu);
             }
 
+            // A sticky status message takes Esc before the screen does
+            if key.code == KeyCode::Esc && app.status_is_sticky() {
+                return Some(Message::DismissStatus);
+            }
+
+            // F1 on the connected screen goes through its function keys,
+            // after passthrough has had a chance to send it to the port
+            if key.code == KeyCode::F(1)
+                && (app.screen != Screen::Connected || app.is_pending_active())
+            {
+                return Some(Message::OpenHelp);
+            }
+
             match app.screen {
-                Screen::PortSelect => map_port_select(key),
+                Screen::ProfileSelect => map_profile_select(key),
+                Screen::PortSelect => map_port_select(key, app),
                 Screen::BaudSelect => map_baud_select(key),
                 Screen::DataBitsSelect => map_list_select(key),
                 Screen::ParitySelect => map_list_select(key),
                 Screen::StopBitsSelect => map_list_select(key),
                 Screen::DisplayModeSelect => map_list_select(key),
+                Screen::PacingSelect => map_list_select(key),
                 Screen::Connected => {
                     if app.is_pending_active() {
-                        map_pending(key, app.pending_connection.unwrap())
+                        map_pending(key, app, app.pending_connection.unwrap())
                     } else {
-                        map_connected(key)
+                        map_connected(key, app)
                     }
                 }
             }
         }
+        // Bracketed paste: the terminal delivers pasted text in one piece
+        Event::Paste(text) => {
+            if app.dialog.is_some()
+                || app.open_menu.is_some()
+                || app.screen != Screen::Connected
+                || app.is_pending_active()
+            {
+                return None;
+            }
+            if app.is_passthrough_active() && app.active_busy_text().is_none() {
+                return Some(Message::SendRaw(text.into_bytes()));
+            }
+            Some(Message::Paste(text))
+        }
         Event::Mouse(mouse) => {
             if app.dialog.is_some() {
                 return None; // ignore mouse while dialog is open
@@ -53,6 +75,14 @@ pub fn poll_event(app: &App) -> Option<Message> {
                 MouseEventKind::Down(MouseButton::Left) => {
                     Some(Message::MenuClick(mouse.column, mouse.row))
                 }
+                MouseEventKind::Down(MouseButton::Right) => {
+                    Some(Message::RightClick(mouse.column, mouse.row))
+                }
+                MouseEventKind::Drag(MouseButton::Left) => Some(Message::MouseDrag(mouse.row)),
+                MouseEventKind::Up(MouseButton::Left) => Some(Message::MouseUp),
+                MouseEventKind::Moved if app.config.focus_follows_mouse => {
+                    Some(Message::MouseMove(mouse.column, mouse.row))
+                }
                 MouseEventKind::ScrollUp => {
                     if app.screen == Screen::Connected {
                         Some(Message::ScrollUp)
@@ -67,34 +97,130 @@ pub fn poll_event(app: &App) -> Option<Message> {
                         None
                     }
                 }
+                MouseEventKind::ScrollLeft if app.screen == Screen::Connected => {
+                    Some(Message::ScrollLeft)
+                }
+                MouseEventKind::ScrollRight if app.screen == Screen::Connected => {
+                    Some(Message::ScrollRight)
+                }
                 _ => None,
             }
         }
+        Event::Resize(cols, rows) => Some(Message::Resize(cols, rows)),
         _ => None,
     }
 }
 
 fn map_dialog(key: KeyEvent, dialog: &Dialog) -> Option<Message> {
     match dialog {
-        Dialog::ConfirmCloseConnection | Dialog::ConfirmQuit => match key.code {
+        Dialog::ConfirmCloseConnection
+        | Dialog::ConfirmQuit
+        | Dialog::Welcome
+        | Dialog::ConfirmZmodemReceive { .. } => match key.code {
             KeyCode::Char('y') | KeyCode::Char('Y') => Some(Message::DialogYes),
             KeyCode::Char('n') | KeyCode::Char('N') => Some(Message::DialogNo),
             KeyCode::Esc => Some(Message::DialogCancel),
             _ => None,
         },
+        Dialog::CommandMenu { .. }
+        | Dialog::ProtocolMenu { .. }
+        | Dialog::GeneratorMenu { .. }
+        | Dialog::CopyAs { .. }
+        | Dialog::ResendMenu { .. }
+        | Dialog::BridgeMenu { .. }
+        | Dialog::DiffMenu { .. }
+        | Dialog::GridMenu { .. }
+        | Dialog::DecoderMenu { .. } => match key.code {
+            KeyCode::Up => Some(Message::DialogUp),
+            KeyCode::Down => Some(Message::DialogDown),
+            KeyCode::Enter => Some(Message::DialogConfirm),
+            KeyCode::Esc => Some(Message::DialogCancel),
+            _ => None,
+        },
+        Dialog::FolderBrowser { .. } => match key.code {
+            KeyCode::Up => Some(Message::DialogUp),
+            KeyCode::Down => Some(Message::DialogDown),
+            KeyCode::Left => Some(Message::DialogCursorLeft),
+            KeyCode::Right => Some(Message::DialogCursorRight),
+            KeyCode::Enter => Some(Message::DialogConfirm),
+            KeyCode::Esc => Some(Message::DialogCancel),
+            _ => None,
+        },
+        Dialog::TriggerList { .. } => match key.code {
+            KeyCode::Up => Some(Message::DialogUp),
+            KeyCode::Down => Some(Message::DialogDown),
+            KeyCode::Enter => Some(Message::DialogConfirm),
+            KeyCode::Delete | KeyCode::Char('d') => Some(Message::DialogDelete),
+            KeyCode::Esc => Some(Message::DialogCancel),
+            _ => None,
+        },
+        Dialog::Preferences { .. } | Dialog::LineSetup { .. } => match key.code {
+            KeyCode::Up => Some(Message::DialogUp),
+            KeyCode::Down => Some(Message::DialogDown),
+            KeyCode::Left => Some(Message::DialogCursorLeft),
+            KeyCode::Right => Some(Message::DialogCursorRight),
+            KeyCode::Enter => Some(Message::DialogConfirm),
+            KeyCode::Esc => Some(Message::DialogCancel),
+            _ => None,
+        },
+        Dialog::Help { .. } | Dialog::AsciiTable { .. } | Dialog::Diff { .. } => match key.code {
+            KeyCode::Up => Some(Message::DialogUp),
+            KeyCode::Down => Some(Message::DialogDown),
+            KeyCode::PageUp => Some(Message::DialogPageUp),
+            KeyCode::PageDown => Some(Message::DialogPageDown),
+            KeyCode::Esc | KeyCode::Enter | KeyCode::F(1) | KeyCode::Char('q') => {
+                Some(Message::DialogCancel)
+            }
+            _ => None,
+        },
+        Dialog::Transfer { .. } => match key.code {
+            KeyCode::Enter => Some(Message::DialogConfirm),
+            KeyCode::Esc => Some(Message::DialogCancel),
+            _ => None,
+        },
         Dialog::FileNamePrompt { .. } => match key.code {
+            KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                Some(Message::DialogToggle)
+            }
             KeyCode::Enter => Some(Message::DialogConfirm),
             KeyCode::Esc => Some(Message::DialogCancel),
             KeyCode::Backspace => Some(Message::DialogBackspace),
             KeyCode::Left => Some(Message::DialogCursorLeft),
             KeyCode::Right => Some(Message::DialogCursorRight),
+            KeyCode::Up => Some(Message::DialogUp),
+            KeyCode::Down => Some(Message::DialogDown),
+            KeyCode::PageUp => Some(Message::DialogPageUp),
+            KeyCode::PageDown => Some(Message::DialogPageDown),
+            KeyCode::Tab => Some(Message::DialogTab),
+            KeyCode::Char(c) => Some(Message::DialogCharInput(c)),
+            _ => None,
+        },
+        Dialog::SearchPrompt { .. }
+        | Dialog::ChecksumPrompt { .. }
+        | Dialog::FilterPrompt { .. }
+        | Dialog::HostPrompt { .. }
+        | Dialog::PathPrompt { .. }
+        | Dialog::SharePrompt { .. }
+        | Dialog::TriggerPrompt { .. }
+        | Dialog::CapturePrompt { .. }
+        | Dialog::GeneratorPrompt { .. }
+        | Dialog::WatchPrompt { .. }
+        | Dialog::FieldsPrompt { .. }
+        | Dialog::LinkTestPrompt { .. }
+        | Dialog::NotePrompt { .. } => match key.code {
+            KeyCode::Enter => Some(Message::DialogConfirm),
+            KeyCode::Esc => Some(Message::DialogCancel),
+            KeyCode::Backspace => Some(Message::DialogBackspace),
+            KeyCode::Left => Some(Message::DialogCursorLeft),
+            KeyCode::Right => Some(Message::DialogCursorRight),
+            KeyCode::Tab => Some(Message::DialogTab),
             KeyCode::Char(c) => Some(Message::DialogCharInput(c)),
             _ => None,
         },
     }
 }
 
-fn map_port_select(key: KeyEvent) -> Option<Message> {
+fn map_profile_select(key: KeyEvent) -> Option<Message> {
     match key.code {
         KeyCode::Char('q') => Some(Message::Quit),
         KeyCode::Char('r') => Some(Message::RefreshPorts),
@@ -106,6 +232,34 @@ fn map_port_select(key: KeyEvent) -> Option<Message> {
     }
 }
 
+/// Letters and digits go to the port filter, so refreshing takes Ctrl+R.
+fn map_port_select(key: KeyEvent, app: &App) -> Option<Message> {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+    let alt = key.modifiers.contains(KeyModifiers::ALT);
+
+    match app.keymap.action(key) {
+        Some(Action::Quit) => return Some(Message::Quit),
+        _ if ctrl && key.code == KeyCode::Char('r') => return Some(Message::RefreshPorts),
+        _ if ctrl && key.code == KeyCode::Char('f') => return Some(Message::ToggleFavoritePort),
+        _ if ctrl => return None,
+        _ => {}
+    }
+
+    match key.code {
+        KeyCode::Char(c @ '1'..='9') if alt => {
+            Some(Message::PickFavoritePort(c as usize - '1' as usize))
+        }
+        KeyCode::Esc => Some(Message::Back),
+        KeyCode::Up => Some(Message::Up),
+        KeyCode::Down => Some(Message::Down),
+        KeyCode::Enter => Some(Message::Select),
+        KeyCode::Backspace => Some(Message::PortFilterBackspace),
+        KeyCode::Char(c) => Some(Message::PortFilterInput(c)),
+        _ => None,
+    }
+}
+
 fn map_baud_select(key: KeyEvent) -> Option<Message> {
     match key.code {
         KeyCode::Esc => Some(Message::Back),
@@ -126,61 +280,202 @@ fn map_list_select(key: KeyEvent) -> Option<Message> {
     }
 }
 
-fn map_pending(key: KeyEvent, pending: PendingScreen) -> Option<Message> {
+fn map_pending(key: KeyEvent, app: &App, pending: PendingScreen) -> Option<Message> {
     let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
     let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+    let alt = key.modifiers.contains(KeyModifiers::ALT);
 
-    if ctrl {
-        return match key.code {
-            KeyCode::Char('q') => Some(Message::Quit),
-            KeyCode::Char('g') => Some(Message::ToggleViewMode),
-            _ => None,
-        };
+    let port_select = pending == PendingScreen::PortSelect;
+
+    match app.keymap.action(key) {
+        Some(Action::Quit) => return Some(Message::Quit),
+        Some(Action::View) => return Some(Message::ToggleViewMode),
+        _ if ctrl && port_select && key.code == KeyCode::Char('r') => {
+            return Some(Message::RefreshPorts)
+        }
+        _ if ctrl && port_select && key.code == KeyCode::Char('f') => {
+            return Some(Message::ToggleFavoritePort)
+        }
+        _ if ctrl => return None,
+        _ => {}
     }
 
     match key.code {
         KeyCode::Tab if shift => Some(Message::PrevTab),
         KeyCode::BackTab => Some(Message::PrevTab),
         KeyCode::Tab => Some(Message::NextTab),
-        KeyCode::Char(c @ '1'..='9') => Some(Message::SwitchTab(c as usize - '1' as usize)),
+        // The port list takes letters and digits as a filter, and Alt+1–9
+        // for its favorites
+        KeyCode::Char(c @ '1'..='9') if port_select && alt => {
+            Some(Message::PickFavoritePort(c as usize - '1' as usize))
+        }
+        KeyCode::Backspace if port_select => Some(Message::PortFilterBackspace),
+        KeyCode::Char(c) if port_select => Some(Message::PortFilterInput(c)),
+        KeyCode::Char(c @ '1'..='9') if alt => Some(Message::SwitchTab(c as usize - '1' as usize)),
         KeyCode::Up => Some(Message::Up),
         KeyCode::Down => Some(Message::Down),
         KeyCode::Enter => Some(Message::Select),
         KeyCode::Esc => Some(Message::Back),
-        KeyCode::Char('r') if matches!(pending, PendingScreen::PortSelect) => {
-            Some(Message::RefreshPorts)
-        }
         _ => None,
     }
 }
 
-fn map_connected(key: KeyEvent) -> Option<Message> {
+fn map_connected(key: KeyEvent, app: &App) -> Option<Message> {
     let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
     let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+    let alt = key.modifiers.contains(KeyModifiers::ALT);
+
+    if app.is_passthrough_active() && app.active_busy_text().is_none() {
+        if app.keymap.action(key) == Some(Action::Passthrough) {
+            return Some(Message::TogglePassthrough);
+        }
+        return key_to_bytes(key).map(Message::SendRaw);
+    }
 
+    if ctrl && key.code == KeyCode::Char('c') && app.active_busy_text().is_some() {
+        return Some(Message::AbortBusy);
+    }
+    if let Some(action) = app.keymap.action(key) {
+        return Some(action.message());
+    }
     if ctrl {
+        // Home and End alone move the Send bar cursor
         return match key.code {
-            KeyCode::Char('q') => Some(Message::Quit),
-            KeyCode::Char('n') => Some(Message::NewConnection),
-            KeyCode::Char('w') => Some(Message::CloseConnection),
-            KeyCode::Char('g') => Some(Message::ToggleViewMode),
-            KeyCode::Char('e') => Some(Message::ExportScrollback),
+            KeyCode::Home => Some(Message::ScrollToTop),
+            KeyCode::End => Some(Message::ScrollToBottom),
             _ => None,
         };
     }
 
+    // While a search is active and the Send bar is empty, n/N step through
+    // matches and Esc ends the search; once something is typed they edit it
+    if app.search.is_some() && app.input_buffer.is_empty() {
+        match key.code {
+            KeyCode::Char('n') => return Some(Message::SearchNext),
+            KeyCode::Char('N') => return Some(Message::SearchPrev),
+            KeyCode::Esc => return Some(Message::ClearSearch),
+            _ => {}
+        }
+    }
+
     match key.code {
         KeyCode::Tab if shift => Some(Message::PrevTab),
         KeyCode::BackTab => Some(Message::PrevTab),
         KeyCode::Tab => Some(Message::NextTab),
-        KeyCode::Char(c @ '1'..='9') => Some(Message::SwitchTab(c as usize - '1' as usize)),
+        // Plain digits belong to the Send bar, for hex bytes and `\x` escapes
+        KeyCode::Char(c @ '1'..='9') if alt => Some(Message::SwitchTab(c as usize - '1' as usize)),
+        KeyCode::Up if shift => Some(Message::CursorUp),
+        KeyCode::Down if shift => Some(Message::CursorDown),
+        KeyCode::Left if shift => Some(Message::ScrollLeft),
+        KeyCode::Right if shift => Some(Message::ScrollRight),
+        KeyCode::Left => Some(Message::InputLeft),
+        KeyCode::Right => Some(Message::InputRight),
+        KeyCode::Home => Some(Message::InputHome),
+        KeyCode::End => Some(Message::InputEnd),
+        KeyCode::Delete => Some(Message::DeleteForward),
+        KeyCode::Esc => Some(Message::ClearCursor),
         KeyCode::Up => Some(Message::ScrollUp),
         KeyCode::Down => Some(Message::ScrollDown),
         KeyCode::PageUp => Some(Message::ScrollUp),
         KeyCode::PageDown => Some(Message::ScrollDown),
+        KeyCode::F(n) => crate::app::function_key(&app.keymap, n),
         KeyCode::Enter => Some(Message::SendInput),
         KeyCode::Backspace => Some(Message::Backspace),
         KeyCode::Char(c) => Some(Message::CharInput(c)),
         _ => None,
     }
 }
+
+/// Translate a key press into the bytes a VT100-style terminal would send.
+pub fn key_to_bytes(key: KeyEvent) -> Option<Vec<u8>> {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let alt = key.modifiers.contains(KeyModifiers::ALT);
+
+    let bytes: Vec<u8> = match key.code {
+        KeyCode::Char(c) if ctrl => {
+            let b = match c.to_ascii_uppercase() {
+                c @ '@'..='_' => c as u8 & 0x1f,
+                ' ' => 0,
+                '?' => 0x7f,
+                _ => return None,
+            };
+            vec![b]
+        }
+        KeyCode::Char(c) => {
+            let mut buf = [0u8; 4];
+            c.encode_utf8(&mut buf).as_bytes().to_vec()
+        }
+        KeyCode::Enter => b"\r".to_vec(),
+        KeyCode::Backspace => vec![0x7f],
+        KeyCode::Tab => b"\t".to_vec(),
+        KeyCode::BackTab => b"\x1b[Z".to_vec(),
+        KeyCode::Esc => vec![0x1b],
+        KeyCode::Up => b"\x1b[A".to_vec(),
+        KeyCode::Down => b"\x1b[B".to_vec(),
+        KeyCode::Right => b"\x1b[C".to_vec(),
+        KeyCode::Left => b"\x1b[D".to_vec(),
+        KeyCode::Home => b"\x1b[H".to_vec(),
+        KeyCode::End => b"\x1b[F".to_vec(),
+        KeyCode::Insert => b"\x1b[2~".to_vec(),
+        KeyCode::Delete => b"\x1b[3~".to_vec(),
+        KeyCode::PageUp => b"\x1b[5~".to_vec(),
+        KeyCode::PageDown => b"\x1b[6~".to_vec(),
+        KeyCode::F(n @ 1..=4) => vec![0x1b, b'O', b'P' + n - 1],
+        KeyCode::F(n @ 5..=12) => {
+            let code = [15, 17, 18, 19, 20, 21, 23, 24][(n - 5) as usize];
+            format!("\x1b[{}~", code).into_bytes()
+        }
+        _ => return None,
+    };
+
+    if alt {
+        let mut prefixed = vec![0x1b];
+        prefixed.extend(bytes);
+        Some(prefixed)
+    } else {
+        Some(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            let key = KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+            if let Some(msg) = map_event(app, Event::Key(key)) {
+                app.update(msg);
+            }
+        }
+    }
+
+    #[test]
+    fn hex_input_takes_digits() {
+        let mut app = App::with_config(Config::default());
+        app.screen = Screen::Connected;
+        app.hex_input = true;
+        type_text(&mut app, "DE 12");
+        assert_eq!(app.input_buffer, "DE 12");
+    }
+
+    #[test]
+    fn escapes_take_digits() {
+        let mut app = App::with_config(Config::default());
+        app.screen = Screen::Connected;
+        type_text(&mut app, r"\x1B\x9F");
+        assert_eq!(app.input_buffer, r"\x1B\x9F");
+    }
+
+    #[test]
+    fn alt_digit_switches_tabs() {
+        let mut app = App::with_config(Config::default());
+        app.screen = Screen::Connected;
+        let key = KeyEvent::new(KeyCode::Char('2'), KeyModifiers::ALT);
+        assert!(matches!(
+            map_event(&app, Event::Key(key)),
+            Some(Message::SwitchTab(1))
+        ));
+    }
+}
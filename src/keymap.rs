@@ -0,0 +1,373 @@
+use std::collections::BTreeMap;
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::i18n;
+use crate::message::Message;
+
+/// A connected-screen command that can be bound to a key chord.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Action {
+    Quit,
+    New,
+    Close,
+    View,
+    Export,
+    Find,
+    Filter,
+    Pause,
+    Wrap,
+    LiveStream,
+    Passthrough,
+    HexInput,
+    LiteralInput,
+    Paste,
+    Commands,
+    Pin,
+    SwapPanes,
+    SplitDirection,
+    CopyAs,
+    EditLine,
+    Resend,
+    GrowInput,
+    ShrinkInput,
+    ClearInput,
+    DeleteWord,
+    WordLeft,
+    WordRight,
+    InputHome,
+    InputEnd,
+    Note,
+    AsciiTable,
+}
+
+/// Each action with its `[keymap]` name and default chord, `none` for
+/// actions that are only bound in the config. Readline's Ctrl+W and Ctrl+E
+/// close and export here, so their Send bar editing actions default to other
+/// chords.
+const ACTIONS: [(Action, &str, &str); 31] = [
+    (Action::Quit, "quit", "ctrl+q"),
+    (Action::New, "new", "ctrl+n"),
+    (Action::Close, "close", "ctrl+w"),
+    (Action::View, "view", "ctrl+g"),
+    (Action::Export, "export", "ctrl+e"),
+    (Action::Find, "find", "ctrl+f"),
+    (Action::Filter, "filter", "ctrl+k"),
+    (Action::Pause, "pause", "ctrl+z"),
+    (Action::Wrap, "wrap", "alt+l"),
+    (Action::LiveStream, "live_stream", "ctrl+l"),
+    (Action::Passthrough, "passthrough", "ctrl+t"),
+    (Action::HexInput, "hex_input", "ctrl+b"),
+    (Action::LiteralInput, "literal_input", "alt+e"),
+    (Action::Paste, "paste", "ctrl+v"),
+    (Action::Commands, "commands", "ctrl+r"),
+    (Action::Pin, "pin", "ctrl+p"),
+    (Action::SwapPanes, "swap_panes", "ctrl+x"),
+    (Action::SplitDirection, "split_direction", "ctrl+o"),
+    (Action::CopyAs, "copy_as", "ctrl+y"),
+    (Action::EditLine, "edit_line", "ctrl+d"),
+    (Action::Resend, "resend", "ctrl+s"),
+    (Action::GrowInput, "grow_input", "ctrl+up"),
+    (Action::ShrinkInput, "shrink_input", "ctrl+down"),
+    (Action::ClearInput, "clear_input", "ctrl+u"),
+    (Action::DeleteWord, "delete_word", "alt+backspace"),
+    (Action::WordLeft, "word_left", "alt+b"),
+    (Action::WordRight, "word_right", "alt+f"),
+    (Action::InputHome, "input_home", "ctrl+a"),
+    (Action::InputEnd, "input_end", "none"),
+    (Action::Note, "note", "alt+m"),
+    (Action::AsciiTable, "ascii_table", "alt+a"),
+];
+
+impl Action {
+    pub fn message(self) -> Message {
+        match self {
+            Action::Quit => Message::Quit,
+            Action::New => Message::NewConnection,
+            Action::Close => Message::CloseConnection,
+            Action::View => Message::ToggleViewMode,
+            Action::Export => Message::ExportScrollback,
+            Action::Find => Message::OpenSearch,
+            Action::Filter => Message::OpenFilter,
+            Action::Pause => Message::TogglePause,
+            Action::Wrap => Message::ToggleWrap,
+            Action::LiveStream => Message::ToggleLiveStream,
+            Action::Passthrough => Message::TogglePassthrough,
+            Action::HexInput => Message::ToggleHexInput,
+            Action::LiteralInput => Message::ToggleLiteralInput,
+            Action::Paste => Message::PasteClipboard,
+            Action::Commands => Message::OpenCommandMenu,
+            Action::Pin => Message::PinConnection,
+            Action::SwapPanes => Message::SwapPanes,
+            Action::SplitDirection => Message::ToggleSplitDirection,
+            Action::CopyAs => Message::OpenCopyAs,
+            Action::EditLine => Message::EditCursorLine,
+            Action::Resend => Message::ResendLast,
+            Action::GrowInput => Message::ResizeInput(1),
+            Action::ShrinkInput => Message::ResizeInput(-1),
+            Action::ClearInput => Message::ClearInput,
+            Action::DeleteWord => Message::DeleteWord,
+            Action::WordLeft => Message::InputWordLeft,
+            Action::WordRight => Message::InputWordRight,
+            Action::InputHome => Message::InputHome,
+            Action::InputEnd => Message::InputEnd,
+            Action::Note => Message::OpenNote,
+            Action::AsciiTable => Message::OpenAsciiTable,
+        }
+    }
+
+    /// Text key of its label on the function key bar, when bound to an
+    /// F-key.
+    pub fn bar_label(self) -> &'static str {
+        match self {
+            Action::Quit => "fkey.quit",
+            Action::New => "fkey.new",
+            Action::Close => "fkey.close",
+            Action::View => "fkey.view",
+            Action::Export => "fkey.export",
+            Action::Find => "fkey.find",
+            Action::Filter => "fkey.filter",
+            Action::Pause => "fkey.pause",
+            Action::Wrap => "fkey.wrap",
+            Action::LiveStream => "fkey.live_stream",
+            Action::Passthrough => "fkey.passthrough",
+            Action::HexInput => "fkey.hex_input",
+            Action::LiteralInput => "fkey.literal_input",
+            Action::Paste => "fkey.paste",
+            Action::Commands => "fkey.commands",
+            Action::Pin => "fkey.pin",
+            Action::SwapPanes => "fkey.swap_panes",
+            Action::SplitDirection => "fkey.split_direction",
+            Action::CopyAs => "fkey.copy_as",
+            Action::EditLine => "fkey.edit_line",
+            Action::Resend => "fkey.resend",
+            Action::GrowInput => "fkey.grow_input",
+            Action::ShrinkInput => "fkey.shrink_input",
+            Action::ClearInput => "fkey.clear_input",
+            Action::DeleteWord => "fkey.delete_word",
+            Action::WordLeft => "fkey.word_left",
+            Action::WordRight => "fkey.word_right",
+            Action::InputHome => "fkey.input_home",
+            Action::InputEnd => "fkey.input_end",
+            Action::Note => "fkey.note",
+            Action::AsciiTable => "fkey.ascii_table",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        ACTIONS
+            .iter()
+            .find(|(_, n, _)| *n == name)
+            .map(|(a, _, _)| *a)
+    }
+}
+
+/// A key with its Ctrl/Alt/Shift state. Letters are kept lowercase with
+/// Shift set, and Shift is dropped for other characters, which terminals
+/// report either way.
+#[derive(Clone, Copy, PartialEq)]
+struct Chord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Chord {
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        let code = match code {
+            KeyCode::Char(c) if c.is_uppercase() => {
+                modifiers |= KeyModifiers::SHIFT;
+                KeyCode::Char(c.to_lowercase().next().unwrap_or(c))
+            }
+            KeyCode::Char(c) if !c.is_alphabetic() => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                code
+            }
+            code => code,
+        };
+        Self { code, modifiers }
+    }
+
+    /// Parse `ctrl+w`, `alt+shift+n`, `f5`, `ctrl+pageup`, ...
+    fn parse(text: &str) -> Result<Self, String> {
+        let invalid = || format!("keymap: invalid key \"{}\"", text);
+        let mut parts: Vec<&str> = text.split('+').collect();
+        // `ctrl++` binds the plus key
+        if text.ends_with("++") {
+            parts.truncate(parts.len() - 2);
+            parts.push("+");
+        }
+        let key = parts.pop().ok_or_else(invalid)?;
+        let mut modifiers = KeyModifiers::NONE;
+        for part in parts {
+            modifiers |= match part.to_ascii_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(invalid()),
+            };
+        }
+        let lower = key.to_ascii_lowercase();
+        let code = match lower.as_str() {
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "tab" => KeyCode::Tab,
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "space" => KeyCode::Char(' '),
+            _ => match lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                Some(n @ 1..=12) => KeyCode::F(n),
+                _ => {
+                    let mut chars = key.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => KeyCode::Char(c),
+                        _ => return Err(invalid()),
+                    }
+                }
+            },
+        };
+        let chord = Self::new(code, modifiers);
+        let typed = !chord
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        if typed && matches!(chord.code, KeyCode::Char(_)) {
+            return Err(format!(
+                "keymap: \"{}\" would take a key the Send bar needs; add ctrl or alt",
+                text
+            ));
+        }
+        Ok(chord)
+    }
+
+    /// As shown in the key hints, e.g. `Ctrl+W`.
+    fn label(self) -> String {
+        let mut label = String::new();
+        for (modifier, key) in [
+            (KeyModifiers::CONTROL, "key.ctrl"),
+            (KeyModifiers::ALT, "key.alt"),
+            (KeyModifiers::SHIFT, "key.shift"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                label.push_str(i18n::t(key));
+                label.push('+');
+            }
+        }
+        let key = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_uppercase().to_string(),
+            KeyCode::F(n) => format!("F{}", n),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::Home => "Home".to_string(),
+            KeyCode::End => "End".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Backspace => "Backspace".to_string(),
+            KeyCode::Delete => "Del".to_string(),
+            KeyCode::Insert => "Ins".to_string(),
+            _ => "?".to_string(),
+        };
+        label.push_str(&key);
+        label
+    }
+}
+
+/// Key chords for the connected screen's commands: the defaults, changed by
+/// the `[keymap]` config. Binding an action there releases its default chord;
+/// binding a chord to `"none"` frees it for the port.
+pub struct Keymap {
+    bindings: Vec<(Chord, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(&BTreeMap::new()).unwrap()
+    }
+}
+
+impl Keymap {
+    pub fn new(config: &BTreeMap<String, String>) -> Result<Self, String> {
+        let mut configured = Vec::new();
+        let mut freed = Vec::new();
+        for (chord, action) in config {
+            let chord = Chord::parse(chord)?;
+            if action == "none" {
+                freed.push(chord);
+                continue;
+            }
+            let action = Action::from_name(action)
+                .ok_or_else(|| format!("keymap: unknown action \"{}\"", action))?;
+            configured.push((chord, action));
+        }
+        let mut bindings: Vec<(Chord, Action)> = ACTIONS
+            .iter()
+            .filter(|(_, _, chord)| *chord != "none")
+            .map(|(action, _, chord)| (Chord::parse(chord).unwrap(), *action))
+            .filter(|(chord, action)| {
+                !freed.contains(chord) && !configured.iter().any(|(c, a)| c == chord || a == action)
+            })
+            .collect();
+        bindings.extend(configured);
+        let keymap = Self { bindings };
+        // Passthrough sends every other key to the port
+        if keymap.label(Action::Passthrough).is_none() {
+            return Err("keymap: passthrough needs a key to leave passthrough mode".to_string());
+        }
+        Ok(keymap)
+    }
+
+    pub fn action(&self, key: KeyEvent) -> Option<Action> {
+        let chord = Chord::new(key.code, key.modifiers);
+        self.bindings
+            .iter()
+            .find(|(c, _)| *c == chord)
+            .map(|(_, action)| *action)
+    }
+
+    /// The chord for `action`, e.g. `Ctrl+W`, if it has one.
+    pub fn label(&self, action: Action) -> Option<String> {
+        self.bindings
+            .iter()
+            .find(|(_, a)| *a == action)
+            .map(|(chord, _)| chord.label())
+    }
+
+    /// Fill `{action}` placeholders in a key hint text with the bound chords.
+    /// Hints are separated by two spaces; one whose action has no chord is
+    /// left out.
+    pub fn fill(&self, text: &str) -> String {
+        text.split("  ")
+            .filter_map(|hint| {
+                let Some((action, start, end)) = placeholder(hint) else {
+                    return Some(hint.to_string());
+                };
+                let label = self.label(action)?;
+                Some(format!("{}{}{}", &hint[..start], label, &hint[end..]))
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+}
+
+/// The action named by a `{name}` placeholder in `hint`, and where it is.
+fn placeholder(hint: &str) -> Option<(Action, usize, usize)> {
+    let start = hint.find('{')?;
+    let end = start + hint[start..].find('}')? + 1;
+    let action = Action::from_name(&hint[start + 1..end - 1])?;
+    Some((action, start, end))
+}
@@ -0,0 +1,29 @@
+//! The terminal's state, update logic and serial I/O; `main.rs` only sets up
+//! the terminal and runs the event loop.
+
+mod ansi;
+pub mod app;
+mod checksum;
+mod command;
+pub mod config;
+mod control;
+mod diff;
+mod export;
+mod hex;
+mod highlight;
+pub mod i18n;
+pub mod input;
+mod keymap;
+mod menu;
+pub mod message;
+mod paths;
+mod script;
+pub mod serial;
+mod session;
+mod theme;
+mod trigger;
+pub mod ui;
+
+pub use app::App;
+pub use message::Message;
+pub use serial::Connection;
@@ -1,64 +1,143 @@
-mod app;
-mod input;
-mod message;
-mod serial;
-mod ui;
+use std::io::Write;
+use std::time::Duration;
 
 use anyhow::Result;
-use ratatui::crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use futures_util::StreamExt;
+use ratatui::crossterm::event::{
+    DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+    EventStream,
+};
 use ratatui::crossterm::execute;
 use ratatui::crossterm::terminal::{
     disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
 };
 
-use app::App;
+use serialtui::{input, ui, App};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Checked before the terminal is taken over, so the error stays readable
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(speed) = args
+        .iter()
+        .position(|a| a == "--speed")
+        .and_then(|pos| args.get(pos + 1))
+    {
+        if !speed.parse::<u32>().is_ok_and(|n| n > 0) {
+            anyhow::bail!("--speed takes a whole number of at least 1, not {}", speed);
+        }
+    }
 
-fn main() -> Result<()> {
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = std::io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
     let mut terminal = ratatui::Terminal::new(ratatui::backend::CrosstermBackend::new(stdout))?;
 
-    let result = run(&mut terminal);
+    let result = run(&mut terminal).await;
 
     // Restore terminal
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableBracketedPaste
     )?;
     terminal.show_cursor()?;
 
-    result
+    // Output of post_close hooks run on the way out
+    for line in result? {
+        println!("{}", line);
+    }
+    Ok(())
 }
 
-fn run(
+fn ticker(period: Duration) -> tokio::time::Interval {
+    let mut ticker = tokio::time::interval(period);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    ticker
+}
+
+async fn run(
     terminal: &mut ratatui::Terminal<ratatui::backend::CrosstermBackend<std::io::Stdout>>,
-) -> Result<()> {
+) -> Result<Vec<String>> {
     let mut app = App::new();
 
-    loop {
-        terminal.draw(|frame| {
-            let size = frame.area();
-            app.terminal_cols = size.width;
-            app.terminal_rows = size.height;
-            ui::render(&app, frame);
-        })?;
+    // `serialtui --follow FILE` opens a read-only tab on another instance's live stream
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(pos) = args.iter().position(|a| a == "--follow") {
+        if let Some(path) = args.get(pos + 1) {
+            app.follow_stream(path.clone(), None);
+        }
+    }
+    // `serialtui --replay FILE [--speed N]` plays a recorded live stream back
+    if let Some(pos) = args.iter().position(|a| a == "--replay") {
+        if let Some(path) = args.get(pos + 1) {
+            let speed = args
+                .iter()
+                .position(|a| a == "--speed")
+                .and_then(|pos| args.get(pos + 1)?.parse().ok())
+                .unwrap_or(1);
+            app.follow_stream(path.clone(), Some(speed));
+        }
+    }
+    // `serialtui --demo` opens a tab on a simulated device
+    if args.iter().any(|a| a == "--demo") {
+        app.open_demo();
+    }
+
+    let mut events = EventStream::new();
+    // `App::tick()` runs its time-driven work this often; Tools → Preferences
+    // can change it
+    let mut tick = app.prefs.tick();
+    let mut ticker = ticker(tick);
 
-        // Poll crossterm input events
-        if let Some(msg) = input::poll_event(&app) {
-            app.update(msg);
+    loop {
+        // Only redraw when something changed; idle, the loop just waits
+        if app.dirty {
+            terminal.draw(|frame| {
+                let size = frame.area();
+                app.terminal_cols = size.width;
+                app.terminal_rows = size.height;
+                ui::render(&app, frame);
+            })?;
+            app.dirty = false;
+        }
+        if std::mem::take(&mut app.ring_bell) {
+            let backend = terminal.backend_mut();
+            backend.write_all(b"\x07")?;
+            backend.flush()?;
         }
 
-        // Drain serial events
-        app.drain_serial_events();
+        // Wait for input, data from a connection or the next tick
+        tokio::select! {
+            event = events.next() => match event {
+                Some(Ok(event)) => {
+                    if let Some(msg) = input::map_event(&app, event) {
+                        app.update(msg);
+                    }
+                }
+                Some(Err(e)) => return Err(e.into()),
+                None => break,
+            },
+            Some(event) = app.serial_rx.recv() => app.drain_serial_events(event),
+            _ = ticker.tick() => app.tick(),
+        }
 
         if app.should_quit {
             break;
         }
+        if app.prefs.tick() != tick {
+            tick = app.prefs.tick();
+            ticker = self::ticker(tick);
+        }
     }
 
-    Ok(())
+    Ok(app.shutdown())
 }
@@ -0,0 +1,275 @@
+use crate::app::{App, OpenMenu};
+use crate::i18n;
+use crate::serial::Connection;
+
+/// Dropdowns are this many columns wide, borders included.
+pub const DROPDOWN_WIDTH: u16 = 16;
+
+/// What clicking a menu item does.
+#[derive(Clone, Copy)]
+pub enum MenuCommand {
+    Export,
+    ExportTo,
+    ExportStats,
+    Quit,
+    New,
+    /// Open another port with the settings of connection `idx`
+    Duplicate(usize),
+    Close(usize),
+    RunCommand,
+    Mute(usize),
+    PowerCycle,
+    Resend,
+    DryRun,
+    Share,
+    Bridge,
+    Sniff,
+    Triggers,
+    Watch,
+    ArmCapture,
+    Note,
+    ToggleDtr,
+    ToggleRts,
+    SendBreak,
+    LineSetup,
+    TabView,
+    GridView,
+    SplitView,
+    Filter,
+    Pause,
+    Wrap,
+    HexPane,
+    Plot,
+    Fields,
+    GridShape,
+    Decoder,
+    Preferences,
+    SendFile,
+    Generator,
+    Upload,
+    Download,
+    LinkTest,
+    Checksum,
+    Diff,
+    LiveStream,
+    Replay,
+    KeyHelp,
+    AsciiTable,
+}
+
+/// A dropdown entry: its label's string key and what it does.
+pub struct MenuItem {
+    pub label: &'static str,
+    pub command: MenuCommand,
+}
+
+fn item(label: &'static str, command: MenuCommand) -> MenuItem {
+    MenuItem { label, command }
+}
+
+/// The menu bar's menus, their titles and the column each title starts at.
+pub fn titles() -> [(OpenMenu, String, u16); 5] {
+    let mut x = 1;
+    [
+        (OpenMenu::File, "menu.file"),
+        (OpenMenu::Connection, "menu.connection"),
+        (OpenMenu::View, "menu.view"),
+        (OpenMenu::Tools, "menu.tools"),
+        (OpenMenu::Help, "menu.help"),
+    ]
+    .map(|(menu, key)| {
+        let title = format!(" {} ", i18n::t(key));
+        let start = x;
+        x += title.chars().count() as u16;
+        (menu, title, start)
+    })
+}
+
+/// Column and row of a dropdown's top-left corner: under its title, or
+/// under the clicked tab for the tab menu.
+pub fn dropdown_origin(menu: OpenMenu) -> (u16, u16) {
+    match menu {
+        OpenMenu::Tab { x, .. } => (x, 2),
+        menu => {
+            let x = titles()
+                .into_iter()
+                .find(|(m, _, _)| *m == menu)
+                .map_or(0, |(_, _, x)| x);
+            (x, 1)
+        }
+    }
+}
+
+/// The entries of `menu`, labelled for the app's current state. Rendering
+/// and click handling both go by this list, so an entry is added here only.
+pub fn items(app: &App, menu: OpenMenu) -> Vec<MenuItem> {
+    let active = app.active_connection;
+    match menu {
+        OpenMenu::File => vec![
+            item("menu.export", MenuCommand::Export),
+            item("menu.export_to", MenuCommand::ExportTo),
+            item("menu.export_stats", MenuCommand::ExportStats),
+            item("menu.quit", MenuCommand::Quit),
+        ],
+        OpenMenu::Connection => vec![
+            item("menu.new", MenuCommand::New),
+            item("menu.duplicate", MenuCommand::Duplicate(active)),
+            item("menu.close", MenuCommand::Close(active)),
+            item("menu.run_command", MenuCommand::RunCommand),
+            item(mute_label(app, active), MenuCommand::Mute(active)),
+            item("menu.power_cycle", MenuCommand::PowerCycle),
+            item("menu.resend", MenuCommand::Resend),
+            item(
+                toggle_label(app, |c| c.dry_run, "menu.end_dry_run", "menu.dry_run"),
+                MenuCommand::DryRun,
+            ),
+            item(
+                toggle_label(app, |c| c.share.is_some(), "menu.stop_share", "menu.share"),
+                MenuCommand::Share,
+            ),
+            item(
+                toggle_label(
+                    app,
+                    |c| c.bridge_to.is_some(),
+                    "menu.unbridge",
+                    "menu.bridge",
+                ),
+                MenuCommand::Bridge,
+            ),
+            item("menu.sniff", MenuCommand::Sniff),
+            item("menu.triggers", MenuCommand::Triggers),
+            item(
+                toggle_label(app, |c| c.watch.is_some(), "menu.edit_watch", "menu.watch"),
+                MenuCommand::Watch,
+            ),
+            item(
+                toggle_label(
+                    app,
+                    |c| c.armed.is_some(),
+                    "menu.disarm_capture",
+                    "menu.arm_capture",
+                ),
+                MenuCommand::ArmCapture,
+            ),
+            item("menu.note", MenuCommand::Note),
+            item("menu.line_setup", MenuCommand::LineSetup),
+            item(
+                line_label(app, |c| c.dtr, "menu.drop_dtr", "menu.raise_dtr"),
+                MenuCommand::ToggleDtr,
+            ),
+            item(
+                line_label(app, |c| c.rts, "menu.drop_rts", "menu.raise_rts"),
+                MenuCommand::ToggleRts,
+            ),
+            item("menu.send_break", MenuCommand::SendBreak),
+        ],
+        OpenMenu::View => vec![
+            item("menu.tab_view", MenuCommand::TabView),
+            item("menu.grid_view", MenuCommand::GridView),
+            item("menu.split_view", MenuCommand::SplitView),
+            item("menu.filter", MenuCommand::Filter),
+            item(
+                toggle_label(
+                    app,
+                    |c| c.paused_new_lines().is_some(),
+                    "menu.resume",
+                    "menu.pause",
+                ),
+                MenuCommand::Pause,
+            ),
+            item(
+                toggle_label(app, |c| c.wrap, "menu.unwrap", "menu.wrap"),
+                MenuCommand::Wrap,
+            ),
+            item(
+                toggle_label(app, |c| c.hex_pane, "menu.hide_hex_pane", "menu.hex_pane"),
+                MenuCommand::HexPane,
+            ),
+            item(
+                toggle_label(app, |c| c.plot.is_some(), "menu.hide_plot", "menu.plot"),
+                MenuCommand::Plot,
+            ),
+            item(
+                toggle_label(
+                    app,
+                    |c| c.fields.is_some(),
+                    "menu.edit_fields",
+                    "menu.fields",
+                ),
+                MenuCommand::Fields,
+            ),
+            item("menu.grid_shape", MenuCommand::GridShape),
+            item("menu.decoder", MenuCommand::Decoder),
+        ],
+        OpenMenu::Tools => vec![
+            item("menu.send_file", MenuCommand::SendFile),
+            item("menu.generator", MenuCommand::Generator),
+            item("menu.upload", MenuCommand::Upload),
+            item("menu.download", MenuCommand::Download),
+            item("menu.link_test", MenuCommand::LinkTest),
+            item("menu.checksum", MenuCommand::Checksum),
+            item("menu.diff", MenuCommand::Diff),
+            item(
+                toggle_label(
+                    app,
+                    |c| c.live_stream_path().is_some(),
+                    "menu.stop_stream",
+                    "menu.live_stream",
+                ),
+                MenuCommand::LiveStream,
+            ),
+            item("menu.replay", MenuCommand::Replay),
+            item("menu.preferences", MenuCommand::Preferences),
+        ],
+        OpenMenu::Help => vec![
+            item("menu.key_help", MenuCommand::KeyHelp),
+            item("menu.ascii_table", MenuCommand::AsciiTable),
+        ],
+        OpenMenu::Tab { idx, .. } => vec![
+            item(mute_label(app, idx), MenuCommand::Mute(idx)),
+            item("menu.duplicate", MenuCommand::Duplicate(idx)),
+            item("menu.close", MenuCommand::Close(idx)),
+        ],
+    }
+}
+
+/// The item to turn a state of the active connection off while it's on,
+/// and on otherwise.
+fn toggle_label(
+    app: &App,
+    on: fn(&Connection) -> bool,
+    turn_off: &'static str,
+    turn_on: &'static str,
+) -> &'static str {
+    if app.connections.get(app.active_connection).is_some_and(on) {
+        turn_off
+    } else {
+        turn_on
+    }
+}
+
+/// The DTR/RTS item: drop the line while it's raised, raise it otherwise.
+fn line_label(
+    app: &App,
+    raised: fn(&Connection) -> bool,
+    drop: &'static str,
+    raise: &'static str,
+) -> &'static str {
+    if app
+        .connections
+        .get(app.active_connection)
+        .is_none_or(raised)
+    {
+        drop
+    } else {
+        raise
+    }
+}
+
+fn mute_label(app: &App, idx: usize) -> &'static str {
+    if app.connections.get(idx).is_some_and(|c| c.muted) {
+        "menu.unmute"
+    } else {
+        "menu.mute"
+    }
+}
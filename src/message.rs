@@ -6,8 +6,13 @@ pub enum Message {
     Select,
     Back,
 
-    // Ports
+    // Ports; typing narrows the port list
     RefreshPorts,
+    PortFilterInput(char),
+    PortFilterBackspace,
+    // Favorites: mark the selected port, or pick the n-th favorite
+    ToggleFavoritePort,
+    PickFavoritePort(usize),
 
     // Connections
     NewConnection,
@@ -18,30 +23,97 @@ pub enum Message {
 
     // View
     ToggleViewMode,
+    PinConnection,
+    SwapPanes,
+    ToggleSplitDirection,
 
     // Input
     CharInput(char),
+    // Pasted text (bracketed paste or the system clipboard)
+    Paste(String),
+    PasteClipboard,
+    ResendLast,
     Backspace,
+    // Send bar cursor movement and the Delete key
+    InputLeft,
+    InputRight,
+    InputHome,
+    InputEnd,
+    DeleteForward,
+    // Readline-style editing: by word, and the whole line
+    InputWordLeft,
+    InputWordRight,
+    DeleteWord,
+    ClearInput,
     SendInput,
+    AbortBusy,
+    TogglePassthrough,
+    ToggleHexInput,
+    ToggleLiteralInput,
+    SendRaw(Vec<u8>),
+
+    // External commands
+    OpenCommandMenu,
 
     // Export
     ExportScrollback,
+    ToggleLiveStream,
+
+    // Search
+    OpenSearch,
+    SearchNext,
+    SearchPrev,
+    ClearSearch,
+    OpenFilter,
+    // Mark the scrollback with a note
+    OpenNote,
+    TogglePause,
+    ToggleWrap,
+
+    // Line cursor in the scrollback
+    CursorUp,
+    CursorDown,
+    ClearCursor,
+    DismissStatus,
+    OpenCopyAs,
+    EditCursorLine,
 
     // Scroll
     ScrollUp,
     ScrollDown,
+    ScrollToTop,
+    ScrollToBottom,
+    ScrollLeft,
+    ScrollRight,
 
     // Menu
     MenuClick(u16, u16),
+    MouseDrag(u16),
+    RightClick(u16, u16),
+    MouseUp,
+    MouseMove(u16, u16),
+    // The terminal's new size in columns and rows
+    Resize(u16, u16),
+    ResizeInput(i16),
     CloseMenu,
+    OpenHelp,
+    OpenAsciiTable,
 
     // Dialog responses
     DialogYes,
     DialogNo,
     DialogCancel,
+    DialogDelete,
     DialogConfirm,
+    DialogUp,
+    DialogDown,
+    DialogPageUp,
+    DialogPageDown,
     DialogCharInput(char),
     DialogBackspace,
     DialogCursorLeft,
     DialogCursorRight,
+    DialogTab,
+    /// Flip the option a dialog shows as a checkbox
+    DialogToggle,
 }
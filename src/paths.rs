@@ -0,0 +1,77 @@
+use std::fs;
+use std::io;
+use std::path::{self, Path};
+
+/// What Tab in a filename prompt makes of the path typed so far.
+pub enum Completion {
+    /// No file or folder name starts with it
+    None,
+    /// The path extended as far as the names starting with it agree, with a
+    /// separator after a folder's name
+    Extended(String),
+    /// It's as far as they agree; the names that could follow
+    Ambiguous(Vec<String>),
+}
+
+/// Complete the last name in `text` from its folder's entries, like a shell.
+/// Hidden entries only match a name started with a dot.
+pub fn complete(text: &str) -> Completion {
+    let split = text.rfind(path::is_separator).map_or(0, |i| i + 1);
+    let (dir, name) = text.split_at(split);
+    let folder = if dir.is_empty() { "." } else { dir };
+    let Ok(entries) = fs::read_dir(folder) else {
+        return Completion::None;
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let mut entry_name = entry.file_name().into_string().ok()?;
+            if !entry_name.starts_with(name)
+                || (entry_name.starts_with('.') && !name.starts_with('.'))
+            {
+                return None;
+            }
+            // Following links, so a linked folder completes like one
+            if entry.path().is_dir() {
+                entry_name.push(path::MAIN_SEPARATOR);
+            }
+            Some(entry_name)
+        })
+        .collect();
+    names.sort();
+    match names.as_slice() {
+        [] => Completion::None,
+        [only] => Completion::Extended(format!("{}{}", dir, only)),
+        [first, rest @ ..] => {
+            let agreed = rest.iter().fold(first.len(), |len, other| {
+                common_prefix(&first[..len], other)
+            });
+            if agreed > name.len() {
+                Completion::Extended(format!("{}{}", dir, &first[..agreed]))
+            } else {
+                Completion::Ambiguous(names)
+            }
+        }
+    }
+}
+
+/// The names of `folder`'s subfolders, sorted, hidden ones left out, for the
+/// export folder browser.
+pub fn subfolders(folder: &Path) -> io::Result<Vec<String>> {
+    let mut names: Vec<String> = fs::read_dir(folder)?
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// Bytes at the start of `a` that `b` starts with too, on a char boundary.
+fn common_prefix(a: &str, b: &str) -> usize {
+    a.char_indices()
+        .zip(b.chars())
+        .find(|((_, x), y)| x != y)
+        .map_or(a.len().min(b.len()), |((i, _), _)| i)
+}
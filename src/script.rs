@@ -0,0 +1,130 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use anyhow::{anyhow, Result};
+use rhai::{CallFnOptions, Engine, FuncArgs, Scope, AST};
+
+/// Work a hook asked for, carried out by the app once the hook returns.
+pub enum ScriptAction {
+    Send { id: usize, data: Vec<u8> },
+    Status(String),
+    Open { port: String, baud: u32 },
+}
+
+/// Hooks a script may define.
+const HOOKS: [&str; 3] = ["on_connect", "on_line", "on_disconnect"];
+
+/// Keeps a runaway hook from freezing the UI.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// A Rhai script (`script` config) whose hooks run on connection events:
+/// `on_connect(id, port)`, `on_line(id, port, line)` and
+/// `on_disconnect(id, port)`. They call `send(id, text)`, `status(text)` and
+/// `open(port, baud)`.
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+    hooks: HashSet<&'static str>,
+    actions: Rc<RefCell<Vec<ScriptAction>>>,
+}
+
+impl Script {
+    pub fn load(path: &str) -> Result<Self> {
+        let actions: Rc<RefCell<Vec<ScriptAction>>> = Rc::default();
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+
+        let queue = actions.clone();
+        engine.register_fn("send", move |id: i64, text: &str| {
+            queue.borrow_mut().push(ScriptAction::Send {
+                id: id as usize,
+                data: text.as_bytes().to_vec(),
+            });
+        });
+        let queue = actions.clone();
+        engine.register_fn("status", move |text: &str| {
+            queue
+                .borrow_mut()
+                .push(ScriptAction::Status(text.to_string()));
+        });
+        let queue = actions.clone();
+        engine.register_fn("open", move |port: &str, baud: i64| {
+            queue.borrow_mut().push(ScriptAction::Open {
+                port: port.to_string(),
+                baud: baud as u32,
+            });
+        });
+        // stdout belongs to the terminal UI
+        let queue = actions.clone();
+        engine.on_print(move |text| {
+            queue
+                .borrow_mut()
+                .push(ScriptAction::Status(text.to_string()));
+        });
+        engine.on_debug(|_, _, _| {});
+
+        let ast = engine
+            .compile_file(path.into())
+            .map_err(|e| anyhow!("loading script {}: {}", path, e))?;
+        let hooks = HOOKS
+            .into_iter()
+            .filter(|hook| ast.iter_functions().any(|f| f.name == *hook))
+            .collect();
+        let mut scope = Scope::new();
+        engine
+            .run_ast_with_scope(&mut scope, &ast)
+            .map_err(|e| anyhow!("running script {}: {}", path, e))?;
+        // No connection is open yet for top-level code to act on
+        actions.borrow_mut().clear();
+        Ok(Self {
+            engine,
+            ast,
+            scope,
+            hooks,
+            actions,
+        })
+    }
+
+    pub fn on_connect(&mut self, id: usize, port: &str) -> Result<Vec<ScriptAction>, String> {
+        self.call("on_connect", (id as i64, port.to_string()))
+    }
+
+    pub fn on_line(
+        &mut self,
+        id: usize,
+        port: &str,
+        line: &str,
+    ) -> Result<Vec<ScriptAction>, String> {
+        self.call("on_line", (id as i64, port.to_string(), line.to_string()))
+    }
+
+    pub fn on_disconnect(&mut self, id: usize, port: &str) -> Result<Vec<ScriptAction>, String> {
+        self.call("on_disconnect", (id as i64, port.to_string()))
+    }
+
+    pub fn has_hook(&self, hook: &str) -> bool {
+        self.hooks.contains(hook)
+    }
+
+    fn call(&mut self, hook: &str, args: impl FuncArgs) -> Result<Vec<ScriptAction>, String> {
+        if !self.has_hook(hook) {
+            return Ok(Vec::new());
+        }
+        let options = CallFnOptions::new().eval_ast(false);
+        let result = self.engine.call_fn_with_options::<rhai::Dynamic>(
+            options,
+            &mut self.scope,
+            &self.ast,
+            hook,
+            args,
+        );
+        // A hook that fails does none of what it queued
+        let actions = self.actions.borrow_mut().drain(..).collect();
+        match result {
+            Ok(_) => Ok(actions),
+            Err(e) => Err(format!("Script {}: {}", hook, e)),
+        }
+    }
+}
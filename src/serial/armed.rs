@@ -0,0 +1,120 @@
+use std::io;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+
+use super::logfile::{Durability, LogFile};
+
+/// When an armed capture stops if its end pattern hasn't appeared.
+#[derive(Clone, Copy)]
+pub enum CaptureLimit {
+    Lines(u64),
+    Time(Duration),
+}
+
+impl CaptureLimit {
+    /// `500` lines or `30s` / `5m`, None for anything else.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (number, unit) = text
+            .find(|c: char| !c.is_ascii_digit())
+            .map_or((text, ""), |i| text.split_at(i));
+        let n: u64 = number.parse().ok().filter(|&n| n > 0)?;
+        match unit.trim() {
+            "" => Some(Self::Lines(n)),
+            "s" => Some(Self::Time(Duration::from_secs(n))),
+            "m" => Some(Self::Time(Duration::from_secs(n * 60))),
+            _ => None,
+        }
+    }
+}
+
+/// A capture waiting for its start pattern, then writing received lines to
+/// `path` until its end pattern or limit. It records one window and is done.
+pub struct ArmedCapture {
+    pub path: String,
+    start: Regex,
+    end: Option<Regex>,
+    limit: Option<CaptureLimit>,
+    durability: Durability,
+    recording: Option<Recording>,
+}
+
+struct Recording {
+    file: LogFile,
+    started: Instant,
+    lines: u64,
+}
+
+/// What feeding an armed capture led to.
+pub enum CaptureEvent {
+    Started,
+    /// Finished with this many lines
+    Saved(u64),
+}
+
+impl ArmedCapture {
+    pub fn new(
+        path: String,
+        start: Regex,
+        end: Option<Regex>,
+        limit: Option<CaptureLimit>,
+        durability: Durability,
+    ) -> Self {
+        Self {
+            path,
+            start,
+            end,
+            limit,
+            durability,
+            recording: None,
+        }
+    }
+
+    /// Lines written so far, None while waiting for the start pattern.
+    pub fn recorded(&self) -> Option<u64> {
+        self.recording.as_ref().map(|r| r.lines)
+    }
+
+    /// Take completed lines (without colour codes): the first matching the
+    /// start pattern opens the file and is its first line.
+    pub fn feed(&mut self, lines: &[String]) -> io::Result<Vec<CaptureEvent>> {
+        let mut events = Vec::new();
+        for line in lines {
+            let recording = match &mut self.recording {
+                Some(recording) => recording,
+                None if self.start.is_match(line) => {
+                    events.push(CaptureEvent::Started);
+                    self.recording.insert(Recording {
+                        file: LogFile::append(&self.path, self.durability)?,
+                        started: Instant::now(),
+                        lines: 0,
+                    })
+                }
+                None => continue,
+            };
+            recording.file.write(format!("{}\n", line).as_bytes())?;
+            recording.lines += 1;
+            let ended =
+                recording.lines > 1 && self.end.as_ref().is_some_and(|end| end.is_match(line));
+            let full =
+                matches!(self.limit, Some(CaptureLimit::Lines(max)) if recording.lines >= max);
+            if ended || full {
+                events.push(CaptureEvent::Saved(recording.lines));
+                break;
+            }
+        }
+        Ok(events)
+    }
+
+    /// Sync the file under its durability policy. Returns the line count
+    /// once the time limit is up.
+    pub fn pump(&mut self) -> io::Result<Option<u64>> {
+        let Some(recording) = &mut self.recording else {
+            return Ok(None);
+        };
+        recording.file.sync_due()?;
+        let expired = matches!(self.limit, Some(CaptureLimit::Time(max)) if recording.started.elapsed() >= max);
+        Ok(expired.then_some(recording.lines))
+    }
+}
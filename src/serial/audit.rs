@@ -0,0 +1,46 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use super::logfile::{Durability, LogFile};
+use super::stream::escape;
+
+// Audit log format: one record per line, tab-separated:
+// `<timestamp> <connection> <event> <detail>`, where event is `TX` (bytes
+// written to the port) or `TRANSFER` (a file transfer or link test started). Received data
+// belongs in exports and live streams. Detail bytes use the live stream
+// escaping so records stay one line.
+
+/// Append-only record of transmitted data, shared by all connections.
+#[derive(Clone)]
+pub struct AuditLog {
+    file: Arc<Mutex<LogFile>>,
+}
+
+impl AuditLog {
+    pub fn open(path: &str, durability: Durability) -> std::io::Result<Self> {
+        let file = LogFile::append(path, durability)?;
+        Ok(Self {
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+
+    pub fn record(&self, connection: &str, event: &str, detail: &[u8]) -> std::io::Result<()> {
+        let timestamp = chrono::Local::now().format("%Y-%m-%dT%H:%M:%S%.3f%:z");
+        let line = format!(
+            "{}\t{}\t{}\t{}\n",
+            timestamp,
+            connection,
+            event,
+            escape(detail)
+        );
+        self.lock().write(line.as_bytes())
+    }
+
+    /// Fsync records that have waited out the durability interval.
+    pub fn sync_due(&self) -> std::io::Result<()> {
+        self.lock().sync_due()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LogFile> {
+        self.file.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
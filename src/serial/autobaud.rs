@@ -0,0 +1,106 @@
+use std::time::{Duration, Instant};
+
+/// Rates tried in turn, the most common first.
+pub const CANDIDATES: [u32; 9] = [
+    115200, 9600, 57600, 38400, 19200, 230400, 4800, 460800, 921600,
+];
+
+/// How long each rate listens, unless `SAMPLE_BYTES` arrive sooner.
+const SAMPLE_TIME: Duration = Duration::from_millis(700);
+const SAMPLE_BYTES: usize = 256;
+
+/// Bytes still in flight at the previous rate are ignored this long after
+/// switching.
+const SETTLE_TIME: Duration = Duration::from_millis(50);
+
+/// A sample at least this long and this clean locks on without trying the
+/// remaining rates.
+const CONFIDENT_BYTES: usize = 64;
+const CONFIDENT_SCORE: f64 = 0.98;
+
+/// What to do after `AutoBaud::step()`.
+pub enum Step {
+    /// Keep listening at the current rate
+    Wait,
+    /// Switch the port to this rate
+    Try(u32),
+    /// All rates tried: the best one and its score, or none if nothing was
+    /// received at any rate
+    Done(Option<(u32, f64)>),
+}
+
+/// Baud rate detection: listens at each candidate rate in turn and scores
+/// what arrives by how much of it reads as text.
+pub struct AutoBaud {
+    index: usize,
+    sample: Vec<u8>,
+    started: Instant,
+    best: Option<(u32, f64)>,
+}
+
+impl AutoBaud {
+    pub fn new() -> Self {
+        Self {
+            index: 0,
+            sample: Vec::new(),
+            started: Instant::now(),
+            best: None,
+        }
+    }
+
+    /// The rate being listened at.
+    pub fn rate(&self) -> u32 {
+        CANDIDATES[self.index]
+    }
+
+    pub fn progress(&self) -> u8 {
+        (self.index * 100 / CANDIDATES.len()) as u8
+    }
+
+    pub fn sample(&mut self, data: &[u8]) {
+        if self.started.elapsed() < SETTLE_TIME {
+            return;
+        }
+        let room = SAMPLE_BYTES.saturating_sub(self.sample.len());
+        self.sample.extend_from_slice(&data[..data.len().min(room)]);
+    }
+
+    /// Score the current rate once it has listened long enough, and move on.
+    pub fn step(&mut self) -> Step {
+        if self.sample.len() < SAMPLE_BYTES && self.started.elapsed() < SAMPLE_TIME {
+            return Step::Wait;
+        }
+        if !self.sample.is_empty() {
+            let score = score(&self.sample);
+            if self.sample.len() >= CONFIDENT_BYTES && score >= CONFIDENT_SCORE {
+                return Step::Done(Some((self.rate(), score)));
+            }
+            if self.best.is_none_or(|(_, best)| score > best) {
+                self.best = Some((self.rate(), score));
+            }
+        }
+        self.index += 1;
+        self.sample.clear();
+        self.started = Instant::now();
+        if self.index == CANDIDATES.len() {
+            return Step::Done(self.best);
+        }
+        Step::Try(self.rate())
+    }
+}
+
+/// Share of the characters that are printable or common whitespace. At the
+/// wrong rate, bytes come out as framing garbage: control codes and invalid
+/// UTF-8.
+fn score(sample: &[u8]) -> f64 {
+    let text = String::from_utf8_lossy(sample);
+    let (mut good, mut total) = (0, 0);
+    for c in text.chars() {
+        total += 1;
+        let printable = !c.is_control() && c != char::REPLACEMENT_CHARACTER;
+        if printable || matches!(c, '\r' | '\n' | '\t') {
+            good += 1;
+        }
+    }
+    good as f64 / total.max(1) as f64
+}
@@ -0,0 +1,111 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Captured data beyond this many bytes, chunk bookkeeping included, drops
+/// the oldest chunks.
+const CAPTURE_LIMIT: usize = 16 * 1024 * 1024;
+
+/// Reads in the same direction this soon after a chunk started go into it,
+/// so a steady stream doesn't cost a chunk per read.
+const MERGE_WINDOW: chrono::TimeDelta = chrono::TimeDelta::milliseconds(100);
+
+/// Which way a chunk went over the line.
+#[derive(Clone, PartialEq)]
+pub enum Direction {
+    Rx,
+    Tx,
+    /// The tapped port, by name, in a sniffer tab
+    Tapped(Box<str>),
+}
+
+impl Direction {
+    fn new(name: &str) -> Self {
+        match name {
+            "RX" => Direction::Rx,
+            "TX" => Direction::Tx,
+            name => Direction::Tapped(name.into()),
+        }
+    }
+
+    /// `RX`, `TX` or the tapped port's name.
+    pub fn name(&self) -> &str {
+        match self {
+            Direction::Rx => "RX",
+            Direction::Tx => "TX",
+            Direction::Tapped(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Reads from or writes to the port, as they went over the line: one, or
+/// several in the same direction within `MERGE_WINDOW`.
+pub struct Chunk {
+    /// When the first of them happened
+    pub time: chrono::DateTime<chrono::Local>,
+    pub direction: Direction,
+    pub data: Vec<u8>,
+}
+
+impl Chunk {
+    /// Memory the chunk takes up.
+    fn size(&self) -> usize {
+        std::mem::size_of::<Chunk>() + self.data.capacity()
+    }
+}
+
+/// The bytes a connection received and sent, kept alongside the rendered
+/// scrollback for exports that need them (raw binary, CSV, JSON lines).
+#[derive(Default)]
+pub struct Capture {
+    chunks: VecDeque<Chunk>,
+    bytes: usize,
+}
+
+impl Capture {
+    /// Record a read or write; `direction` is `RX`, `TX` or, in a sniffer
+    /// tab, the tapped port's name.
+    pub fn record(&mut self, direction: &str, data: &[u8]) {
+        let time = chrono::Local::now();
+        match self.chunks.back_mut() {
+            Some(last) if last.direction.name() == direction && time - last.time < MERGE_WINDOW => {
+                self.bytes -= last.size();
+                last.data.extend_from_slice(data);
+                self.bytes += last.size();
+            }
+            _ => {
+                let chunk = Chunk {
+                    time,
+                    direction: Direction::new(direction),
+                    data: data.to_vec(),
+                };
+                self.bytes += chunk.size();
+                self.chunks.push_back(chunk);
+            }
+        }
+        while self.bytes > CAPTURE_LIMIT {
+            let Some(chunk) = self.chunks.pop_front() else {
+                break;
+            };
+            self.bytes -= chunk.size();
+        }
+    }
+
+    pub fn chunks(&self) -> impl Iterator<Item = &Chunk> {
+        self.chunks.iter()
+    }
+
+    /// Everything received, in order.
+    pub fn received(&self) -> Vec<u8> {
+        self.chunks
+            .iter()
+            .filter(|c| c.direction != Direction::Tx)
+            .flat_map(|c| c.data.iter().copied())
+            .collect()
+    }
+}
@@ -1,7 +1,39 @@
-use std::sync::mpsc;
-use std::thread::{self, JoinHandle};
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use super::worker::{self, SerialEvent};
+use regex::Regex;
+use tokio::runtime::Handle;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::ansi;
+use crate::highlight::LineFilter;
+use crate::i18n;
+
+use super::armed::{ArmedCapture, CaptureEvent};
+use super::audit::AuditLog;
+use super::autobaud::{self, AutoBaud, Step};
+use super::capture::Capture;
+use super::decoder::{self, DecodedLine, Decoder, DecoderInfo};
+use super::demo;
+use super::fields::FieldTable;
+use super::framing::Framing;
+use super::generator::Generator;
+use super::linktest::LinkTest;
+use super::logfile::Durability;
+use super::pacing::Pacing;
+use super::plot::Plot;
+use super::rfc2217::LineSettings;
+use super::share::ShareServer;
+use super::stats::{SessionStats, StatsReport};
+use super::stream::{self, LiveStream, Rotation};
+use super::tcp::{self, NetAddress};
+use super::transfer::{Protocol, Transfer, TransferKind};
+use super::worker::{self, LineControl, ModemLines, ReadSettings, SerialEvent, WorkerRequest};
+use super::zmodem::INIT_SEQUENCE as ZMODEM_INIT;
 
 #[derive(Clone, Copy, PartialEq)]
 pub enum DisplayMode {
@@ -9,6 +41,47 @@ pub enum DisplayMode {
     HexDump,
 }
 
+/// A long-running job (file transfer, paced send, script) driving a connection.
+/// While set, manual input to the connection is locked out.
+pub struct BusyState {
+    pub label: String,
+    pub progress: Option<u8>,
+    // Bytes moved so far, for jobs whose total size is unknown
+    pub bytes: Option<usize>,
+    cancel: Arc<AtomicBool>,
+}
+
+/// Send bar entries kept per connection for resending.
+const HISTORY_LEN: usize = 50;
+
+/// Columns one sideways scroll step moves unwrapped lines.
+const SCROLL_COLUMNS: usize = 8;
+
+/// A Send bar entry whose pacing delays add up to more than this goes out
+/// as a send job, with progress and Ctrl+C to abort.
+const LONG_SEND: Duration = Duration::from_secs(1);
+
+/// A line (or hex frame) sent from the Send bar, as typed and as bytes.
+pub struct SentInput {
+    pub text: String,
+    pub data: Vec<u8>,
+}
+
+/// Pattern bytes a generator's send job keeps queued ahead of the line.
+const GENERATOR_BLOCK: usize = 4096;
+
+/// Bytes queued for transmission, paced out at roughly the line rate.
+struct SendJob {
+    data: Vec<u8>,
+    sent: usize,
+    // When the next byte is due to be handed to the worker
+    due: Instant,
+    // Tops up `data` as it goes out, for a test pattern
+    generator: Option<Generator>,
+    // Bytes sent and dropped from the front of `data` since the start
+    done: usize,
+}
+
 pub struct Connection {
     pub id: usize,
     pub port_name: String,
@@ -16,15 +89,112 @@ pub struct Connection {
     pub data_bits: serialport::DataBits,
     pub parity: serialport::Parity,
     pub stop_bits: serialport::StopBits,
+    pub pacing: Pacing,
+    // Kept for `reopen()`
+    pub read: ReadSettings,
     pub display_mode: DisplayMode,
-    pub scrollback: Vec<String>,
+    pub scrollback: VecDeque<String>,
+    // Oldest lines are dropped once the scrollback holds this many
+    pub max_scrollback: usize,
+    // Lines dropped from the front so far, so line numbers stay stable
+    evicted_lines: usize,
+    // Line numbers (counting evicted lines) of the notes added with
+    // `annotate()`, for exporting the lines between them
+    pub notes: Vec<usize>,
     pub scroll_offset: usize,
-    pub write_tx: Option<mpsc::Sender<Vec<u8>>>,
+    // The view tracks new data; off while scrolled back, so the view stays put
+    pub follow: bool,
+    // Long lines wrap; off, they're cut at the pane edge and scroll sideways
+    pub wrap: bool,
+    // First column shown while wrapping is off
+    pub scroll_column: usize,
+    // Show each line's bytes in hex next to the text
+    pub hex_pane: bool,
+    // Numbers from received lines, charted instead of the text while set
+    pub plot: Option<Plot>,
+    // Named regex groups pulled from received lines, shown as a table
+    // instead of the text while set
+    pub fields: Option<FieldTable>,
+    pub write_tx: Option<mpsc::UnboundedSender<WorkerRequest>>,
     pub alive: bool,
-    thread_handle: Option<JoinHandle<()>>,
-    line_buffer: String,
-    raw_bytes: Vec<u8>,
-    hex_bytes_formatted: usize,
+    pub read_only: bool,
+    pub busy: Option<BusyState>,
+    // Interactive mode: keystrokes go straight to the port instead of the input buffer
+    pub passthrough: bool,
+    // Name of the saved profile this connection was opened from
+    pub profile_name: Option<String>,
+    live_stream: Option<LiveStream>,
+    // When the live stream starts a new file
+    pub log_rotation: Rotation,
+    send_job: Option<SendJob>,
+    // Last few received bytes, for spotting the ZMODEM init sequence across reads
+    zmodem_tail: Vec<u8>,
+    // The worker task that owns the port
+    task: Option<JoinHandle<()>>,
+    // Turns received bytes into lines; `decoding` says which one
+    decoder: Box<dyn Decoder>,
+    pub decoding: &'static DecoderInfo,
+    // Absolute number of the decoder's provisional line, until it's finished
+    provisional_line: Option<usize>,
+    // When data last arrived, to tell a stalled partial line from one still growing
+    last_rx: Instant,
+    // Idle time after which a text-mode partial line is flushed to the scrollback
+    pub partial_flush: Option<Duration>,
+    // A pause in received data at least this long gets a marker line
+    pub idle_marker: Option<Duration>,
+    // Absolute number of the last idle marker line, which isn't received data
+    idle_marker_line: Option<usize>,
+    // Transmit audit log, when configured
+    pub audit: Option<AuditLog>,
+    // Alerts for this connection are suppressed
+    pub muted: bool,
+    // Sends are previewed in the scrollback instead of transmitted
+    pub dry_run: bool,
+    // Hides scrollback lines from view without dropping them
+    pub filter: Option<LineFilter>,
+    // Absolute line count when the view was paused; later lines stay hidden
+    paused_at: Option<usize>,
+    // An alert was raised while this tab was in the background
+    pub alert: bool,
+    // Traffic totals, and bytes/sec as of the last `update_rates()`
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub rx_rate: f64,
+    pub tx_rate: f64,
+    rate_sample: (Instant, u64, u64),
+    // Send bar history, oldest first
+    pub history: VecDeque<SentInput>,
+    // Minimum time between a Send bar input and a resend
+    pub resend_cooldown: Option<Duration>,
+    last_input: Option<Instant>,
+    // Splits the hex dump into packets; offsets then count from each packet's start
+    framing: Option<Framing>,
+    // Counters for the stats report
+    pub stats: SessionStats,
+    // The bytes behind the scrollback, for raw, CSV and JSON exports
+    pub capture: Capture,
+    // Modem control lines as last set; ports open with both asserted
+    pub dtr: bool,
+    pub rts: bool,
+    // TCP listener mirroring received data to other clients
+    pub share: Option<ShareServer>,
+    // Id of the connection that received data is written to
+    pub bridge_to: Option<usize>,
+    // For a sniffer tab, the ids of the two connections it interleaves
+    pub taps: Option<[usize; 2]>,
+    // Baud rate detection in progress; received data goes to it meanwhile
+    auto_baud: Option<AutoBaud>,
+    // Trigger rules that already answered the partial line, so they don't
+    // answer it again once it completes
+    pub fired_triggers: Vec<usize>,
+    // Received lines matching this raise an alert
+    pub watch: Option<Regex>,
+    // Waiting for its start pattern, or writing lines to its file
+    pub armed: Option<ArmedCapture>,
+    // Speed-up of a recording being played back (read-only tabs only)
+    pub replay: Option<u32>,
+    // CTS/DSR/DCD/RI as the worker last read them, None if the port has none
+    pub modem_lines: Option<ModemLines>,
 }
 
 impl Connection {
@@ -36,17 +206,12 @@ impl Connection {
         parity: serialport::Parity,
         stop_bits: serialport::StopBits,
         display_mode: DisplayMode,
-        serial_tx: mpsc::Sender<SerialEvent>,
+        pacing: Pacing,
+        read: ReadSettings,
+        serial_tx: mpsc::UnboundedSender<SerialEvent>,
+        profile_name: Option<String>,
+        pre_open: Option<String>,
     ) -> Self {
-        let (write_tx, write_rx) = mpsc::channel();
-        let name = port_name.clone();
-
-        let handle = thread::spawn(move || {
-            worker::connection_thread(
-                id, &name, baud_rate, data_bits, parity, stop_bits, serial_tx, write_rx,
-            );
-        });
-
         let data_bits_str = match data_bits {
             serialport::DataBits::Five => "5",
             serialport::DataBits::Six => "6",
@@ -62,34 +227,212 @@ impl Connection {
             serialport::StopBits::One => "1",
             serialport::StopBits::Two => "2",
         };
-        let mode_str = match display_mode {
-            DisplayMode::Text => "text",
-            DisplayMode::HexDump => "hex",
+        let mode_str = decoder::for_mode(display_mode).name;
+        let start_msg = if !has_line_settings(&port_name) {
+            format!("--- Connected to {} ({}) ---", port_name, mode_str)
+        } else {
+            format!(
+                "--- Connected to {} at {} baud ({}{}{}, {}) ---",
+                port_name, baud_rate, data_bits_str, parity_str, stop_str, mode_str
+            )
         };
-        let start_msg = format!(
-            "--- Connected to {} at {} baud ({}{}{}, {}) ---",
-            port_name, baud_rate, data_bits_str, parity_str, stop_str, mode_str
+        let mut conn = Self::base(id, port_name, display_mode);
+        conn.baud_rate = baud_rate;
+        conn.data_bits = data_bits;
+        conn.parity = parity;
+        conn.stop_bits = stop_bits;
+        conn.pacing = pacing;
+        conn.read = read;
+        conn.profile_name = profile_name;
+        conn.scrollback.push_back(start_msg);
+
+        let pre_open = pre_open.map(|command| (command, conn.command_vars()));
+        conn.spawn_worker(pre_open, serial_tx);
+        conn
+    }
+
+    fn spawn_worker(
+        &mut self,
+        pre_open: Option<(String, Vec<(String, String)>)>,
+        serial_tx: mpsc::UnboundedSender<SerialEvent>,
+    ) {
+        let (write_tx, write_rx) = mpsc::unbounded_channel();
+        let (id, name) = (self.id, self.port_name.clone());
+        let (baud_rate, data_bits, parity, stop_bits, pacing, read) = (
+            self.baud_rate,
+            self.data_bits,
+            self.parity,
+            self.stop_bits,
+            self.pacing,
+            self.read,
         );
+        self.task = Some(tokio::spawn(async move {
+            if let Some(address) = NetAddress::parse(&name) {
+                let line = LineSettings {
+                    baud_rate,
+                    data_bits,
+                    parity,
+                    stop_bits,
+                    flow_control: pacing.flow_control,
+                };
+                tcp::connection_task(
+                    id, address, line, pacing, read, pre_open, serial_tx, write_rx,
+                )
+                .await;
+                return;
+            }
+            if demo::is_demo(&name) {
+                demo::connection_task(id, name, pacing, read, pre_open, serial_tx, write_rx).await;
+                return;
+            }
+            worker::connection_task(
+                id, name, baud_rate, data_bits, parity, stop_bits, pacing, read, pre_open,
+                serial_tx, write_rx,
+            )
+            .await;
+        }));
+        self.write_tx = Some(write_tx);
+        self.alive = true;
+    }
+
+    /// Open the port again with the same settings after `close()`, keeping
+    /// the scrollback.
+    pub fn reopen(&mut self, serial_tx: mpsc::UnboundedSender<SerialEvent>) {
+        if self.read_only || self.task.is_some() {
+            return;
+        }
+        self.push_line(format!("--- Reconnecting to {} ---", self.port_name));
+        self.spawn_worker(None, serial_tx);
+    }
+
+    /// Open a read-only connection that follows another instance's live stream
+    /// file, or with `replay` plays a recorded one back that many times faster.
+    pub fn follow(
+        id: usize,
+        path: String,
+        replay: Option<u32>,
+        display_mode: DisplayMode,
+        serial_tx: mpsc::UnboundedSender<SerialEvent>,
+    ) -> Self {
+        let (write_tx, write_rx) = mpsc::unbounded_channel();
+        let (task, start_msg) = match replay {
+            Some(speed) => (
+                tokio::spawn(stream::replay_task(
+                    id,
+                    path.clone(),
+                    speed,
+                    serial_tx,
+                    write_rx,
+                )),
+                format!("--- Replaying {} at {}x (read-only) ---", path, speed),
+            ),
+            None => (
+                tokio::spawn(stream::follow_task(id, path.clone(), serial_tx, write_rx)),
+                format!("--- Following live stream {} (read-only) ---", path),
+            ),
+        };
+        let mut conn = Self::base(id, path, display_mode);
+        conn.scrollback.push_back(start_msg);
+        conn.write_tx = Some(write_tx);
+        conn.read_only = true;
+        conn.task = Some(task);
+        conn.replay = replay;
+        conn
+    }
+
+    /// Open a read-only tab interleaving what two other connections (by id)
+    /// receive, fed through `sniffed()`.
+    pub fn sniffer(id: usize, name: String, taps: [usize; 2], display_mode: DisplayMode) -> Self {
+        let mut conn = Self::base(id, name, display_mode);
+        conn.read_only = true;
+        conn.taps = Some(taps);
+        conn
+    }
+
+    /// The state every kind of connection starts from: no port behind it
+    /// yet, an empty scrollback and 8N1 at no particular baud rate.
+    fn base(id: usize, port_name: String, display_mode: DisplayMode) -> Self {
         Self {
             id,
             port_name,
-            baud_rate,
-            data_bits,
-            parity,
-            stop_bits,
+            baud_rate: 0,
+            data_bits: serialport::DataBits::Eight,
+            parity: serialport::Parity::None,
+            stop_bits: serialport::StopBits::One,
+            pacing: Pacing::NONE,
+            read: ReadSettings::DEFAULT,
             display_mode,
-            scrollback: vec![start_msg],
+            scrollback: VecDeque::new(),
+            max_scrollback: usize::MAX,
+            evicted_lines: 0,
             scroll_offset: 0,
-            write_tx: Some(write_tx),
+            follow: true,
+            wrap: true,
+            scroll_column: 0,
+            hex_pane: false,
+            plot: None,
+            fields: None,
+            write_tx: None,
             alive: true,
-            thread_handle: Some(handle),
-            line_buffer: String::new(),
-            raw_bytes: Vec::new(),
-            hex_bytes_formatted: 0,
+            read_only: false,
+            busy: None,
+            passthrough: false,
+            profile_name: None,
+            live_stream: None,
+            log_rotation: Rotation::default(),
+            send_job: None,
+            zmodem_tail: Vec::new(),
+            task: None,
+            decoder: (decoder::for_mode(display_mode).new)(None),
+            decoding: decoder::for_mode(display_mode),
+            provisional_line: None,
+            last_rx: Instant::now(),
+            partial_flush: None,
+            idle_marker: None,
+            idle_marker_line: None,
+            audit: None,
+            muted: false,
+            dry_run: false,
+            filter: None,
+            paused_at: None,
+            notes: Vec::new(),
+            alert: false,
+            rx_bytes: 0,
+            tx_bytes: 0,
+            rx_rate: 0.0,
+            tx_rate: 0.0,
+            rate_sample: (Instant::now(), 0, 0),
+            history: VecDeque::new(),
+            resend_cooldown: None,
+            last_input: None,
+            framing: None,
+            stats: SessionStats::new(&[]),
+            capture: Capture::default(),
+            dtr: true,
+            rts: true,
+            share: None,
+            bridge_to: None,
+            taps: None,
+            auto_baud: None,
+            fired_triggers: Vec::new(),
+            watch: None,
+            armed: None,
+            replay: None,
+            modem_lines: None,
         }
     }
 
     pub fn label(&self) -> String {
+        if self.taps.is_some() {
+            return format!("{} (sniff)", self.port_name);
+        }
+        if let Some(speed) = self.replay {
+            return format!("{} (replay {}x)", self.port_name, speed);
+        }
+        if self.read_only {
+            return format!("{} (follow)", self.port_name);
+        }
+
         let data_bits_ch = match self.data_bits {
             serialport::DataBits::Five => '5',
             serialport::DataBits::Six => '6',
@@ -105,99 +448,952 @@ impl Connection {
             serialport::StopBits::One => '1',
             serialport::StopBits::Two => '2',
         };
-        let suffix = match self.display_mode {
-            DisplayMode::HexDump => " HEX",
-            DisplayMode::Text => "",
+        let suffix = match self.decoding.name {
+            "text" => String::new(),
+            name => format!(" {}", name.to_uppercase()),
         };
+        if !has_line_settings(&self.port_name) {
+            return format!("{}{}", self.port_name, suffix);
+        }
         format!(
             "{}@{}/{}{}{}{}",
             self.port_name, self.baud_rate, data_bits_ch, parity_ch, stop_ch, suffix
         )
     }
 
+    /// Variables available to external commands, both as `${NAME}` substitutions
+    /// and as environment variables.
+    pub fn command_vars(&self) -> Vec<(String, String)> {
+        let data_bits = match self.data_bits {
+            serialport::DataBits::Five => "5",
+            serialport::DataBits::Six => "6",
+            serialport::DataBits::Seven => "7",
+            serialport::DataBits::Eight => "8",
+        };
+        let parity = match self.parity {
+            serialport::Parity::None => "none",
+            serialport::Parity::Odd => "odd",
+            serialport::Parity::Even => "even",
+        };
+        let stop_bits = match self.stop_bits {
+            serialport::StopBits::One => "1",
+            serialport::StopBits::Two => "2",
+        };
+        [
+            ("PORT", self.port_name.clone()),
+            ("BAUD", self.baud_rate.to_string()),
+            ("DATABITS", data_bits.to_string()),
+            ("PARITY", parity.to_string()),
+            ("STOPBITS", stop_bits.to_string()),
+            ("LOGFILE", self.live_stream_path().unwrap_or("").to_string()),
+            ("PROFILE", self.profile_name.clone().unwrap_or_default()),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect()
+    }
+
+    /// Tab bar text, padded and with a marker when alerts are muted.
+    pub fn tab_title(&self) -> String {
+        let mute = if self.muted { " 🔇" } else { "" };
+        format!(" {}{} ", self.label(), mute)
+    }
+
     pub fn push_data(&mut self, data: &[u8]) {
-        match self.display_mode {
-            DisplayMode::Text => {
-                let text = String::from_utf8_lossy(data);
-                for ch in text.chars() {
-                    if ch == '\n' {
-                        self.scrollback.push(std::mem::take(&mut self.line_buffer));
-                    } else if ch != '\r' {
-                        self.line_buffer.push(ch);
+        self.push_data_at(data, Instant::now());
+    }
+
+    /// Decode `data` that arrived at `time`; a gap since the previous
+    /// chunk's arrival can end a framed packet first.
+    fn push_data_at(&mut self, data: &[u8], time: Instant) {
+        let idle = time.saturating_duration_since(self.last_rx);
+        self.last_rx = self.last_rx.max(time);
+        let mut lines = self.decoder.idle(idle);
+        lines.extend(self.decoder.feed(data));
+        self.file_lines(lines);
+    }
+
+    /// Add what the decoder made of received data to the scrollback.
+    fn file_lines(&mut self, lines: Vec<DecodedLine>) {
+        for line in lines {
+            match line {
+                DecodedLine::Line(line) => self.push_line(line),
+                DecodedLine::Provisional(line) => {
+                    self.provisional_line = Some(self.evicted_lines + self.scrollback.len());
+                    self.push_line(line);
+                }
+                DecodedLine::Finish(line) => {
+                    let provisional = self
+                        .provisional_line
+                        .take()
+                        .and_then(|n| n.checked_sub(self.evicted_lines))
+                        .and_then(|idx| self.scrollback.get_mut(idx));
+                    if let Some(provisional) = provisional {
+                        *provisional = line;
                     }
                 }
             }
-            DisplayMode::HexDump => {
-                self.raw_bytes.extend_from_slice(data);
-                // Format complete 16-byte rows into scrollback
-                let complete_rows = self.raw_bytes.len() / 16;
-                let already_done = self.hex_bytes_formatted / 16;
-                for row in already_done..complete_rows {
-                    let offset = row * 16;
-                    let line = format_hex_line(offset, &self.raw_bytes[offset..offset + 16]);
-                    self.scrollback.push(line);
-                }
-                self.hex_bytes_formatted = complete_rows * 16;
-                // Update line_buffer with partial row (so scrollback_with_partial works)
-                let remaining = &self.raw_bytes[self.hex_bytes_formatted..];
-                if remaining.is_empty() {
-                    self.line_buffer.clear();
-                } else {
-                    self.line_buffer = format_hex_line(self.hex_bytes_formatted, remaining);
+        }
+    }
+
+    /// Decode received data with the decoder named `name` from now on. What
+    /// the old one had pending is finished first. False if there is no such
+    /// decoder.
+    pub fn set_decoder(&mut self, name: &str) -> bool {
+        let Some(info) = decoder::find(name) else {
+            return false;
+        };
+        let mut lines = self.decoder.idle(Duration::MAX);
+        lines.extend(self.decoder.flush());
+        self.file_lines(lines);
+        let partial = self.decoder.partial().to_string();
+        if !partial.is_empty() {
+            self.push_line(partial);
+        }
+        self.decoder = (info.new)(self.framing.as_ref());
+        self.decoding = info;
+        self.display_mode = info.mode;
+        // Hex rows are hex already, with no numbers to plot or fields to pick
+        self.hex_pane &= info.mode == DisplayMode::Text;
+        if info.mode != DisplayMode::Text {
+            self.plot = None;
+            self.fields = None;
+        }
+        self.push_line(format!("--- Decoding as {} ---", info.name));
+        true
+    }
+
+    /// Split hex dumps into packets. Set when the connection opens: a new
+    /// decoder is made, so a partial line would be lost.
+    pub fn set_framing(&mut self, framing: Option<Framing>) {
+        self.framing = framing;
+        self.decoder = (self.decoding.new)(self.framing.as_ref());
+    }
+
+    /// End a framed packet once the line has been idle for the framing gap.
+    /// Returns whether it did.
+    pub fn end_idle_packet(&mut self) -> bool {
+        let lines = self.decoder.idle(self.last_rx.elapsed());
+        let ended = !lines.is_empty();
+        self.file_lines(lines);
+        ended
+    }
+
+    /// Append a complete line to the scrollback, ahead of any partial line,
+    /// dropping the oldest line once the scrollback is full.
+    pub fn push_line(&mut self, line: String) {
+        self.stats.count_line(&line);
+        if let Some(plot) = &mut self.plot {
+            plot.feed(&line);
+        }
+        if let Some(fields) = &mut self.fields {
+            fields.feed(&line);
+        }
+        self.scrollback.push_back(line);
+        while self.scrollback.len() > self.max_scrollback.max(1) {
+            self.scrollback.pop_front();
+            self.evicted_lines += 1;
+        }
+        self.scroll_offset = self.scroll_offset.min(self.scrollback.len());
+    }
+
+    /// Chart the numbers in received lines, starting with the ones already
+    /// in the scrollback, or go back to the text.
+    pub fn toggle_plot(&mut self) {
+        self.plot = match self.plot {
+            Some(_) => None,
+            None => {
+                let mut plot = Plot::default();
+                for line in &self.scrollback {
+                    plot.feed(line);
                 }
+                Some(plot)
+            }
+        };
+    }
+
+    /// Number of lines dropped from the front of the scrollback. Adding it to
+    /// an index into `scrollback_with_partial()` gives a line number that
+    /// survives later evictions.
+    pub fn evicted_lines(&self) -> usize {
+        self.evicted_lines
+    }
+
+    /// The lines added since the scrollback had held `since` in all, less
+    /// the idle marker: what received data made of them.
+    pub fn received_lines_since(&self, since: usize) -> impl Iterator<Item = &String> {
+        let first = since.saturating_sub(self.evicted_lines);
+        self.scrollback
+            .iter()
+            .enumerate()
+            .skip(first)
+            .filter(move |(i, _)| Some(self.evicted_lines + i) != self.idle_marker_line)
+            .map(|(_, line)| line)
+    }
+
+    /// Add what a tapped connection received as one line, tagged with its
+    /// time and port.
+    pub fn sniffed(&mut self, port_name: &str, data: &[u8]) {
+        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f");
+        let bytes = match self.display_mode {
+            DisplayMode::Text => stream::escape(data),
+            DisplayMode::HexDump => data
+                .iter()
+                .map(|b| format!("{:02X}", b))
+                .collect::<Vec<_>>()
+                .join(" "),
+        };
+        self.rx_bytes += data.len() as u64;
+        self.capture.record(port_name, data);
+        self.push_line(format!("{} [{}] {}", timestamp, port_name, bytes));
+    }
+
+    /// Mark the scrollback and the live stream with a note, as a line with
+    /// the time; an empty note marks just the time.
+    pub fn annotate(&mut self, note: &str) {
+        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f");
+        self.push_line(match note {
+            "" => format!("=== {} ===", timestamp),
+            note => format!("=== {} {} ===", timestamp, note),
+        });
+        self.notes
+            .push(self.evicted_lines + self.scrollback.len() - 1);
+        self.stream_frame("NOTE", note.as_bytes());
+    }
+
+    /// Whether input may be sent. A dry run transmits nothing, so it works
+    /// on read-only connections too, e.g. to try automation on a replay.
+    pub fn can_send(&self) -> bool {
+        !self.read_only || self.dry_run
+    }
+
+    /// Change a modem control line, or send a break.
+    pub fn control(&mut self, control: LineControl) {
+        match control {
+            LineControl::Dtr(on) => self.dtr = on,
+            LineControl::Rts(on) => self.rts = on,
+            LineControl::Break => {}
+        }
+        if let Some(tx) = &self.write_tx {
+            let _ = tx.send(WorkerRequest::Control(control));
+        }
+    }
+
+    pub fn send(&mut self, data: &[u8]) {
+        if self.dry_run {
+            self.push_line(format!("[DRY RUN] {}", stream::escape(data)));
+            return;
+        }
+        if let Some(tx) = &self.write_tx {
+            let _ = tx.send(WorkerRequest::Write(data.to_vec()));
+        }
+        self.tx_bytes += data.len() as u64;
+        self.capture.record("TX", data);
+        self.stream_frame("TX", data);
+        self.audit("TX", data);
+    }
+
+    /// Send a Send bar entry and remember it for resending. One that pacing
+    /// makes slow to go out becomes a send job.
+    pub fn send_input(&mut self, text: String, data: Vec<u8>) {
+        if !self.dry_run && self.pacing.duration(&data) > LONG_SEND {
+            self.start_send_job(&format!("send {} bytes", data.len()), data.clone());
+        } else {
+            self.send(&data);
+        }
+        self.last_input = Some(Instant::now());
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(SentInput { text, data });
+    }
+
+    /// Send the last `count` history entries again, oldest first. Refused
+    /// while the resend cooldown since the previous input is running.
+    pub fn resend(&mut self, count: usize) -> Result<String, String> {
+        if self.history.is_empty() {
+            return Err(i18n::t("status.nothing_sent").into());
+        }
+        if let (Some(cooldown), Some(last)) = (self.resend_cooldown, self.last_input) {
+            let elapsed = last.elapsed();
+            if elapsed < cooldown {
+                let left = format!("{:.1}", (cooldown - elapsed).as_secs_f64());
+                return Err(i18n::tf("status.resend_cooldown", &[&left]));
+            }
+        }
+        let count = count.min(self.history.len());
+        let entries = self.history.range(self.history.len() - count..);
+        let data: Vec<u8> = entries.flat_map(|e| e.data.iter().copied()).collect();
+        self.last_input = Some(Instant::now());
+        if count == 1 {
+            let text = self
+                .history
+                .back()
+                .map(|e| e.text.clone())
+                .unwrap_or_default();
+            self.send(&data);
+            Ok(i18n::tf("status.resent", &[&text]))
+        } else {
+            self.start_send_job(&format!("resend {} lines", count), data);
+            Ok(i18n::tf("status.resending", &[&count]))
+        }
+    }
+
+    /// Id, settings and profile, e.g. `#0 /dev/ttyUSB0@115200/8N1 [Router]`.
+    fn identity(&self) -> String {
+        match &self.profile_name {
+            Some(profile) => format!("#{} {} [{}]", self.id, self.label(), profile),
+            None => format!("#{} {}", self.id, self.label()),
+        }
+    }
+
+    /// The session statistics as of now, ready to write out.
+    pub fn stats_report(&self) -> StatsReport<'_> {
+        StatsReport {
+            connection: self.identity(),
+            rx_bytes: self.rx_bytes,
+            tx_bytes: self.tx_bytes,
+            stats: &self.stats,
+        }
+    }
+
+    /// Add a record to the audit log (if any) under this connection's identity.
+    pub fn audit(&mut self, event: &str, detail: &[u8]) {
+        let Some(audit) = &self.audit else {
+            return;
+        };
+        if let Err(e) = audit.record(&self.identity(), event, detail) {
+            self.audit = None;
+            self.push_data(format!("\n[AUDIT LOG STOPPED: {}]\n", e).as_bytes());
+        }
+    }
+
+    /// Record data read from the port at `time` to the live stream and
+    /// share clients (if any) and the scrollback.
+    pub fn receive(&mut self, data: &[u8], time: Instant) {
+        let first = self.rx_bytes == 0;
+        self.rx_bytes += data.len() as u64;
+        if let Some(auto_baud) = &mut self.auto_baud {
+            auto_baud.sample(data);
+            return;
+        }
+        self.capture.record("RX", data);
+        self.stream_frame("RX", data);
+        if let Some(share) = &self.share {
+            share.broadcast(data);
+        }
+        if !first {
+            self.mark_idle_gap(time);
+        }
+        self.push_data_at(data, time);
+    }
+
+    /// BEL characters received since the last call.
+    pub fn take_bells(&mut self) -> usize {
+        self.decoder.take_bells()
+    }
+
+    /// Note a long pause before data that just arrived, between lines only:
+    /// a marker under a line still being received would come before its
+    /// start.
+    fn mark_idle_gap(&mut self, time: Instant) {
+        let idle = time.saturating_duration_since(self.last_rx);
+        if self.idle_marker.is_none_or(|min| idle < min) {
+            return;
+        }
+        // Whatever the pause ended (a framed packet) goes above the marker
+        let lines = self.decoder.idle(idle);
+        self.file_lines(lines);
+        if self.decoder.partial().is_empty() {
+            self.idle_marker_line = Some(self.evicted_lines + self.scrollback.len());
+            self.push_line(format!(
+                "\x1b[2m—— {:.1} s idle ——\x1b[22m",
+                idle.as_secs_f64()
+            ));
+        }
+    }
+
+    /// Mark the connection busy; the job should stop once the returned flag is set.
+    pub fn begin_busy(&mut self, label: &str) -> Arc<AtomicBool> {
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.busy = Some(BusyState {
+            label: label.to_string(),
+            progress: None,
+            bytes: None,
+            cancel: cancel.clone(),
+        });
+        cancel
+    }
+
+    pub fn set_busy_progress(&mut self, percent: u8) {
+        if let Some(busy) = &mut self.busy {
+            busy.progress = Some(percent.min(100));
+        }
+    }
+
+    pub fn set_busy_bytes(&mut self, bytes: usize, total: Option<usize>) {
+        if let Some(busy) = &mut self.busy {
+            match total {
+                Some(total) => busy.progress = Some((bytes * 100 / total.max(1)).min(100) as u8),
+                None => busy.bytes = Some(bytes),
+            }
+        }
+    }
+
+    pub fn end_busy(&mut self) {
+        self.busy = None;
+    }
+
+    /// Signal the running job to stop, returning its label.
+    pub fn abort_busy(&mut self) -> Option<String> {
+        let busy = self.busy.take()?;
+        busy.cancel.store(true, Ordering::Relaxed);
+        Some(busy.label)
+    }
+
+    pub fn busy_text(&self) -> Option<String> {
+        self.busy.as_ref().map(|b| match b.progress {
+            Some(p) => format!("busy: {} {}%", b.label, p),
+            None => match b.bytes {
+                Some(n) => format!("busy: {} {} bytes", b.label, n),
+                None => format!("busy: {}", b.label),
+            },
+        })
+    }
+
+    /// Hand a file transfer to the worker, which takes over the port until it
+    /// reports `TransferDone`.
+    pub fn start_transfer(&mut self, label: &str, protocol: Protocol, kind: TransferKind) {
+        let detail = match &kind {
+            TransferKind::Send { name, data } => {
+                format!("{} send {} ({} bytes)", protocol.name(), name, data.len())
             }
+            TransferKind::Receive(path) => format!("{} receive into {}", protocol.name(), path),
+        };
+        self.audit("TRANSFER", detail.as_bytes());
+        let cancel = self.begin_busy(label);
+        if let Some(tx) = &self.write_tx {
+            let _ = tx.send(WorkerRequest::Transfer(Transfer {
+                protocol,
+                kind,
+                cancel,
+            }));
+        }
+    }
+
+    /// Hand a link test to the worker, which takes over the port until it
+    /// reports `TransferDone` with the results.
+    pub fn start_link_test(&mut self, duration: Duration) {
+        let label = format!("Link test {} s", duration.as_secs());
+        self.audit("TRANSFER", label.as_bytes());
+        // Start and stop bits, the data bits and a parity bit if any
+        let bits =
+            2 + match self.data_bits {
+                serialport::DataBits::Five => 5,
+                serialport::DataBits::Six => 6,
+                serialport::DataBits::Seven => 7,
+                serialport::DataBits::Eight => 8,
+            } + u32::from(self.parity != serialport::Parity::None)
+                + u32::from(self.stop_bits == serialport::StopBits::Two);
+        // The demo port only pretends to have a baud rate
+        let nominal = (has_line_settings(&self.port_name) && !demo::is_demo(&self.port_name))
+            .then(|| f64::from(self.baud_rate) / f64::from(bits));
+        let cancel = self.begin_busy(&label);
+        if let Some(tx) = &self.write_tx {
+            let _ = tx.send(WorkerRequest::LinkTest(LinkTest {
+                duration,
+                nominal,
+                cancel,
+            }));
         }
     }
 
-    pub fn send(&self, data: &[u8]) {
+    /// Change the baud rate of the open port.
+    pub fn set_baud_rate(&mut self, baud_rate: u32) {
+        self.baud_rate = baud_rate;
         if let Some(tx) = &self.write_tx {
-            let _ = tx.send(data.to_vec());
+            let _ = tx.send(WorkerRequest::SetBaudRate(baud_rate));
+        }
+    }
+
+    /// The line settings can be changed on the open port: a serial port or
+    /// an RFC 2217 one, not a sniffer or a followed file.
+    pub fn can_change_line_settings(&self) -> bool {
+        self.alive && !self.read_only && self.taps.is_none() && has_line_settings(&self.port_name)
+    }
+
+    /// Ask the worker to apply new line settings to the open port without
+    /// closing it; `line_settings_applied()` takes them over once it has.
+    pub fn set_line_settings(
+        &mut self,
+        baud_rate: u32,
+        data_bits: serialport::DataBits,
+        parity: serialport::Parity,
+        stop_bits: serialport::StopBits,
+    ) {
+        let flow_control = self.pacing.flow_control;
+        let previous = LineSettings {
+            baud_rate: self.baud_rate,
+            data_bits: self.data_bits,
+            parity: self.parity,
+            stop_bits: self.stop_bits,
+            flow_control,
+        };
+        let line = LineSettings {
+            baud_rate,
+            data_bits,
+            parity,
+            stop_bits,
+            flow_control,
+        };
+        if let Some(tx) = &self.write_tx {
+            let _ = tx.send(WorkerRequest::SetLineSettings { line, previous });
+        }
+    }
+
+    /// The worker applied new line settings; the scrollback stays and
+    /// `reopen()` uses them from now on.
+    pub fn line_settings_applied(&mut self, line: LineSettings) {
+        self.baud_rate = line.baud_rate;
+        self.data_bits = line.data_bits;
+        self.parity = line.parity;
+        self.stop_bits = line.stop_bits;
+    }
+
+    /// Cycle through the common baud rates to find the one the device
+    /// talks at, locking input until `pump_auto_baud()` settles on one.
+    pub fn start_auto_baud(&mut self) {
+        let auto_baud = AutoBaud::new();
+        if self.baud_rate != auto_baud.rate() {
+            self.set_baud_rate(auto_baud.rate());
+        }
+        self.auto_baud = Some(auto_baud);
+        self.begin_busy("detecting baud rate");
+        self.push_line("--- Detecting baud rate, waiting for data ---".into());
+    }
+
+    /// Move baud rate detection on. Returns the outcome once it is over:
+    /// the rate found, or why the current one was kept.
+    pub fn pump_auto_baud(&mut self) -> Option<Result<String, String>> {
+        let auto_baud = self.auto_baud.as_mut()?;
+        if self.busy.is_none() {
+            // Aborted
+            self.auto_baud = None;
+            self.push_line(format!(
+                "--- Baud rate detection aborted at {} ---",
+                self.baud_rate
+            ));
+            return None;
+        }
+        let result = match auto_baud.step() {
+            Step::Wait => return None,
+            Step::Try(baud_rate) => {
+                let progress = auto_baud.progress();
+                self.set_baud_rate(baud_rate);
+                self.set_busy_progress(progress);
+                return None;
+            }
+            Step::Done(Some((baud_rate, score))) => {
+                self.set_baud_rate(baud_rate);
+                let readable = format!("{:.0}", score * 100.0);
+                Ok(i18n::tf("status.baud_detected", &[&baud_rate, &readable]))
+            }
+            Step::Done(None) => {
+                let baud_rate = autobaud::CANDIDATES[0];
+                self.set_baud_rate(baud_rate);
+                Err(i18n::tf("status.baud_not_detected", &[&baud_rate]))
+            }
+        };
+        self.auto_baud = None;
+        self.end_busy();
+        let (Ok(text) | Err(text)) = &result;
+        self.push_line(format!("--- {} ---", text));
+        Some(result)
+    }
+
+    /// Watch received data for a ZMODEM sender starting up (e.g. `sz` run on
+    /// the device). The sequence may be split across reads.
+    pub fn detect_zmodem_start(&mut self, data: &[u8]) -> bool {
+        self.zmodem_tail.extend_from_slice(data);
+        let found = self
+            .zmodem_tail
+            .windows(ZMODEM_INIT.len())
+            .any(|w| w == ZMODEM_INIT);
+        let keep = ZMODEM_INIT.len() - 1;
+        if found {
+            self.zmodem_tail.clear();
+        } else if self.zmodem_tail.len() > keep {
+            self.zmodem_tail.drain(..self.zmodem_tail.len() - keep);
+        }
+        found
+    }
+
+    /// Queue `data` for paced transmission, locking input until it is sent.
+    pub fn start_send_job(&mut self, label: &str, data: Vec<u8>) {
+        self.begin_busy(label);
+        self.send_job = Some(SendJob {
+            data,
+            sent: 0,
+            due: Instant::now(),
+            generator: None,
+            done: 0,
+        });
+    }
+
+    /// Send a test pattern as a send job, until the generator runs out or
+    /// the job is aborted.
+    pub fn start_generator(&mut self, label: &str, generator: Generator) {
+        self.begin_busy(label);
+        self.send_job = Some(SendJob {
+            data: Vec::new(),
+            sent: 0,
+            due: Instant::now(),
+            generator: Some(generator),
+            done: 0,
+        });
+    }
+
+    /// Transmit the next slice of the queued data. Returns the total byte count
+    /// once the job has completed.
+    pub fn pump_send_job(&mut self) -> Option<usize> {
+        let unmetered = !has_line_settings(&self.port_name);
+        let job = self.send_job.as_mut()?;
+        if self.busy.is_none() {
+            // Aborted
+            self.send_job = None;
+            return None;
+        }
+        if let Some(generator) = &mut job.generator {
+            if job.data.len() - job.sent < GENERATOR_BLOCK {
+                job.done += job.sent;
+                job.data.drain(..job.sent);
+                job.sent = 0;
+                job.data.extend(generator.take(GENERATOR_BLOCK));
+            }
+        }
+        // ~10 bits per byte on the wire (start + 8 data + stop), or slower
+        // when the worker meters out bytes with a delay after each character
+        // and line. A network connection has no line rate and takes it all
+        // at once.
+        let byte_time = if unmetered {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(10.0 / self.baud_rate.max(10) as f64)
+        };
+        let now = Instant::now();
+        let mut end = job.sent;
+        while end < job.data.len() && job.due <= now {
+            let delay = self
+                .pacing
+                .delay_after(job.data[end], job.data.get(end + 1).copied());
+            job.due += byte_time.max(delay);
+            end += 1;
+        }
+        if end > job.sent {
+            let chunk = job.data[job.sent..end].to_vec();
+            job.sent = end;
+            let sent = job.done + end;
+            let total = match &job.generator {
+                Some(generator) => generator.total().map(|total| total as usize),
+                None => Some(job.data.len()),
+            };
+            self.send(&chunk);
+            match total {
+                Some(total) => self.set_busy_progress((sent * 100 / total.max(1)) as u8),
+                // A pattern sent until stopped
+                None => self.set_busy_bytes(sent, None),
+            }
+        }
+        let job = self.send_job.as_ref()?;
+        if job.sent >= job.data.len() {
+            let total = job.done + job.data.len();
+            self.send_job = None;
+            self.end_busy();
+            return Some(total);
+        }
+        None
+    }
+
+    pub fn start_live_stream(&mut self, path: &str, durability: Durability) -> std::io::Result<()> {
+        self.live_stream = Some(LiveStream::create(
+            path,
+            &self.port_name,
+            self.baud_rate,
+            durability,
+            self.log_rotation,
+        )?);
+        Ok(())
+    }
+
+    /// Stop the live stream, returning the path it was writing to.
+    pub fn stop_live_stream(&mut self) -> Option<String> {
+        self.live_stream.take().map(|s| s.path)
+    }
+
+    /// The file the live stream is writing now; rotation moves it on.
+    pub fn live_stream_path(&self) -> Option<&str> {
+        self.live_stream.as_ref().map(|s| s.path.as_str())
+    }
+
+    fn stream_frame(&mut self, direction: &str, data: &[u8]) {
+        if let Some(stream) = &mut self.live_stream {
+            let result = stream.write_frame(direction, data);
+            self.check_live_stream(result);
+        }
+    }
+
+    /// Pass completed lines (without colour codes) to the armed capture.
+    /// Returns what to report once it started, finished or failed.
+    pub fn feed_armed(&mut self, lines: &[String]) -> Option<Result<String, String>> {
+        let armed = self.armed.as_mut()?;
+        let path = armed.path.clone();
+        let mut report = None;
+        match armed.feed(lines) {
+            Ok(events) => {
+                for event in events {
+                    report = Some(Ok(match event {
+                        CaptureEvent::Started => {
+                            self.push_line(format!("--- Capture started: {} ---", path));
+                            i18n::tf("status.capturing", &[&path])
+                        }
+                        CaptureEvent::Saved(lines) => {
+                            self.armed = None;
+                            self.capture_saved(&path, lines)
+                        }
+                    }));
+                }
+            }
+            Err(e) => {
+                self.armed = None;
+                self.push_line(format!("--- Capture failed: {} ---", e));
+                report = Some(Err(i18n::tf("status.capture_failed", &[&path, &e])));
+            }
+        }
+        report
+    }
+
+    /// Sync the armed capture's file and end it once its time is up.
+    pub fn pump_armed(&mut self) -> Option<Result<String, String>> {
+        let armed = self.armed.as_mut()?;
+        let path = armed.path.clone();
+        match armed.pump() {
+            Ok(None) => None,
+            Ok(Some(lines)) => {
+                self.armed = None;
+                Some(Ok(self.capture_saved(&path, lines)))
+            }
+            Err(e) => {
+                self.armed = None;
+                self.push_line(format!("--- Capture failed: {} ---", e));
+                Some(Err(i18n::tf("status.capture_failed", &[&path, &e])))
+            }
+        }
+    }
+
+    /// Drop the armed capture, keeping what it wrote. Returns what to report.
+    pub fn disarm(&mut self) -> Option<String> {
+        let armed = self.armed.take()?;
+        Some(match armed.recorded() {
+            Some(lines) => self.capture_saved(&armed.path, lines),
+            None => i18n::tf("status.capture_disarmed", &[&armed.path]),
+        })
+    }
+
+    fn capture_saved(&mut self, path: &str, lines: u64) -> String {
+        self.push_line(format!(
+            "--- Capture saved: {} lines to {} ---",
+            lines, path
+        ));
+        i18n::tf("status.captured", &[&lines, &path])
+    }
+
+    /// Fsync the live stream if its durability interval has run out. Returns
+    /// whether it failed and was stopped.
+    pub fn sync_live_stream(&mut self) -> bool {
+        let Some(stream) = &mut self.live_stream else {
+            return false;
+        };
+        let result = stream.sync_due();
+        self.check_live_stream(result);
+        self.live_stream.is_none()
+    }
+
+    fn check_live_stream(&mut self, result: std::io::Result<()>) {
+        if let Err(e) = result {
+            self.live_stream = None;
+            self.push_data(format!("\n[LIVE STREAM STOPPED: {}]\n", e).as_bytes());
         }
     }
 
     pub fn close(&mut self) {
-        self.write_tx.take(); // drop sender to signal thread
-        if let Some(handle) = self.thread_handle.take() {
-            let _ = handle.join();
+        self.abort_busy();
+        self.share = None;
+        self.write_tx.take(); // drop sender to signal the worker
+        if let Some(task) = self.task.take() {
+            // A write held up by flow control would never see that
+            task.abort();
+            // Wait for the port to be released, so it can be opened again
+            let _ = tokio::task::block_in_place(|| Handle::current().block_on(task));
         }
         self.alive = false;
     }
 
-    pub fn scrollback_with_partial(&self) -> impl Iterator<Item = &str> {
-        self.scrollback
-            .iter()
-            .map(|s| s.as_str())
-            .chain(if self.line_buffer.is_empty() {
+    /// Move the partial line into the scrollback if it has been idle for the
+    /// configured flush timeout, if the decoder lets it go (hex rows stay).
+    /// Returns whether it moved.
+    pub fn flush_stale_partial(&mut self) -> bool {
+        let Some(timeout) = self.partial_flush else {
+            return false;
+        };
+        if self.decoder.partial().is_empty() || self.last_rx.elapsed() < timeout {
+            return false;
+        }
+        let lines = self.decoder.flush();
+        let flushed = !lines.is_empty();
+        self.file_lines(lines);
+        flushed
+    }
+
+    /// Recompute the bytes/sec figures, about once a second. Returns whether
+    /// it did.
+    pub fn update_rates(&mut self) -> bool {
+        let (at, rx, tx) = self.rate_sample;
+        let elapsed = at.elapsed().as_secs_f64();
+        if elapsed < 1.0 {
+            return false;
+        }
+        self.rx_rate = (self.rx_bytes - rx) as f64 / elapsed;
+        self.tx_rate = (self.tx_bytes - tx) as f64 / elapsed;
+        self.stats.sample_rate(self.rx_rate, elapsed);
+        self.rate_sample = (Instant::now(), self.rx_bytes, self.tx_bytes);
+        true
+    }
+
+    /// How long the partial (unterminated) line has gone without new data.
+    pub fn partial_age(&self) -> Option<Duration> {
+        if self.decoder.partial().is_empty() {
+            None
+        } else {
+            Some(self.last_rx.elapsed())
+        }
+    }
+
+    /// The bytes a scrollback line stands for, as the decoder recovers them:
+    /// the row's bytes in hex mode, the line's text otherwise. None for lines
+    /// that aren't data (hex mode banners and messages).
+    pub fn line_bytes(&self, idx: usize) -> Option<Vec<u8>> {
+        let line = self.scrollback_with_partial().nth(idx)?;
+        (self.decoding.line_bytes)(line)
+    }
+
+    /// Scroll to `offset` lines above the bottom. Following resumes at the bottom.
+    pub fn scroll_to(&mut self, offset: usize) {
+        self.scroll_offset = offset;
+        self.follow = offset == 0;
+    }
+
+    /// The part of `total` shown lines a scrollback `rows` tall has on
+    /// screen; the top of the scrollback always fills it.
+    pub fn view_window(&self, total: usize, rows: usize) -> Range<usize> {
+        let offset = self.scroll_offset.min(total.saturating_sub(rows));
+        total.saturating_sub(rows + offset)..total - offset
+    }
+
+    /// Lines shown (plus those evicted), to pass to `keep_view()`.
+    pub fn view_end(&self) -> usize {
+        self.evicted_lines + self.shown_lines().len()
+    }
+
+    /// Keep a view that isn't following on the same lines after new ones
+    /// arrived, given `view_end()` from before.
+    pub fn keep_view(&mut self, before: usize) {
+        if !self.follow {
+            self.scroll_offset += self.view_end().saturating_sub(before);
+        }
+    }
+
+    /// Freeze the view at the current last line, or resume following.
+    pub fn toggle_pause(&mut self) {
+        self.paused_at = match self.paused_at {
+            Some(_) => {
+                self.scroll_to(0);
                 None
-            } else {
-                Some(self.line_buffer.as_str())
-            })
+            }
+            None => Some(self.evicted_lines + self.scrollback_with_partial().count()),
+        };
+    }
+
+    /// Wrap long lines, or show them cut off at the pane edge so wide
+    /// tables stay aligned.
+    pub fn toggle_wrap(&mut self) {
+        self.wrap = !self.wrap;
+        self.scroll_column = 0;
     }
-}
 
-fn format_hex_line(offset: usize, bytes: &[u8]) -> String {
-    let mut hex_part = String::with_capacity(49);
-    for (i, &b) in bytes.iter().enumerate() {
-        if i == 8 {
-            hex_part.push(' ');
+    /// Whether long lines are wrapped right now: the hex pane turns
+    /// wrapping off while it's shown, so its rows line up with the text.
+    pub fn wraps(&self) -> bool {
+        self.wrap && !self.hex_pane
+    }
+
+    /// Scroll unwrapped lines sideways, no further than the widest line
+    /// reaches.
+    pub fn scroll_sideways(&mut self, right: bool) {
+        if self.wraps() {
+            return;
         }
-        if i > 0 {
-            hex_part.push(' ');
+        if !right {
+            self.scroll_column = self.scroll_column.saturating_sub(SCROLL_COLUMNS);
+            return;
         }
-        hex_part.push_str(&format!("{:02X}", b));
+        let widest = self
+            .scrollback_with_partial()
+            .map(|line| ansi::strip(line).chars().count())
+            .max()
+            .unwrap_or(0);
+        self.scroll_column = (self.scroll_column + SCROLL_COLUMNS).min(widest.saturating_sub(1));
     }
-    // Pad hex section to full width (16 bytes = "XX XX XX XX XX XX XX XX  XX XX XX XX XX XX XX XX")
-    let full_hex_width = 48; // 16*3 - 1 + 1 (extra space between groups)
-    while hex_part.len() < full_hex_width {
-        hex_part.push(' ');
+
+    /// Lines that arrived since the view was paused, or None if it isn't.
+    pub fn paused_new_lines(&self) -> Option<usize> {
+        let paused_at = self.paused_at?;
+        let total = self.evicted_lines + self.scrollback_with_partial().count();
+        Some(total.saturating_sub(paused_at))
     }
 
-    let ascii: String = bytes
-        .iter()
-        .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
-        .collect();
+    /// Indices into `scrollback_with_partial()` of the lines the filter lets
+    /// through, up to the pause point. `scroll_offset` counts these, not raw
+    /// lines.
+    pub fn shown_lines(&self) -> Vec<usize> {
+        let end = match self.paused_at {
+            Some(paused_at) => paused_at.saturating_sub(self.evicted_lines),
+            None => usize::MAX,
+        };
+        let lines = self.scrollback_with_partial().take(end);
+        match &self.filter {
+            Some(filter) => lines
+                .enumerate()
+                .filter(|(_, line)| filter.shows(line))
+                .map(|(i, _)| i)
+                .collect(),
+            None => (0..lines.count()).collect(),
+        }
+    }
 
-    format!("{:08X}  {}  |{}|", offset, hex_part, ascii)
+    pub fn scrollback_with_partial(&self) -> impl Iterator<Item = &str> {
+        self.scrollback
+            .iter()
+            .map(|s| s.as_str())
+            .chain(Some(self.decoder.partial()).filter(|line| !line.is_empty()))
+    }
+
+    /// The line still being received, empty in hex mode.
+    pub fn partial_line(&self) -> &str {
+        match self.display_mode {
+            DisplayMode::Text => self.decoder.partial(),
+            DisplayMode::HexDump => "",
+        }
+    }
 }
 
 impl Drop for Connection {
@@ -205,3 +1401,9 @@ impl Drop for Connection {
         self.close();
     }
 }
+
+/// Local ports and RFC 2217 servers have a baud rate, parity etc.; raw TCP
+/// and telnet connections don't.
+fn has_line_settings(port_name: &str) -> bool {
+    NetAddress::parse(port_name).is_none_or(|a| a.has_line_settings())
+}
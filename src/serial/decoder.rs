@@ -0,0 +1,400 @@
+use std::time::Duration;
+
+use super::connection::DisplayMode;
+use super::framing::{Framing, Packet};
+
+/// What a decoder makes of received bytes.
+pub enum DecodedLine {
+    /// A finished line for the scrollback
+    Line(String),
+    /// A line whose final text is only known later, such as a packet header
+    /// waiting for the packet's length
+    Provisional(String),
+    /// The final text of the last `Provisional` line
+    Finish(String),
+}
+
+/// Turns a connection's received bytes into scrollback lines. Decoders keep
+/// whatever state they need between reads; `Connection::push_data()` feeds
+/// them and files the lines.
+pub trait Decoder {
+    fn feed(&mut self, data: &[u8]) -> Vec<DecodedLine>;
+
+    /// The line still being decoded, shown under the scrollback; empty for
+    /// none.
+    fn partial(&self) -> &str;
+
+    /// The line has been quiet for `idle`, e.g. to end a frame at a gap.
+    fn idle(&mut self, _idle: Duration) -> Vec<DecodedLine> {
+        Vec::new()
+    }
+
+    /// Give up waiting for the rest of the partial line and finish it as it
+    /// is. Decoders whose partial line can't stand on its own keep it.
+    fn flush(&mut self) -> Vec<DecodedLine> {
+        Vec::new()
+    }
+
+    /// BEL characters received since the last call, for the `bell` setting.
+    fn take_bells(&mut self) -> usize {
+        0
+    }
+}
+
+/// A decoder that can be picked for a connection.
+pub struct DecoderInfo {
+    /// Shown in the pane title, unless it's "text"
+    pub name: &'static str,
+    /// Shown in the View → Decoder list
+    pub label: &'static str,
+    /// Text decoders' lines are matched by triggers, watches and scripts;
+    /// hex rows aren't
+    pub mode: DisplayMode,
+    pub new: fn(Option<&Framing>) -> Box<dyn Decoder>,
+    /// The received bytes behind one of its lines, for Copy As, Edit Line
+    /// and the hex pane; None for lines that hold no data
+    pub line_bytes: fn(&str) -> Option<Vec<u8>>,
+}
+
+/// The decoders to pick from. A new one is an entry here.
+pub const DECODERS: [DecoderInfo; 3] = [
+    DecoderInfo {
+        name: "text",
+        label: "Text (UTF-8)",
+        mode: DisplayMode::Text,
+        new: |_| Box::new(Text::default()),
+        line_bytes: |line| Some(text_line_bytes(line, false)),
+    },
+    DecoderInfo {
+        name: "control",
+        label: "Text + Control Chars",
+        mode: DisplayMode::Text,
+        new: |_| {
+            Box::new(Text {
+                controls: true,
+                ..Text::default()
+            })
+        },
+        line_bytes: |line| Some(text_line_bytes(line, true)),
+    },
+    DecoderInfo {
+        name: "hex",
+        label: "Hex Dump",
+        mode: DisplayMode::HexDump,
+        new: |framing| Box::new(HexDump::new(framing.cloned())),
+        line_bytes: parse_hex_line,
+    },
+];
+
+/// The decoder named `name`.
+pub fn find(name: &str) -> Option<&'static DecoderInfo> {
+    DECODERS.iter().find(|d| d.name == name)
+}
+
+/// The built-in decoder for a display mode.
+pub fn for_mode(mode: DisplayMode) -> &'static DecoderInfo {
+    DECODERS
+        .iter()
+        .find(|d| d.mode == mode)
+        .expect("a decoder for every display mode")
+}
+
+/// Marks a byte the text decoder can't show as text: reverse video, so it
+/// stands out from a literal `\xNN`.
+const BYTE_START: &str = "\x1b[7m";
+const BYTE_END: &str = "\x1b[27m";
+
+/// UTF-8 text split at `\n`, with `\r` dropped. Invalid UTF-8 and control
+/// bytes other than tab and ESC (kept for ANSI colors) show as `\xNN`.
+#[derive(Default)]
+struct Text {
+    line: String,
+    // The start of a UTF-8 sequence the next read may finish
+    pending: Vec<u8>,
+    // Show control characters, line endings included, as symbols such as
+    // ␍ ␊ ␛, so stray ones can be spotted
+    controls: bool,
+    bells: usize,
+    // Inside an OSC sequence (ESC ] ...), which a BEL ends rather than rings
+    osc: bool,
+    last: char,
+}
+
+impl Text {
+    fn push_str(&mut self, text: &str, lines: &mut Vec<DecodedLine>) {
+        for ch in text.chars() {
+            match ch {
+                ']' if self.last == '\x1b' => self.osc = true,
+                '\x07' if !self.osc => self.bells += 1,
+                // BEL or ST ends the sequence, a line end one left unfinished
+                '\x07' | '\x1b' | '\n' => self.osc = false,
+                _ => {}
+            }
+            self.last = ch;
+            if self.controls {
+                push_visible(&mut self.line, ch);
+            }
+            match ch {
+                '\n' => lines.push(DecodedLine::Line(std::mem::take(&mut self.line))),
+                _ if self.controls => {}
+                '\r' => {}
+                '\t' | '\x1b' => self.line.push(ch),
+                '\0'..='\x1f' | '\x7f' => push_byte(&mut self.line, ch as u8),
+                _ => self.line.push(ch),
+            }
+        }
+    }
+}
+
+impl Decoder for Text {
+    fn feed(&mut self, data: &[u8]) -> Vec<DecodedLine> {
+        let mut lines = Vec::new();
+        let mut bytes = std::mem::take(&mut self.pending);
+        bytes.extend_from_slice(data);
+        let mut rest = &bytes[..];
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(text) => {
+                    self.push_str(text, &mut lines);
+                    break;
+                }
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    self.push_str(std::str::from_utf8(valid).unwrap_or_default(), &mut lines);
+                    // A sequence cut off at the end of the read
+                    let Some(len) = e.error_len() else {
+                        self.pending = after.to_vec();
+                        break;
+                    };
+                    for &b in &after[..len] {
+                        push_byte(&mut self.line, b);
+                    }
+                    rest = &after[len..];
+                }
+            }
+        }
+        lines
+    }
+
+    fn partial(&self) -> &str {
+        &self.line
+    }
+
+    fn take_bells(&mut self) -> usize {
+        std::mem::take(&mut self.bells)
+    }
+
+    fn flush(&mut self) -> Vec<DecodedLine> {
+        for b in std::mem::take(&mut self.pending) {
+            push_byte(&mut self.line, b);
+        }
+        if self.line.is_empty() {
+            return Vec::new();
+        }
+        vec![DecodedLine::Line(std::mem::take(&mut self.line))]
+    }
+}
+
+/// Add `b` to `line` as a marked `\xNN`.
+fn push_byte(line: &mut String, b: u8) {
+    line.push_str(&format!("{}\\x{:02X}{}", BYTE_START, b, BYTE_END));
+}
+
+/// Recover the bytes of a text line: the ones `push_byte()` marked and,
+/// with `controls`, those `push_visible()` showed as symbols.
+fn text_line_bytes(line: &str, controls: bool) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(ch) = rest.chars().next() {
+        if let Some((b, len)) = marked_byte(rest) {
+            bytes.push(b);
+            rest = &rest[len..];
+            continue;
+        }
+        let mut buf = [0; 4];
+        let (recovered, len) = match ch {
+            '\u{2400}'..='\u{241f}' if controls => (char::from_u32(ch as u32 - 0x2400), 3),
+            '␡' if controls => (Some('\x7f'), 3),
+            '<' if controls => match rest.get(1..4).filter(|s| s.ends_with('>')) {
+                Some(hex) => (
+                    u32::from_str_radix(&hex[..2], 16)
+                        .ok()
+                        .filter(|c| (0x80..=0x9f).contains(c))
+                        .and_then(char::from_u32),
+                    4,
+                ),
+                None => (None, 0),
+            },
+            _ => (None, 0),
+        };
+        match recovered {
+            Some(c) => {
+                bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                rest = &rest[len..];
+            }
+            None => {
+                bytes.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+                rest = &rest[ch.len_utf8()..];
+            }
+        }
+    }
+    bytes
+}
+
+/// The byte `push_byte()` marked at the start of `text`, and the length of
+/// the mark.
+fn marked_byte(text: &str) -> Option<(u8, usize)> {
+    let rest = text.strip_prefix(BYTE_START)?;
+    let hex = rest.get(..4)?.strip_prefix("\\x")?;
+    let b = u8::from_str_radix(hex, 16).ok()?;
+    rest[4..]
+        .starts_with(BYTE_END)
+        .then_some((b, BYTE_START.len() + 4 + BYTE_END.len()))
+}
+
+/// 16-byte rows of offset, hex and ASCII, split into packets under framing.
+struct HexDump {
+    // Bytes of the unfinished row, and the offset where that row starts
+    row: Vec<u8>,
+    offset: usize,
+    // The unfinished row as shown
+    partial: String,
+    // Splits the dump into packets; offsets then count from each packet's start
+    framing: Option<Framing>,
+    packet: Option<Packet>,
+}
+
+impl HexDump {
+    fn new(framing: Option<Framing>) -> Self {
+        Self {
+            row: Vec::new(),
+            offset: 0,
+            partial: String::new(),
+            framing,
+            packet: None,
+        }
+    }
+
+    /// Finish the unfinished row.
+    fn push_row(&mut self, lines: &mut Vec<DecodedLine>) {
+        let row = std::mem::take(&mut self.row);
+        lines.push(DecodedLine::Line(format_hex_line(self.offset, &row)));
+        self.offset += 16;
+    }
+
+    /// Open a packet under framing: a header, then rows from offset 0.
+    fn start_packet(&mut self, lines: &mut Vec<DecodedLine>) {
+        let packet = Packet::new();
+        lines.push(DecodedLine::Provisional(packet.header(false)));
+        self.offset = 0;
+        self.packet = Some(packet);
+    }
+
+    /// Close the current packet: flush its last row and fill in its length.
+    fn end_packet(&mut self, lines: &mut Vec<DecodedLine>) {
+        let Some(packet) = self.packet.take() else {
+            return;
+        };
+        if !self.row.is_empty() {
+            self.push_row(lines);
+        }
+        self.partial.clear();
+        self.offset = 0;
+        lines.push(DecodedLine::Finish(packet.header(true)));
+    }
+}
+
+impl Decoder for HexDump {
+    fn feed(&mut self, data: &[u8]) -> Vec<DecodedLine> {
+        let mut lines = Vec::new();
+        for &b in data {
+            if self.framing.is_some() && self.packet.is_none() {
+                self.start_packet(&mut lines);
+            }
+            self.row.push(b);
+            if self.row.len() == 16 {
+                self.push_row(&mut lines);
+            }
+            let ended = match (&self.framing, &mut self.packet) {
+                (Some(framing), Some(packet)) => packet.push(b, &framing.delimiter),
+                _ => false,
+            };
+            if ended {
+                self.end_packet(&mut lines);
+            }
+        }
+        if self.row.is_empty() {
+            self.partial.clear();
+        } else {
+            self.partial = format_hex_line(self.offset, &self.row);
+        }
+        lines
+    }
+
+    fn partial(&self) -> &str {
+        &self.partial
+    }
+
+    fn idle(&mut self, idle: Duration) -> Vec<DecodedLine> {
+        let mut lines = Vec::new();
+        let gap = self.framing.as_ref().and_then(|f| f.gap);
+        if self.packet.is_some() && gap.is_some_and(|gap| idle >= gap) {
+            self.end_packet(&mut lines);
+        }
+        lines
+    }
+}
+
+/// Add `ch` to `line`, with C0 controls and DEL as their Unicode control
+/// pictures and C1 controls as `<9B>`.
+fn push_visible(line: &mut String, ch: char) {
+    match ch {
+        '\0'..='\x1f' => line.extend(char::from_u32(0x2400 + ch as u32)),
+        '\x7f' => line.push('␡'),
+        '\u{80}'..='\u{9f}' => line.push_str(&format!("<{:02X}>", ch as u32)),
+        _ => line.push(ch),
+    }
+}
+
+/// Recover the bytes of a row written by `format_hex_line`.
+fn parse_hex_line(line: &str) -> Option<Vec<u8>> {
+    let offset = line.get(..8)?;
+    if !offset.bytes().all(|b| b.is_ascii_hexdigit()) || line.get(58..61)? != "  |" {
+        return None;
+    }
+    line.get(10..58)?
+        .split_whitespace()
+        .map(|b| u8::from_str_radix(b, 16).ok())
+        .collect()
+}
+
+fn format_hex_line(offset: usize, bytes: &[u8]) -> String {
+    let mut hex_part = String::with_capacity(49);
+    for (i, &b) in bytes.iter().enumerate() {
+        if i == 8 {
+            hex_part.push(' ');
+        }
+        if i > 0 {
+            hex_part.push(' ');
+        }
+        hex_part.push_str(&format!("{:02X}", b));
+    }
+    // Pad hex section to full width (16 bytes = "XX XX XX XX XX XX XX XX  XX XX XX XX XX XX XX XX")
+    let full_hex_width = 48; // 16*3 - 1 + 1 (extra space between groups)
+    while hex_part.len() < full_hex_width {
+        hex_part.push(' ');
+    }
+
+    let ascii: String = bytes
+        .iter()
+        .map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '.'
+            }
+        })
+        .collect();
+
+    format!("{:08X}  {}  |{}|", offset, hex_part, ascii)
+}
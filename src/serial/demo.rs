@@ -0,0 +1,163 @@
+use std::collections::VecDeque;
+use std::io;
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+use super::pacing::Pacing;
+use super::rfc2217::LineSettings;
+use super::worker::{
+    self, LineControl, ModemLines, Port, ReadSettings, SerialEvent, WorkerRequest,
+};
+
+/// Port names starting with this open a simulated device instead of a port:
+/// `demo://` plays the built-in script, `demo://FILE` the lines of FILE.
+pub const DEMO_SCHEME: &str = "demo://";
+
+/// How often the simulated device prints a line.
+const LINE_INTERVAL: Duration = Duration::from_secs(1);
+
+const BANNER: [&str; 4] = [
+    "serialtui demo device 1.0",
+    "Sensor board ready, reporting once a second",
+    "Everything sent to this port is echoed back",
+    "",
+];
+
+/// The port name is a simulated device's.
+pub fn is_demo(port_name: &str) -> bool {
+    port_name.starts_with(DEMO_SCHEME)
+}
+
+/// Worker for a simulated device; the counterpart of
+/// `worker::connection_task` that needs no hardware.
+pub async fn connection_task(
+    id: usize,
+    port_name: String,
+    pacing: Pacing,
+    read: ReadSettings,
+    pre_open: Option<(String, Vec<(String, String)>)>,
+    serial_tx: mpsc::UnboundedSender<SerialEvent>,
+    mut write_rx: mpsc::UnboundedReceiver<WorkerRequest>,
+) {
+    if !worker::run_pre_open(id, pre_open, &serial_tx).await {
+        return;
+    }
+
+    let path = &port_name[DEMO_SCHEME.len()..];
+    let lines = match path {
+        "" => None,
+        path => match std::fs::read_to_string(path) {
+            Ok(text) if text.lines().next().is_some() => {
+                Some(text.lines().map(str::to_string).collect())
+            }
+            Ok(_) => {
+                let _ = serial_tx.send(SerialEvent::Error {
+                    id,
+                    err: format!("{}: empty script", path),
+                });
+                return;
+            }
+            Err(e) => {
+                let _ = serial_tx.send(SerialEvent::Error {
+                    id,
+                    err: format!("{}: {}", path, e),
+                });
+                return;
+            }
+        },
+    };
+
+    let mut port = DemoPort {
+        lines,
+        count: 0,
+        next_line: Instant::now(),
+        pending: VecDeque::new(),
+    };
+    worker::serve(id, &mut port, pacing, read, &serial_tx, &mut write_rx).await;
+    let _ = serial_tx.send(SerialEvent::Disconnected { id });
+}
+
+/// A device that prints a line every `LINE_INTERVAL` and echoes what is
+/// written to it.
+struct DemoPort {
+    /// The script's lines, played in a loop; None for the built-in script
+    lines: Option<Vec<String>>,
+    /// Lines printed so far
+    count: usize,
+    next_line: Instant,
+    /// Echoed bytes and the rest of a line the last read had no room for
+    pending: VecDeque<u8>,
+}
+
+impl DemoPort {
+    fn line(&self) -> String {
+        if let Some(lines) = &self.lines {
+            return lines[self.count % lines.len()].clone();
+        }
+        if let Some(line) = BANNER.get(self.count) {
+            return line.to_string();
+        }
+        let n = self.count - BANNER.len();
+        // A slow wave, so plots and statistics have something to show
+        let temp = 21.5 + 1.5 * (n as f64 / 8.0).sin();
+        let humidity = 45 + (n * 7) % 11;
+        match n % 20 {
+            9 => format!("WARN: humidity {}% above threshold", humidity),
+            19 => "ERROR: sensor 2 not responding, retrying".to_string(),
+            _ => format!("t={} temp={:.1}C rh={}% status=OK", n, temp, humidity),
+        }
+    }
+}
+
+impl Port for DemoPort {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending.is_empty() {
+            // Nothing changes before the sleep ends, so dropping the read
+            // loses nothing
+            tokio::time::sleep_until(self.next_line).await;
+            let line = self.line();
+            self.count += 1;
+            // The banner comes all at once
+            let banner = self.lines.is_none() && self.count < BANNER.len();
+            self.next_line = Instant::now()
+                + if banner {
+                    Duration::ZERO
+                } else {
+                    LINE_INTERVAL
+                };
+            self.pending.extend(line.bytes().chain(*b"\r\n"));
+        }
+        let n = buf.len().min(self.pending.len());
+        for (dst, byte) in buf.iter_mut().zip(self.pending.drain(..n)) {
+            *dst = byte;
+        }
+        Ok(n)
+    }
+
+    async fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+        self.pending.extend(data);
+        Ok(())
+    }
+
+    async fn control(&mut self, _control: LineControl) -> io::Result<()> {
+        Ok(())
+    }
+
+    async fn set_baud_rate(&mut self, _baud_rate: u32) -> io::Result<()> {
+        Ok(())
+    }
+
+    async fn set_line_settings(&mut self, _line: LineSettings) -> io::Result<()> {
+        Ok(())
+    }
+
+    async fn drain(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn modem_lines(&mut self) -> Option<ModemLines> {
+        None
+    }
+}
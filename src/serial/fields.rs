@@ -0,0 +1,59 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Local};
+use regex::Regex;
+
+/// Matching lines kept in a field table's history.
+const FIELD_HISTORY: usize = 1000;
+
+/// Values pulled out of received lines by a regex's named groups, e.g.
+/// `V=(?P<volts>[\d.]+)` picking readings out of log chatter.
+pub struct FieldTable {
+    regex: Regex,
+    /// The named groups, in the order they appear in the regex
+    pub names: Vec<String>,
+    /// Each field's most recent value, from whichever line last had one
+    pub latest: Vec<Option<String>>,
+    /// Matching lines, newest first, with a value per field
+    pub rows: VecDeque<(DateTime<Local>, Vec<Option<String>>)>,
+}
+
+impl FieldTable {
+    /// Fails unless the regex compiles and has a named group.
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let regex = Regex::new(pattern).map_err(|e| e.to_string())?;
+        let names: Vec<String> = regex.capture_names().flatten().map(String::from).collect();
+        if names.is_empty() {
+            return Err("name the values to show, e.g. (?P<volts>[0-9.]+)".into());
+        }
+        Ok(Self {
+            regex,
+            latest: vec![None; names.len()],
+            names,
+            rows: VecDeque::new(),
+        })
+    }
+
+    pub fn pattern(&self) -> &str {
+        self.regex.as_str()
+    }
+
+    pub fn feed(&mut self, line: &str) {
+        let line = crate::ansi::strip(line);
+        let Some(caps) = self.regex.captures(&line) else {
+            return;
+        };
+        let values: Vec<Option<String>> = self
+            .names
+            .iter()
+            .map(|name| caps.name(name).map(|m| m.as_str().to_string()))
+            .collect();
+        for (latest, value) in self.latest.iter_mut().zip(&values) {
+            if value.is_some() {
+                latest.clone_from(value);
+            }
+        }
+        self.rows.push_front((Local::now(), values));
+        self.rows.truncate(FIELD_HISTORY);
+    }
+}
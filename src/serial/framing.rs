@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+/// Splits a hex dump into packets, each starting its own block of rows under
+/// a header with its arrival time and length.
+#[derive(Clone, Default)]
+pub struct Framing {
+    /// A packet ends with this byte sequence; empty for none
+    pub delimiter: Vec<u8>,
+    /// A packet ends after this long without data
+    pub gap: Option<Duration>,
+}
+
+/// The packet currently being received.
+pub(super) struct Packet {
+    started: chrono::DateTime<chrono::Local>,
+    len: usize,
+    // The last bytes received, to spot a delimiter split across reads
+    tail: Vec<u8>,
+}
+
+impl Packet {
+    pub fn new() -> Self {
+        Self {
+            started: chrono::Local::now(),
+            len: 0,
+            tail: Vec::new(),
+        }
+    }
+
+    /// Count a received byte. True if it completes the delimiter.
+    pub fn push(&mut self, byte: u8, delimiter: &[u8]) -> bool {
+        self.len += 1;
+        if delimiter.is_empty() {
+            return false;
+        }
+        self.tail.push(byte);
+        if self.tail.len() > delimiter.len() {
+            self.tail.remove(0);
+        }
+        self.tail == delimiter
+    }
+
+    pub fn header(&self, done: bool) -> String {
+        let time = self.started.format("%H:%M:%S%.3f");
+        if done {
+            format!("── {}  {} bytes ──", time, self.len)
+        } else {
+            format!("── {}  receiving… ──", time)
+        }
+    }
+}
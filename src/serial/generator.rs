@@ -0,0 +1,127 @@
+/// What a test pattern generator sends, picked from Tools → Generator.
+#[derive(Clone, Copy, PartialEq)]
+pub enum PatternKind {
+    Incrementing,
+    Constant,
+    Random,
+    Frame,
+}
+
+impl PatternKind {
+    pub const ALL: [PatternKind; 4] = [
+        PatternKind::Incrementing,
+        PatternKind::Constant,
+        PatternKind::Random,
+        PatternKind::Frame,
+    ];
+
+    /// For the busy label, e.g. `send random pattern`.
+    pub fn name(self) -> &'static str {
+        match self {
+            PatternKind::Incrementing => "incrementing",
+            PatternKind::Constant => "constant",
+            PatternKind::Random => "random",
+            PatternKind::Frame => "frame",
+        }
+    }
+
+    /// Locale key of its name in the generator menu.
+    pub fn label(self) -> &'static str {
+        match self {
+            PatternKind::Incrementing => "dialog.generator_incrementing",
+            PatternKind::Constant => "dialog.generator_constant",
+            PatternKind::Random => "dialog.generator_random",
+            PatternKind::Frame => "dialog.generator_frame",
+        }
+    }
+
+    /// Whether it needs bytes entered: the constant byte or the frame.
+    pub fn takes_bytes(self) -> bool {
+        matches!(self, PatternKind::Constant | PatternKind::Frame)
+    }
+}
+
+/// A pattern with its bytes.
+#[derive(Clone)]
+pub enum Pattern {
+    /// 00, 01, ... FF, 00, ...
+    Incrementing,
+    Constant(u8),
+    Random,
+    /// The same frame over and over
+    Frame(Vec<u8>),
+}
+
+impl Pattern {
+    /// Bytes per repetition: a frame, or else one byte.
+    fn unit(&self) -> usize {
+        match self {
+            Pattern::Frame(frame) => frame.len(),
+            _ => 1,
+        }
+    }
+}
+
+/// Produces a pattern's bytes as a send job takes them, `count` times or
+/// until stopped.
+pub struct Generator {
+    pattern: Pattern,
+    /// Repetitions left, None to go on until stopped
+    left: Option<u64>,
+    count: Option<u64>,
+    /// The incrementing pattern's next byte
+    next: u8,
+    /// xorshift64 state for the random pattern
+    state: u64,
+}
+
+impl Generator {
+    pub fn new(pattern: Pattern, count: Option<u64>) -> Self {
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        Self {
+            pattern,
+            left: count,
+            count,
+            next: 0,
+            // xorshift never leaves zero
+            state: seed | 1,
+        }
+    }
+
+    /// Whole repetitions making up about `max` bytes (at least one), none
+    /// once all `count` have been produced.
+    pub fn take(&mut self, max: usize) -> Vec<u8> {
+        let mut n = (max / self.pattern.unit().max(1)).max(1) as u64;
+        if let Some(left) = &mut self.left {
+            n = n.min(*left);
+            *left -= n;
+        }
+        let n = n as usize;
+        match &self.pattern {
+            Pattern::Incrementing => (0..n)
+                .map(|_| {
+                    let byte = self.next;
+                    self.next = self.next.wrapping_add(1);
+                    byte
+                })
+                .collect(),
+            Pattern::Constant(byte) => vec![*byte; n],
+            Pattern::Random => (0..n)
+                .map(|_| {
+                    self.state ^= self.state << 13;
+                    self.state ^= self.state >> 7;
+                    self.state ^= self.state << 17;
+                    (self.state >> 32) as u8
+                })
+                .collect(),
+            Pattern::Frame(frame) => frame.repeat(n),
+        }
+    }
+
+    /// Bytes in all `count` repetitions, None when sending until stopped.
+    pub fn total(&self) -> Option<u64> {
+        Some(self.count? * self.pattern.unit() as u64)
+    }
+}
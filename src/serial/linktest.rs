@@ -0,0 +1,246 @@
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc;
+
+use super::transfer;
+use super::worker::SerialEvent;
+
+/// Bytes written per round; the echo is read back in between.
+const CHUNK: usize = 256;
+
+/// How long to wait for the echo when a read found nothing.
+const POLL: Duration = Duration::from_millis(10);
+
+/// How long the echo may trail the last byte sent before the test ends.
+const SETTLE: Duration = Duration::from_millis(500);
+
+/// Bytes since a mismatch, with matches here and there by chance, taken as
+/// lost bytes rather than corrupted ones, after which the checker looks for
+/// where the stream picks up again.
+const RESYNC_AFTER: usize = 8;
+
+/// Matches in a row that end a run of mismatches.
+const IN_STEP: usize = 4;
+
+/// Pattern offsets searched ahead when resynchronizing.
+const RESYNC_WINDOW: u64 = 4096;
+
+/// A throughput and error rate test handed to the connection's worker: a
+/// pseudo-random stream is sent at full rate for `duration`, and whatever
+/// comes back (with the TX and RX lines looped) is checked against it.
+/// Setting `cancel` ends it early with the results so far.
+pub struct LinkTest {
+    pub duration: Duration,
+    /// Bytes per second the line settings allow, None for network ports
+    pub nominal: Option<f64>,
+    pub cancel: Arc<AtomicBool>,
+}
+
+impl LinkTest {
+    /// `10` or `10s` seconds, `2m` minutes; None for anything else.
+    pub fn parse_duration(text: &str) -> Option<Duration> {
+        let text = text.trim();
+        let (number, unit) = text
+            .find(|c: char| !c.is_ascii_digit())
+            .map_or((text, ""), |i| text.split_at(i));
+        let n: u64 = number.parse().ok().filter(|&n| n > 0)?;
+        match unit.trim() {
+            "" | "s" => Some(Duration::from_secs(n)),
+            "m" => Some(Duration::from_secs(n * 60)),
+            _ => None,
+        }
+    }
+}
+
+/// Run the test on the worker thread, reporting bytes sent as progress.
+/// Reads on `port` should return at once when there is nothing to read.
+pub fn run<P: Read + Write>(
+    id: usize,
+    port: &mut P,
+    test: LinkTest,
+    serial_tx: &mpsc::UnboundedSender<SerialEvent>,
+) -> Result<String, String> {
+    let cancel = &test.cancel;
+    let mut checker = Checker::default();
+    let mut buf = [0u8; 4096];
+    let mut sent: u64 = 0;
+    let start = Instant::now();
+
+    while start.elapsed() < test.duration && !cancel.load(Ordering::Relaxed) {
+        let chunk: Vec<u8> = (sent..sent + CHUNK as u64).map(pattern).collect();
+        match transfer::write_all(port, &chunk, cancel) {
+            Ok(()) => sent += CHUNK as u64,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => break,
+            Err(e) => return Err(e.to_string()),
+        }
+        let _ = serial_tx.send(SerialEvent::Progress {
+            id,
+            bytes: sent as usize,
+            total: None,
+        });
+        if let Some(n) = read(port, &mut buf)? {
+            checker.feed(&buf[..n]);
+        }
+    }
+    // Without an echo to time, the line is done once the port has sent it all
+    if checker.received == 0 {
+        port.flush().map_err(|e| e.to_string())?;
+    }
+    let send_time = start.elapsed();
+
+    // The echo of what was still in the port's buffers
+    let mut last_rx = Instant::now();
+    while last_rx.elapsed() < SETTLE && checker.received < sent {
+        if cancel.load(Ordering::Relaxed) {
+            break;
+        }
+        match read(port, &mut buf)? {
+            Some(n) => {
+                checker.feed(&buf[..n]);
+                last_rx = Instant::now();
+            }
+            None => std::thread::sleep(POLL),
+        }
+    }
+    let rx_time = checker
+        .first
+        .zip(checker.last)
+        .map(|(first, last)| last - first);
+
+    Ok(report(&test, sent, send_time, &checker, rx_time))
+}
+
+/// One read, None when the port's read timeout passed without data.
+fn read<P: Read>(port: &mut P, buf: &mut [u8]) -> Result<Option<usize>, String> {
+    match port.read(buf) {
+        Ok(0) => Ok(None),
+        Ok(n) => Ok(Some(n)),
+        Err(e) if e.kind() == io::ErrorKind::TimedOut => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn report(
+    test: &LinkTest,
+    sent: u64,
+    send_time: Duration,
+    checker: &Checker,
+    rx_time: Option<Duration>,
+) -> String {
+    // Received bytes pace the line better than writes into the OS buffer
+    let (bytes, time) = match rx_time {
+        Some(time) if checker.received > CHUNK as u64 => (checker.received, time),
+        _ => (sent, send_time),
+    };
+    let rate = bytes as f64 / time.as_secs_f64().max(0.001);
+    let mut text = format!(
+        "Link test: sent {} bytes in {:.1} s, {:.0} B/s",
+        sent,
+        send_time.as_secs_f64(),
+        rate
+    );
+    if let Some(nominal) = test.nominal {
+        text += &format!(" ({:.0}% of the line rate)", rate * 100.0 / nominal);
+    }
+    if checker.received == 0 {
+        text += "; nothing came back, loop TX to RX for error rates";
+        return text;
+    }
+    let lost = sent.saturating_sub(checker.expected) + checker.lost;
+    text += &format!(
+        "; received {}, {} byte errors (BER {:.1e}), {} bytes lost",
+        checker.received,
+        checker.byte_errors,
+        checker.bit_errors as f64 / (checker.received * 8) as f64,
+        lost
+    );
+    text
+}
+
+/// Byte `offset` of the test stream: splitmix64 of the offset's 8-byte
+/// block, so any part of it can be computed when resynchronizing.
+fn pattern(offset: u64) -> u8 {
+    let mut z = (offset / 8)
+        .wrapping_add(1)
+        .wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> ((offset % 8) * 8)) as u8
+}
+
+/// Compares received bytes with the test stream.
+#[derive(Default)]
+struct Checker {
+    /// Offset in the stream of the next byte expected
+    expected: u64,
+    received: u64,
+    byte_errors: u64,
+    bit_errors: u64,
+    /// Bytes skipped over by resynchronizing
+    lost: u64,
+    /// The bytes since the latest run of mismatches began, with their bit
+    /// errors (0 where they matched)
+    run: Vec<(u8, u32)>,
+    /// Matches in a row at the end of `run`
+    matched: usize,
+    /// When the first and the latest bytes came back
+    first: Option<Instant>,
+    last: Option<Instant>,
+}
+
+impl Checker {
+    fn feed(&mut self, data: &[u8]) {
+        self.first.get_or_insert_with(Instant::now);
+        self.last = Some(Instant::now());
+        for &byte in data {
+            self.received += 1;
+            let want = pattern(self.expected);
+            self.expected += 1;
+            if byte == want {
+                if !self.run.is_empty() {
+                    self.run.push((byte, 0));
+                    self.matched += 1;
+                    if self.matched >= IN_STEP {
+                        self.run.clear();
+                    }
+                }
+                continue;
+            }
+            let bits = (byte ^ want).count_ones();
+            self.byte_errors += 1;
+            self.bit_errors += u64::from(bits);
+            self.run.push((byte, bits));
+            self.matched = 0;
+            if self.run.len() >= RESYNC_AFTER {
+                self.resync();
+            }
+        }
+    }
+
+    /// Find the run further on in the stream. If it's there, it came right
+    /// after some lost bytes and its mismatches don't count as errors;
+    /// otherwise they stay errors and the next run is tried.
+    fn resync(&mut self) {
+        let run = self.run.len() as u64;
+        let from = self.expected - run;
+        let found = (1..=RESYNC_WINDOW).find(|skip| {
+            self.run
+                .iter()
+                .zip(from + skip..)
+                .all(|(&(byte, _), offset)| pattern(offset) == byte)
+        });
+        if let Some(skip) = found {
+            for &(_, bits) in self.run.iter().filter(|(_, bits)| *bits > 0) {
+                self.byte_errors -= 1;
+                self.bit_errors -= u64::from(bits);
+            }
+            self.lost += skip;
+            self.expected = from + skip + run;
+        }
+        self.run.clear();
+    }
+}
@@ -0,0 +1,129 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::time::{Duration, Instant};
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+/// How hard a log file tries to get each record onto disk.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Durability {
+    /// Records collect in memory and are written out in blocks. Fastest, but a
+    /// crash loses the unwritten block and followers see data late.
+    Buffered,
+    /// Every record goes to the OS as it's written: survives serialtui
+    /// crashing, not the machine losing power.
+    Line,
+    /// Like `Line`, plus an fsync no later than `interval` after a record, so
+    /// records survive power loss. A zero interval syncs every record.
+    Fsync { interval: Duration },
+}
+
+/// Whether a file named `path` is written gzip-compressed.
+pub fn is_gzip(path: &str) -> bool {
+    path.ends_with(".gz")
+}
+
+/// An append-only log file written under a `Durability` policy. A `.gz`
+/// file is compressed; every `LogFile` opened on it adds a gzip member,
+/// which `zcat` and replays read as one.
+pub struct LogFile {
+    writer: Writer,
+    durability: Durability,
+    // When the oldest record not yet fsynced was written
+    unsynced_since: Option<Instant>,
+}
+
+impl LogFile {
+    pub fn append(path: &str, durability: Durability) -> io::Result<Self> {
+        let file = BufWriter::new(OpenOptions::new().create(true).append(true).open(path)?);
+        let writer = match is_gzip(path) {
+            true => Writer::Gzip(GzEncoder::new(file, Compression::default())),
+            false => Writer::Plain(file),
+        };
+        Ok(Self {
+            writer,
+            durability,
+            unsynced_since: None,
+        })
+    }
+
+    /// Write one record; it's passed on according to the policy. Passing a
+    /// compressed record on flushes the compressor, which costs some of the
+    /// compression; `Buffered` keeps all of it.
+    pub fn write(&mut self, record: &[u8]) -> io::Result<()> {
+        self.writer.write_all(record)?;
+        if self.durability == Durability::Buffered {
+            return Ok(());
+        }
+        self.writer.flush()?;
+        if let Durability::Fsync { .. } = self.durability {
+            self.unsynced_since.get_or_insert_with(Instant::now);
+        }
+        self.sync_due()
+    }
+
+    /// Fsync once the oldest unsynced record has waited out the interval.
+    /// Called after each write and periodically, so a quiet log is synced too.
+    pub fn sync_due(&mut self) -> io::Result<()> {
+        let Durability::Fsync { interval } = self.durability else {
+            return Ok(());
+        };
+        if self.unsynced_since.is_some_and(|t| t.elapsed() >= interval) {
+            // Cleared first so a failing disk is reported once per batch, not every tick
+            self.unsynced_since = None;
+            self.writer.file().sync_data()?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for LogFile {
+    fn drop(&mut self) {
+        let _ = self.writer.finish();
+        if self.unsynced_since.is_some() {
+            let _ = self.writer.file().sync_data();
+        }
+    }
+}
+
+enum Writer {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl Writer {
+    fn file(&self) -> &File {
+        match self {
+            Writer::Plain(writer) => writer.get_ref(),
+            Writer::Gzip(encoder) => encoder.get_ref().get_ref(),
+        }
+    }
+
+    /// Write out everything, ending a compressed file's gzip member.
+    fn finish(&mut self) -> io::Result<()> {
+        match self {
+            Writer::Plain(writer) => writer.flush(),
+            Writer::Gzip(encoder) => {
+                encoder.try_finish()?;
+                encoder.get_mut().flush()
+            }
+        }
+    }
+}
+
+impl Write for Writer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Writer::Plain(writer) => writer.write(buf),
+            Writer::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Writer::Plain(writer) => writer.flush(),
+            Writer::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
@@ -1,6 +1,47 @@
+mod armed;
+mod audit;
+mod autobaud;
+mod capture;
 mod connection;
+mod decoder;
+mod demo;
+mod fields;
+mod framing;
+mod generator;
+mod linktest;
+mod logfile;
+mod pacing;
+mod plot;
+mod rfc2217;
+mod share;
+mod stats;
+mod stream;
+mod tcp;
+mod transfer;
 mod worker;
+mod xmodem;
+mod zmodem;
 
+pub use armed::{ArmedCapture, CaptureLimit};
+pub use audit::AuditLog;
+pub use autobaud::CANDIDATES as AUTO_BAUD_RATES;
+pub use capture::{Capture, Chunk};
 pub use connection::Connection;
 pub use connection::DisplayMode;
-pub use worker::SerialEvent;
+pub use decoder::{DecodedLine, Decoder, DecoderInfo, DECODERS};
+pub use demo::{is_demo, DEMO_SCHEME};
+pub use fields::FieldTable;
+pub use framing::Framing;
+pub use generator::{Generator, Pattern, PatternKind};
+pub use linktest::LinkTest;
+pub use logfile::{is_gzip, Durability};
+pub use pacing::Pacing;
+pub use plot::Plot;
+pub use share::ShareServer;
+pub use stats::SessionStats;
+pub use stream::{escape, unescape, Rotation};
+pub use tcp::{network_port_name, NetAddress};
+pub use transfer::{Protocol, TransferKind};
+pub use worker::{
+    connection_task, LineControl, ModemLines, ReadSettings, SerialEvent, WorkerRequest,
+};
@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use serialport::FlowControl;
+
+/// Delays and flow control for everything written to a port except file
+/// transfers, which bring their own flow control.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Pacing {
+    /// Pause after every byte
+    pub char_delay: Duration,
+    /// Extra pause after each line ending
+    pub line_delay: Duration,
+    pub flow_control: FlowControl,
+}
+
+impl Pacing {
+    /// Bytes go out as fast as the line allows.
+    pub const NONE: Pacing = Pacing {
+        char_delay: Duration::ZERO,
+        line_delay: Duration::ZERO,
+        flow_control: FlowControl::None,
+    };
+    /// No delays; RTS/CTS stops us when the other side's buffer fills.
+    pub const FAST_PC: Pacing = Pacing {
+        char_delay: Duration::ZERO,
+        line_delay: Duration::ZERO,
+        flow_control: FlowControl::Hardware,
+    };
+    /// Room for a polled UART with a one-byte buffer and a slow line parser.
+    pub const SLOW_MCU: Pacing = Pacing {
+        char_delay: Duration::from_millis(2),
+        line_delay: Duration::from_millis(50),
+        flow_control: FlowControl::None,
+    };
+    /// Teletype speed (10 chars/s) with time for the carriage return.
+    pub const PAPER_TAPE: Pacing = Pacing {
+        char_delay: Duration::from_millis(100),
+        line_delay: Duration::from_millis(300),
+        flow_control: FlowControl::Software,
+    };
+
+    /// Whether writes have to be metered out byte by byte.
+    pub fn is_delayed(&self) -> bool {
+        !self.char_delay.is_zero() || !self.line_delay.is_zero()
+    }
+
+    /// The delays writing `data` adds up to.
+    pub fn duration(&self, data: &[u8]) -> Duration {
+        (0..data.len())
+            .map(|i| self.delay_after(data[i], data.get(i + 1).copied()))
+            .sum()
+    }
+
+    /// How long to wait after writing `byte`, given the byte after it. A line
+    /// ends at `\n`, or at a `\r` that isn't followed by one.
+    pub fn delay_after(&self, byte: u8, next: Option<u8>) -> Duration {
+        let line_end = byte == b'\n' || (byte == b'\r' && next != Some(b'\n'));
+        if line_end {
+            self.char_delay + self.line_delay
+        } else {
+            self.char_delay
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::VecDeque;
+
+/// Samples kept per series; older ones scroll off the chart.
+pub const PLOT_SAMPLES: usize = 500;
+
+/// Numbers parsed from received lines for the plot view, Arduino serial
+/// plotter style: a line of numbers separated by commas, semicolons, tabs or
+/// spaces is one sample, each number labelled `name=value` or `name:value`
+/// or else named by its column. Lines with anything else are skipped.
+#[derive(Default)]
+pub struct Plot {
+    /// In the order they first appeared
+    pub series: Vec<Series>,
+    /// Samples taken so far, the x of the next one
+    pub samples: u64,
+}
+
+pub struct Series {
+    pub name: String,
+    /// (sample number, value), the latest `PLOT_SAMPLES` samples
+    pub points: VecDeque<(f64, f64)>,
+}
+
+impl Plot {
+    pub fn feed(&mut self, line: &str) {
+        let Some(values) = parse(&crate::ansi::strip(line)) else {
+            return;
+        };
+        let x = self.samples as f64;
+        for (name, value) in values {
+            let idx = match self.series.iter().position(|s| s.name == name) {
+                Some(idx) => idx,
+                None => {
+                    self.series.push(Series {
+                        name,
+                        points: VecDeque::new(),
+                    });
+                    self.series.len() - 1
+                }
+            };
+            self.series[idx].points.push_back((x, value));
+        }
+        self.samples += 1;
+        // Series that stopped appearing age out too, and leave the legend
+        // once their last point has
+        let oldest = self.samples.saturating_sub(PLOT_SAMPLES as u64) as f64;
+        for series in &mut self.series {
+            while series.points.front().is_some_and(|&(x, _)| x < oldest) {
+                series.points.pop_front();
+            }
+        }
+        self.series.retain(|s| !s.points.is_empty());
+    }
+
+    /// The sample numbers on screen: the latest `PLOT_SAMPLES`.
+    pub fn x_bounds(&self) -> [f64; 2] {
+        let end = self.samples.max(PLOT_SAMPLES as u64);
+        [(end - PLOT_SAMPLES as u64) as f64, end as f64]
+    }
+
+    /// The range of the values on screen with a little room above and
+    /// below, None before the first sample.
+    pub fn y_bounds(&self) -> Option<[f64; 2]> {
+        let values = self
+            .series
+            .iter()
+            .flat_map(|s| s.points.iter().map(|&(_, y)| y));
+        let (min, max) = values.fold(None, |range, y| match range {
+            None => Some((y, y)),
+            Some((min, max)) => Some((y.min(min), y.max(max))),
+        })?;
+        let margin = if max > min { (max - min) * 0.05 } else { 1.0 };
+        Some([min - margin, max + margin])
+    }
+}
+
+/// The numbers in `line` with their series names, None unless every field
+/// is one.
+fn parse(line: &str) -> Option<Vec<(String, f64)>> {
+    // `temp: 21.5` and `temp = 21.5` are one field
+    let line = line
+        .replace(": ", ":")
+        .replace(" = ", "=")
+        .replace("= ", "=");
+    let mut values = Vec::new();
+    for (column, field) in line
+        .split([',', ';', '\t', ' '])
+        .filter(|f| !f.is_empty())
+        .enumerate()
+    {
+        // A second separator, as in `12:00:01`, leaves no number behind it
+        let (name, value) = match field.split_once(['=', ':']) {
+            Some((name, value)) if !name.is_empty() => (name.to_string(), value),
+            _ => ((column + 1).to_string(), field),
+        };
+        let value: f64 = value.parse().ok().filter(|v: &f64| v.is_finite())?;
+        values.push((name, value));
+    }
+    (!values.is_empty()).then_some(values)
+}
@@ -0,0 +1,202 @@
+use super::tcp::{self, IAC, SB, SE};
+use super::worker::{LineControl, ModemLines};
+
+/// Telnet COM-PORT-OPTION: the server drives a serial port as we ask.
+pub(super) const COM_PORT_OPTION: u8 = 44;
+
+// Client commands; the server answers each with the command + 100
+const SET_BAUDRATE: u8 = 1;
+const SET_DATASIZE: u8 = 2;
+const SET_PARITY: u8 = 3;
+const SET_STOPSIZE: u8 = 4;
+const SET_CONTROL: u8 = 5;
+const NOTIFY_MODEMSTATE: u8 = 7;
+const SET_MODEMSTATE_MASK: u8 = 11;
+const SERVER_REPLY: u8 = 100;
+
+// SET-CONTROL values
+const FLOW_NONE: u8 = 1;
+const FLOW_XON_XOFF: u8 = 2;
+const FLOW_HARDWARE: u8 = 3;
+pub(super) const BREAK_ON: u8 = 5;
+pub(super) const BREAK_OFF: u8 = 6;
+const DTR_ON: u8 = 8;
+const DTR_OFF: u8 = 9;
+const RTS_ON: u8 = 11;
+const RTS_OFF: u8 = 12;
+
+/// Modem state bits reported by NOTIFY-MODEMSTATE that we ask to hear about.
+const MODEM_LINES: [(u8, &str); 4] = [(0x10, "CTS"), (0x20, "DSR"), (0x80, "CD"), (0x40, "RI")];
+const MODEM_MASK: u8 = 0xF0;
+
+/// Serial line settings requested from the server, or applied to an open
+/// port by `WorkerRequest::SetLineSettings`.
+#[derive(Clone, Copy)]
+pub struct LineSettings {
+    pub baud_rate: u32,
+    pub data_bits: serialport::DataBits,
+    pub parity: serialport::Parity,
+    pub stop_bits: serialport::StopBits,
+    pub flow_control: serialport::FlowControl,
+}
+
+impl LineSettings {
+    fn data_size(&self) -> u8 {
+        match self.data_bits {
+            serialport::DataBits::Five => 5,
+            serialport::DataBits::Six => 6,
+            serialport::DataBits::Seven => 7,
+            serialport::DataBits::Eight => 8,
+        }
+    }
+
+    fn parity_value(&self) -> u8 {
+        match self.parity {
+            serialport::Parity::None => 1,
+            serialport::Parity::Odd => 2,
+            serialport::Parity::Even => 3,
+        }
+    }
+
+    fn stop_size(&self) -> u8 {
+        match self.stop_bits {
+            serialport::StopBits::One => 1,
+            serialport::StopBits::Two => 2,
+        }
+    }
+}
+
+/// The client side of RFC 2217 for one connection.
+pub(super) struct ComPort {
+    line: LineSettings,
+    // Last reported CTS/DSR/CD/RI bits, to only mention changes
+    modem_state: Option<u8>,
+}
+
+impl ComPort {
+    pub fn new(line: LineSettings) -> Self {
+        Self {
+            line,
+            modem_state: None,
+        }
+    }
+
+    /// Requests applying the line settings, sent once the server has agreed
+    /// to the option.
+    pub fn configure(&self) -> Vec<u8> {
+        let flow = match self.line.flow_control {
+            serialport::FlowControl::None => FLOW_NONE,
+            serialport::FlowControl::Software => FLOW_XON_XOFF,
+            serialport::FlowControl::Hardware => FLOW_HARDWARE,
+        };
+        [
+            command(SET_BAUDRATE, &self.line.baud_rate.to_be_bytes()),
+            command(SET_DATASIZE, &[self.line.data_size()]),
+            command(SET_PARITY, &[self.line.parity_value()]),
+            command(SET_STOPSIZE, &[self.line.stop_size()]),
+            command(SET_CONTROL, &[flow]),
+            command(SET_MODEMSTATE_MASK, &[MODEM_MASK]),
+        ]
+        .concat()
+    }
+
+    /// Request a new baud rate; the server's answer is checked against it.
+    pub fn set_baud_rate(&mut self, baud_rate: u32) -> Vec<u8> {
+        self.line.baud_rate = baud_rate;
+        command(SET_BAUDRATE, &baud_rate.to_be_bytes())
+    }
+
+    /// Request new line settings; the server's answers are checked against
+    /// them.
+    pub fn set_line_settings(&mut self, line: LineSettings) -> Vec<u8> {
+        self.line = line;
+        self.configure()
+    }
+
+    /// Handle a COM-PORT-OPTION subnegotiation from the server (option byte
+    /// first). Returns a line for the scrollback when there's something to
+    /// tell: a setting the server didn't take, or modem lines changing.
+    pub fn handle(&mut self, payload: &[u8]) -> Option<String> {
+        let [COM_PORT_OPTION, reply, value @ ..] = payload else {
+            return None;
+        };
+        let (name, wanted, got) = match reply.checked_sub(SERVER_REPLY)? {
+            SET_BAUDRATE => {
+                let got = u32::from_be_bytes(value.try_into().ok()?);
+                ("baud rate", self.line.baud_rate, got)
+            }
+            SET_DATASIZE => (
+                "data bits",
+                self.line.data_size().into(),
+                *value.first()? as u32,
+            ),
+            SET_PARITY => (
+                "parity",
+                self.line.parity_value().into(),
+                *value.first()? as u32,
+            ),
+            SET_STOPSIZE => (
+                "stop bits",
+                self.line.stop_size().into(),
+                *value.first()? as u32,
+            ),
+            NOTIFY_MODEMSTATE => return self.modem_state_changed(*value.first()?),
+            _ => return None,
+        };
+        (wanted != got).then(|| {
+            format!(
+                "--- Server did not take {} {}, it answered {} ---",
+                name, wanted, got
+            )
+        })
+    }
+
+    /// The modem lines as the server last reported them, None before it has.
+    pub fn modem_lines(&self) -> Option<ModemLines> {
+        let state = self.modem_state?;
+        Some(ModemLines {
+            cts: state & 0x10 != 0,
+            dsr: state & 0x20 != 0,
+            dcd: state & 0x80 != 0,
+            ri: state & 0x40 != 0,
+        })
+    }
+
+    fn modem_state_changed(&mut self, state: u8) -> Option<String> {
+        let lines = state & MODEM_MASK;
+        if self.modem_state.replace(lines) == Some(lines) {
+            return None;
+        }
+        let states: Vec<String> = MODEM_LINES
+            .iter()
+            .map(|(bit, name)| {
+                let on = if lines & bit != 0 { "on" } else { "off" };
+                format!("{} {}", name, on)
+            })
+            .collect();
+        Some(format!("--- Modem lines: {} ---", states.join(", ")))
+    }
+}
+
+/// The SET-CONTROL value for a DTR/RTS change.
+pub(super) fn control_value(control: LineControl) -> u8 {
+    match control {
+        LineControl::Dtr(true) => DTR_ON,
+        LineControl::Dtr(false) => DTR_OFF,
+        LineControl::Rts(true) => RTS_ON,
+        LineControl::Rts(false) => RTS_OFF,
+        LineControl::Break => BREAK_ON,
+    }
+}
+
+/// A SET-CONTROL request.
+pub(super) fn set_control(value: u8) -> Vec<u8> {
+    command(SET_CONTROL, &[value])
+}
+
+fn command(command: u8, value: &[u8]) -> Vec<u8> {
+    let mut out = vec![IAC, SB, COM_PORT_OPTION, command];
+    out.extend(tcp::escape(value));
+    out.extend([IAC, SE]);
+    out
+}
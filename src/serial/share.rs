@@ -0,0 +1,137 @@
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+
+use super::worker::SerialEvent;
+
+/// How often the accept loop checks whether sharing was stopped.
+const ACCEPT_POLL: Duration = Duration::from_millis(100);
+
+/// A client that can't take received data this fast is dropped rather than
+/// holding up the UI.
+const WRITE_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// An open connection offered on a TCP listener: received bytes are copied
+/// to every client, and what clients send comes back as `ShareInput` to be
+/// written to the port.
+pub struct ShareServer {
+    pub address: SocketAddr,
+    clients: Arc<Mutex<Vec<TcpStream>>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl ShareServer {
+    pub fn start(
+        id: usize,
+        address: &str,
+        serial_tx: mpsc::UnboundedSender<SerialEvent>,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let server = Self {
+            address: listener.local_addr()?,
+            clients: Arc::new(Mutex::new(Vec::new())),
+            stop: Arc::new(AtomicBool::new(false)),
+        };
+        let clients = server.clients.clone();
+        let stop = server.stop.clone();
+        thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, peer)) => {
+                        let added = add_client(id, stream, peer, &clients, &stop, &serial_tx);
+                        if let Err(e) = added {
+                            let text = format!("--- Share client {} failed: {} ---", peer, e);
+                            let _ = serial_tx.send(SerialEvent::Info { id, text });
+                        }
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                        thread::sleep(ACCEPT_POLL);
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+        Ok(server)
+    }
+
+    /// Copy received bytes to the clients, dropping those that fail.
+    pub fn broadcast(&self, data: &[u8]) {
+        if let Ok(mut clients) = self.clients.lock() {
+            clients.retain_mut(|client| {
+                let ok = client.write_all(data).is_ok();
+                if !ok {
+                    let _ = client.shutdown(Shutdown::Both);
+                }
+                ok
+            });
+        }
+    }
+
+    pub fn client_count(&self) -> usize {
+        self.clients.lock().map_or(0, |clients| clients.len())
+    }
+}
+
+impl Drop for ShareServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // Ends the client readers too
+        if let Ok(clients) = self.clients.lock() {
+            for client in clients.iter() {
+                let _ = client.shutdown(Shutdown::Both);
+            }
+        }
+    }
+}
+
+fn add_client(
+    id: usize,
+    stream: TcpStream,
+    peer: SocketAddr,
+    clients: &Arc<Mutex<Vec<TcpStream>>>,
+    stop: &Arc<AtomicBool>,
+    serial_tx: &mpsc::UnboundedSender<SerialEvent>,
+) -> io::Result<()> {
+    // Accepted sockets may inherit the listener's non-blocking mode
+    stream.set_nonblocking(false)?;
+    stream.set_nodelay(true)?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let mut reader = stream.try_clone()?;
+    if let Ok(mut clients) = clients.lock() {
+        clients.push(stream);
+    }
+    let text = format!("--- Share client {} connected ---", peer);
+    let _ = serial_tx.send(SerialEvent::Info { id, text });
+
+    let serial_tx = serial_tx.clone();
+    let stop = stop.clone();
+    thread::spawn(move || {
+        let mut buf = [0u8; 1024];
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    let data = buf[..n].to_vec();
+                    if serial_tx
+                        .send(SerialEvent::ShareInput { id, data })
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+            }
+        }
+        // Clients going away with the server need no mention
+        if !stop.load(Ordering::Relaxed) {
+            let text = format!("--- Share client {} disconnected ---", peer);
+            let _ = serial_tx.send(SerialEvent::Info { id, text });
+        }
+    });
+    Ok(())
+}
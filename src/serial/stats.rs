@@ -0,0 +1,146 @@
+use chrono::{DateTime, Local, SecondsFormat};
+use regex::Regex;
+
+/// Upper bounds (bytes/sec, exclusive) of the throughput histogram buckets;
+/// the last bucket takes everything faster.
+const RATE_BUCKETS: [u64; 5] = [1, 100, 1_000, 10_000, 100_000];
+
+/// Counters kept over a connection's lifetime for test reports.
+pub struct SessionStats {
+    pub started: DateTime<Local>,
+    /// Lines added to the scrollback
+    pub lines: u64,
+    /// Errors reported by the port
+    pub errors: u64,
+    /// Lines matching each `[stats] patterns` regex
+    pub patterns: Vec<(Regex, u64)>,
+    /// Seconds spent at each receive rate, by `RATE_BUCKETS`
+    throughput: [u64; RATE_BUCKETS.len() + 1],
+}
+
+impl SessionStats {
+    pub fn new(patterns: &[Regex]) -> Self {
+        Self {
+            started: Local::now(),
+            lines: 0,
+            errors: 0,
+            patterns: patterns.iter().map(|re| (re.clone(), 0)).collect(),
+            throughput: [0; RATE_BUCKETS.len() + 1],
+        }
+    }
+
+    pub fn count_line(&mut self, line: &str) {
+        self.lines += 1;
+        let text = crate::ansi::strip(line);
+        for (re, count) in &mut self.patterns {
+            if re.is_match(&text) {
+                *count += 1;
+            }
+        }
+    }
+
+    /// Record a receive rate measured over `secs` seconds.
+    pub fn sample_rate(&mut self, rate: f64, secs: f64) {
+        let bucket = RATE_BUCKETS
+            .iter()
+            .position(|&max| rate < max as f64)
+            .unwrap_or(RATE_BUCKETS.len());
+        self.throughput[bucket] += secs.round() as u64;
+    }
+
+    /// Histogram bucket labels (`"100-999"`, `">=100000"`) with their seconds.
+    fn histogram(&self) -> Vec<(String, u64)> {
+        let mut lower = 0;
+        let mut buckets = Vec::new();
+        for (i, &max) in RATE_BUCKETS.iter().enumerate() {
+            let label = if max - lower == 1 {
+                lower.to_string()
+            } else {
+                format!("{}-{}", lower, max - 1)
+            };
+            buckets.push((label, self.throughput[i]));
+            lower = max;
+        }
+        buckets.push((format!(">={}", lower), self.throughput[RATE_BUCKETS.len()]));
+        buckets
+    }
+}
+
+/// What a stats report describes besides the counters themselves.
+pub struct StatsReport<'a> {
+    pub connection: String,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub stats: &'a SessionStats,
+}
+
+impl StatsReport<'_> {
+    pub fn to_json(&self) -> String {
+        let ended = Local::now();
+        let patterns: serde_json::Map<String, serde_json::Value> = self
+            .stats
+            .patterns
+            .iter()
+            .map(|(re, count)| (re.as_str().to_string(), (*count).into()))
+            .collect();
+        let throughput: serde_json::Map<String, serde_json::Value> = self
+            .stats
+            .histogram()
+            .into_iter()
+            .map(|(label, secs)| (label, secs.into()))
+            .collect();
+        let report = serde_json::json!({
+            "connection": self.connection,
+            "started": timestamp(self.stats.started),
+            "ended": timestamp(ended),
+            "duration_secs": (ended - self.stats.started).num_seconds(),
+            "rx_bytes": self.rx_bytes,
+            "tx_bytes": self.tx_bytes,
+            "lines": self.stats.lines,
+            "errors": self.stats.errors,
+            "patterns": patterns,
+            "throughput_secs_by_rx_bytes_per_sec": throughput,
+        });
+        serde_json::to_string_pretty(&report).unwrap_or_default()
+    }
+
+    /// `metric,value` rows; patterns and histogram buckets get a prefix.
+    pub fn to_csv(&self) -> String {
+        let ended = Local::now();
+        let mut rows = vec![
+            ("metric".to_string(), "value".to_string()),
+            ("connection".into(), self.connection.clone()),
+            ("started".into(), timestamp(self.stats.started)),
+            ("ended".into(), timestamp(ended)),
+            (
+                "duration_secs".into(),
+                (ended - self.stats.started).num_seconds().to_string(),
+            ),
+            ("rx_bytes".into(), self.rx_bytes.to_string()),
+            ("tx_bytes".into(), self.tx_bytes.to_string()),
+            ("lines".into(), self.stats.lines.to_string()),
+            ("errors".into(), self.stats.errors.to_string()),
+        ];
+        for (re, count) in &self.stats.patterns {
+            rows.push((format!("pattern:{}", re.as_str()), count.to_string()));
+        }
+        for (label, secs) in self.stats.histogram() {
+            rows.push((format!("throughput_secs:{} B/s", label), secs.to_string()));
+        }
+        rows.iter()
+            .map(|(k, v)| format!("{},{}\n", csv_field(k), csv_field(v)))
+            .collect()
+    }
+}
+
+fn timestamp(at: DateTime<Local>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Millis, false)
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
@@ -0,0 +1,413 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::NaiveDate;
+
+use tokio::sync::mpsc;
+
+use flate2::read::MultiGzDecoder;
+
+use super::logfile::{is_gzip, Durability, LogFile};
+use super::worker::{SerialEvent, WorkerRequest};
+
+// Live stream format: one frame per line, `<timestamp> <RX|TX> <escaped bytes>`.
+// Printable ASCII is written as-is, everything else as `\xNN` (plus `\\`), so the
+// file stays readable with `tail -f` and can be followed by another instance.
+const HEADER: &str = "#serialtui-stream v1";
+
+/// Frame timestamps, local time with its UTC offset so replays measure the
+/// gaps right across a daylight saving change.
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3f%:z";
+
+/// Frame timestamps of streams written before the offset was recorded.
+const LOCAL_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3f";
+
+/// When a live stream moves on to a new file.
+#[derive(Clone, Copy, Default)]
+pub struct Rotation {
+    /// Start the next file once the current one has reached this size;
+    /// the files are numbered `NAME_1.EXT`, `NAME_2.EXT`, ...
+    pub max_bytes: Option<u64>,
+    /// One file per day, named `NAME_YYYY-MM-DD.EXT`
+    pub daily: bool,
+}
+
+pub struct LiveStream {
+    /// The file being written; changes when the stream rotates
+    pub path: String,
+    file: LogFile,
+    // The name the stream was started with, which rotated names are made from
+    base: String,
+    header: String,
+    durability: Durability,
+    rotation: Rotation,
+    // Bytes in the current file, and the day and number it was opened for
+    size: u64,
+    date: NaiveDate,
+    part: u32,
+}
+
+impl LiveStream {
+    pub fn create(
+        base: &str,
+        port_name: &str,
+        baud_rate: u32,
+        durability: Durability,
+        rotation: Rotation,
+    ) -> std::io::Result<Self> {
+        let date = chrono::Local::now().date_naive();
+        let path = rotated_path(base, rotation.daily.then_some(date), 0);
+        let mut stream = Self {
+            file: LogFile::append(&path, durability)?,
+            path,
+            base: base.to_string(),
+            header: format!("{} {} {}\n", HEADER, port_name, baud_rate),
+            durability,
+            rotation,
+            size: 0,
+            date,
+            part: 0,
+        };
+        stream.start_file()?;
+        Ok(stream)
+    }
+
+    pub fn write_frame(&mut self, direction: &str, data: &[u8]) -> std::io::Result<()> {
+        let now = chrono::Local::now();
+        if self.rotation.daily && now.date_naive() != self.date {
+            self.date = now.date_naive();
+            self.rotate(0)?;
+        } else if self.rotation.max_bytes.is_some_and(|max| self.size >= max) {
+            self.rotate(self.part + 1)?;
+        }
+        let timestamp = now.format(TIME_FORMAT);
+        let line = format!("{} {} {}\n", timestamp, direction, escape(data));
+        self.file.write(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Continue in file number `part` of the current day, or a later one if
+    /// an earlier run already filled it.
+    fn rotate(&mut self, mut part: u32) -> std::io::Result<()> {
+        let date = self.rotation.daily.then_some(self.date);
+        let mut path = rotated_path(&self.base, date, part);
+        while let Some(max) = self.rotation.max_bytes {
+            if file_size(&path) < max {
+                break;
+            }
+            part += 1;
+            path = rotated_path(&self.base, date, part);
+        }
+        // Replacing the old file flushes and syncs it
+        self.file = LogFile::append(&path, self.durability)?;
+        self.path = path;
+        self.part = part;
+        self.start_file()
+    }
+
+    /// Write the header to a newly opened file, so each one can be followed
+    /// on its own.
+    fn start_file(&mut self) -> std::io::Result<()> {
+        self.size = file_size(&self.path);
+        self.file.write(self.header.as_bytes())?;
+        self.size += self.header.len() as u64;
+        Ok(())
+    }
+
+    /// Fsync frames that have waited out the durability interval.
+    pub fn sync_due(&mut self) -> std::io::Result<()> {
+        self.file.sync_due()
+    }
+}
+
+/// Bytes in the file at `path` before compression, 0 if there is none. A
+/// `.gz` file is read through to count them, up to any damaged end.
+fn file_size(path: &str) -> u64 {
+    if !is_gzip(path) {
+        return std::fs::metadata(path).map_or(0, |m| m.len());
+    }
+    let Ok(file) = File::open(path) else {
+        return 0;
+    };
+    let mut decoder = MultiGzDecoder::new(BufReader::new(file));
+    let mut buf = [0u8; 8192];
+    let mut size = 0;
+    while let Ok(n @ 1..) = decoder.read(&mut buf) {
+        size += n as u64;
+    }
+    size
+}
+
+/// `base` with the date and part number of a rotated file added before its
+/// extension (and before `.gz`); part 0 has no number.
+fn rotated_path(base: &str, date: Option<NaiveDate>, part: u32) -> String {
+    if let Some(base) = base.strip_suffix(".gz") {
+        return format!("{}.gz", rotated_path(base, date, part));
+    }
+    let path = Path::new(base);
+    let Some(stem) = path.file_stem() else {
+        return base.to_string();
+    };
+    let mut name = stem.to_string_lossy().into_owned();
+    if let Some(date) = date {
+        name.push_str(&date.format("_%Y-%m-%d").to_string());
+    }
+    if part > 0 {
+        name.push_str(&format!("_{}", part));
+    }
+    if let Some(ext) = path.extension() {
+        name.push('.');
+        name.push_str(&ext.to_string_lossy());
+    }
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
+
+/// Bytes as text, with `\\` and anything unprintable as `\xNN`.
+pub fn escape(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len());
+    for &b in data {
+        match b {
+            b'\\' => out.push_str("\\\\"),
+            b' '..=b'~' => out.push(b as char),
+            _ => out.push_str(&format!("\\x{:02X}", b)),
+        }
+    }
+    out
+}
+
+/// The reverse of `escape()`, also taking `\r`, `\n` and `\t`. Anything
+/// else after a backslash is kept as typed.
+pub fn unescape(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && i + 1 < bytes.len() {
+            let simple = match bytes[i + 1] {
+                b'\\' => Some(b'\\'),
+                b'r' => Some(b'\r'),
+                b'n' => Some(b'\n'),
+                b't' => Some(b'\t'),
+                _ => None,
+            };
+            if let Some(b) = simple {
+                out.push(b);
+                i += 2;
+                continue;
+            }
+            if bytes[i + 1] == b'x' && i + 3 < bytes.len() {
+                let hex = std::str::from_utf8(&bytes[i + 2..i + 4]).ok();
+                if let Some(b) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                    out.push(b);
+                    i += 4;
+                    continue;
+                }
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    out
+}
+
+/// How often a followed file is checked for new frames once it's caught up.
+const FOLLOW_POLL: Duration = Duration::from_millis(100);
+
+/// Tail a live stream file, forwarding its RX frames as if they came from a port.
+/// Writes are discarded; dropping `write_rx`'s sender stops the task.
+pub async fn follow_task(
+    id: usize,
+    path: String,
+    serial_tx: mpsc::UnboundedSender<SerialEvent>,
+    mut write_rx: mpsc::UnboundedReceiver<WorkerRequest>,
+) {
+    // Compressed frames can't be read while they're being written
+    if is_gzip(&path) {
+        let _ = serial_tx.send(SerialEvent::Error {
+            id,
+            err: "a compressed stream can't be followed; replay it instead".into(),
+        });
+        return;
+    }
+    let file = match File::open(&path) {
+        Ok(f) => f,
+        Err(e) => {
+            let _ = serial_tx.send(SerialEvent::Error {
+                id,
+                err: e.to_string(),
+            });
+            return;
+        }
+    };
+    let mut reader = BufReader::new(file);
+    let mut line = String::new();
+
+    loop {
+        if let Err(mpsc::error::TryRecvError::Disconnected) = write_rx.try_recv() {
+            break;
+        }
+
+        match reader.read_line(&mut line) {
+            Ok(0) => tokio::time::sleep(FOLLOW_POLL).await,
+            // Keep accumulating until the writer has finished the line
+            Ok(_) if !line.ends_with('\n') => {}
+            Ok(_) => {
+                let frame = line.trim_end_matches(['\r', '\n']);
+                if !frame.starts_with('#') {
+                    let mut parts = frame.splitn(3, ' ');
+                    if let (Some(_), Some("RX"), Some(payload)) =
+                        (parts.next(), parts.next(), parts.next())
+                    {
+                        let _ = serial_tx.send(SerialEvent::Data {
+                            id,
+                            data: unescape(payload),
+                            time: std::time::Instant::now(),
+                        });
+                    }
+                }
+                line.clear();
+            }
+            Err(e) => {
+                let _ = serial_tx.send(SerialEvent::Error {
+                    id,
+                    err: e.to_string(),
+                });
+                break;
+            }
+        }
+    }
+
+    let _ = serial_tx.send(SerialEvent::Disconnected { id });
+}
+
+/// When a frame was recorded, in UTC; a stamp without an offset is taken as
+/// it is.
+fn frame_time(stamp: &str) -> Option<chrono::NaiveDateTime> {
+    match chrono::DateTime::parse_from_str(stamp, TIME_FORMAT) {
+        Ok(time) => Some(time.naive_utc()),
+        Err(_) => chrono::NaiveDateTime::parse_from_str(stamp, LOCAL_TIME_FORMAT).ok(),
+    }
+}
+
+/// Play a recorded live stream file back, forwarding its RX frames with the
+/// gaps they were recorded with, shortened `speed` times. Notes come back as
+/// scrollback lines. Dropping `write_rx`'s sender stops the playback.
+pub async fn replay_task(
+    id: usize,
+    path: String,
+    speed: u32,
+    serial_tx: mpsc::UnboundedSender<SerialEvent>,
+    mut write_rx: mpsc::UnboundedReceiver<WorkerRequest>,
+) {
+    let file = match File::open(&path) {
+        Ok(f) => f,
+        Err(e) => {
+            let _ = serial_tx.send(SerialEvent::Error {
+                id,
+                err: e.to_string(),
+            });
+            return;
+        }
+    };
+    let mut last: Option<chrono::NaiveDateTime> = None;
+    // A `.gz` stream has a gzip member per time it was opened for writing
+    let reader: Box<dyn BufRead + Send> = match is_gzip(&path) {
+        true => Box::new(BufReader::new(MultiGzDecoder::new(file))),
+        false => Box::new(BufReader::new(file)),
+    };
+
+    for line in reader.lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                let _ = serial_tx.send(SerialEvent::Error {
+                    id,
+                    err: e.to_string(),
+                });
+                break;
+            }
+        };
+        if line.starts_with('#') {
+            continue;
+        }
+        let mut parts = line.splitn(3, ' ');
+        let (Some(stamp), Some(direction)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        let payload = parts.next().unwrap_or("");
+        if direction != "RX" && direction != "NOTE" {
+            continue;
+        }
+
+        // Wait out the recorded gap, discarding writes meanwhile
+        let time = frame_time(stamp);
+        if let (Some(prev), Some(time)) = (last, time) {
+            let gap = (time - prev).to_std().unwrap_or_default() / speed.max(1);
+            let sleep = tokio::time::sleep(gap);
+            tokio::pin!(sleep);
+            loop {
+                tokio::select! {
+                    _ = &mut sleep => break,
+                    request = write_rx.recv() => {
+                        if request.is_none() {
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+        if time.is_some() {
+            last = time;
+        }
+
+        let event = if direction == "RX" {
+            SerialEvent::Data {
+                id,
+                data: unescape(payload),
+                time: std::time::Instant::now(),
+            }
+        } else {
+            let clock = stamp.split_once('T').map_or(stamp, |(_, clock)| clock);
+            let text = match String::from_utf8_lossy(&unescape(payload)) {
+                note if note.is_empty() => format!("=== {} ===", clock),
+                note => format!("=== {} {} ===", clock, note),
+            };
+            SerialEvent::Info { id, text }
+        };
+        let _ = serial_tx.send(event);
+    }
+
+    let _ = serial_tx.send(SerialEvent::Info {
+        id,
+        text: "--- Replay finished ---".to_string(),
+    });
+    let _ = serial_tx.send(SerialEvent::Disconnected { id });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unescape_turns_escapes_into_bytes() {
+        assert_eq!(unescape(r"a\r\n\tb\\c"), b"a\r\n\tb\\c");
+        assert_eq!(unescape(r"\xFF\x1b\x00"), [0xFF, 0x1B, 0x00]);
+    }
+
+    #[test]
+    fn unescape_keeps_unfinished_escapes_as_typed() {
+        assert_eq!(unescape(r"\x1"), b"\\x1");
+        assert_eq!(unescape(r"\xZZ"), b"\\xZZ");
+        assert_eq!(unescape(r"\q"), b"\\q");
+        assert_eq!(unescape("\\"), b"\\");
+    }
+
+    #[test]
+    fn escape_round_trips() {
+        let data: Vec<u8> = (0..=255).collect();
+        assert_eq!(unescape(&escape(&data)), data);
+    }
+}
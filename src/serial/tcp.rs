@@ -0,0 +1,431 @@
+use std::io;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+
+use super::pacing::Pacing;
+use super::rfc2217::{self, ComPort, LineSettings};
+use super::worker::{
+    self, LineControl, ModemLines, Port, ReadSettings, SerialEvent, WorkerRequest,
+};
+
+/// How long to wait for a terminal server to accept the connection.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How a network port talks to its server.
+#[derive(Clone, Copy, PartialEq)]
+pub enum NetProtocol {
+    /// Bytes as they are
+    Raw,
+    /// Telnet option negotiation and IAC escaping
+    Telnet,
+    /// Telnet plus remote control of the serial line (RFC 2217)
+    Rfc2217,
+}
+
+const SCHEMES: [(&str, NetProtocol); 3] = [
+    ("tcp://", NetProtocol::Raw),
+    ("telnet://", NetProtocol::Telnet),
+    ("rfc2217://", NetProtocol::Rfc2217),
+];
+
+/// A network port name: `tcp://host:port` for a raw socket,
+/// `telnet://host:port` for a server that negotiates telnet options, or
+/// `rfc2217://host:port` for a remote serial port.
+pub struct NetAddress<'a> {
+    pub host_port: &'a str,
+    pub protocol: NetProtocol,
+}
+
+impl<'a> NetAddress<'a> {
+    pub fn parse(name: &'a str) -> Option<Self> {
+        SCHEMES.iter().find_map(|&(scheme, protocol)| {
+            name.strip_prefix(scheme).map(|host_port| Self {
+                host_port,
+                protocol,
+            })
+        })
+    }
+
+    /// Baud rate, parity etc. mean something: the server drives a real
+    /// serial port for us.
+    pub fn has_line_settings(&self) -> bool {
+        self.protocol == NetProtocol::Rfc2217
+    }
+}
+
+/// Turn what was typed into the wizard (`host:port`, optionally with a
+/// `tcp://`, `telnet://` or `rfc2217://` scheme) into a network port name.
+pub fn network_port_name(input: &str) -> Result<String, String> {
+    let input = input.trim();
+    let (scheme, host_port) = match NetAddress::parse(input) {
+        Some(addr) => (&input[..input.len() - addr.host_port.len()], addr.host_port),
+        None => (SCHEMES[0].0, input),
+    };
+    let Some((host, port)) = host_port.rsplit_once(':') else {
+        return Err(format!("{:?} needs a port, as in host:port", host_port));
+    };
+    if host.is_empty() {
+        return Err("Missing host name".into());
+    }
+    if port.parse::<u16>().map_or(true, |p| p == 0) {
+        return Err(format!("Invalid TCP port {:?}", port));
+    }
+    Ok(format!("{}{}", scheme, host_port))
+}
+
+/// Worker for a connection to a terminal server; the counterpart of
+/// `worker::connection_task` for network ports. `line` is only used with
+/// RFC 2217.
+#[allow(clippy::too_many_arguments)]
+pub async fn connection_task(
+    id: usize,
+    address: NetAddress<'_>,
+    line: LineSettings,
+    pacing: Pacing,
+    read: ReadSettings,
+    pre_open: Option<(String, Vec<(String, String)>)>,
+    serial_tx: mpsc::UnboundedSender<SerialEvent>,
+    mut write_rx: mpsc::UnboundedReceiver<WorkerRequest>,
+) {
+    if !worker::run_pre_open(id, pre_open, &serial_tx).await {
+        return;
+    }
+
+    let mut stream = match NetStream::connect(id, &address, line, serial_tx.clone()).await {
+        Ok(s) => s,
+        Err(e) => {
+            let _ = serial_tx.send(SerialEvent::Error {
+                id,
+                err: format!("{}: {}", address.host_port, e),
+            });
+            return;
+        }
+    };
+
+    worker::serve(id, &mut stream, pacing, read, &serial_tx, &mut write_rx).await;
+    let _ = serial_tx.send(SerialEvent::Disconnected { id });
+}
+
+/// A TCP connection that reads and writes like a serial port: the remote
+/// closing is an error, and telnet commands are handled here so only data
+/// reaches the caller.
+struct NetStream {
+    stream: TcpStream,
+    telnet: Option<Telnet>,
+    com_port: Option<ComPort>,
+    // Telnet answers not sent yet; they go out before the next read so that
+    // reading stays cancel safe
+    reply: Vec<u8>,
+    // For reporting what an RFC 2217 server says about the line
+    id: usize,
+    serial_tx: mpsc::UnboundedSender<SerialEvent>,
+}
+
+impl NetStream {
+    async fn connect(
+        id: usize,
+        address: &NetAddress<'_>,
+        line: LineSettings,
+        serial_tx: mpsc::UnboundedSender<SerialEvent>,
+    ) -> io::Result<Self> {
+        let mut last_err = io::Error::new(io::ErrorKind::NotFound, "host not found");
+        for addr in tokio::net::lookup_host(address.host_port).await? {
+            let connected = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(addr))
+                .await
+                .unwrap_or_else(|_| Err(io::ErrorKind::TimedOut.into()));
+            match connected {
+                Ok(stream) => {
+                    // Keystrokes go out as typed rather than being coalesced
+                    stream.set_nodelay(true)?;
+                    let mut net = Self {
+                        stream,
+                        telnet: (address.protocol != NetProtocol::Raw).then(Telnet::new),
+                        com_port: None,
+                        reply: Vec::new(),
+                        id,
+                        serial_tx,
+                    };
+                    if address.protocol == NetProtocol::Rfc2217 {
+                        // The line is configured once the server agrees
+                        if let Some(telnet) = &mut net.telnet {
+                            net.stream
+                                .write_all(&telnet.offer(rfc2217::COM_PORT_OPTION))
+                                .await?;
+                        }
+                        net.com_port = Some(ComPort::new(line));
+                    }
+                    return Ok(net);
+                }
+                Err(e) => last_err = e,
+            }
+        }
+        Err(last_err)
+    }
+
+    /// Act on what the server said about the serial line since the last read.
+    fn handle_com_port(&mut self) {
+        let (Some(telnet), Some(com_port)) = (&mut self.telnet, &mut self.com_port) else {
+            return;
+        };
+        if telnet.agreed.contains(&rfc2217::COM_PORT_OPTION) {
+            self.reply.extend(com_port.configure());
+        }
+        telnet.agreed.clear();
+        for payload in telnet.subnegotiations.drain(..) {
+            if let Some(text) = com_port.handle(&payload) {
+                let _ = self.serial_tx.send(SerialEvent::Info { id: self.id, text });
+            }
+        }
+    }
+}
+
+impl Port for NetStream {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            // Each write is kept track of, so a cancelled read loses nothing
+            while !self.reply.is_empty() {
+                let n = self.stream.write(&self.reply).await?;
+                if n == 0 {
+                    return Err(io::ErrorKind::WriteZero.into());
+                }
+                self.reply.drain(..n);
+            }
+            let n = self.stream.read(buf).await?;
+            if n == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::ConnectionAborted,
+                    "connection closed by the remote host",
+                ));
+            }
+            let Some(telnet) = &mut self.telnet else {
+                return Ok(n);
+            };
+            let (len, reply) = telnet.receive(&mut buf[..n]);
+            self.reply.extend(reply);
+            self.handle_com_port();
+            // Nothing but telnet commands: answer them and read on
+            if len > 0 {
+                return Ok(len);
+            }
+        }
+    }
+
+    async fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+        if self.telnet.is_none() || !data.contains(&IAC) {
+            return self.stream.write_all(data).await;
+        }
+        self.stream.write_all(&escape(data)).await
+    }
+
+    async fn control(&mut self, control: LineControl) -> io::Result<()> {
+        if self.com_port.is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "no modem lines on a plain TCP/telnet connection (use rfc2217://)",
+            ));
+        }
+        if let LineControl::Break = control {
+            self.stream
+                .write_all(&rfc2217::set_control(rfc2217::BREAK_ON))
+                .await?;
+            tokio::time::sleep(worker::BREAK_DURATION).await;
+            return self
+                .stream
+                .write_all(&rfc2217::set_control(rfc2217::BREAK_OFF))
+                .await;
+        }
+        self.stream
+            .write_all(&rfc2217::set_control(rfc2217::control_value(control)))
+            .await
+    }
+
+    async fn set_baud_rate(&mut self, baud_rate: u32) -> io::Result<()> {
+        let Some(com_port) = &mut self.com_port else {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "no baud rate on a plain TCP/telnet connection (use rfc2217://)",
+            ));
+        };
+        let request = com_port.set_baud_rate(baud_rate);
+        self.stream.write_all(&request).await
+    }
+
+    async fn set_line_settings(&mut self, line: LineSettings) -> io::Result<()> {
+        let Some(com_port) = &mut self.com_port else {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "no line settings on a plain TCP/telnet connection (use rfc2217://)",
+            ));
+        };
+        let request = com_port.set_line_settings(line);
+        self.stream.write_all(&request).await
+    }
+
+    // What the socket has taken is out of reach; the far end's queue is
+    // unknown
+    async fn drain(&mut self) -> io::Result<()> {
+        self.stream.flush().await
+    }
+
+    fn modem_lines(&mut self) -> Option<ModemLines> {
+        self.com_port.as_ref()?.modem_lines()
+    }
+}
+
+/// Double 0xFF data bytes so they aren't taken for telnet commands.
+pub(super) fn escape(data: &[u8]) -> Vec<u8> {
+    let mut escaped = Vec::with_capacity(data.len() + 1);
+    for &byte in data {
+        escaped.push(byte);
+        if byte == IAC {
+            escaped.push(IAC);
+        }
+    }
+    escaped
+}
+
+pub(super) const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+pub(super) const SB: u8 = 250;
+pub(super) const SE: u8 = 240;
+
+const BINARY: u8 = 0;
+const ECHO: u8 = 1;
+const SUPPRESS_GO_AHEAD: u8 = 3;
+
+#[derive(Clone, Copy)]
+enum TelnetState {
+    Data,
+    Iac,
+    // DO/DONT/WILL/WONT, waiting for the option byte
+    Option(u8),
+    Subnegotiation,
+    SubnegotiationIac,
+}
+
+/// Telnet option negotiation (RFC 854). We agree to an 8-bit clean line
+/// without go-aheads and let the server echo; everything else is refused
+/// unless we offered it ourselves.
+struct Telnet {
+    state: TelnetState,
+    // Options in effect on our side and on the server's
+    local: [bool; 256],
+    remote: [bool; 256],
+    // Options we offered with WILL and haven't had an answer to
+    offered: [bool; 256],
+    /// Offered options the server agreed to, since last cleared
+    agreed: Vec<u8>,
+    // Subnegotiation being received, and the finished ones (option byte first)
+    sub: Vec<u8>,
+    subnegotiations: Vec<Vec<u8>>,
+}
+
+impl Telnet {
+    fn new() -> Self {
+        Self {
+            state: TelnetState::Data,
+            local: [false; 256],
+            remote: [false; 256],
+            offered: [false; 256],
+            agreed: Vec::new(),
+            sub: Vec::new(),
+            subnegotiations: Vec::new(),
+        }
+    }
+
+    /// Offer to enable `option` on our side; returns the request to send.
+    fn offer(&mut self, option: u8) -> Vec<u8> {
+        self.offered[option as usize] = true;
+        vec![IAC, WILL, option]
+    }
+
+    /// Strip telnet commands from `buf` in place. Returns the length of the
+    /// data left and the replies to send back.
+    fn receive(&mut self, buf: &mut [u8]) -> (usize, Vec<u8>) {
+        let mut len = 0;
+        let mut reply = Vec::new();
+        for i in 0..buf.len() {
+            let byte = buf[i];
+            self.state = match (self.state, byte) {
+                (TelnetState::Data, IAC) => TelnetState::Iac,
+                (TelnetState::Data, _) => {
+                    buf[len] = byte;
+                    len += 1;
+                    TelnetState::Data
+                }
+                // Escaped 0xFF data byte
+                (TelnetState::Iac, IAC) => {
+                    buf[len] = IAC;
+                    len += 1;
+                    TelnetState::Data
+                }
+                (TelnetState::Iac, DO | DONT | WILL | WONT) => TelnetState::Option(byte),
+                (TelnetState::Iac, SB) => {
+                    self.sub.clear();
+                    TelnetState::Subnegotiation
+                }
+                // NOP, go-ahead and the like carry nothing for us
+                (TelnetState::Iac, _) => TelnetState::Data,
+                (TelnetState::Option(verb), option) => {
+                    self.negotiate(verb, option, &mut reply);
+                    TelnetState::Data
+                }
+                (TelnetState::Subnegotiation, IAC) => TelnetState::SubnegotiationIac,
+                (TelnetState::Subnegotiation, _) => {
+                    self.sub.push(byte);
+                    TelnetState::Subnegotiation
+                }
+                (TelnetState::SubnegotiationIac, SE) => {
+                    self.subnegotiations.push(std::mem::take(&mut self.sub));
+                    TelnetState::Data
+                }
+                (TelnetState::SubnegotiationIac, _) => {
+                    self.sub.push(byte);
+                    TelnetState::Subnegotiation
+                }
+            };
+        }
+        (len, reply)
+    }
+
+    /// Answer a request, acknowledging only actual changes so neither side
+    /// loops (RFC 854, "option negotiation").
+    fn negotiate(&mut self, verb: u8, option: u8, reply: &mut Vec<u8>) {
+        let i = option as usize;
+        // An answer to our own offer needs no reply
+        let offered = std::mem::take(&mut self.offered[i]) && matches!(verb, DO | DONT);
+        // DO/DONT are about our side, WILL/WONT about the server's
+        let (enabled, supported, accept, refuse) = match verb {
+            DO | DONT => (
+                &mut self.local[i],
+                matches!(option, BINARY | SUPPRESS_GO_AHEAD),
+                WILL,
+                WONT,
+            ),
+            _ => (
+                &mut self.remote[i],
+                matches!(option, BINARY | ECHO | SUPPRESS_GO_AHEAD),
+                DO,
+                DONT,
+            ),
+        };
+        let wanted = matches!(verb, DO | WILL);
+        if offered {
+            *enabled = wanted;
+            if wanted {
+                self.agreed.push(option);
+            }
+        } else if wanted && !supported {
+            reply.extend([IAC, refuse, option]);
+        } else if wanted != *enabled {
+            *enabled = wanted;
+            reply.extend([IAC, if wanted { accept } else { refuse }, option]);
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc;
+
+use super::worker::SerialEvent;
+use super::{xmodem, zmodem};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Protocol {
+    Xmodem,
+    Ymodem,
+    Zmodem,
+}
+
+impl Protocol {
+    pub const ALL: [Protocol; 3] = [Protocol::Xmodem, Protocol::Ymodem, Protocol::Zmodem];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Protocol::Xmodem => "XMODEM",
+            Protocol::Ymodem => "YMODEM",
+            Protocol::Zmodem => "ZMODEM",
+        }
+    }
+
+    /// YMODEM and ZMODEM carry file names, so downloads go into a folder.
+    pub fn receives_into_folder(self) -> bool {
+        self != Protocol::Xmodem
+    }
+}
+
+pub enum TransferKind {
+    Send {
+        name: String,
+        data: Vec<u8>,
+    },
+    /// Receive into this file (XMODEM) or folder (YMODEM/ZMODEM)
+    Receive(String),
+}
+
+/// A transfer handed to the connection's worker, which runs it with exclusive
+/// use of the port. Setting `cancel` aborts it.
+pub struct Transfer {
+    pub protocol: Protocol,
+    pub kind: TransferKind,
+    pub cancel: Arc<AtomicBool>,
+}
+
+/// A file that arrived through a batch protocol.
+pub struct ReceivedFile {
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+/// Run a transfer to completion on the worker thread, reporting progress.
+pub fn run<P: Read + Write>(
+    id: usize,
+    port: &mut P,
+    transfer: Transfer,
+    serial_tx: &mpsc::UnboundedSender<SerialEvent>,
+) -> Result<String, String> {
+    let cancel = &transfer.cancel;
+    let name = transfer.protocol.name();
+    let progress = |bytes: usize, total: Option<usize>| {
+        let _ = serial_tx.send(SerialEvent::Progress { id, bytes, total });
+    };
+
+    match transfer.kind {
+        TransferKind::Send { name: file, data } => {
+            let result = match transfer.protocol {
+                Protocol::Xmodem => xmodem::send(port, &data, cancel, progress),
+                Protocol::Ymodem => xmodem::ymodem_send(port, &file, &data, cancel, progress),
+                Protocol::Zmodem => zmodem::send(port, &file, &data, cancel, progress),
+            };
+            result.map_err(|e| e.to_string())?;
+            Ok(format!("{}: sent {} ({} bytes)", name, file, data.len()))
+        }
+        TransferKind::Receive(path) => {
+            if transfer.protocol == Protocol::Xmodem {
+                let data = xmodem::receive(port, cancel, progress).map_err(|e| e.to_string())?;
+                std::fs::write(&path, &data).map_err(|e| format!("writing {}: {}", path, e))?;
+                return Ok(format!(
+                    "{}: received {} bytes into {}",
+                    name,
+                    data.len(),
+                    path
+                ));
+            }
+            let files = match transfer.protocol {
+                Protocol::Ymodem => xmodem::ymodem_receive(port, cancel, progress),
+                _ => zmodem::receive(port, cancel, progress),
+            }
+            .map_err(|e| e.to_string())?;
+            let mut names = Vec::new();
+            for file in &files {
+                // Only keep the final path component; never write outside the folder
+                let Some(file_name) = Path::new(&file.name).file_name() else {
+                    continue;
+                };
+                let target = Path::new(&path).join(file_name);
+                std::fs::write(&target, &file.data)
+                    .map_err(|e| format!("writing {}: {}", target.display(), e))?;
+                names.push(file_name.to_string_lossy().into_owned());
+            }
+            Ok(format!(
+                "{}: received {} file(s) into {}: {}",
+                name,
+                names.len(),
+                path,
+                names.join(", ")
+            ))
+        }
+    }
+}
+
+pub(super) fn cancelled() -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, "cancelled")
+}
+
+/// Read one byte, or None after `timeout`. Relies on the port's own short read
+/// timeout so cancellation is noticed promptly; a cancel tells the peer too.
+pub(super) fn read_byte<P: Read + Write>(
+    port: &mut P,
+    timeout: Duration,
+    cancel: &AtomicBool,
+) -> io::Result<Option<u8>> {
+    let deadline = Instant::now() + timeout;
+    let mut byte = [0u8; 1];
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            let _ = port.write_all(&[0x18; 8]);
+            return Err(cancelled());
+        }
+        match port.read(&mut byte) {
+            Ok(1) => return Ok(Some(byte[0])),
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => {}
+            Err(e) => return Err(e),
+        }
+        if Instant::now() >= deadline {
+            return Ok(None);
+        }
+    }
+}
+
+/// Like `Write::write_all`, but rides out the port's short write timeout while
+/// the kernel buffer drains at the line rate.
+pub(super) fn write_all<P: Write>(
+    port: &mut P,
+    mut data: &[u8],
+    cancel: &AtomicBool,
+) -> io::Result<()> {
+    while !data.is_empty() {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(cancelled());
+        }
+        match port.write(data) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(n) => data = &data[n..],
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// CRC-16/XMODEM (poly 0x1021, initial value 0).
+pub(super) fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for &b in data {
+        crc ^= (b as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+    use std::thread;
+
+    /// One end of an in-memory serial line. Reads time out like a real port
+    /// when nothing arrives, and writes after the far end hung up are lost.
+    pub(crate) struct Loopback {
+        rx: Receiver<u8>,
+        tx: Sender<u8>,
+    }
+
+    impl Read for Loopback {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let first = match self.rx.recv_timeout(Duration::from_millis(5)) {
+                Ok(b) => b,
+                Err(RecvTimeoutError::Timeout) => return Err(io::ErrorKind::TimedOut.into()),
+                Err(RecvTimeoutError::Disconnected) => return Ok(0),
+            };
+            buf[0] = first;
+            let mut n = 1;
+            while n < buf.len() {
+                let Ok(b) = self.rx.try_recv() else { break };
+                buf[n] = b;
+                n += 1;
+            }
+            Ok(n)
+        }
+    }
+
+    impl Write for Loopback {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            for &b in buf {
+                let _ = self.tx.send(b);
+            }
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Run `sender` on its own thread against `receiver`, connected by a
+    /// loopback line, and return what both ends came back with.
+    pub(crate) fn exchange<S, R>(
+        sender: impl FnOnce(&mut Loopback) -> S + Send + 'static,
+        receiver: impl FnOnce(&mut Loopback) -> R,
+    ) -> (S, R)
+    where
+        S: Send + 'static,
+    {
+        let (a_tx, b_rx) = channel();
+        let (b_tx, a_rx) = channel();
+        let mut a = Loopback { rx: a_rx, tx: a_tx };
+        let mut b = Loopback { rx: b_rx, tx: b_tx };
+        let handle = thread::spawn(move || sender(&mut a));
+        let received = receiver(&mut b);
+        drop(b);
+        (handle.join().unwrap(), received)
+    }
+
+    #[test]
+    fn crc16_matches_the_xmodem_check_value() {
+        assert_eq!(crc16(b"123456789"), 0x31C3);
+        assert_eq!(crc16(&[]), 0);
+    }
+
+    #[test]
+    fn batch_downloads_stay_inside_the_folder() {
+        let base = std::env::temp_dir().join(format!("serialtui-ymodem-{}", std::process::id()));
+        let folder = base.join("downloads");
+        std::fs::create_dir_all(&folder).unwrap();
+
+        let data: Vec<u8> = (0..=255).cycle().take(1500).collect();
+        let sent = data.clone();
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let (sent_ok, result) = exchange(
+            move |port| {
+                let cancel = AtomicBool::new(false);
+                xmodem::ymodem_send(port, "../escape.bin", &sent, &cancel, |_, _| {}).is_ok()
+            },
+            |port| {
+                let transfer = Transfer {
+                    protocol: Protocol::Ymodem,
+                    kind: TransferKind::Receive(folder.to_string_lossy().into_owned()),
+                    cancel: Arc::new(AtomicBool::new(false)),
+                };
+                run(0, port, transfer, &tx)
+            },
+        );
+
+        let written = std::fs::read(folder.join("escape.bin"));
+        let escaped = base.join("escape.bin").exists();
+        let _ = std::fs::remove_dir_all(&base);
+        assert!(sent_ok);
+        assert!(result.unwrap().ends_with(": escape.bin"));
+        assert_eq!(written.unwrap(), data);
+        assert!(!escaped);
+    }
+}
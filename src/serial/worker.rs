@@ -1,29 +1,282 @@
-use std::io::Read;
-use std::sync::mpsc;
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
 use std::time::Duration;
 
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::runtime::Handle;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tokio_serial::SerialPortBuilderExt;
+
+use super::linktest::{self, LinkTest};
+use super::pacing::Pacing;
+use super::rfc2217::LineSettings;
+use super::transfer::{self, Transfer};
+
+/// How a connection's worker reads from its port.
+#[derive(Clone, Copy, PartialEq)]
+pub struct ReadSettings {
+    /// Bytes taken from the port per read; high-rate captures need more
+    pub buffer: usize,
+    /// How long a transfer's read waits for data before the protocol checks
+    /// for cancellation and its own timeouts
+    pub timeout: Duration,
+}
+
+impl ReadSettings {
+    pub const DEFAULT: ReadSettings = ReadSettings {
+        buffer: 1024,
+        timeout: Duration::from_millis(10),
+    };
+}
+
+/// How long a break condition is held on the line.
+pub(super) const BREAK_DURATION: Duration = Duration::from_millis(250);
+
+/// How often the worker reads the modem status lines.
+const MODEM_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How often a drain checks the port's output queue.
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A drain gives up once the output queue hasn't shrunk for this long, as
+/// when flow control holds it.
+const DRAIN_STALL: Duration = Duration::from_secs(1);
+
+/// Requests from the main thread to a connection's worker.
+pub enum WorkerRequest {
+    Write(Vec<u8>),
+    Transfer(Transfer),
+    LinkTest(LinkTest),
+    Control(LineControl),
+    SetBaudRate(u32),
+    /// Apply `line`, going back to `previous` if the port takes only part
+    /// of it. Answered by `LineSettingsDone`.
+    SetLineSettings {
+        line: LineSettings,
+        previous: LineSettings,
+    },
+}
+
+/// Modem control line changes.
+#[derive(Clone, Copy)]
+pub enum LineControl {
+    Dtr(bool),
+    Rts(bool),
+    /// Hold the line in break for `BREAK_DURATION`
+    Break,
+}
+
+/// Modem status lines, as driven by the other end.
+#[derive(Clone, Copy, PartialEq)]
+pub struct ModemLines {
+    pub cts: bool,
+    pub dsr: bool,
+    pub dcd: bool,
+    pub ri: bool,
+}
+
 pub enum SerialEvent {
-    Data { id: usize, data: Vec<u8> },
-    Error { id: usize, err: String },
-    Disconnected { id: usize },
+    Data {
+        id: usize,
+        data: Vec<u8>,
+        /// When the read returned, so gaps between chunks are measured as
+        /// they came in, not as the main loop got to them
+        time: std::time::Instant,
+    },
+    Error {
+        id: usize,
+        err: String,
+    },
+    Disconnected {
+        id: usize,
+    },
+    // Informational line for the scrollback (e.g. external command output)
+    Info {
+        id: usize,
+        text: String,
+    },
+    // File transfer progress; `total` is unknown while receiving
+    Progress {
+        id: usize,
+        bytes: usize,
+        total: Option<usize>,
+    },
+    TransferDone {
+        id: usize,
+        result: Result<String, String>,
+    },
+    PowerCycleDone {
+        id: usize,
+        result: Result<(), String>,
+    },
+    // The line settings the port now runs at, or why it kept the old ones
+    LineSettingsDone {
+        id: usize,
+        result: Result<LineSettings, String>,
+    },
+    // Bytes from a share client, to be written to the port
+    ShareInput {
+        id: usize,
+        data: Vec<u8>,
+    },
+    // The modem status lines changed
+    ModemLines {
+        id: usize,
+        lines: ModemLines,
+    },
+    // The serial ports present, from the port list's background rescan
+    PortsScanned {
+        ports: Vec<serialport::SerialPortInfo>,
+    },
+}
+
+/// What the worker loop needs from a port. `read` must be cancel safe: the
+/// loop drops a pending read whenever a request comes in first.
+pub(super) trait Port {
+    /// Wait for data; running out of data is an error.
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize>;
+
+    async fn write_all(&mut self, data: &[u8]) -> io::Result<()>;
+
+    async fn control(&mut self, control: LineControl) -> io::Result<()>;
+
+    async fn set_baud_rate(&mut self, baud_rate: u32) -> io::Result<()>;
+
+    async fn set_line_settings(&mut self, line: LineSettings) -> io::Result<()>;
+
+    /// Wait until what was written has left the port's output queue.
+    async fn drain(&mut self) -> io::Result<()>;
+
+    /// The modem status lines as they are now, None if the port has none.
+    fn modem_lines(&mut self) -> Option<ModemLines>;
 }
 
-pub fn connection_thread(
+impl Port for tokio_serial::SerialStream {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match AsyncReadExt::read(self, buf).await? {
+            // A hung-up line reads as end of file
+            0 => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "port closed")),
+            n => Ok(n),
+        }
+    }
+
+    async fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+        AsyncWriteExt::write_all(self, data).await
+    }
+
+    async fn control(&mut self, control: LineControl) -> io::Result<()> {
+        use tokio_serial::SerialPort;
+        match control {
+            LineControl::Dtr(on) => self.write_data_terminal_ready(on)?,
+            LineControl::Rts(on) => self.write_request_to_send(on)?,
+            LineControl::Break => {
+                self.set_break()?;
+                tokio::time::sleep(BREAK_DURATION).await;
+                self.clear_break()?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn set_baud_rate(&mut self, baud_rate: u32) -> io::Result<()> {
+        tokio_serial::SerialPort::set_baud_rate(self, baud_rate)?;
+        Ok(())
+    }
+
+    async fn set_line_settings(&mut self, line: LineSettings) -> io::Result<()> {
+        use tokio_serial::SerialPort;
+        SerialPort::set_baud_rate(self, line.baud_rate)?;
+        self.set_data_bits(line.data_bits)?;
+        self.set_parity(line.parity)?;
+        self.set_stop_bits(line.stop_bits)?;
+        self.set_flow_control(line.flow_control)?;
+        Ok(())
+    }
+
+    async fn drain(&mut self) -> io::Result<()> {
+        use tokio_serial::SerialPort;
+        let mut queued = self.bytes_to_write()?;
+        let mut shrunk = Instant::now();
+        while queued > 0 && shrunk.elapsed() < DRAIN_STALL {
+            tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+            let now = self.bytes_to_write()?;
+            if now < queued {
+                shrunk = Instant::now();
+            }
+            queued = now;
+        }
+        Ok(())
+    }
+
+    fn modem_lines(&mut self) -> Option<ModemLines> {
+        use tokio_serial::SerialPort;
+        // A PTY fails these, it has no lines to read
+        Some(ModemLines {
+            cts: self.read_clear_to_send().ok()?,
+            dsr: self.read_data_set_ready().ok()?,
+            dcd: self.read_carrier_detect().ok()?,
+            ri: self.read_ring_indicator().ok()?,
+        })
+    }
+}
+
+/// A port as the blocking `Read + Write` the transfer protocols are written
+/// against, for use inside `block_in_place`. A read that gets nothing within
+/// `timeout` fails with `TimedOut`, like a serial port's would; a flush
+/// drains the port.
+struct Blocking<'a, P> {
+    port: &'a mut P,
+    runtime: Handle,
+    timeout: Duration,
+}
+
+impl<P: Port> Read for Blocking<'_, P> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.runtime
+            .block_on(tokio::time::timeout(self.timeout, self.port.read(buf)))
+            .unwrap_or_else(|_| Err(io::ErrorKind::TimedOut.into()))
+    }
+}
+
+impl<P: Port> Write for Blocking<'_, P> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.runtime.block_on(self.port.write_all(buf))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.runtime.block_on(self.port.drain())
+    }
+}
+
+pub async fn connection_task(
     id: usize,
-    port_name: &str,
+    port_name: String,
     baud_rate: u32,
     data_bits: serialport::DataBits,
     parity: serialport::Parity,
     stop_bits: serialport::StopBits,
-    serial_tx: mpsc::Sender<SerialEvent>,
-    write_rx: mpsc::Receiver<Vec<u8>>,
+    pacing: Pacing,
+    read: ReadSettings,
+    pre_open: Option<(String, Vec<(String, String)>)>,
+    serial_tx: mpsc::UnboundedSender<SerialEvent>,
+    mut write_rx: mpsc::UnboundedReceiver<WorkerRequest>,
 ) {
-    let port = serialport::new(port_name, baud_rate)
-        .timeout(Duration::from_millis(10))
+    if !run_pre_open(id, pre_open, &serial_tx).await {
+        return;
+    }
+
+    let port = serialport::new(&port_name, baud_rate)
         .data_bits(data_bits)
         .parity(parity)
         .stop_bits(stop_bits)
-        .open();
+        .flow_control(pacing.flow_control)
+        .open_native_async();
+
+    // A PTY may refuse line settings; whoever created it (QEMU, renode,
+    // socat) has set it up already
+    let port = port.or_else(|e| open_pty(&port_name).unwrap_or(Err(e.into())));
 
     let mut port = match port {
         Ok(p) => p,
@@ -36,47 +289,188 @@ pub fn connection_thread(
         }
     };
 
-    let mut buf = [0u8; 1024];
+    serve(id, &mut port, pacing, read, &serial_tx, &mut write_rx).await;
+    let _ = serial_tx.send(SerialEvent::Disconnected { id });
+}
 
-    loop {
-        // Check for data to write
-        match write_rx.try_recv() {
-            Ok(data) => {
-                use std::io::Write;
-                if let Err(e) = port.write_all(&data) {
-                    let _ = serial_tx.send(SerialEvent::Error {
-                        id,
-                        err: e.to_string(),
+/// Open a pseudo-terminal as it is, without applying termios settings. None
+/// if `port_name` isn't a PTY.
+#[cfg(unix)]
+fn open_pty(port_name: &str) -> Option<io::Result<tokio_serial::SerialStream>> {
+    use std::os::fd::{FromRawFd, IntoRawFd};
+
+    let path = std::fs::canonicalize(port_name).ok()?;
+    // Linux and BSD put PTYs under /dev/pts/, macOS names them /dev/ttysNNN
+    let macos_pty = path.parent() == Some(std::path::Path::new("/dev"))
+        && path.file_name()?.to_str()?.starts_with("ttys");
+    if !path.starts_with("/dev/pts") && !macos_pty {
+        return None;
+    }
+    let open = || -> io::Result<tokio_serial::SerialStream> {
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)?;
+        // SAFETY: the descriptor was just opened and is handed over to the port
+        let port = unsafe { serialport::TTYPort::from_raw_fd(file.into_raw_fd()) };
+        Ok(tokio_serial::SerialStream::try_from(port)?)
+    };
+    Some(open())
+}
+
+#[cfg(not(unix))]
+fn open_pty(_port_name: &str) -> Option<io::Result<tokio_serial::SerialStream>> {
+    None
+}
+
+/// Run the profile's pre_open hook (power on the target, ...), if any. The
+/// port is only opened if this returns true; failures are reported.
+pub(super) async fn run_pre_open(
+    id: usize,
+    pre_open: Option<(String, Vec<(String, String)>)>,
+    serial_tx: &mpsc::UnboundedSender<SerialEvent>,
+) -> bool {
+    let Some((command, vars)) = pre_open else {
+        return true;
+    };
+    let tx = serial_tx.clone();
+    let status = tokio::task::spawn_blocking(move || {
+        crate::command::run(id, "pre_open", &command, &vars, &tx)
+    })
+    .await;
+    let err = match status {
+        Ok(Ok(status)) if status.success() => return true,
+        Ok(Ok(status)) => format!("pre_open hook failed ({})", status),
+        Ok(Err(e)) => format!("pre_open hook failed to start: {}", e),
+        Err(e) => format!("pre_open hook failed: {}", e),
+    };
+    let _ = serial_tx.send(SerialEvent::Error { id, err });
+    false
+}
+
+/// Pass data between an open port and the main thread until either side
+/// goes away or the port fails.
+pub(super) async fn serve<P: Port>(
+    id: usize,
+    port: &mut P,
+    pacing: Pacing,
+    read: ReadSettings,
+    serial_tx: &mpsc::UnboundedSender<SerialEvent>,
+    write_rx: &mut mpsc::UnboundedReceiver<WorkerRequest>,
+) {
+    let mut buf = vec![0u8; read.buffer];
+    // Paced bytes waiting their turn, and when the next one is due
+    let mut outgoing: VecDeque<u8> = VecDeque::new();
+    let mut next_write = Instant::now();
+    // Only changes of the modem status lines are reported
+    let mut modem_poll = tokio::time::interval(MODEM_POLL_INTERVAL);
+    modem_poll.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut modem_lines = None;
+
+    let result = loop {
+        tokio::select! {
+            request = write_rx.recv() => match request {
+                Some(WorkerRequest::Transfer(transfer)) => {
+                    let result = tokio::task::block_in_place(|| {
+                        let mut port = Blocking {
+                            port: &mut *port,
+                            runtime: Handle::current(),
+                            timeout: read.timeout,
+                        };
+                        transfer::run(id, &mut port, transfer, serial_tx)
                     });
-                    break;
+                    let _ = serial_tx.send(SerialEvent::TransferDone { id, result });
+                }
+                Some(WorkerRequest::LinkTest(test)) => {
+                    let result = tokio::task::block_in_place(|| {
+                        // Reads return at once, so they don't hold up the writes
+                        let mut port = Blocking {
+                            port: &mut *port,
+                            runtime: Handle::current(),
+                            timeout: Duration::ZERO,
+                        };
+                        linktest::run(id, &mut port, test, serial_tx)
+                    });
+                    let _ = serial_tx.send(SerialEvent::TransferDone { id, result });
+                }
+                Some(WorkerRequest::Control(control)) => {
+                    // Not fatal: the port may just lack the line
+                    if let Err(e) = port.control(control).await {
+                        let _ = serial_tx.send(SerialEvent::Info {
+                            id,
+                            text: format!("--- Modem control failed: {} ---", e),
+                        });
+                    }
+                }
+                Some(WorkerRequest::SetBaudRate(baud_rate)) => {
+                    if let Err(e) = port.set_baud_rate(baud_rate).await {
+                        let _ = serial_tx.send(SerialEvent::Info {
+                            id,
+                            text: format!("--- Baud rate change failed: {} ---", e),
+                        });
+                    }
+                }
+                Some(WorkerRequest::SetLineSettings { line, previous }) => {
+                    let result = match port.set_line_settings(line).await {
+                        Ok(()) => Ok(line),
+                        Err(e) => {
+                            // A serial port applies them one at a time
+                            let _ = port.set_line_settings(previous).await;
+                            Err(e.to_string())
+                        }
+                    };
+                    let _ = serial_tx.send(SerialEvent::LineSettingsDone { id, result });
+                }
+                Some(WorkerRequest::Write(data)) if pacing.is_delayed() => {
+                    outgoing.extend(data);
+                }
+                Some(WorkerRequest::Write(data)) => {
+                    if let Err(e) = port.write_all(&data).await {
+                        break Err(e);
+                    }
                 }
-            }
-            Err(mpsc::TryRecvError::Disconnected) => {
                 // Main thread dropped write_tx — time to exit
-                break;
-            }
-            Err(mpsc::TryRecvError::Empty) => {}
-        }
+                None => break Ok(()),
+            },
 
-        // Read from port
-        match port.read(&mut buf) {
-            Ok(n) if n > 0 => {
-                let _ = serial_tx.send(SerialEvent::Data {
-                    id,
-                    data: buf[..n].to_vec(),
-                });
+            // The next paced byte's turn
+            _ = tokio::time::sleep_until(next_write), if !outgoing.is_empty() => {
+                let Some(byte) = outgoing.pop_front() else {
+                    continue;
+                };
+                if let Err(e) = port.write_all(&[byte]).await {
+                    break Err(e);
+                }
+                next_write = Instant::now() + pacing.delay_after(byte, outgoing.front().copied());
             }
-            Ok(_) => {}
-            Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => {}
-            Err(e) => {
-                let _ = serial_tx.send(SerialEvent::Error {
-                    id,
-                    err: e.to_string(),
-                });
-                break;
+
+            _ = modem_poll.tick() => {
+                let lines = port.modem_lines();
+                if lines != modem_lines {
+                    modem_lines = lines;
+                    if let Some(lines) = lines {
+                        let _ = serial_tx.send(SerialEvent::ModemLines { id, lines });
+                    }
+                }
             }
+
+            read = port.read(&mut buf) => match read {
+                Ok(n) => {
+                    let _ = serial_tx.send(SerialEvent::Data {
+                        id,
+                        data: buf[..n].to_vec(),
+                        time: Instant::now().into_std(),
+                    });
+                }
+                Err(e) => break Err(e),
+            },
         }
-    }
+    };
 
-    let _ = serial_tx.send(SerialEvent::Disconnected { id });
+    if let Err(e) = result {
+        let _ = serial_tx.send(SerialEvent::Error {
+            id,
+            err: e.to_string(),
+        });
+    }
 }
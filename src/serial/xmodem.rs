@@ -0,0 +1,444 @@
+use std::io::{self, Read, Write};
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
+use super::transfer::{crc16, read_byte, write_all, ReceivedFile};
+
+// XMODEM and its YMODEM batch extension. XMODEM sends 128-byte blocks with
+// CRC-16 or the plain checksum, whichever the receiver asks for; YMODEM adds a
+// block 0 carrying name and size, and sends 1K blocks. Receiving accepts both
+// block sizes.
+const SOH: u8 = 0x01;
+const STX: u8 = 0x02;
+const EOT: u8 = 0x04;
+const ACK: u8 = 0x06;
+const NAK: u8 = 0x15;
+const CAN: u8 = 0x18;
+const SUB: u8 = 0x1A;
+const CRC_REQUEST: u8 = b'C';
+
+const BLOCK_SIZE: usize = 128;
+const BLOCK_SIZE_1K: usize = 1024;
+const MAX_RETRIES: u32 = 10;
+const START_TIMEOUT: Duration = Duration::from_secs(60);
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+enum Received {
+    Block(u8, Vec<u8>),
+    Eot,
+    Timeout,
+    Bad,
+}
+
+pub fn send<P: Read + Write>(
+    port: &mut P,
+    data: &[u8],
+    cancel: &AtomicBool,
+    mut progress: impl FnMut(usize, Option<usize>),
+) -> io::Result<()> {
+    let use_crc = wait_for_start(port, cancel)?;
+    send_blocks(port, data, BLOCK_SIZE, use_crc, cancel, &mut progress)?;
+    send_eot(port, cancel)
+}
+
+pub fn receive<P: Read + Write>(
+    port: &mut P,
+    cancel: &AtomicBool,
+    mut progress: impl FnMut(usize, Option<usize>),
+) -> io::Result<Vec<u8>> {
+    let (use_crc, first) = start_receive(port, false, cancel)?;
+    let mut data = receive_data(port, use_crc, first, None, cancel, &mut progress)?;
+    // Strip the padding of the final block
+    while data.last() == Some(&SUB) {
+        data.pop();
+    }
+    Ok(data)
+}
+
+pub fn ymodem_send<P: Read + Write>(
+    port: &mut P,
+    name: &str,
+    data: &[u8],
+    cancel: &AtomicBool,
+    mut progress: impl FnMut(usize, Option<usize>),
+) -> io::Result<()> {
+    let use_crc = wait_for_start(port, cancel)?;
+    let info = format!("{}\0{}\0", name, data.len());
+    let size = if info.len() > BLOCK_SIZE {
+        BLOCK_SIZE_1K
+    } else {
+        BLOCK_SIZE
+    };
+    send_packet(
+        port,
+        &make_block(0, info.as_bytes(), size, 0, use_crc),
+        0,
+        cancel,
+    )?;
+
+    // The receiver asks again before the data blocks
+    let use_crc = wait_for_start(port, cancel)?;
+    send_blocks(port, data, BLOCK_SIZE_1K, use_crc, cancel, &mut progress)?;
+    send_eot(port, cancel)?;
+
+    // An empty block 0 ends the batch
+    let use_crc = wait_for_start(port, cancel)?;
+    send_packet(port, &make_block(0, &[], BLOCK_SIZE, 0, use_crc), 0, cancel)
+}
+
+pub fn ymodem_receive<P: Read + Write>(
+    port: &mut P,
+    cancel: &AtomicBool,
+    mut progress: impl FnMut(usize, Option<usize>),
+) -> io::Result<Vec<ReceivedFile>> {
+    let mut files = Vec::new();
+    loop {
+        let (_, mut reply) = start_receive(port, true, cancel)?;
+        let mut errors = 0;
+        let header = loop {
+            match reply {
+                Received::Block(0, payload) => break payload,
+                Received::Block(..) | Received::Eot => {
+                    write_all(port, &[CAN, CAN, CAN], cancel)?;
+                    return Err(io::Error::other("expected a YMODEM header block"));
+                }
+                Received::Bad | Received::Timeout => {
+                    errors += 1;
+                    if errors > MAX_RETRIES {
+                        write_all(port, &[CAN, CAN, CAN], cancel)?;
+                        return Err(io::Error::other("too many errors"));
+                    }
+                    write_all(port, &[NAK], cancel)?;
+                    reply = read_block(port, true, REPLY_TIMEOUT, cancel)?;
+                }
+            }
+        };
+        write_all(port, &[ACK], cancel)?;
+
+        let (name, size) = parse_header(&header);
+        if name.is_empty() {
+            return Ok(files);
+        }
+
+        write_all(port, &[CRC_REQUEST], cancel)?;
+        let first = read_block(port, true, REPLY_TIMEOUT, cancel)?;
+        let mut data = receive_data(port, true, first, size, cancel, &mut progress)?;
+        match size {
+            Some(size) => data.truncate(size),
+            None => {
+                while data.last() == Some(&SUB) {
+                    data.pop();
+                }
+            }
+        }
+        files.push(ReceivedFile { name, data });
+    }
+}
+
+/// Block 0 payload: `name NUL size [mtime mode ...] NUL`, padded with NULs.
+fn parse_header(payload: &[u8]) -> (String, Option<usize>) {
+    let mut fields = payload.split(|&b| b == 0);
+    let name = String::from_utf8_lossy(fields.next().unwrap_or_default()).into_owned();
+    let size = fields
+        .next()
+        .map(String::from_utf8_lossy)
+        .and_then(|info| info.split_whitespace().next()?.parse().ok());
+    (name, size)
+}
+
+fn send_blocks<P: Read + Write>(
+    port: &mut P,
+    data: &[u8],
+    block_size: usize,
+    use_crc: bool,
+    cancel: &AtomicBool,
+    progress: &mut impl FnMut(usize, Option<usize>),
+) -> io::Result<()> {
+    for (i, chunk) in data.chunks(block_size).enumerate() {
+        let packet = make_block((i + 1) as u8, chunk, block_size, SUB, use_crc);
+        send_packet(port, &packet, i + 1, cancel)?;
+        progress(i * block_size + chunk.len(), Some(data.len()));
+    }
+    Ok(())
+}
+
+fn send_eot<P: Read + Write>(port: &mut P, cancel: &AtomicBool) -> io::Result<()> {
+    // YMODEM receivers commonly NAK the first EOT; just repeat it
+    for _ in 0..MAX_RETRIES {
+        write_all(port, &[EOT], cancel)?;
+        if read_byte(port, REPLY_TIMEOUT, cancel)? == Some(ACK) {
+            return Ok(());
+        }
+    }
+    Err(io::Error::other(
+        "receiver did not acknowledge end of transfer",
+    ))
+}
+
+fn make_block(num: u8, chunk: &[u8], size: usize, pad: u8, use_crc: bool) -> Vec<u8> {
+    let header = if size == BLOCK_SIZE_1K { STX } else { SOH };
+    let mut packet = Vec::with_capacity(size + 5);
+    packet.extend_from_slice(&[header, num, !num]);
+    packet.extend_from_slice(chunk);
+    packet.resize(3 + size, pad);
+    if use_crc {
+        let crc = crc16(&packet[3..]);
+        packet.extend_from_slice(&crc.to_be_bytes());
+    } else {
+        let sum = checksum(&packet[3..]);
+        packet.push(sum);
+    }
+    packet
+}
+
+/// Wait for the receiver's start request. Returns true for CRC mode.
+fn wait_for_start<P: Read + Write>(port: &mut P, cancel: &AtomicBool) -> io::Result<bool> {
+    let deadline = Instant::now() + START_TIMEOUT;
+    while Instant::now() < deadline {
+        match read_byte(port, Duration::from_secs(1), cancel)? {
+            Some(CRC_REQUEST) => return Ok(true),
+            Some(NAK) => return Ok(false),
+            Some(CAN) => return Err(io::Error::other("cancelled by receiver")),
+            _ => {}
+        }
+    }
+    Err(io::Error::other("receiver did not start"))
+}
+
+fn send_packet<P: Read + Write>(
+    port: &mut P,
+    packet: &[u8],
+    block: usize,
+    cancel: &AtomicBool,
+) -> io::Result<()> {
+    for _ in 0..MAX_RETRIES {
+        write_all(port, packet, cancel)?;
+        port.flush()?;
+        match read_byte(port, REPLY_TIMEOUT, cancel)? {
+            Some(ACK) => return Ok(()),
+            Some(CAN) => return Err(io::Error::other("cancelled by receiver")),
+            _ => {}
+        }
+    }
+    Err(io::Error::other(format!(
+        "too many retries at block {}",
+        block
+    )))
+}
+
+/// Ask the sender to start, in CRC mode first and falling back to checksum mode
+/// for old senders unless `crc_only`. Returns the mode and the first reply.
+fn start_receive<P: Read + Write>(
+    port: &mut P,
+    crc_only: bool,
+    cancel: &AtomicBool,
+) -> io::Result<(bool, Received)> {
+    for attempt in 0..MAX_RETRIES {
+        let use_crc = crc_only || attempt < 3;
+        write_all(port, &[if use_crc { CRC_REQUEST } else { NAK }], cancel)?;
+        match read_block(port, use_crc, Duration::from_secs(3), cancel)? {
+            Received::Timeout => {}
+            first => return Ok((use_crc, first)),
+        }
+    }
+    Err(io::Error::other("sender did not start"))
+}
+
+/// Receive numbered data blocks (starting at 1) up to EOT.
+fn receive_data<P: Read + Write>(
+    port: &mut P,
+    use_crc: bool,
+    first: Received,
+    size: Option<usize>,
+    cancel: &AtomicBool,
+    progress: &mut impl FnMut(usize, Option<usize>),
+) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    let mut expected: u8 = 1;
+    let mut errors = 0;
+    let mut reply = first;
+
+    loop {
+        match reply {
+            Received::Block(num, payload) if num == expected => {
+                data.extend_from_slice(&payload);
+                expected = expected.wrapping_add(1);
+                errors = 0;
+                write_all(port, &[ACK], cancel)?;
+                progress(size.map_or(data.len(), |s| data.len().min(s)), size);
+            }
+            Received::Block(num, _) if num == expected.wrapping_sub(1) => {
+                // Our ACK got lost and the sender repeated the block
+                write_all(port, &[ACK], cancel)?;
+            }
+            Received::Block(num, _) => {
+                write_all(port, &[CAN, CAN, CAN], cancel)?;
+                return Err(io::Error::other(format!(
+                    "block sequence error (expected {}, got {})",
+                    expected, num
+                )));
+            }
+            Received::Eot => {
+                write_all(port, &[ACK], cancel)?;
+                return Ok(data);
+            }
+            Received::Timeout | Received::Bad => {
+                errors += 1;
+                if errors > MAX_RETRIES {
+                    write_all(port, &[CAN, CAN, CAN], cancel)?;
+                    return Err(io::Error::other("too many errors"));
+                }
+                purge(port, cancel)?;
+                write_all(port, &[NAK], cancel)?;
+            }
+        }
+        reply = read_block(port, use_crc, REPLY_TIMEOUT, cancel)?;
+    }
+}
+
+fn read_block<P: Read + Write>(
+    port: &mut P,
+    use_crc: bool,
+    timeout: Duration,
+    cancel: &AtomicBool,
+) -> io::Result<Received> {
+    let size = match read_byte(port, timeout, cancel)? {
+        Some(SOH) => BLOCK_SIZE,
+        Some(STX) => BLOCK_SIZE_1K,
+        Some(EOT) => return Ok(Received::Eot),
+        Some(CAN) => return Err(io::Error::other("cancelled by sender")),
+        Some(_) => return Ok(Received::Bad),
+        None => return Ok(Received::Timeout),
+    };
+    let len = 2 + size + if use_crc { 2 } else { 1 };
+    let mut packet = Vec::with_capacity(len);
+    while packet.len() < len {
+        match read_byte(port, Duration::from_secs(1), cancel)? {
+            Some(b) => packet.push(b),
+            None => return Ok(Received::Bad),
+        }
+    }
+
+    let (num, payload, check) = (packet[0], &packet[2..2 + size], &packet[2 + size..]);
+    let valid = num == !packet[1]
+        && if use_crc {
+            check == crc16(payload).to_be_bytes()
+        } else {
+            check[0] == checksum(payload)
+        };
+    Ok(if valid {
+        Received::Block(num, payload.to_vec())
+    } else {
+        Received::Bad
+    })
+}
+
+/// Discard the rest of a garbled block so it isn't mistaken for the next one.
+fn purge<P: Read + Write>(port: &mut P, cancel: &AtomicBool) -> io::Result<()> {
+    while read_byte(port, Duration::from_millis(200), cancel)?.is_some() {}
+    Ok(())
+}
+
+fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, &b| sum.wrapping_add(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::transfer::tests::exchange;
+    use std::io::Cursor;
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 7 % 251) as u8).collect()
+    }
+
+    #[test]
+    fn xmodem_crc_round_trip() {
+        // Not a multiple of the block size, so the last block is padded
+        let data = sample(1000);
+        let sent = data.clone();
+        let (sent_ok, received) = exchange(
+            move |port| send(port, &sent, &AtomicBool::new(false), |_, _| {}).is_ok(),
+            |port| receive(port, &AtomicBool::new(false), |_, _| {}).unwrap(),
+        );
+        assert!(sent_ok);
+        assert_eq!(received, data);
+    }
+
+    #[test]
+    fn xmodem_receives_1k_blocks() {
+        let data = sample(3000);
+        let sent = data.clone();
+        let (sent_ok, received) = exchange(
+            move |port| {
+                let cancel = AtomicBool::new(false);
+                let use_crc = wait_for_start(port, &cancel)?;
+                send_blocks(port, &sent, BLOCK_SIZE_1K, use_crc, &cancel, &mut |_, _| {})?;
+                send_eot(port, &cancel)
+            },
+            |port| receive(port, &AtomicBool::new(false), |_, _| {}).unwrap(),
+        );
+        assert!(sent_ok.is_ok());
+        assert_eq!(received, data);
+    }
+
+    #[test]
+    fn ymodem_round_trip_keeps_name_and_exact_size() {
+        // Ends in SUB bytes, which only the size in the header can preserve
+        let mut data = sample(2100);
+        data.extend_from_slice(&[SUB; 3]);
+        let sent = data.clone();
+        let (sent_ok, files) = exchange(
+            move |port| {
+                ymodem_send(port, "log.bin", &sent, &AtomicBool::new(false), |_, _| {}).is_ok()
+            },
+            |port| ymodem_receive(port, &AtomicBool::new(false), |_, _| {}).unwrap(),
+        );
+        assert!(sent_ok);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "log.bin");
+        assert_eq!(files[0].data, data);
+    }
+
+    #[test]
+    fn parse_header_reads_name_and_size() {
+        assert_eq!(
+            parse_header(b"log.bin\x002100 14527044346 100644\0\0\0"),
+            ("log.bin".to_string(), Some(2100))
+        );
+        assert_eq!(parse_header(b"log.bin\0\0"), ("log.bin".to_string(), None));
+        assert_eq!(parse_header(&[0; BLOCK_SIZE]), (String::new(), None));
+    }
+
+    #[test]
+    fn blocks_read_back_in_both_sizes_and_modes() {
+        let cancel = AtomicBool::new(false);
+        for (size, header) in [(BLOCK_SIZE, SOH), (BLOCK_SIZE_1K, STX)] {
+            for use_crc in [true, false] {
+                let chunk = sample(size - 10);
+                let packet = make_block(3, &chunk, size, SUB, use_crc);
+                assert_eq!(packet[..3], [header, 3, !3]);
+                assert_eq!(packet.len(), size + if use_crc { 5 } else { 4 });
+
+                let Received::Block(num, payload) =
+                    read_block(&mut Cursor::new(packet), use_crc, REPLY_TIMEOUT, &cancel).unwrap()
+                else {
+                    panic!("block did not read back");
+                };
+                assert_eq!(num, 3);
+                assert_eq!(payload[..chunk.len()], chunk);
+                assert!(payload[chunk.len()..].iter().all(|&b| b == SUB));
+            }
+        }
+    }
+
+    #[test]
+    fn corrupted_blocks_are_rejected() {
+        let cancel = AtomicBool::new(false);
+        let mut packet = make_block(1, b"hello", BLOCK_SIZE, SUB, true);
+        packet[10] ^= 0x01;
+        let result = read_block(&mut Cursor::new(packet), true, REPLY_TIMEOUT, &cancel);
+        assert!(matches!(result.unwrap(), Received::Bad));
+    }
+}
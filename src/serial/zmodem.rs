@@ -0,0 +1,760 @@
+use std::io::{self, Read, Write};
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
+use super::transfer::{crc16, read_byte, write_all, ReceivedFile};
+
+// ZMODEM, single-file send and batch receive. Frames are a header (hex, or
+// binary with CRC-16/CRC-32) optionally followed by ZDLE-escaped data
+// subpackets. Data is streamed without per-packet ACKs; the receiver resyncs
+// the sender with ZRPOS after an error.
+const ZPAD: u8 = b'*';
+const ZDLE: u8 = 0x18;
+const ZBIN: u8 = b'A';
+const ZHEX: u8 = b'B';
+const ZBIN32: u8 = b'C';
+
+// Frame types
+const ZRQINIT: u8 = 0;
+const ZRINIT: u8 = 1;
+const ZSINIT: u8 = 2;
+const ZACK: u8 = 3;
+const ZFILE: u8 = 4;
+const ZSKIP: u8 = 5;
+const ZNAK: u8 = 6;
+const ZABORT: u8 = 7;
+const ZFIN: u8 = 8;
+const ZRPOS: u8 = 9;
+const ZDATA: u8 = 10;
+const ZEOF: u8 = 11;
+const ZFERR: u8 = 12;
+const ZCHALLENGE: u8 = 14;
+const ZCAN: u8 = 16;
+
+// Subpacket ends
+const ZCRCE: u8 = b'h';
+const ZCRCG: u8 = b'i';
+const ZCRCQ: u8 = b'j';
+const ZCRCW: u8 = b'k';
+const ZRUB0: u8 = b'l';
+const ZRUB1: u8 = b'm';
+
+// ZRINIT capability flags (ZF0)
+const CANFDX: u8 = 0x01;
+const CANOVIO: u8 = 0x02;
+const CANFC32: u8 = 0x20;
+
+const XON: u8 = 0x11;
+const XOFF: u8 = 0x13;
+
+const SUBPACKET_SIZE: usize = 1024;
+const MAX_RETRIES: u32 = 10;
+const START_TIMEOUT: Duration = Duration::from_secs(60);
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The hex ZRQINIT header a sender opens with (`sz` prints this when started).
+pub const INIT_SEQUENCE: &[u8] = b"**\x18B00";
+
+struct Header {
+    kind: u8,
+    // ZP0..ZP3 (little-endian position) or ZF3..ZF0 flags
+    data: [u8; 4],
+    // Sent as binary CRC-32, so its subpackets use CRC-32 too
+    crc32: bool,
+}
+
+impl Header {
+    fn position(&self) -> usize {
+        u32::from_le_bytes(self.data) as usize
+    }
+}
+
+fn pos_data(pos: usize) -> [u8; 4] {
+    (pos as u32).to_le_bytes()
+}
+
+pub fn send<P: Read + Write>(
+    port: &mut P,
+    name: &str,
+    data: &[u8],
+    cancel: &AtomicBool,
+    mut progress: impl FnMut(usize, Option<usize>),
+) -> io::Result<()> {
+    // Wait for the receiver's capabilities, repeating our request now and then
+    let deadline = Instant::now() + START_TIMEOUT;
+    let use_crc32 = loop {
+        if Instant::now() >= deadline {
+            return Err(io::Error::other("receiver did not start"));
+        }
+        send_hex_header(port, ZRQINIT, [0; 4], cancel)?;
+        match read_header(port, Duration::from_secs(5), cancel)? {
+            Some(h) if h.kind == ZRINIT => break h.data[3] & CANFC32 != 0,
+            Some(h) if h.kind == ZCHALLENGE => send_hex_header(port, ZACK, h.data, cancel)?,
+            _ => {}
+        }
+    };
+
+    // Offer the file until the receiver says where to start
+    let info = format!("{}\0{} 0 100644\0", name, data.len());
+    let mut retries = 0;
+    let mut pos = loop {
+        send_bin_header(port, ZFILE, [0; 4], use_crc32, cancel)?;
+        send_subpacket(port, info.as_bytes(), ZCRCW, use_crc32, cancel)?;
+        match read_header(port, REPLY_TIMEOUT, cancel)? {
+            Some(h) if h.kind == ZRPOS => break h.position().min(data.len()),
+            Some(h) if h.kind == ZSKIP => {
+                return Err(io::Error::other("receiver skipped the file"))
+            }
+            Some(h) if h.kind == ZABORT || h.kind == ZFERR || h.kind == ZCAN => {
+                return Err(io::Error::other("receiver aborted"));
+            }
+            _ => {}
+        }
+        retries += 1;
+        if retries > MAX_RETRIES {
+            return Err(io::Error::other("receiver did not accept the file"));
+        }
+    };
+
+    retries = 0;
+    'frame: loop {
+        send_bin_header(port, ZDATA, pos_data(pos), use_crc32, cancel)?;
+        while pos < data.len() {
+            let end = (pos + SUBPACKET_SIZE).min(data.len());
+            let frame_end = if end == data.len() { ZCRCE } else { ZCRCG };
+            send_subpacket(port, &data[pos..end], frame_end, use_crc32, cancel)?;
+            pos = end;
+            progress(pos, Some(data.len()));
+
+            // The receiver interrupts the stream with ZRPOS when it lost data
+            if let Some(h) = poll_header(port, cancel)? {
+                if h.kind == ZRPOS {
+                    if frame_end == ZCRCG {
+                        send_subpacket(port, &[], ZCRCE, use_crc32, cancel)?;
+                    }
+                    retries += 1;
+                    if retries > MAX_RETRIES {
+                        return Err(io::Error::other("too many errors"));
+                    }
+                    pos = h.position().min(data.len());
+                    continue 'frame;
+                }
+            }
+        }
+        if data.is_empty() {
+            send_subpacket(port, &[], ZCRCE, use_crc32, cancel)?;
+        }
+
+        send_hex_header(port, ZEOF, pos_data(data.len()), cancel)?;
+        match read_header(port, REPLY_TIMEOUT, cancel)? {
+            Some(h) if h.kind == ZRINIT => break,
+            Some(h) if h.kind == ZRPOS => {
+                retries += 1;
+                if retries > MAX_RETRIES {
+                    return Err(io::Error::other("too many errors"));
+                }
+                pos = h.position().min(data.len());
+            }
+            Some(h) if h.kind == ZABORT || h.kind == ZFERR || h.kind == ZCAN => {
+                return Err(io::Error::other("receiver aborted"));
+            }
+            _ => {
+                retries += 1;
+                if retries > MAX_RETRIES {
+                    return Err(io::Error::other("receiver did not confirm end of file"));
+                }
+            }
+        }
+    }
+
+    finish_session(port, cancel)
+}
+
+pub fn receive<P: Read + Write>(
+    port: &mut P,
+    cancel: &AtomicBool,
+    mut progress: impl FnMut(usize, Option<usize>),
+) -> io::Result<Vec<ReceivedFile>> {
+    let mut files = Vec::new();
+    // File being received: name, expected size, data so far
+    let mut current: Option<(String, Option<usize>, Vec<u8>)> = None;
+    let mut errors = 0;
+
+    send_rinit(port, cancel)?;
+    loop {
+        let header = match read_header(port, REPLY_TIMEOUT, cancel)? {
+            Some(h) => h,
+            None => {
+                errors += 1;
+                if errors > MAX_RETRIES {
+                    abort(port, cancel)?;
+                    return Err(io::Error::other("sender stopped responding"));
+                }
+                match &current {
+                    Some((_, _, data)) => {
+                        send_hex_header(port, ZRPOS, pos_data(data.len()), cancel)?
+                    }
+                    None => send_rinit(port, cancel)?,
+                }
+                continue;
+            }
+        };
+        match header.kind {
+            ZRQINIT => send_rinit(port, cancel)?,
+            ZSINIT => {
+                // Attention string; not needed since we never interrupt the sender
+                let _ = read_subpacket(port, header.crc32, cancel)?;
+                send_hex_header(port, ZACK, [0; 4], cancel)?;
+            }
+            ZFILE => match read_subpacket(port, header.crc32, cancel)? {
+                Some((info, _)) => {
+                    let (name, size) = parse_file_info(&info);
+                    current = Some((name, size, Vec::new()));
+                    send_hex_header(port, ZRPOS, [0; 4], cancel)?;
+                }
+                None => send_hex_header(port, ZNAK, [0; 4], cancel)?,
+            },
+            ZDATA => {
+                // The sender acted on a stale ZRPOS after the file was done;
+                // its data is skipped and the ZEOF that follows answered below
+                let Some((_, size, data)) = &mut current else {
+                    continue;
+                };
+                if header.position() != data.len() {
+                    send_hex_header(port, ZRPOS, pos_data(data.len()), cancel)?;
+                    continue;
+                }
+                loop {
+                    match read_subpacket(port, header.crc32, cancel)? {
+                        Some((chunk, frame_end)) => {
+                            data.extend_from_slice(&chunk);
+                            errors = 0;
+                            progress(data.len(), *size);
+                            match frame_end {
+                                ZCRCW => {
+                                    send_hex_header(port, ZACK, pos_data(data.len()), cancel)?;
+                                    break;
+                                }
+                                ZCRCQ => send_hex_header(port, ZACK, pos_data(data.len()), cancel)?,
+                                ZCRCG => {}
+                                _ => break,
+                            }
+                        }
+                        None => {
+                            errors += 1;
+                            if errors > MAX_RETRIES {
+                                abort(port, cancel)?;
+                                return Err(io::Error::other("too many errors"));
+                            }
+                            send_hex_header(port, ZRPOS, pos_data(data.len()), cancel)?;
+                            break;
+                        }
+                    }
+                }
+            }
+            ZEOF => {
+                // A ZEOF that doesn't match our position is stale; ignore it
+                let complete =
+                    matches!(&current, Some((_, _, data)) if header.position() == data.len());
+                if complete || current.is_none() {
+                    if let Some((name, _, data)) = current.take() {
+                        files.push(ReceivedFile { name, data });
+                    }
+                    send_rinit(port, cancel)?;
+                }
+            }
+            ZFIN => {
+                send_hex_header(port, ZFIN, [0; 4], cancel)?;
+                // Swallow the sender's "OO" sign-off
+                let _ = read_byte(port, Duration::from_millis(500), cancel)?;
+                let _ = read_byte(port, Duration::from_millis(500), cancel)?;
+                return Ok(files);
+            }
+            ZCAN | ZABORT => return Err(io::Error::other("cancelled by sender")),
+            _ => {}
+        }
+    }
+}
+
+/// Parse the ZFILE subpacket: `name NUL size [mtime mode ...] NUL`.
+fn parse_file_info(info: &[u8]) -> (String, Option<usize>) {
+    let mut fields = info.split(|&b| b == 0);
+    let name = String::from_utf8_lossy(fields.next().unwrap_or_default()).into_owned();
+    let size = fields
+        .next()
+        .map(String::from_utf8_lossy)
+        .and_then(|s| s.split_whitespace().next()?.parse().ok());
+    (name, size)
+}
+
+fn send_rinit<P: Read + Write>(port: &mut P, cancel: &AtomicBool) -> io::Result<()> {
+    send_hex_header(port, ZRINIT, [0, 0, 0, CANFDX | CANOVIO | CANFC32], cancel)
+}
+
+fn finish_session<P: Read + Write>(port: &mut P, cancel: &AtomicBool) -> io::Result<()> {
+    for _ in 0..MAX_RETRIES {
+        send_hex_header(port, ZFIN, [0; 4], cancel)?;
+        if let Some(h) = read_header(port, REPLY_TIMEOUT, cancel)? {
+            if h.kind == ZFIN {
+                return write_all(port, b"OO", cancel);
+            }
+        }
+    }
+    // The file went through; a lost ZFIN handshake isn't worth failing over
+    Ok(())
+}
+
+/// Tell the peer to give up (CANs followed by backspaces to clean up its screen).
+fn abort<P: Read + Write>(port: &mut P, cancel: &AtomicBool) -> io::Result<()> {
+    write_all(port, &[ZDLE; 8], cancel)?;
+    write_all(port, &[0x08; 8], cancel)
+}
+
+fn send_hex_header<P: Read + Write>(
+    port: &mut P,
+    kind: u8,
+    data: [u8; 4],
+    cancel: &AtomicBool,
+) -> io::Result<()> {
+    let mut raw = vec![kind];
+    raw.extend_from_slice(&data);
+    let crc = crc16(&raw);
+    raw.extend_from_slice(&crc.to_be_bytes());
+
+    let mut out = vec![ZPAD, ZPAD, ZDLE, ZHEX];
+    for b in raw {
+        out.extend_from_slice(format!("{:02x}", b).as_bytes());
+    }
+    out.extend_from_slice(&[b'\r', b'\n' | 0x80]);
+    if kind != ZFIN && kind != ZACK {
+        out.push(XON);
+    }
+    write_all(port, &out, cancel)?;
+    port.flush()
+}
+
+fn send_bin_header<P: Read + Write>(
+    port: &mut P,
+    kind: u8,
+    data: [u8; 4],
+    use_crc32: bool,
+    cancel: &AtomicBool,
+) -> io::Result<()> {
+    let mut raw = vec![kind];
+    raw.extend_from_slice(&data);
+    let mut out = vec![ZPAD, ZDLE, if use_crc32 { ZBIN32 } else { ZBIN }];
+    if use_crc32 {
+        let crc = crc32(&raw);
+        raw.extend_from_slice(&crc.to_le_bytes());
+    } else {
+        let crc = crc16(&raw);
+        raw.extend_from_slice(&crc.to_be_bytes());
+    }
+    escape_into(&raw, &mut out);
+    write_all(port, &out, cancel)
+}
+
+fn send_subpacket<P: Read + Write>(
+    port: &mut P,
+    data: &[u8],
+    frame_end: u8,
+    use_crc32: bool,
+    cancel: &AtomicBool,
+) -> io::Result<()> {
+    let mut out = Vec::with_capacity(data.len() * 2 + 12);
+    escape_into(data, &mut out);
+    out.extend_from_slice(&[ZDLE, frame_end]);
+
+    let mut covered = data.to_vec();
+    covered.push(frame_end);
+    if use_crc32 {
+        escape_into(&crc32(&covered).to_le_bytes(), &mut out);
+    } else {
+        escape_into(&crc16(&covered).to_be_bytes(), &mut out);
+    }
+    if frame_end == ZCRCW {
+        out.push(XON);
+    }
+    write_all(port, &out, cancel)?;
+    port.flush()
+}
+
+fn escape_into(data: &[u8], out: &mut Vec<u8>) {
+    for &b in data {
+        match b {
+            ZDLE | 0x10 | 0x90 | XON | 0x91 | XOFF | 0x93 | b'\r' | 0x8d => {
+                out.push(ZDLE);
+                out.push(b ^ 0x40);
+            }
+            _ => out.push(b),
+        }
+    }
+}
+
+/// Check for a header the receiver sent while we were streaming, without
+/// waiting if there is nothing.
+fn poll_header<P: Read + Write>(port: &mut P, cancel: &AtomicBool) -> io::Result<Option<Header>> {
+    loop {
+        match read_byte(port, Duration::ZERO, cancel)? {
+            Some(ZPAD) => return scan_header(port, REPLY_TIMEOUT, true, cancel),
+            Some(ZDLE) => {
+                // Possibly the start of a cancel sequence
+                let mut cans = 1;
+                while read_byte(port, Duration::from_millis(100), cancel)? == Some(ZDLE) {
+                    cans += 1;
+                    if cans >= 5 {
+                        return Err(io::Error::other("cancelled by receiver"));
+                    }
+                }
+            }
+            // Line endings and XON trailing earlier headers
+            Some(_) => {}
+            None => return Ok(None),
+        }
+    }
+}
+
+/// Scan for the next header. Returns None on timeout or a corrupted header.
+fn read_header<P: Read + Write>(
+    port: &mut P,
+    timeout: Duration,
+    cancel: &AtomicBool,
+) -> io::Result<Option<Header>> {
+    scan_header(port, timeout, false, cancel)
+}
+
+/// Skip everything up to `ZPAD ZDLE` and parse the header after it. Stray
+/// pads and unknown formats (e.g. inside streamed data) are skipped over.
+fn scan_header<P: Read + Write>(
+    port: &mut P,
+    timeout: Duration,
+    mut saw_pad: bool,
+    cancel: &AtomicBool,
+) -> io::Result<Option<Header>> {
+    let deadline = Instant::now() + timeout;
+    let mut cans = 0;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match read_byte(port, remaining, cancel)? {
+            None => return Ok(None),
+            Some(ZPAD) => saw_pad = true,
+            Some(ZDLE) if saw_pad => match read_header_body(port, cancel)? {
+                Scanned::Header(header) => return Ok(Some(header)),
+                Scanned::Garbled => return Ok(None),
+                Scanned::Noise => saw_pad = false,
+            },
+            Some(ZDLE) => {
+                cans += 1;
+                if cans >= 5 {
+                    return Err(io::Error::other("cancelled by peer"));
+                }
+            }
+            Some(_) => {
+                saw_pad = false;
+                cans = 0;
+            }
+        }
+    }
+}
+
+enum Scanned {
+    Header(Header),
+    // A header whose check failed
+    Garbled,
+    // Not a header after all
+    Noise,
+}
+
+/// Parse the format byte and header that follow `ZPAD ZDLE`.
+fn read_header_body<P: Read + Write>(port: &mut P, cancel: &AtomicBool) -> io::Result<Scanned> {
+    let format = read_byte(port, REPLY_TIMEOUT, cancel)?;
+    let raw = match format {
+        Some(ZHEX) => {
+            let mut raw = Vec::with_capacity(7);
+            for _ in 0..7 {
+                let (Some(hi), Some(lo)) = (
+                    read_byte(port, REPLY_TIMEOUT, cancel)?,
+                    read_byte(port, REPLY_TIMEOUT, cancel)?,
+                ) else {
+                    return Ok(Scanned::Garbled);
+                };
+                let digits = [hi, lo];
+                let Some(byte) = std::str::from_utf8(&digits)
+                    .ok()
+                    .and_then(|s| u8::from_str_radix(s, 16).ok())
+                else {
+                    return Ok(Scanned::Garbled);
+                };
+                raw.push(byte);
+            }
+            if crc16(&raw[..5]).to_be_bytes() != raw[5..7] {
+                return Ok(Scanned::Garbled);
+            }
+            raw
+        }
+        Some(ZBIN) => match read_escaped(port, 7, cancel)? {
+            Some(raw) if crc16(&raw[..5]).to_be_bytes() == raw[5..7] => raw,
+            _ => return Ok(Scanned::Garbled),
+        },
+        Some(ZBIN32) => match read_escaped(port, 9, cancel)? {
+            Some(raw) if crc32(&raw[..5]).to_le_bytes() == raw[5..9] => raw,
+            _ => return Ok(Scanned::Garbled),
+        },
+        _ => return Ok(Scanned::Noise),
+    };
+    Ok(Scanned::Header(Header {
+        kind: raw[0],
+        data: [raw[1], raw[2], raw[3], raw[4]],
+        crc32: format == Some(ZBIN32),
+    }))
+}
+
+enum Escaped {
+    Byte(u8),
+    End(u8),
+}
+
+/// Read one ZDLE-decoded byte or subpacket end marker.
+fn read_zdle<P: Read + Write>(port: &mut P, cancel: &AtomicBool) -> io::Result<Escaped> {
+    let next = |port: &mut P| -> io::Result<u8> {
+        read_byte(port, REPLY_TIMEOUT, cancel)?
+            .ok_or_else(|| io::Error::from(io::ErrorKind::TimedOut))
+    };
+    loop {
+        match next(port)? {
+            ZDLE => break,
+            XON | XOFF | 0x91 | 0x93 => {}
+            b => return Ok(Escaped::Byte(b)),
+        }
+    }
+    let mut cans = 1;
+    loop {
+        match next(port)? {
+            ZDLE => {
+                cans += 1;
+                if cans >= 5 {
+                    return Err(io::Error::other("cancelled by peer"));
+                }
+            }
+            end @ (ZCRCE | ZCRCG | ZCRCQ | ZCRCW) => return Ok(Escaped::End(end)),
+            ZRUB0 => return Ok(Escaped::Byte(0x7f)),
+            ZRUB1 => return Ok(Escaped::Byte(0xff)),
+            XON | XOFF | 0x91 | 0x93 => {}
+            b if b & 0x60 == 0x40 => return Ok(Escaped::Byte(b ^ 0x40)),
+            _ => return Err(io::Error::from(io::ErrorKind::InvalidData)),
+        }
+    }
+}
+
+fn read_escaped<P: Read + Write>(
+    port: &mut P,
+    len: usize,
+    cancel: &AtomicBool,
+) -> io::Result<Option<Vec<u8>>> {
+    let mut out = Vec::with_capacity(len);
+    while out.len() < len {
+        match recoverable(read_zdle(port, cancel))? {
+            Some(Escaped::Byte(b)) => out.push(b),
+            Some(Escaped::End(_)) | None => return Ok(None),
+        }
+    }
+    Ok(Some(out))
+}
+
+/// Read a data subpacket, returning its payload and end marker, or None if it
+/// was corrupted or cut short.
+fn read_subpacket<P: Read + Write>(
+    port: &mut P,
+    use_crc32: bool,
+    cancel: &AtomicBool,
+) -> io::Result<Option<(Vec<u8>, u8)>> {
+    let mut data = Vec::with_capacity(SUBPACKET_SIZE);
+    let frame_end = loop {
+        match recoverable(read_zdle(port, cancel))? {
+            Some(Escaped::Byte(b)) => {
+                data.push(b);
+                if data.len() > 8 * SUBPACKET_SIZE {
+                    return Ok(None);
+                }
+            }
+            Some(Escaped::End(end)) => break end,
+            None => return Ok(None),
+        }
+    };
+
+    let crc_len = if use_crc32 { 4 } else { 2 };
+    let Some(crc) = read_escaped(port, crc_len, cancel)? else {
+        return Ok(None);
+    };
+    let mut covered = data.clone();
+    covered.push(frame_end);
+    let valid = if use_crc32 {
+        crc32(&covered).to_le_bytes()[..] == crc[..]
+    } else {
+        crc16(&covered).to_be_bytes()[..] == crc[..]
+    };
+    Ok(valid.then_some((data, frame_end)))
+}
+
+/// Map line noise (timeouts, bad escapes) to None; cancellation and port
+/// failures still abort the transfer.
+fn recoverable<T>(result: io::Result<T>) -> io::Result<Option<T>> {
+    match result {
+        Ok(v) => Ok(Some(v)),
+        Err(e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::TimedOut | io::ErrorKind::InvalidData
+            ) =>
+        {
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// CRC-32 (IEEE 802.3), as used by ZMODEM's 32-bit frames.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xFFFF_FFFF;
+    for &b in data {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::transfer::tests::exchange;
+    use std::io::Cursor;
+
+    // Bytes that must never appear bare on the line
+    const ESCAPED: [u8; 9] = [ZDLE, 0x10, 0x90, XON, 0x91, XOFF, 0x93, b'\r', 0x8d];
+
+    fn written(f: impl FnOnce(&mut Cursor<Vec<u8>>) -> io::Result<()>) -> Vec<u8> {
+        let mut port = Cursor::new(Vec::new());
+        f(&mut port).unwrap();
+        port.into_inner()
+    }
+
+    #[test]
+    fn crc32_matches_the_ieee_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn escaping_hides_control_bytes() {
+        let data: Vec<u8> = (0..=255).collect();
+        let mut out = Vec::new();
+        escape_into(&data, &mut out);
+        assert_eq!(out.len(), data.len() + ESCAPED.len());
+        for (i, &b) in out.iter().enumerate() {
+            if b == ZDLE {
+                assert!(ESCAPED.contains(&(out[i + 1] ^ 0x40)));
+            } else {
+                assert!(!ESCAPED.contains(&b));
+            }
+        }
+    }
+
+    #[test]
+    fn subpackets_round_trip_with_both_crcs() {
+        let cancel = AtomicBool::new(false);
+        let data: Vec<u8> = (0..=255).chain(0..=255).collect();
+        for use_crc32 in [false, true] {
+            let raw = written(|port| send_subpacket(port, &data, ZCRCW, use_crc32, &cancel));
+            let (payload, frame_end) = read_subpacket(&mut Cursor::new(raw), use_crc32, &cancel)
+                .unwrap()
+                .unwrap();
+            assert_eq!(payload, data);
+            assert_eq!(frame_end, ZCRCW);
+        }
+    }
+
+    #[test]
+    fn corrupted_subpackets_are_rejected() {
+        let cancel = AtomicBool::new(false);
+        let mut raw = written(|port| send_subpacket(port, b"hello", ZCRCE, true, &cancel));
+        raw[1] ^= 0x01;
+        assert!(read_subpacket(&mut Cursor::new(raw), true, &cancel)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn headers_round_trip_in_every_format() {
+        let cancel = AtomicBool::new(false);
+        // A position whose bytes need escaping in binary headers
+        let pos = pos_data(0x1311_0d18);
+        let frames = [
+            (
+                written(|port| send_hex_header(port, ZRPOS, pos, &cancel)),
+                false,
+            ),
+            (
+                written(|port| send_bin_header(port, ZRPOS, pos, false, &cancel)),
+                false,
+            ),
+            (
+                written(|port| send_bin_header(port, ZRPOS, pos, true, &cancel)),
+                true,
+            ),
+        ];
+        for (raw, crc32) in frames {
+            let header = read_header(&mut Cursor::new(raw), REPLY_TIMEOUT, &cancel)
+                .unwrap()
+                .unwrap();
+            assert_eq!(header.kind, ZRPOS);
+            assert_eq!(header.position(), 0x1311_0d18);
+            assert_eq!(header.crc32, crc32);
+        }
+    }
+
+    #[test]
+    fn hex_headers_start_with_the_init_sequence() {
+        let cancel = AtomicBool::new(false);
+        let raw = written(|port| send_hex_header(port, ZRQINIT, [0; 4], &cancel));
+        assert!(raw.starts_with(INIT_SEQUENCE));
+    }
+
+    #[test]
+    fn garbled_headers_are_rejected() {
+        let cancel = AtomicBool::new(false);
+        let mut raw = written(|port| send_bin_header(port, ZDATA, pos_data(42), true, &cancel));
+        raw[4] ^= 0x01;
+        assert!(read_header(&mut Cursor::new(raw), REPLY_TIMEOUT, &cancel)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn parse_file_info_reads_name_and_size() {
+        assert_eq!(
+            parse_file_info(b"log.bin\x003000 0 100644\0"),
+            ("log.bin".to_string(), Some(3000))
+        );
+        assert_eq!(parse_file_info(b"log.bin\0"), ("log.bin".to_string(), None));
+    }
+
+    #[test]
+    fn zmodem_round_trip() {
+        // Several subpackets, full of bytes that need escaping
+        let data: Vec<u8> = (0..=255).cycle().take(3000).collect();
+        let sent = data.clone();
+        let (sent_ok, files) = exchange(
+            move |port| send(port, "log.bin", &sent, &AtomicBool::new(false), |_, _| {}).is_ok(),
+            |port| receive(port, &AtomicBool::new(false), |_, _| {}).unwrap(),
+        );
+        assert!(sent_ok);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "log.bin");
+        assert_eq!(files[0].data, data);
+    }
+}
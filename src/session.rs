@@ -0,0 +1,56 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{GridSetting, SplitSetting, ViewSetting};
+
+/// UI state carried over between runs, in `<state dir>/serialtui/session.toml`.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Session {
+    pub view: Option<ViewSetting>,
+    pub split: Option<SplitSetting>,
+    pub input_height: Option<u16>,
+    /// Port and profile of the tab that was active, picked first next time
+    pub active_port: Option<String>,
+    pub active_profile: Option<String>,
+    /// Grid shape picked from the View menu; a near-square grid if unset
+    pub grid: Option<GridSetting>,
+    /// Tick and read settings from Tools → Preferences, over the config's
+    pub tick_ms: Option<u64>,
+    pub read_buffer_bytes: Option<usize>,
+    pub read_timeout_ms: Option<u64>,
+    /// Favorites marked in the port list, over the config's
+    pub favorite_ports: Option<Vec<String>>,
+}
+
+impl Session {
+    pub fn path() -> Option<PathBuf> {
+        dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .map(|d| d.join("serialtui").join("session.toml"))
+    }
+
+    /// Load the last session; none saved yet yields the defaults.
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("reading {}", path.display()))?;
+        toml::from_str(&text).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path().context("no state directory on this system")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        let text = toml::to_string(self)?;
+        std::fs::write(&path, text).with_context(|| format!("writing {}", path.display()))
+    }
+}
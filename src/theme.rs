@@ -0,0 +1,151 @@
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::config::{ThemeColors, ThemeSetting};
+use crate::highlight::parse_color;
+
+/// UI colors by role, from the `theme` config and its `[colors]` overrides.
+/// Scrollback text keeps the device's ANSI colors and the `[[highlight]]`
+/// rules.
+#[derive(Clone)]
+pub struct Theme {
+    /// Plain text in dialogs and lists
+    pub text: Color,
+    /// Hints, inactive borders, unavailable entries
+    pub dim: Color,
+    /// Active borders, key hints, progress, info messages
+    pub accent: Color,
+    /// Selected list entries and the active tab
+    pub selected_fg: Color,
+    pub selected_bg: Color,
+    /// Menu bar, dropdowns, input fields and the status bar hints
+    pub menu_fg: Color,
+    pub menu_bg: Color,
+    /// The open menu's title, the traffic counters and modem lines
+    pub menu_open_fg: Color,
+    pub menu_open_bg: Color,
+    pub dialog_border: Color,
+    /// Background tabs with an alert, warnings, busy and paused states
+    pub alert: Color,
+    /// Dropped connections and errors
+    pub error: Color,
+    pub success: Color,
+    /// Passthrough mode and the filter indicator
+    pub passthrough: Color,
+    /// Search matches, and the one the view is on
+    pub matched: Color,
+    pub current_match: Color,
+    /// Text on accent, alert, success, passthrough and match backgrounds
+    pub on_accent: Color,
+    /// Plot series, in the order the series first appeared
+    pub series: [Color; 6],
+}
+
+impl Theme {
+    /// For dark terminal backgrounds.
+    pub const DARK: Theme = Theme {
+        text: Color::White,
+        dim: Color::DarkGray,
+        accent: Color::Cyan,
+        selected_fg: Color::Black,
+        selected_bg: Color::Cyan,
+        menu_fg: Color::Black,
+        menu_bg: Color::White,
+        menu_open_fg: Color::White,
+        menu_open_bg: Color::DarkGray,
+        dialog_border: Color::Yellow,
+        alert: Color::Yellow,
+        error: Color::Red,
+        success: Color::Green,
+        passthrough: Color::Magenta,
+        matched: Color::Yellow,
+        current_match: Color::LightRed,
+        on_accent: Color::Black,
+        series: [
+            Color::Cyan,
+            Color::Yellow,
+            Color::Magenta,
+            Color::Green,
+            Color::LightRed,
+            Color::LightBlue,
+        ],
+    };
+
+    /// For light terminal backgrounds.
+    pub const LIGHT: Theme = Theme {
+        text: Color::Black,
+        dim: Color::Gray,
+        accent: Color::Blue,
+        selected_fg: Color::White,
+        selected_bg: Color::Blue,
+        menu_fg: Color::Black,
+        menu_bg: Color::Gray,
+        menu_open_fg: Color::White,
+        menu_open_bg: Color::DarkGray,
+        dialog_border: Color::Magenta,
+        alert: Color::Indexed(136),
+        error: Color::Red,
+        success: Color::Green,
+        passthrough: Color::Magenta,
+        matched: Color::Indexed(136),
+        current_match: Color::Red,
+        on_accent: Color::White,
+        series: [
+            Color::Blue,
+            Color::Indexed(136),
+            Color::Magenta,
+            Color::Green,
+            Color::Red,
+            Color::Cyan,
+        ],
+    };
+
+    pub fn new(setting: ThemeSetting, colors: &ThemeColors) -> Result<Self, String> {
+        let mut theme = match setting {
+            ThemeSetting::Dark => Self::DARK,
+            ThemeSetting::Light => Self::LIGHT,
+        };
+        let slots = [
+            (&mut theme.text, &colors.text),
+            (&mut theme.dim, &colors.dim),
+            (&mut theme.accent, &colors.accent),
+            (&mut theme.selected_fg, &colors.selected_fg),
+            (&mut theme.selected_bg, &colors.selected_bg),
+            (&mut theme.menu_fg, &colors.menu_fg),
+            (&mut theme.menu_bg, &colors.menu_bg),
+            (&mut theme.menu_open_fg, &colors.menu_open_fg),
+            (&mut theme.menu_open_bg, &colors.menu_open_bg),
+            (&mut theme.dialog_border, &colors.dialog_border),
+            (&mut theme.alert, &colors.alert),
+            (&mut theme.error, &colors.error),
+            (&mut theme.success, &colors.success),
+            (&mut theme.passthrough, &colors.passthrough),
+            (&mut theme.matched, &colors.matched),
+            (&mut theme.current_match, &colors.current_match),
+            (&mut theme.on_accent, &colors.on_accent),
+        ];
+        for (slot, name) in slots {
+            if let Some(name) = name {
+                *slot = parse_color(name).map_err(|e| format!("colors: {}", e))?;
+            }
+        }
+        // Fewer than six series colors repeat
+        if let Some(names) = colors.series.as_deref().filter(|n| !n.is_empty()) {
+            for (slot, name) in theme.series.iter_mut().zip(names.iter().cycle()) {
+                *slot = parse_color(name).map_err(|e| format!("colors: {}", e))?;
+            }
+        }
+        Ok(theme)
+    }
+
+    /// The highlighted entry of a list.
+    pub fn selected(&self) -> Style {
+        Style::default()
+            .fg(self.selected_fg)
+            .bg(self.selected_bg)
+            .add_modifier(Modifier::BOLD)
+    }
+
+    pub fn menu(&self) -> Style {
+        Style::default().fg(self.menu_fg).bg(self.menu_bg)
+    }
+}
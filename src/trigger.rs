@@ -0,0 +1,108 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+/// Send `send` when a received line matches `pattern`, e.g. answer `login:`
+/// with a user name.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct TriggerRule {
+    pub pattern: String,
+    /// Written with `\r`, `\n`, `\t` and `\xNN` escapes
+    pub send: String,
+    #[serde(default)]
+    pub delay_ms: u64,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct TriggerFile {
+    #[serde(default, rename = "trigger")]
+    triggers: Vec<TriggerRule>,
+}
+
+/// Trigger rules from `<config dir>/serialtui/triggers.toml`, kept apart from
+/// the config so the rule editor can rewrite them without losing its comments.
+#[derive(Default)]
+pub struct Triggers {
+    pub rules: Vec<TriggerRule>,
+    regexes: Vec<Regex>,
+}
+
+impl Triggers {
+    pub fn path() -> Option<PathBuf> {
+        Config::path().and_then(|p| p.parent().map(|d| d.join("triggers.toml")))
+    }
+
+    /// Load the saved rules; none saved yet yields no rules.
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("reading {}", path.display()))?;
+        let file: TriggerFile =
+            toml::from_str(&text).with_context(|| format!("parsing {}", path.display()))?;
+        let mut triggers = Self::default();
+        for rule in file.triggers {
+            triggers
+                .set(None, rule)
+                .map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+        }
+        Ok(triggers)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path().context("no config directory on this system")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        let file = TriggerFile {
+            triggers: self.rules.clone(),
+        };
+        let text = toml::to_string(&file)?;
+        std::fs::write(&path, text).with_context(|| format!("writing {}", path.display()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Replace rule `index`, or add the rule if `index` is None.
+    pub fn set(&mut self, index: Option<usize>, rule: TriggerRule) -> Result<(), String> {
+        let regex = Regex::new(&rule.pattern)
+            .map_err(|e| format!("trigger \"{}\": {}", rule.pattern, e))?;
+        match index {
+            Some(i) if i < self.rules.len() => {
+                self.rules[i] = rule;
+                self.regexes[i] = regex;
+            }
+            _ => {
+                self.rules.push(rule);
+                self.regexes.push(regex);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.rules.len() {
+            self.rules.remove(index);
+            self.regexes.remove(index);
+        }
+    }
+
+    /// Indexes of the rules matching `text`.
+    pub fn matching<'a>(&'a self, text: &'a str) -> impl Iterator<Item = usize> + 'a {
+        self.regexes
+            .iter()
+            .enumerate()
+            .filter(move |(_, regex)| regex.is_match(text))
+            .map(|(i, _)| i)
+    }
+}
@@ -0,0 +1,219 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+use super::help::overlay_area;
+use crate::app::App;
+use crate::i18n;
+use crate::input::key_to_bytes;
+use crate::keymap::Action;
+
+/// Mnemonics of the control characters 0x00–0x1F.
+const CONTROL_NAMES: [&str; 32] = [
+    "NUL", "SOH", "STX", "ETX", "EOT", "ENQ", "ACK", "BEL", "BS", "HT", "LF", "VT", "FF", "CR",
+    "SO", "SI", "DLE", "DC1", "DC2", "DC3", "DC4", "NAK", "SYN", "ETB", "CAN", "EM", "SUB", "ESC",
+    "FS", "GS", "RS", "US",
+];
+
+/// Columns of the printable characters' table.
+const PRINTABLE_COLUMNS: usize = 4;
+
+/// Width of the key column, as in the help.
+const KEY_WIDTH: usize = 22;
+
+/// Full-screen ASCII table with the Ctrl chords and Send bar escapes of the
+/// control characters and the bytes passthrough sends for special keys,
+/// scrolled down `scroll` lines.
+pub fn render(app: &App, frame: &mut Frame, scroll: usize) {
+    let area = overlay_area(frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(i18n::t("ascii.title"))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.dialog_border));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [text_area, hint_area] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(inner);
+    let lines = lines(app);
+    let scroll = scroll.min(lines.len().saturating_sub(text_area.height as usize));
+    frame.render_widget(Paragraph::new(lines).scroll((scroll as u16, 0)), text_area);
+
+    let hint =
+        Paragraph::new(Line::raw(i18n::t("help.hint"))).style(Style::default().fg(app.theme.dim));
+    frame.render_widget(hint, hint_area);
+}
+
+/// How far the table can scroll at the current terminal size.
+pub fn max_scroll(app: &App) -> usize {
+    let area = overlay_area(Rect::new(0, 0, app.terminal_cols, app.terminal_rows));
+    // Borders and the hint row
+    let visible = area.height.saturating_sub(3) as usize;
+    lines(app).len().saturating_sub(visible)
+}
+
+/// `ESC [15~`: a byte sequence with control characters (and space) by their
+/// mnemonics, set apart from the characters around them.
+fn spelled(bytes: &[u8]) -> String {
+    let mut text = String::new();
+    for &b in bytes {
+        let name = match b {
+            0x00..=0x1f => CONTROL_NAMES[b as usize],
+            b' ' => "SP",
+            0x7f => "DEL",
+            _ => {
+                text.push(char::from(b));
+                continue;
+            }
+        };
+        if !text.is_empty() && !text.ends_with(' ') {
+            text.push(' ');
+        }
+        text.push_str(name);
+        text.push(' ');
+    }
+    text.trim_end().to_string()
+}
+
+fn lines(app: &App) -> Vec<Line<'static>> {
+    let text = Style::default().fg(app.theme.text);
+    let dim = Style::default().fg(app.theme.dim);
+    let accent = Style::default().fg(app.theme.accent);
+    let mut lines = Vec::new();
+    let heading = |lines: &mut Vec<Line<'static>>, key: &'static str| {
+        if !lines.is_empty() {
+            lines.push(Line::raw(""));
+        }
+        lines.push(Line::styled(
+            i18n::t(key),
+            Style::default()
+                .fg(app.theme.accent)
+                .add_modifier(Modifier::BOLD),
+        ));
+    };
+
+    heading(&mut lines, "ascii.controls");
+    lines.push(Line::styled(
+        format!(
+            "  {:>3}  {:<3}  {:<4}  {:<8}  {}",
+            "Dec",
+            "Hex",
+            "",
+            i18n::t("ascii.key"),
+            i18n::t("ascii.send_bar")
+        ),
+        dim,
+    ));
+    let ctrl = i18n::t("key.ctrl");
+    for b in (0x00..=0x1fu8).chain([0x7f]) {
+        let (name, chord) = match b {
+            0x7f => ("DEL", format!("{}+?", ctrl)),
+            _ => (
+                CONTROL_NAMES[b as usize],
+                format!("{}+{}", ctrl, char::from(b | 0x40)),
+            ),
+        };
+        let escape = match b {
+            b'\r' => "\\r".to_string(),
+            b'\n' => "\\n".to_string(),
+            b'\t' => "\\t".to_string(),
+            _ => format!("\\x{:02x}", b),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {:>3}  {:02X}   ", b, b), text),
+            Span::styled(format!("{:<4}", name), accent),
+            Span::styled(format!("  {:<8}  {}", chord, escape), text),
+        ]));
+    }
+
+    heading(&mut lines, "ascii.printable");
+    let printable: Vec<u8> = (0x20..0x7f).collect();
+    let rows = printable.len().div_ceil(PRINTABLE_COLUMNS);
+    for row in 0..rows {
+        let mut spans = vec![Span::raw(" ")];
+        for &b in printable.iter().skip(row).step_by(rows) {
+            spans.push(Span::styled(format!(" {:>3}  {:02X}  ", b, b), text));
+            spans.push(Span::styled(format!("{:<6}", spelled(&[b])), accent));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    let entry = |lines: &mut Vec<Line<'static>>, keys: String, bytes: &[u8]| {
+        let hex: Vec<String> = bytes.iter().map(|b| format!("{:02X}", b)).collect();
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {:<width$} ", keys, width = KEY_WIDTH), text),
+            Span::styled(format!("{:<12}", spelled(bytes)), accent),
+            Span::styled(hex.join(" "), text),
+        ]));
+    };
+
+    heading(&mut lines, "ascii.passthrough");
+    let special = [
+        ("Enter", KeyCode::Enter),
+        ("Backspace", KeyCode::Backspace),
+        ("Tab", KeyCode::Tab),
+        ("Shift+Tab", KeyCode::BackTab),
+        ("Esc", KeyCode::Esc),
+        ("↑", KeyCode::Up),
+        ("↓", KeyCode::Down),
+        ("→", KeyCode::Right),
+        ("←", KeyCode::Left),
+        ("Home", KeyCode::Home),
+        ("End", KeyCode::End),
+        ("Insert", KeyCode::Insert),
+        ("Delete", KeyCode::Delete),
+        ("PgUp", KeyCode::PageUp),
+        ("PgDn", KeyCode::PageDown),
+    ];
+    let function_keys = (1..=12).map(|n| (format!("F{}", n), KeyCode::F(n)));
+    for (label, code) in special
+        .into_iter()
+        .map(|(label, code)| (label.to_string(), code))
+        .chain(function_keys)
+    {
+        if let Some(bytes) = key_to_bytes(KeyEvent::new(code, KeyModifiers::NONE)) {
+            entry(&mut lines, label, &bytes);
+        }
+    }
+    let alt = format!("{}+…", i18n::t("key.alt"));
+    lines.push(Line::from(vec![
+        Span::styled(format!("  {:<width$} ", alt, width = KEY_WIDTH), text),
+        Span::raw(i18n::t("ascii.alt_prefix")),
+    ]));
+    if let Some(label) = app.keymap.label(Action::Passthrough) {
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {:<width$} ", label, width = KEY_WIDTH), text),
+            Span::raw(i18n::t("help.leave_passthrough")),
+        ]));
+    }
+
+    heading(&mut lines, "ascii.escapes");
+    for (escape, key) in [
+        ("\\r  \\n  \\t", "ascii.escape_controls"),
+        ("\\xNN", "ascii.escape_hex"),
+        ("\\\\", "ascii.escape_backslash"),
+    ] {
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {:<width$} ", escape, width = KEY_WIDTH), accent),
+            Span::raw(i18n::t(key)),
+        ]));
+    }
+    for (action, key) in [
+        (Action::LiteralInput, "help.literal_input"),
+        (Action::HexInput, "help.hex_input"),
+    ] {
+        if let Some(label) = app.keymap.label(action) {
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {:<width$} ", label, width = KEY_WIDTH), text),
+                Span::raw(i18n::t(key)),
+            ]));
+        }
+    }
+
+    lines
+}
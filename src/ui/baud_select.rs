@@ -1,35 +1,23 @@
 use ratatui::layout::{Constraint, Layout, Rect};
-use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::Line;
 use ratatui::widgets::{Block, Borders, List, ListItem, ListState};
 use ratatui::Frame;
 
 use crate::app::{App, BAUD_RATES};
+use crate::i18n;
 
 pub fn render(app: &App, frame: &mut Frame, area: Rect) {
     let [main_area, status_area] =
         Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(area);
 
-    let port_name = app
-        .available_ports
-        .get(app.selected_port_index)
-        .map(|p| p.name.as_str())
-        .unwrap_or("?");
+    let port_name = app.wizard_port_name();
 
-    let items: Vec<ListItem> = BAUD_RATES
-        .iter()
-        .map(|b| ListItem::new(Line::raw(b.to_string())))
-        .collect();
+    let items = baud_items();
 
-    let title = format!(" Baud Rate for {} ", port_name);
+    let title = i18n::tf("screen.baud_rate", &[&port_name]);
     let list = List::new(items)
         .block(Block::default().title(title).borders(Borders::ALL))
-        .highlight_style(
-            Style::default()
-                .fg(Color::Black)
-                .bg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )
+        .highlight_style(app.theme.selected())
         .highlight_symbol("▶ ");
 
     let mut state = ListState::default().with_selected(Some(app.selected_baud_index));
@@ -40,20 +28,21 @@ pub fn render(app: &App, frame: &mut Frame, area: Rect) {
 
 /// Render just the baud list (no status bar, no outer block) for inline use in tabs/grid.
 pub fn render_content(app: &App, frame: &mut Frame, area: Rect) {
-    let items: Vec<ListItem> = BAUD_RATES
-        .iter()
-        .map(|b| ListItem::new(Line::raw(b.to_string())))
-        .collect();
+    let items = baud_items();
 
     let list = List::new(items)
-        .highlight_style(
-            Style::default()
-                .fg(Color::Black)
-                .bg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )
+        .highlight_style(app.theme.selected())
         .highlight_symbol("▶ ");
 
     let mut state = ListState::default().with_selected(Some(app.selected_baud_index));
     frame.render_stateful_widget(list, area, &mut state);
 }
+
+/// The rates, then "Auto" to detect the rate once connected.
+fn baud_items() -> Vec<ListItem<'static>> {
+    BAUD_RATES
+        .iter()
+        .map(|b| ListItem::new(Line::raw(b.to_string())))
+        .chain([ListItem::new(Line::raw(i18n::t("screen.auto_baud")))])
+        .collect()
+}
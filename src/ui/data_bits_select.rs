@@ -1,35 +1,26 @@
 use ratatui::layout::{Constraint, Layout, Rect};
-use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::Line;
 use ratatui::widgets::{Block, Borders, List, ListItem, ListState};
 use ratatui::Frame;
 
 use crate::app::{App, DATA_BITS_OPTIONS};
+use crate::i18n;
 
 pub fn render(app: &App, frame: &mut Frame, area: Rect) {
     let [main_area, status_area] =
         Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(area);
 
-    let port_name = app
-        .available_ports
-        .get(app.selected_port_index)
-        .map(|p| p.name.as_str())
-        .unwrap_or("?");
+    let port_name = app.wizard_port_name();
 
     let items: Vec<ListItem> = DATA_BITS_OPTIONS
         .iter()
         .map(|(label, _)| ListItem::new(Line::raw(*label)))
         .collect();
 
-    let title = format!(" Data Bits for {} ", port_name);
+    let title = i18n::tf("screen.data_bits", &[&port_name]);
     let list = List::new(items)
         .block(Block::default().title(title).borders(Borders::ALL))
-        .highlight_style(
-            Style::default()
-                .fg(Color::Black)
-                .bg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )
+        .highlight_style(app.theme.selected())
         .highlight_symbol("▶ ");
 
     let mut state = ListState::default().with_selected(Some(app.selected_data_bits_index));
@@ -46,12 +37,7 @@ pub fn render_content(app: &App, frame: &mut Frame, area: Rect) {
         .collect();
 
     let list = List::new(items)
-        .highlight_style(
-            Style::default()
-                .fg(Color::Black)
-                .bg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )
+        .highlight_style(app.theme.selected())
         .highlight_symbol("▶ ");
 
     let mut state = ListState::default().with_selected(Some(app.selected_data_bits_index));
@@ -1,35 +1,583 @@
 use ratatui::layout::{Constraint, Flex, Layout, Rect};
-use ratatui::style::{Color, Modifier, Style};
+use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::widgets::{
+    Block, Borders, Clear, Gauge, List, ListItem, ListState, Padding, Paragraph,
+};
 use ratatui::Frame;
 
-use crate::app::Dialog;
+use crate::app::{
+    option_label, App, CaptureField, Dialog, FileAction, GeneratorField, GridShape, TriggerField,
+    DATA_BITS_OPTIONS, PARITY_OPTIONS, STOP_BITS_OPTIONS,
+};
+use crate::checksum;
+use crate::config::Config;
+use crate::export::{ExportFormat, ExportRange};
+use crate::hex::{self, ArrayFormat};
+use crate::i18n;
+use crate::serial::{Connection, PatternKind, Protocol, DECODERS};
+use crate::theme::Theme;
 
-pub fn render(dialog: &Dialog, frame: &mut Frame) {
+pub fn render(app: &App, dialog: &Dialog, frame: &mut Frame) {
+    let theme = &app.theme;
     match dialog {
         Dialog::ConfirmCloseConnection => {
             render_confirm(
+                theme,
                 frame,
-                " Close Connection ",
-                "Save session before closing?",
-                "[Y]es  [N]o  [Esc] Cancel",
+                i18n::t("dialog.close_title"),
+                i18n::t("dialog.close_body"),
+                i18n::t("dialog.yes_no_cancel"),
             );
         }
         Dialog::ConfirmQuit => {
             render_confirm(
+                theme,
                 frame,
-                " Quit ",
-                "Export all open sessions before quitting?",
-                "[Y]es  [N]o  [Esc] Cancel",
+                i18n::t("dialog.quit_title"),
+                i18n::t("dialog.quit_body"),
+                i18n::t("dialog.yes_no_cancel"),
             );
         }
         Dialog::FileNamePrompt {
             filename,
             cursor_pos,
+            action,
             ..
         } => {
-            render_filename_prompt(frame, filename, *cursor_pos);
+            let title = match action {
+                FileAction::Export(export) => {
+                    &i18n::tf("dialog.export_title", &[&export.format.name()])
+                }
+                FileAction::ExportStats => i18n::t("dialog.stats_title"),
+                FileAction::LiveStream => i18n::t("dialog.live_stream_title"),
+                FileAction::Diff => i18n::t("dialog.diff_file_title"),
+                FileAction::Replay(speed) => &i18n::tf("dialog.replay_title", &[speed]),
+                FileAction::SendFile => i18n::t("dialog.send_file_title"),
+                FileAction::Upload(p) => &i18n::tf("dialog.upload_title", &[&p.name()]),
+                FileAction::Download(p) if p.receives_into_folder() => {
+                    &i18n::tf("dialog.download_folder_title", &[&p.name()])
+                }
+                FileAction::Download(p) => &i18n::tf("dialog.download_title", &[&p.name()]),
+            };
+            let hint = match action {
+                FileAction::Export(export) => match export.format {
+                    ExportFormat::Text => i18n::t("dialog.export_hint"),
+                    format if format.has_timestamps() => i18n::t("dialog.export_times_hint"),
+                    _ => i18n::t("dialog.export_raw_hint"),
+                },
+                FileAction::Replay(_) => i18n::t("dialog.replay_hint"),
+                _ => i18n::t("dialog.filename_hint"),
+            };
+            let detail = match action {
+                FileAction::Export(export) if export.format == ExportFormat::Text => {
+                    Some(match export.range {
+                        ExportRange::All => i18n::t("dialog.range_all").to_string(),
+                        ExportRange::Screen => i18n::t("dialog.range_screen").to_string(),
+                        ExportRange::Notes => i18n::t("dialog.range_notes").to_string(),
+                        ExportRange::Last(n) => i18n::tf("dialog.range_last", &[&n]),
+                    })
+                }
+                FileAction::Export(export) if export.format.has_timestamps() => {
+                    let check = if export.timestamps { "x" } else { " " };
+                    Some(i18n::tf("dialog.export_timestamps", &[&check]))
+                }
+                FileAction::Export(_) => Some(i18n::t("dialog.range_capture").to_string()),
+                _ => None,
+            };
+            render_filename_prompt(
+                theme,
+                frame,
+                title,
+                filename,
+                *cursor_pos,
+                detail.as_deref(),
+                hint,
+            );
+        }
+        Dialog::SearchPrompt { query, cursor_pos } => {
+            render_query_prompt(
+                theme,
+                frame,
+                i18n::t("dialog.search_title"),
+                query,
+                *cursor_pos,
+                i18n::t("dialog.search_hint"),
+            );
+        }
+        Dialog::FilterPrompt { query, cursor_pos } => {
+            render_query_prompt(
+                theme,
+                frame,
+                i18n::t("dialog.filter_title"),
+                query,
+                *cursor_pos,
+                i18n::t("dialog.filter_hint"),
+            );
+        }
+        Dialog::WatchPrompt { query, cursor_pos } => {
+            render_query_prompt(
+                theme,
+                frame,
+                i18n::t("dialog.watch_title"),
+                query,
+                *cursor_pos,
+                i18n::t("dialog.watch_hint"),
+            );
+        }
+        Dialog::LinkTestPrompt {
+            duration,
+            cursor_pos,
+            ..
+        } => {
+            render_query_prompt(
+                theme,
+                frame,
+                i18n::t("dialog.link_test_title"),
+                duration,
+                *cursor_pos,
+                i18n::t("dialog.link_test_hint"),
+            );
+        }
+        Dialog::FieldsPrompt { query, cursor_pos } => {
+            render_query_prompt(
+                theme,
+                frame,
+                i18n::t("dialog.fields_title"),
+                query,
+                *cursor_pos,
+                i18n::t("dialog.fields_hint"),
+            );
+        }
+        Dialog::NotePrompt { text, cursor_pos } => {
+            render_query_prompt(
+                theme,
+                frame,
+                i18n::t("dialog.note_title"),
+                text,
+                *cursor_pos,
+                i18n::t("dialog.note_hint"),
+            );
+        }
+        Dialog::HostPrompt { host, cursor_pos } => {
+            render_query_prompt(
+                theme,
+                frame,
+                i18n::t("dialog.host_title"),
+                host,
+                *cursor_pos,
+                i18n::t("dialog.host_hint"),
+            );
+        }
+        Dialog::PathPrompt { path, cursor_pos } => {
+            render_query_prompt(
+                theme,
+                frame,
+                i18n::t("dialog.path_title"),
+                path,
+                *cursor_pos,
+                i18n::t("dialog.path_hint"),
+            );
+        }
+        Dialog::SharePrompt {
+            address,
+            cursor_pos,
+        } => {
+            render_query_prompt(
+                theme,
+                frame,
+                i18n::t("dialog.share_title"),
+                address,
+                *cursor_pos,
+                i18n::t("dialog.share_hint"),
+            );
+        }
+        Dialog::ChecksumPrompt { query, cursor_pos } => {
+            render_checksum(theme, frame, query, *cursor_pos);
+        }
+        Dialog::BridgeMenu {
+            connection_idx,
+            targets,
+            selected,
+            sniff,
+        } => {
+            let port = |id: usize| {
+                app.connections
+                    .iter()
+                    .find(|c| c.id == id)
+                    .map_or("", |c| c.port_name.as_str())
+            };
+            let items: Vec<String> = targets
+                .iter()
+                .map(|&(id, both_ways)| {
+                    let key = match (sniff, both_ways) {
+                        (false, false) => "dialog.bridge_one_way",
+                        (false, true) => "dialog.bridge_both_ways",
+                        (true, false) => "dialog.sniff_taps",
+                        (true, true) => "dialog.sniff_passthrough",
+                    };
+                    i18n::tf(key, &[&port(id)])
+                })
+                .collect();
+            let source = app
+                .connections
+                .get(*connection_idx)
+                .map_or("", |c| c.port_name.as_str());
+            let (title, hint) = if *sniff {
+                ("dialog.sniff_title", "dialog.sniff_hint")
+            } else {
+                ("dialog.bridge_title", "dialog.bridge_hint")
+            };
+            render_list(
+                theme,
+                frame,
+                &i18n::tf(title, &[&source]),
+                &items,
+                *selected,
+                i18n::t(hint),
+            );
+        }
+        Dialog::CommandMenu {
+            commands, selected, ..
+        } => {
+            let items: Vec<String> = commands
+                .iter()
+                .map(|c| format!("{} — {}", c.name, c.run))
+                .collect();
+            render_list(
+                theme,
+                frame,
+                i18n::t("dialog.command_title"),
+                &items,
+                *selected,
+                i18n::t("dialog.command_hint"),
+            );
+        }
+        Dialog::ProtocolMenu {
+            upload, selected, ..
+        } => {
+            let items: Vec<String> = Protocol::ALL.iter().map(|p| p.name().to_string()).collect();
+            let title = if *upload {
+                i18n::t("dialog.upload")
+            } else {
+                i18n::t("dialog.download")
+            };
+            render_list(
+                theme,
+                frame,
+                title,
+                &items,
+                *selected,
+                i18n::t("dialog.protocol_hint"),
+            );
+        }
+        Dialog::ResendMenu {
+            connection_idx,
+            selected,
+        } => {
+            let history = app.connections.get(*connection_idx).map(|c| &c.history);
+            let items: Vec<String> = history
+                .into_iter()
+                .flatten()
+                .rev()
+                .enumerate()
+                .map(|(i, entry)| format!("{:>2}  {}", i + 1, entry.text))
+                .collect();
+            let title = match selected {
+                0 => i18n::t("dialog.resend_one").to_string(),
+                n => i18n::tf("dialog.resend_many", &[&(n + 1)]),
+            };
+            render_list(
+                theme,
+                frame,
+                &title,
+                &items,
+                *selected,
+                i18n::t("dialog.resend_hint"),
+            );
+        }
+        Dialog::CopyAs { bytes, selected } => {
+            let items: Vec<String> = ArrayFormat::ALL
+                .iter()
+                .map(|f| f.name().to_string())
+                .collect();
+            let title = i18n::tf("dialog.copy_title", &[&bytes.len()]);
+            render_list(
+                theme,
+                frame,
+                &title,
+                &items,
+                *selected,
+                i18n::t("dialog.copy_hint"),
+            );
+        }
+        Dialog::GridMenu { selected } => {
+            let items: Vec<String> = GridShape::ALL
+                .iter()
+                .map(|shape| match shape {
+                    GridShape::Auto => i18n::t("dialog.grid_auto").to_string(),
+                    GridShape::Columns(n) => i18n::tf("dialog.grid_columns", &[n]),
+                    GridShape::Rows(n) => i18n::tf("dialog.grid_rows", &[n]),
+                })
+                .collect();
+            render_list(
+                theme,
+                frame,
+                i18n::t("dialog.grid_title"),
+                &items,
+                *selected,
+                i18n::t("dialog.grid_hint"),
+            );
+        }
+        Dialog::Preferences { selected, prefs } => {
+            let items = [
+                i18n::tf("dialog.prefs_tick", &[&prefs.tick_ms]),
+                i18n::tf("dialog.prefs_read_buffer", &[&prefs.read_buffer]),
+                i18n::tf("dialog.prefs_read_timeout", &[&prefs.read_timeout_ms]),
+            ];
+            render_list(
+                theme,
+                frame,
+                i18n::t("dialog.prefs_title"),
+                &items,
+                *selected,
+                i18n::t("dialog.prefs_hint"),
+            );
+        }
+        Dialog::LineSetup {
+            connection_idx,
+            selected,
+            line,
+        } => {
+            let items = [
+                i18n::tf("dialog.line_baud", &[&line.baud_rate]),
+                i18n::tf(
+                    "dialog.line_data_bits",
+                    &[&option_label(DATA_BITS_OPTIONS, line.data_bits)],
+                ),
+                i18n::tf(
+                    "dialog.line_parity",
+                    &[&i18n::t(option_label(PARITY_OPTIONS, line.parity))],
+                ),
+                i18n::tf(
+                    "dialog.line_stop_bits",
+                    &[&option_label(STOP_BITS_OPTIONS, line.stop_bits)],
+                ),
+            ];
+            let port = app
+                .connections
+                .get(*connection_idx)
+                .map_or("", |c| c.port_name.as_str());
+            render_list(
+                theme,
+                frame,
+                &i18n::tf("dialog.line_title", &[&port]),
+                &items,
+                *selected,
+                i18n::t("dialog.line_hint"),
+            );
+        }
+        Dialog::DecoderMenu {
+            connection_idx,
+            selected,
+        } => {
+            let items: Vec<String> = DECODERS.iter().map(|d| d.label.to_string()).collect();
+            let port = app
+                .connections
+                .get(*connection_idx)
+                .map_or("", |c| c.port_name.as_str());
+            render_list(
+                theme,
+                frame,
+                &i18n::tf("dialog.decoder_title", &[&port]),
+                &items,
+                *selected,
+                i18n::t("dialog.decoder_hint"),
+            );
+        }
+        Dialog::TriggerList { selected } => {
+            let mut items: Vec<String> = app
+                .triggers
+                .rules
+                .iter()
+                .map(|rule| match rule.delay_ms {
+                    0 => format!("{}  →  {}", rule.pattern, rule.send),
+                    ms => i18n::tf("dialog.trigger_delayed", &[&rule.pattern, &rule.send, &ms]),
+                })
+                .collect();
+            items.push(i18n::t("dialog.trigger_add").to_string());
+            render_list(
+                theme,
+                frame,
+                i18n::t("dialog.trigger_title"),
+                &items,
+                *selected,
+                i18n::t("dialog.trigger_hint"),
+            );
+        }
+        Dialog::TriggerPrompt {
+            field,
+            text,
+            cursor_pos,
+            ..
+        } => {
+            let (title, hint) = match field {
+                TriggerField::Pattern => ("dialog.trigger_pattern", "dialog.trigger_pattern_hint"),
+                TriggerField::Send => ("dialog.trigger_send", "dialog.trigger_send_hint"),
+                TriggerField::Delay => ("dialog.trigger_delay", "dialog.trigger_delay_hint"),
+            };
+            render_query_prompt(
+                theme,
+                frame,
+                i18n::t(title),
+                text,
+                *cursor_pos,
+                i18n::t(hint),
+            );
+        }
+        Dialog::CapturePrompt {
+            field,
+            text,
+            cursor_pos,
+            ..
+        } => {
+            let (title, hint) = match field {
+                CaptureField::Start => ("dialog.capture_start", "dialog.capture_start_hint"),
+                CaptureField::End => ("dialog.capture_end", "dialog.capture_end_hint"),
+                CaptureField::Limit => ("dialog.capture_limit", "dialog.capture_limit_hint"),
+                CaptureField::File => ("dialog.capture_file", "dialog.capture_file_hint"),
+            };
+            render_query_prompt(
+                theme,
+                frame,
+                i18n::t(title),
+                text,
+                *cursor_pos,
+                i18n::t(hint),
+            );
+        }
+        Dialog::FolderBrowser {
+            folder,
+            subfolders,
+            selected,
+            ..
+        } => {
+            let items: Vec<String> = std::iter::once(i18n::t("dialog.folder_here").to_string())
+                .chain(
+                    subfolders
+                        .iter()
+                        .map(|name| format!("{}{}", name, std::path::MAIN_SEPARATOR)),
+                )
+                .collect();
+            // The end of a long path says more than its start; 40 characters
+            // stay inside the width the hint gives the dialog
+            let path = folder.to_string_lossy();
+            let skip = path.chars().count().saturating_sub(40);
+            let path = match skip {
+                0 => path.into_owned(),
+                _ => format!("…{}", path.chars().skip(skip + 1).collect::<String>()),
+            };
+            render_list(
+                theme,
+                frame,
+                &i18n::tf("dialog.folder_title", &[&path]),
+                &items,
+                *selected,
+                i18n::t("dialog.folder_hint"),
+            );
+        }
+        Dialog::GeneratorMenu { selected, .. } => {
+            let items: Vec<String> = PatternKind::ALL
+                .iter()
+                .map(|kind| i18n::t(kind.label()).to_string())
+                .collect();
+            render_list(
+                theme,
+                frame,
+                i18n::t("dialog.generator_title"),
+                &items,
+                *selected,
+                i18n::t("dialog.generator_hint"),
+            );
+        }
+        Dialog::GeneratorPrompt {
+            generator,
+            field,
+            text,
+            cursor_pos,
+            ..
+        } => {
+            let frames = generator.kind == PatternKind::Frame;
+            let (title, hint) = match field {
+                GeneratorField::Bytes if frames => (
+                    "dialog.generator_frame_bytes",
+                    "dialog.generator_frame_hint",
+                ),
+                GeneratorField::Bytes => ("dialog.generator_byte", "dialog.generator_byte_hint"),
+                GeneratorField::Count if frames => (
+                    "dialog.generator_count_frames",
+                    "dialog.generator_count_hint",
+                ),
+                GeneratorField::Count => (
+                    "dialog.generator_count_bytes",
+                    "dialog.generator_count_hint",
+                ),
+            };
+            render_query_prompt(
+                theme,
+                frame,
+                i18n::t(title),
+                text,
+                *cursor_pos,
+                i18n::t(hint),
+            );
+        }
+        Dialog::Welcome => render_welcome(theme, frame),
+        Dialog::Help { scroll } => super::help::render(app, frame, *scroll),
+        Dialog::DiffMenu {
+            connection_idx,
+            targets,
+            selected,
+        } => {
+            let mut items: Vec<String> = targets
+                .iter()
+                .filter_map(|&id| app.connections.iter().find(|c| c.id == id))
+                .map(|c| c.label())
+                .collect();
+            items.push(i18n::t("dialog.diff_log").to_string());
+            let source = app
+                .connections
+                .get(*connection_idx)
+                .map_or(String::new(), |c| c.label());
+            render_list(
+                theme,
+                frame,
+                &i18n::tf("dialog.diff_menu_title", &[&source]),
+                &items,
+                *selected,
+                i18n::t("dialog.diff_menu_hint"),
+            );
+        }
+        Dialog::Diff {
+            old,
+            new,
+            lines,
+            scroll,
+        } => super::diff::render(app, frame, old, new, lines, *scroll),
+        Dialog::ConfirmZmodemReceive { .. } => {
+            render_confirm(
+                theme,
+                frame,
+                i18n::t("dialog.zmodem_title"),
+                i18n::t("dialog.zmodem_body"),
+                i18n::t("dialog.yes_no_ignore"),
+            );
+        }
+        Dialog::AsciiTable { scroll } => super::ascii::render(app, frame, *scroll),
+        Dialog::Transfer { connection_id } => {
+            if let Some(conn) = app.connections.iter().find(|c| c.id == *connection_id) {
+                render_transfer(theme, frame, conn);
+            }
         }
     }
 }
@@ -54,7 +602,7 @@ fn center_rect(width: u16, height: u16, area: Rect) -> Rect {
     harea
 }
 
-fn render_confirm(frame: &mut Frame, title: &str, message: &str, hint: &str) {
+fn render_confirm(theme: &Theme, frame: &mut Frame, title: &str, message: &str, hint: &str) {
     let width = (message.len() as u16 + 4)
         .max(hint.len() as u16 + 4)
         .max(30);
@@ -65,7 +613,7 @@ fn render_confirm(frame: &mut Frame, title: &str, message: &str, hint: &str) {
     let block = Block::default()
         .title(title)
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Yellow));
+        .border_style(Style::default().fg(theme.dialog_border));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -73,71 +621,287 @@ fn render_confirm(frame: &mut Frame, title: &str, message: &str, hint: &str) {
     let [msg_area, hint_area] =
         Layout::vertical([Constraint::Length(1), Constraint::Length(1)]).areas(inner);
 
-    let msg = Paragraph::new(Line::raw(message)).style(Style::default().fg(Color::White));
+    let msg = Paragraph::new(Line::raw(message)).style(Style::default().fg(theme.text));
     frame.render_widget(msg, msg_area);
 
     let hints = Paragraph::new(Line::raw(hint)).style(
         Style::default()
-            .fg(Color::Cyan)
+            .fg(theme.accent)
             .add_modifier(Modifier::BOLD),
     );
     frame.render_widget(hints, hint_area);
 }
 
-fn render_filename_prompt(frame: &mut Frame, filename: &str, cursor_pos: usize) {
-    let width = (filename.len() as u16 + 6).max(40);
+fn render_welcome(theme: &Theme, frame: &mut Frame) {
+    let path = Config::path()
+        .map(|p| p.display().to_string())
+        .unwrap_or_default();
+    let mut lines: Vec<Line> = i18n::t("welcome.body")
+        .lines()
+        .map(|l| Line::styled(l, Style::default().fg(theme.text)))
+        .collect();
+    lines.push(Line::raw(""));
+    lines.push(Line::styled(
+        i18n::t("welcome.config"),
+        Style::default().fg(theme.text),
+    ));
+    lines.push(Line::styled(
+        format!("  {}", path),
+        Style::default().fg(theme.alert),
+    ));
+    lines.push(Line::raw(""));
+    lines.push(Line::styled(
+        i18n::t("welcome.hint"),
+        Style::default()
+            .fg(theme.accent)
+            .add_modifier(Modifier::BOLD),
+    ));
+
+    let width =
+        (lines.iter().map(|l| l.width()).max().unwrap_or(0) as u16 + 4).min(frame.area().width);
+    let area = center_rect(width, lines.len() as u16 + 2, frame.area());
+
+    frame.render_widget(Clear, area);
+    let block = Block::default()
+        .title(i18n::t("welcome.title"))
+        .borders(Borders::ALL)
+        .padding(Padding::horizontal(1))
+        .border_style(Style::default().fg(theme.dialog_border));
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn render_filename_prompt(
+    theme: &Theme,
+    frame: &mut Frame,
+    title: &str,
+    filename: &str,
+    cursor_pos: usize,
+    detail: Option<&str>,
+    hint: &str,
+) {
+    let width = (filename.chars().count() as u16 + 6).max(hint.chars().count() as u16 + 4);
     let area = center_rect(width, 6, frame.area());
 
     frame.render_widget(Clear, area);
 
     let block = Block::default()
-        .title(" Export Filename ")
+        .title(title)
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Yellow));
+        .border_style(Style::default().fg(theme.dialog_border));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
-    let [label_area, input_area, hint_area] = Layout::vertical([
+    let [label_area, input_area, detail_area, hint_area] = Layout::vertical([
         Constraint::Length(1),
         Constraint::Length(1),
+        Constraint::Length(u16::from(detail.is_some())),
         Constraint::Length(1),
     ])
     .areas(inner);
 
-    let label = Paragraph::new(Line::raw("Filename (edit or press Enter):"))
-        .style(Style::default().fg(Color::White));
+    let label = Paragraph::new(Line::raw(i18n::t("dialog.filename_label")))
+        .style(Style::default().fg(theme.text));
     frame.render_widget(label, label_area);
 
-    // Build input line with visual cursor (inverted char at cursor position)
-    let base_style = Style::default()
-        .fg(Color::Black)
-        .bg(Color::White)
-        .add_modifier(Modifier::BOLD);
+    frame.render_widget(
+        Paragraph::new(input_line(theme, filename, cursor_pos)),
+        input_area,
+    );
+
+    if let Some(detail) = detail {
+        let detail = Paragraph::new(Line::raw(detail)).style(Style::default().fg(theme.text));
+        frame.render_widget(detail, detail_area);
+    }
+
+    let hints = Paragraph::new(Line::raw(hint.to_string())).style(Style::default().fg(theme.dim));
+    frame.render_widget(hints, hint_area);
+}
+
+fn render_query_prompt(
+    theme: &Theme,
+    frame: &mut Frame,
+    title: &str,
+    query: &str,
+    cursor_pos: usize,
+    hint: &str,
+) {
+    let width = (query.len() as u16 + 6).max(40);
+    let area = center_rect(width, 5, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.dialog_border));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [input_area, hint_area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Length(1)]).areas(inner);
+
+    frame.render_widget(
+        Paragraph::new(input_line(theme, query, cursor_pos)),
+        input_area,
+    );
+
+    let hints = Paragraph::new(Line::raw(hint)).style(Style::default().fg(theme.dim));
+    frame.render_widget(hints, hint_area);
+}
+
+/// Hex input with the checksums of the bytes typed so far below it.
+fn render_checksum(theme: &Theme, frame: &mut Frame, query: &str, cursor_pos: usize) {
+    let results = match hex::parse(query) {
+        Ok(bytes) => checksum::all(&bytes)
+            .into_iter()
+            .map(|(name, value)| {
+                Line::from(vec![
+                    Span::raw(format!("{:<20}", name)),
+                    Span::styled(value, Style::default().fg(theme.accent)),
+                ])
+            })
+            .collect(),
+        Err(e) => vec![Line::styled(e, Style::default().fg(theme.error))],
+    };
+    let max_width = frame.area().width.saturating_sub(4);
+    let width = (query.len() as u16 + 6).max(44).min(max_width);
+    let height = results.len() as u16 + 5;
+    let area = center_rect(width, height, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(i18n::t("dialog.checksum_title"))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.dialog_border));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [input_area, results_area, hint_area] = Layout::vertical([
+        Constraint::Length(2),
+        Constraint::Min(1),
+        Constraint::Length(1),
+    ])
+    .areas(inner);
+
+    frame.render_widget(
+        Paragraph::new(input_line(theme, query, cursor_pos)),
+        input_area,
+    );
+    frame.render_widget(Paragraph::new(results), results_area);
+
+    let hints = Paragraph::new(Line::raw(i18n::t("dialog.checksum_hint")))
+        .style(Style::default().fg(theme.dim));
+    frame.render_widget(hints, hint_area);
+}
+
+fn render_list(
+    theme: &Theme,
+    frame: &mut Frame,
+    title: &str,
+    items: &[String],
+    selected: usize,
+    hint: &str,
+) {
+    let max_width = frame.area().width.saturating_sub(4);
+    let width = (items.iter().map(|i| i.chars().count()).max().unwrap_or(0) as u16 + 6)
+        .max(hint.len() as u16 + 4)
+        .max(30)
+        .min(max_width);
+    let height = (items.len() as u16 + 3).min(frame.area().height.saturating_sub(2));
+    let area = center_rect(width, height, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.dialog_border));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [list_area, hint_area] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(inner);
+
+    let list_items: Vec<ListItem> = items.iter().map(|i| ListItem::new(i.as_str())).collect();
+    let list = List::new(list_items)
+        .highlight_style(theme.selected())
+        .highlight_symbol("▶ ");
+    let mut state = ListState::default().with_selected(Some(selected));
+    frame.render_stateful_widget(list, list_area, &mut state);
+
+    let hints = Paragraph::new(Line::raw(hint)).style(Style::default().fg(theme.dim));
+    frame.render_widget(hints, hint_area);
+}
+
+fn render_transfer(theme: &Theme, frame: &mut Frame, conn: &Connection) {
+    let area = center_rect(50, 6, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(i18n::t("dialog.transfer_title"))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.dialog_border));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [label_area, gauge_area, hint_area] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(1),
+    ])
+    .areas(inner);
+
+    let (label, progress, bytes) = match &conn.busy {
+        Some(busy) => (busy.label.as_str(), busy.progress, busy.bytes),
+        None => (i18n::t("dialog.transfer_finishing"), None, None),
+    };
+    frame.render_widget(
+        Paragraph::new(Line::raw(label)).style(Style::default().fg(theme.text)),
+        label_area,
+    );
+
+    let gauge_label = match (progress, bytes) {
+        (Some(p), _) => format!("{}%", p),
+        (None, Some(n)) => i18n::tf("dialog.transfer_bytes", &[&n]),
+        (None, None) => i18n::t("dialog.transfer_waiting").to_string(),
+    };
+    let gauge = Gauge::default()
+        .gauge_style(Style::default().fg(theme.accent).bg(theme.dim))
+        .percent(progress.unwrap_or(0) as u16)
+        .label(gauge_label);
+    frame.render_widget(gauge, gauge_area);
+
+    let hints = Paragraph::new(Line::raw(i18n::t("dialog.transfer_hint")))
+        .style(Style::default().fg(theme.dim));
+    frame.render_widget(hints, hint_area);
+}
+
+/// Build an input line with visual cursor (inverted char at cursor position).
+fn input_line(theme: &Theme, text: &str, cursor_pos: usize) -> Line<'static> {
+    let base_style = theme.menu().add_modifier(Modifier::BOLD);
     let cursor_style = Style::default()
-        .fg(Color::White)
-        .bg(Color::Black)
+        .fg(theme.menu_bg)
+        .bg(theme.menu_fg)
         .add_modifier(Modifier::BOLD);
 
-    let before = &filename[..cursor_pos];
-    let (cursor_char, after) = if cursor_pos < filename.len() {
-        (
-            &filename[cursor_pos..cursor_pos + 1],
-            &filename[cursor_pos + 1..],
-        )
-    } else {
-        (" ", "")
+    // `cursor_pos` is a byte offset on a char boundary
+    let before = &text[..cursor_pos];
+    let (cursor_char, after) = match text[cursor_pos..].chars().next() {
+        Some(c) => text[cursor_pos..].split_at(c.len_utf8()),
+        None => (" ", ""),
     };
 
-    let input = Paragraph::new(Line::from(vec![
+    Line::from(vec![
         Span::styled("> ", base_style),
         Span::styled(before.to_string(), base_style),
         Span::styled(cursor_char.to_string(), cursor_style),
         Span::styled(after.to_string(), base_style),
-    ]));
-    frame.render_widget(input, input_area);
-
-    let hints = Paragraph::new(Line::raw("Enter Confirm  ←→ Move  Esc Cancel"))
-        .style(Style::default().fg(Color::DarkGray));
-    frame.render_widget(hints, hint_area);
+    ])
 }
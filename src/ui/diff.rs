@@ -0,0 +1,76 @@
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+use super::help::overlay_area;
+use crate::app::{App, Dialog};
+use crate::diff::DiffLine;
+use crate::i18n;
+
+/// Full-screen unified diff from `old` to `new`, scrolled down `scroll` lines.
+pub fn render(
+    app: &App,
+    frame: &mut Frame,
+    old: &str,
+    new: &str,
+    diff: &[DiffLine],
+    scroll: usize,
+) {
+    let area = overlay_area(frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(i18n::tf("dialog.diff_title", &[&old, &new]))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.dialog_border));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [text_area, hint_area] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(inner);
+    let lines = lines(app, old, new, diff);
+    let scroll = scroll.min(lines.len().saturating_sub(text_area.height as usize));
+    frame.render_widget(Paragraph::new(lines).scroll((scroll as u16, 0)), text_area);
+
+    let removed = diff
+        .iter()
+        .filter(|l| matches!(l, DiffLine::Removed(_)))
+        .count();
+    let added = diff
+        .iter()
+        .filter(|l| matches!(l, DiffLine::Added(_)))
+        .count();
+    let hint = Paragraph::new(Line::raw(i18n::tf("dialog.diff_hint", &[&removed, &added])))
+        .style(Style::default().fg(app.theme.dim));
+    frame.render_widget(hint, hint_area);
+}
+
+/// How far the open diff can scroll at the current terminal size.
+pub fn max_scroll(app: &App) -> usize {
+    let Some(Dialog::Diff { lines, .. }) = &app.dialog else {
+        return 0;
+    };
+    let area = overlay_area(Rect::new(0, 0, app.terminal_cols, app.terminal_rows));
+    // Borders and the hint row
+    let visible = area.height.saturating_sub(3) as usize;
+    // The file header
+    (lines.len() + 2).saturating_sub(visible)
+}
+
+fn lines(app: &App, old: &str, new: &str, diff: &[DiffLine]) -> Vec<Line<'static>> {
+    let style = |color: Color| Style::default().fg(color);
+    let header = style(app.theme.text).add_modifier(Modifier::BOLD);
+    let mut lines = vec![
+        Line::styled(format!("--- {}", old), header),
+        Line::styled(format!("+++ {}", new), header),
+    ];
+    lines.extend(diff.iter().map(|line| match line {
+        DiffLine::Hunk(text) => Line::styled(text.clone(), style(app.theme.accent)),
+        DiffLine::Same(text) => Line::styled(format!(" {}", text), style(app.theme.text)),
+        DiffLine::Removed(text) => Line::styled(format!("-{}", text), style(app.theme.error)),
+        DiffLine::Added(text) => Line::styled(format!("+{}", text), style(app.theme.success)),
+    }));
+    lines
+}
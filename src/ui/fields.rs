@@ -0,0 +1,64 @@
+use ratatui::layout::{Constraint, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Paragraph, Row, Table};
+use ratatui::Frame;
+
+use crate::app::App;
+use crate::i18n;
+use crate::serial::FieldTable;
+
+/// Width of the time column.
+const TIME_WIDTH: u16 = 12;
+
+/// A connection's field table: a column per named group, the latest value
+/// of each on top, then the matching lines newest first.
+pub fn render(app: &App, frame: &mut Frame, area: Rect, table: &FieldTable) {
+    let mut header = vec![String::new()];
+    header.extend(table.names.iter().cloned());
+    let header = Row::new(header).style(
+        Style::default()
+            .fg(app.theme.text)
+            .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+    );
+
+    let value = |v: &Option<String>| v.clone().unwrap_or_default();
+    let mut latest = vec![i18n::t("terminal.fields_latest").to_string()];
+    latest.extend(table.latest.iter().map(value));
+    let mut rows = vec![Row::new(latest).style(
+        Style::default()
+            .fg(app.theme.accent)
+            .add_modifier(Modifier::BOLD),
+    )];
+    rows.extend(
+        table
+            .rows
+            .iter()
+            .take(area.height as usize)
+            .map(|(time, values)| {
+                let mut cells = vec![time.format("%H:%M:%S%.3f").to_string()];
+                cells.extend(values.iter().map(value));
+                Row::new(cells).style(Style::default().fg(app.theme.text))
+            }),
+    );
+
+    let mut widths = vec![Constraint::Length(TIME_WIDTH)];
+    widths.extend(table.names.iter().map(|_| Constraint::Fill(1)));
+    frame.render_widget(
+        Table::new(rows, widths).header(header).column_spacing(2),
+        area,
+    );
+
+    if table.rows.is_empty() && area.height > 3 {
+        let hint = i18n::tf("terminal.fields_empty", &[&table.pattern()]);
+        let hint_area = Rect {
+            y: area.y + 3,
+            height: 1,
+            ..area
+        };
+        frame.render_widget(
+            Paragraph::new(Line::raw(hint)).style(Style::default().fg(app.theme.dim)),
+            hint_area,
+        );
+    }
+}
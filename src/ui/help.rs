@@ -0,0 +1,181 @@
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+use crate::app::{function_keys, App};
+use crate::i18n;
+use crate::keymap::Action;
+
+/// Width of the key column.
+const KEY_WIDTH: usize = 22;
+
+/// The remappable commands and what they do, in the help's order.
+const ACTIONS: [(Action, &str); 31] = [
+    (Action::New, "help.new"),
+    (Action::Close, "help.close"),
+    (Action::View, "help.view"),
+    (Action::Pin, "help.pin"),
+    (Action::SwapPanes, "help.swap_panes"),
+    (Action::SplitDirection, "help.split_direction"),
+    (Action::Find, "help.find"),
+    (Action::Filter, "help.filter"),
+    (Action::Pause, "help.pause"),
+    (Action::Wrap, "help.wrap"),
+    (Action::Export, "help.export"),
+    (Action::LiveStream, "help.live_stream"),
+    (Action::Note, "help.note"),
+    (Action::CopyAs, "help.copy_as"),
+    (Action::EditLine, "help.edit_line"),
+    (Action::Resend, "help.resend"),
+    (Action::Paste, "help.paste"),
+    (Action::HexInput, "help.hex_input"),
+    (Action::LiteralInput, "help.literal_input"),
+    (Action::Passthrough, "help.passthrough"),
+    (Action::AsciiTable, "help.ascii_table"),
+    (Action::Commands, "help.commands"),
+    (Action::GrowInput, "help.grow_input"),
+    (Action::ShrinkInput, "help.shrink_input"),
+    (Action::WordLeft, "help.word_left"),
+    (Action::WordRight, "help.word_right"),
+    (Action::InputHome, "help.input_home"),
+    (Action::InputEnd, "help.input_end"),
+    (Action::DeleteWord, "help.delete_word"),
+    (Action::ClearInput, "help.clear_input"),
+    (Action::Quit, "help.quit"),
+];
+
+/// Full-screen list of the key bindings per screen and the mouse gestures,
+/// scrolled down `scroll` lines.
+pub fn render(app: &App, frame: &mut Frame, scroll: usize) {
+    let area = overlay_area(frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(i18n::t("help.title"))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.dialog_border));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [text_area, hint_area] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(inner);
+    let lines = lines(app);
+    let scroll = scroll.min(lines.len().saturating_sub(text_area.height as usize));
+    frame.render_widget(Paragraph::new(lines).scroll((scroll as u16, 0)), text_area);
+
+    let hint =
+        Paragraph::new(Line::raw(i18n::t("help.hint"))).style(Style::default().fg(app.theme.dim));
+    frame.render_widget(hint, hint_area);
+}
+
+/// How far the help can scroll at the current terminal size.
+pub fn max_scroll(app: &App) -> usize {
+    let area = overlay_area(Rect::new(0, 0, app.terminal_cols, app.terminal_rows));
+    // Borders and the hint row
+    let visible = area.height.saturating_sub(3) as usize;
+    lines(app).len().saturating_sub(visible)
+}
+
+pub(super) fn overlay_area(frame: Rect) -> Rect {
+    Rect::new(
+        frame.x + 2,
+        frame.y + 1,
+        frame.width.saturating_sub(4),
+        frame.height.saturating_sub(2),
+    )
+}
+
+fn lines(app: &App) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    let heading = |lines: &mut Vec<Line<'static>>, key: &'static str| {
+        if !lines.is_empty() {
+            lines.push(Line::raw(""));
+        }
+        lines.push(Line::styled(
+            i18n::t(key),
+            Style::default()
+                .fg(app.theme.accent)
+                .add_modifier(Modifier::BOLD),
+        ));
+    };
+    let entry = |lines: &mut Vec<Line<'static>>, keys: String, text: &'static str| {
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("  {:<width$} ", keys, width = KEY_WIDTH),
+                Style::default().fg(app.theme.text),
+            ),
+            Span::raw(i18n::t(text)),
+        ]));
+    };
+
+    heading(&mut lines, "help.lists");
+    entry(&mut lines, "↑ / ↓".into(), "help.navigate");
+    entry(&mut lines, "Enter".into(), "help.select");
+    entry(&mut lines, "r".into(), "help.refresh");
+    entry(&mut lines, "Esc".into(), "help.back");
+    entry(&mut lines, "q".into(), "help.quit_list");
+
+    heading(&mut lines, "help.connected");
+    entry(&mut lines, "Tab / Shift+Tab".into(), "help.switch_tab");
+    entry(&mut lines, "Alt+1–9".into(), "help.jump_tab");
+    entry(&mut lines, "Enter".into(), "help.send");
+    entry(&mut lines, "↑ / ↓  PgUp / PgDn".into(), "help.scroll");
+    let ctrl = i18n::t("key.ctrl");
+    entry(&mut lines, format!("{}+Home", ctrl), "help.top");
+    entry(&mut lines, format!("{}+End", ctrl), "help.bottom");
+    entry(&mut lines, "← / →  Home / End".into(), "help.input_cursor");
+    entry(&mut lines, "Delete".into(), "help.delete_forward");
+    entry(&mut lines, "Shift+↑ / Shift+↓".into(), "help.line_cursor");
+    entry(
+        &mut lines,
+        "Shift+← / Shift+→".into(),
+        "help.scroll_sideways",
+    );
+    for (action, text) in ACTIONS {
+        if let Some(label) = app.keymap.label(action) {
+            entry(&mut lines, label, text);
+        }
+    }
+    entry(
+        &mut lines,
+        format!("{}+C", i18n::t("key.ctrl")),
+        "help.abort",
+    );
+    for (key, label, _) in function_keys(&app.keymap) {
+        entry(&mut lines, format!("F{}", key), label);
+    }
+
+    heading(&mut lines, "help.search");
+    entry(&mut lines, "n / N".into(), "help.older_newer");
+    entry(&mut lines, "Esc".into(), "help.end_search");
+
+    heading(&mut lines, "help.passthrough_mode");
+    if let Some(label) = app.keymap.label(Action::Passthrough) {
+        entry(&mut lines, label, "help.leave_passthrough");
+    }
+    entry(&mut lines, String::new(), "help.all_keys");
+
+    heading(&mut lines, "help.dialogs");
+    entry(&mut lines, "← / →".into(), "help.cursor");
+    entry(&mut lines, "↑ / ↓".into(), "help.choose");
+    entry(&mut lines, "Enter".into(), "help.confirm");
+    entry(&mut lines, "Esc".into(), "help.cancel");
+    entry(&mut lines, "Y / N".into(), "help.yes_no");
+    entry(&mut lines, "d / Del".into(), "help.delete");
+
+    heading(&mut lines, "help.mouse");
+    for (gesture, text) in [
+        ("help.click", "help.click_text"),
+        ("help.right_click", "help.right_click_text"),
+        ("help.wheel", "help.wheel_text"),
+        ("help.scrollbar", "help.scrollbar_text"),
+        ("help.drag_border", "help.drag_border_text"),
+        ("help.point", "help.point_text"),
+    ] {
+        entry(&mut lines, i18n::t(gesture).to_string(), text);
+    }
+
+    lines
+}
@@ -1,100 +1,70 @@
 use ratatui::layout::Rect;
-use ratatui::style::{Color, Modifier, Style};
+use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Clear, Paragraph};
 use ratatui::Frame;
 
-use crate::app::{App, OpenMenu};
-
-const NORMAL: Style = Style::new().fg(Color::Black).bg(Color::White);
-const HIGHLIGHT: Style = Style::new()
-    .fg(Color::White)
-    .bg(Color::DarkGray)
-    .add_modifier(Modifier::BOLD);
+use crate::app::App;
+use crate::i18n;
+use crate::menu::{self, MenuItem, DROPDOWN_WIDTH};
+use crate::theme::Theme;
 
 pub fn render(app: &App, frame: &mut Frame, area: Rect) {
-    let file_style = if app.open_menu == Some(OpenMenu::File) {
-        HIGHLIGHT
-    } else {
-        NORMAL
-    };
-    let conn_style = if app.open_menu == Some(OpenMenu::Connection) {
-        HIGHLIGHT
-    } else {
-        NORMAL
-    };
-    let view_style = if app.open_menu == Some(OpenMenu::View) {
-        HIGHLIGHT
-    } else {
-        NORMAL
-    };
-
-    let bar = Line::from(vec![
-        Span::styled(" File ", file_style),
-        Span::styled(" Connection ", conn_style),
-        Span::styled(" View ", view_style),
-    ]);
+    let normal = app.theme.menu();
+    let highlight = Style::default()
+        .fg(app.theme.menu_open_fg)
+        .bg(app.theme.menu_open_bg)
+        .add_modifier(Modifier::BOLD);
+    let bar = Line::from(
+        menu::titles()
+            .into_iter()
+            .map(|(menu, title, _)| {
+                let style = if app.open_menu == Some(menu) {
+                    highlight
+                } else {
+                    normal
+                };
+                Span::styled(title, style)
+            })
+            .collect::<Vec<_>>(),
+    );
 
-    let bg = Paragraph::new(bar).style(NORMAL);
+    let bg = Paragraph::new(bar).style(normal);
     frame.render_widget(bg, area);
 
     // Render dropdown if a menu is open
     if let Some(menu) = app.open_menu {
-        let frame_area = frame.area();
-        match menu {
-            OpenMenu::File => {
-                render_dropdown(
-                    frame,
-                    1,
-                    1,
-                    &[" Export       ", " Quit         "],
-                    frame_area,
-                );
-            }
-            OpenMenu::Connection => {
-                render_dropdown(
-                    frame,
-                    7,
-                    1,
-                    &[" New          ", " Close        "],
-                    frame_area,
-                );
-            }
-            OpenMenu::View => {
-                render_dropdown(
-                    frame,
-                    19,
-                    1,
-                    &[" Tab View     ", " Grid View    "],
-                    frame_area,
-                );
-            }
-        }
+        let (x, y) = menu::dropdown_origin(menu);
+        render_dropdown(&app.theme, frame, x, y, &menu::items(app, menu));
     }
 }
 
-fn render_dropdown(frame: &mut Frame, x: u16, y: u16, items: &[&str], frame_area: Rect) {
-    let width = 16_u16;
+/// Draw a dropdown of the menu items.
+fn render_dropdown(theme: &Theme, frame: &mut Frame, x: u16, y: u16, items: &[MenuItem]) {
+    let frame_area = frame.area();
     let height = items.len() as u16 + 2; // +2 for border
 
-    if x + width > frame_area.width || y + height > frame_area.height {
+    if x + DROPDOWN_WIDTH > frame_area.width || y + height > frame_area.height {
         return;
     }
 
-    let area = Rect::new(x, y, width, height);
+    let area = Rect::new(x, y, DROPDOWN_WIDTH, height);
 
     // Clear the area behind the dropdown
     frame.render_widget(Clear, area);
 
-    let lines: Vec<Line> = items.iter().map(|s| Line::raw(*s)).collect();
+    let lines: Vec<Line> = items
+        .iter()
+        .map(|item| Line::raw(format!(" {:<13}", i18n::t(item.label))))
+        .collect();
 
     let dropdown = Paragraph::new(lines)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::White)),
+                .border_style(Style::default().fg(theme.menu_bg)),
         )
-        .style(Style::default().fg(Color::Black).bg(Color::White));
+        .style(theme.menu());
 
     frame.render_widget(dropdown, area);
 }
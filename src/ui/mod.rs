@@ -1,30 +1,95 @@
+mod ascii;
 mod baud_select;
 mod data_bits_select;
 mod dialog;
+mod diff;
 mod display_mode_select;
+mod fields;
+mod help;
 mod menu_bar;
+mod pacing_select;
 mod parity_select;
+mod plot;
 mod port_select;
+mod profile_select;
 mod status_bar;
 mod stop_bits_select;
 mod terminal_view;
 
-use ratatui::layout::{Constraint, Layout};
-use ratatui::Frame;
+use ratatui::backend::TestBackend;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::{Frame, Terminal};
 
 use crate::app::{App, Screen};
 
+/// Width of the status bar's right-hand indicators, for mapping clicks on
+/// the function key bar left of them.
+pub fn status_indicators_width(app: &App) -> u16 {
+    status_bar::indicators_width(app)
+}
+
+/// How far the help overlay scrolls at the current terminal size.
+pub fn help_max_scroll(app: &App) -> usize {
+    help::max_scroll(app)
+}
+
+/// How far the ASCII table overlay scrolls at the current terminal size.
+pub fn ascii_max_scroll(app: &App) -> usize {
+    ascii::max_scroll(app)
+}
+
+/// How far the diff overlay scrolls at the current terminal size.
+pub fn diff_max_scroll(app: &App) -> usize {
+    diff::max_scroll(app)
+}
+
+/// Each connection's scrollback on screen, as (connection index, area), for
+/// mapping clicks on their scrollbars.
+pub fn scrollback_areas(app: &App) -> Vec<(usize, Rect)> {
+    let screen = Rect::new(0, 0, app.terminal_cols, app.terminal_rows);
+    let [_, content_area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).areas(screen);
+    let [main_area, _, _] = Layout::vertical([
+        Constraint::Min(1),
+        Constraint::Length(app.input_height),
+        Constraint::Length(1),
+    ])
+    .areas(content_area);
+    terminal_view::scrollback_areas(app, main_area)
+}
+
+/// The screen as text, one string per row, rendered off-screen at the
+/// terminal's current size.
+pub fn screenshot(app: &App) -> Vec<String> {
+    let backend = TestBackend::new(app.terminal_cols, app.terminal_rows);
+    // The test backend can't fail
+    let Ok(mut terminal) = Terminal::new(backend);
+    let Ok(_) = terminal.draw(|frame| render(app, frame));
+    let buffer = terminal.backend().buffer();
+    (0..buffer.area.height)
+        .map(|y| {
+            let row: String = (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect();
+            row.trim_end().to_string()
+        })
+        .collect()
+}
+
 pub fn render(app: &App, frame: &mut Frame) {
     let [menu_area, content_area] =
         Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).areas(frame.area());
 
     match app.screen {
+        Screen::ProfileSelect => profile_select::render(app, frame, content_area),
         Screen::PortSelect => port_select::render(app, frame, content_area),
         Screen::BaudSelect => baud_select::render(app, frame, content_area),
         Screen::DataBitsSelect => data_bits_select::render(app, frame, content_area),
         Screen::ParitySelect => parity_select::render(app, frame, content_area),
         Screen::StopBitsSelect => stop_bits_select::render(app, frame, content_area),
         Screen::DisplayModeSelect => display_mode_select::render(app, frame, content_area),
+        Screen::PacingSelect => pacing_select::render(app, frame, content_area),
         Screen::Connected => terminal_view::render(app, frame, content_area),
     }
 
@@ -33,6 +98,14 @@ pub fn render(app: &App, frame: &mut Frame) {
 
     // Dialog renders last, on top of everything
     if let Some(ref dialog) = app.dialog {
-        dialog::render(dialog, frame);
+        dialog::render(app, dialog, frame);
+    }
+
+    // A received BEL with `bell = "flash"`
+    if app.flash_until.is_some() {
+        let area = frame.area();
+        frame
+            .buffer_mut()
+            .set_style(area, Style::new().add_modifier(Modifier::REVERSED));
     }
 }
@@ -0,0 +1,45 @@
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState};
+use ratatui::Frame;
+
+use crate::app::{App, PACING_OPTIONS};
+use crate::i18n;
+
+pub fn render(app: &App, frame: &mut Frame, area: Rect) {
+    let [main_area, status_area] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(area);
+
+    let port_name = app.wizard_port_name();
+
+    let items: Vec<ListItem> = PACING_OPTIONS
+        .iter()
+        .map(|(label, _)| ListItem::new(Line::raw(i18n::t(label))))
+        .collect();
+
+    let title = i18n::tf("screen.pacing", &[&port_name]);
+    let list = List::new(items)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .highlight_style(app.theme.selected())
+        .highlight_symbol("▶ ");
+
+    let mut state = ListState::default().with_selected(Some(app.selected_pacing_index));
+    frame.render_stateful_widget(list, main_area, &mut state);
+
+    super::status_bar::render(app, frame, status_area);
+}
+
+/// Render just the pacing preset list (no status bar, no outer block) for inline use in tabs/grid.
+pub fn render_content(app: &App, frame: &mut Frame, area: Rect) {
+    let items: Vec<ListItem> = PACING_OPTIONS
+        .iter()
+        .map(|(label, _)| ListItem::new(Line::raw(i18n::t(label))))
+        .collect();
+
+    let list = List::new(items)
+        .highlight_style(app.theme.selected())
+        .highlight_symbol("▶ ");
+
+    let mut state = ListState::default().with_selected(Some(app.selected_pacing_index));
+    frame.render_stateful_widget(list, area, &mut state);
+}
@@ -0,0 +1,73 @@
+use ratatui::layout::{Constraint, Rect};
+use ratatui::style::Style;
+use ratatui::symbols::Marker;
+use ratatui::text::Line;
+use ratatui::widgets::{Axis, Chart, Dataset, GraphType, Paragraph};
+use ratatui::Frame;
+
+use crate::app::App;
+use crate::i18n;
+use crate::serial::Plot;
+
+/// A connection's plot: each series as a line over the latest samples, the
+/// y axis fitted to the values on screen.
+pub fn render(app: &App, frame: &mut Frame, area: Rect, plot: &Plot) {
+    let Some([low, high]) = plot.y_bounds() else {
+        let hint = Paragraph::new(Line::raw(i18n::t("terminal.plot_empty")))
+            .style(Style::default().fg(app.theme.dim));
+        frame.render_widget(hint, area);
+        return;
+    };
+
+    let points: Vec<Vec<(f64, f64)>> = plot
+        .series
+        .iter()
+        .map(|s| s.points.iter().copied().collect())
+        .collect();
+    let datasets = plot
+        .series
+        .iter()
+        .zip(&points)
+        .enumerate()
+        .map(|(i, (series, points))| {
+            Dataset::default()
+                .name(series.name.clone())
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(app.theme.series[i % app.theme.series.len()]))
+                .data(points)
+        })
+        .collect();
+
+    let axis_style = Style::default().fg(app.theme.dim);
+    let [start, end] = plot.x_bounds();
+    let x_axis = Axis::default()
+        .style(axis_style)
+        .bounds([start, end])
+        .labels([format!("{}", start), format!("{}", end)]);
+    let y_axis = Axis::default()
+        .style(axis_style)
+        .bounds([low, high])
+        .labels([
+            value_label(low, high - low),
+            value_label((low + high) / 2.0, high - low),
+            value_label(high, high - low),
+        ]);
+    let chart = Chart::new(datasets)
+        .x_axis(x_axis)
+        .y_axis(y_axis)
+        .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)));
+    frame.render_widget(chart, area);
+}
+
+/// `value` with as many decimals as a chart spanning `range` needs.
+fn value_label(value: f64, range: f64) -> String {
+    let decimals = if range >= 100.0 {
+        0
+    } else if range >= 1.0 {
+        1
+    } else {
+        3
+    };
+    format!("{:.*}", decimals, value)
+}
@@ -1,86 +1,108 @@
 use ratatui::layout::{Constraint, Layout, Rect};
-use ratatui::style::{Color, Modifier, Style};
-use ratatui::text::Line;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
 use ratatui::Frame;
 
 use crate::app::App;
+use crate::i18n;
 
 pub fn render(app: &App, frame: &mut Frame, area: Rect) {
     let [main_area, status_area] =
         Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(area);
 
-    if app.available_ports.is_empty() {
-        let msg = Paragraph::new("No serial ports found. Press 'r' to refresh.").block(
-            Block::default()
-                .title(" Serial Ports ")
-                .borders(Borders::ALL),
-        );
-        frame.render_widget(msg, main_area);
+    let title = if !app.port_filter.is_empty() {
+        i18n::tf("screen.select_port_filter", &[&app.port_filter])
+    } else if app.available_ports.is_empty() {
+        i18n::t("screen.serial_ports").to_string()
     } else {
-        let items: Vec<ListItem> = app
-            .available_ports
-            .iter()
-            .map(|p| {
-                let text = if p.description.is_empty() {
-                    p.name.clone()
-                } else {
-                    format!("{} — {}", p.name, p.description)
-                };
-                ListItem::new(Line::raw(text))
-            })
-            .collect();
-
-        let list = List::new(items)
-            .block(
-                Block::default()
-                    .title(" Select Port ")
-                    .borders(Borders::ALL),
-            )
-            .highlight_style(
-                Style::default()
-                    .fg(Color::Black)
-                    .bg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD),
-            )
-            .highlight_symbol("▶ ");
-
-        let mut state = ListState::default().with_selected(Some(app.selected_port_index));
-        frame.render_stateful_widget(list, main_area, &mut state);
-    }
+        i18n::t("screen.select_port").to_string()
+    };
+    let block = Block::default().title(title).borders(Borders::ALL);
+    let inner = block.inner(main_area);
+    frame.render_widget(block, main_area);
+    render_content(app, frame, inner);
 
     super::status_bar::render(app, frame, status_area);
 }
 
 /// Render just the port list (no status bar, no outer block) for inline use in tabs/grid.
 pub fn render_content(app: &App, frame: &mut Frame, area: Rect) {
-    if app.available_ports.is_empty() {
-        let msg = Paragraph::new("No serial ports found. Press 'r' to refresh.");
-        frame.render_widget(msg, area);
-    } else {
-        let items: Vec<ListItem> = app
-            .available_ports
-            .iter()
-            .map(|p| {
-                let text = if p.description.is_empty() {
-                    p.name.clone()
-                } else {
-                    format!("{} — {}", p.name, p.description)
+    let labels: Vec<String> = app
+        .available_ports
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let mut text = if p.description.is_empty() {
+                p.name.clone()
+            } else {
+                format!("{} — {}", p.name, p.description)
+            };
+            // Favorites come first, so the first nine have Alt+1–9
+            if app.is_favorite(p) {
+                text = match i {
+                    0..=8 => format!("★{} {}", i + 1, text),
+                    _ => format!("★  {}", text),
                 };
-                ListItem::new(Line::raw(text))
-            })
-            .collect();
-
-        let list = List::new(items)
-            .highlight_style(
+            }
+            if app.port_in_use(&p.name) {
+                text = format!("{} {}", text, i18n::t("screen.in_use"));
+            }
+            if app.new_ports.contains(&p.name) {
+                text = format!("{} {}", text, i18n::t("screen.new_port"));
+            }
+            text
+        })
+        .collect();
+    // USB details line up in a second column
+    let width = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let mut items: Vec<ListItem> = labels
+        .into_iter()
+        .zip(&app.available_ports)
+        .map(|(label, p)| {
+            // A port that was just plugged in stands out
+            let style = if app.new_ports.contains(&p.name) {
+                Style::default().fg(app.theme.accent)
+            } else {
                 Style::default()
-                    .fg(Color::Black)
-                    .bg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD),
-            )
-            .highlight_symbol("▶ ");
+            };
+            let details = p.details();
+            if details.is_empty() {
+                return ListItem::new(Line::styled(label, style));
+            }
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:<width$}  ", label), style),
+                Span::styled(details, Style::default().fg(app.theme.dim)),
+            ]))
+        })
+        .collect();
+    items.push(ListItem::new(Line::raw(i18n::t("screen.network"))));
+    items.push(ListItem::new(Line::raw(i18n::t("screen.device_path"))));
+
+    // With no ports the hint goes below the Network and Device path entries,
+    // which stay first so list clicks line up
+    let list_area = if app.available_ports.is_empty() {
+        let [list_area, _, hint_area] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Length(1),
+            Constraint::Min(0),
+        ])
+        .areas(area);
+        let hint = if app.port_filter.is_empty() {
+            i18n::t("screen.no_ports")
+        } else {
+            i18n::t("screen.no_port_matches")
+        };
+        frame.render_widget(Paragraph::new(hint), hint_area);
+        list_area
+    } else {
+        area
+    };
+
+    let list = List::new(items)
+        .highlight_style(app.theme.selected())
+        .highlight_symbol("▶ ");
 
-        let mut state = ListState::default().with_selected(Some(app.selected_port_index));
-        frame.render_stateful_widget(list, area, &mut state);
-    }
+    let mut state = ListState::default().with_selected(Some(app.selected_port_index));
+    frame.render_stateful_widget(list, list_area, &mut state);
 }
@@ -0,0 +1,52 @@
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState};
+use ratatui::Frame;
+
+use crate::app::App;
+use crate::i18n;
+
+pub fn render(app: &App, frame: &mut Frame, area: Rect) {
+    let [main_area, status_area] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(area);
+
+    let mut items: Vec<ListItem> = app
+        .config
+        .profiles
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let available = app.profile_available.get(i).copied().unwrap_or(false);
+            let (dot, text) = if available {
+                (app.theme.success, app.theme.text)
+            } else {
+                (app.theme.dim, app.theme.dim)
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled("● ", Style::default().fg(dot)),
+                Span::styled(
+                    format!("{} — {} @ {}", p.name, p.port, p.summary()),
+                    Style::default().fg(text),
+                ),
+            ]))
+        })
+        .collect();
+    items.push(ListItem::new(Line::raw(i18n::t(
+        "screen.manual_connection",
+    ))));
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(i18n::t("screen.saved_profiles"))
+                .borders(Borders::ALL),
+        )
+        .highlight_style(app.theme.selected())
+        .highlight_symbol("▶ ");
+
+    let mut state = ListState::default().with_selected(Some(app.selected_profile_index));
+    frame.render_stateful_widget(list, main_area, &mut state);
+
+    super::status_bar::render(app, frame, status_area);
+}
@@ -1,56 +1,200 @@
-use ratatui::layout::Rect;
-use ratatui::style::{Color, Style};
-use ratatui::text::Line;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
 use ratatui::widgets::Paragraph;
 use ratatui::Frame;
 
-use crate::app::App;
+use crate::app::{App, Severity};
+use crate::i18n;
+use crate::serial::Connection;
 
 pub fn render(app: &App, frame: &mut Frame, area: Rect) {
-    if let Some(status) = app.status_text() {
-        let bar = Paragraph::new(Line::raw(status))
-            .style(Style::default().fg(Color::Black).bg(Color::Green));
+    if let Some(status) = app.status() {
+        let theme = &app.theme;
+        let (fg, bg) = match status.severity {
+            Severity::Info => (theme.on_accent, theme.accent),
+            Severity::Success => (theme.on_accent, theme.success),
+            Severity::Warning => (theme.on_accent, theme.alert),
+            Severity::Error => (theme.text, theme.error),
+        };
+        let text = if app.status_is_sticky() {
+            i18n::tf("hint.dismiss", &[&status.text])
+        } else {
+            status.text.clone()
+        };
+        let bar = Paragraph::new(Line::raw(text)).style(Style::default().fg(fg).bg(bg));
         frame.render_widget(bar, area);
         return;
     }
 
+    // Traffic counters for the active connection, right-aligned
+    let mut area = area;
+    if app.screen == crate::app::Screen::Connected && !app.is_pending_active() {
+        if let Some(conn) = app.connections.get(app.active_connection) {
+            let text = counters(conn);
+            let [left, right] =
+                Layout::horizontal([Constraint::Min(0), Constraint::Length(text.len() as u16)])
+                    .areas(area);
+            let bar = Paragraph::new(Line::raw(text)).style(
+                Style::default()
+                    .fg(app.theme.menu_open_fg)
+                    .bg(app.theme.menu_open_bg),
+            );
+            frame.render_widget(bar, right);
+            area = left;
+
+            // Filter indicator, left of the counters
+            if let Some(text) = filter_indicator(conn) {
+                let [left, right] = Layout::horizontal([
+                    Constraint::Min(0),
+                    Constraint::Length(Span::raw(&text).width() as u16),
+                ])
+                .areas(area);
+                let bar = Paragraph::new(Line::raw(text)).style(
+                    Style::default()
+                        .fg(app.theme.on_accent)
+                        .bg(app.theme.passthrough),
+                );
+                frame.render_widget(bar, right);
+                area = left;
+            }
+
+            // Modem status lines, left of the filter indicator
+            if let Some(lines) = modem_indicator(app, conn) {
+                let [left, right] = Layout::horizontal([
+                    Constraint::Min(0),
+                    Constraint::Length(lines.width() as u16),
+                ])
+                .areas(area);
+                frame.render_widget(Paragraph::new(lines), right);
+                area = left;
+            }
+        }
+
+        if let Some(busy) = app.active_busy_text() {
+            let bar = Paragraph::new(Line::raw(i18n::tf("hint.busy", &[&busy])))
+                .style(Style::default().fg(app.theme.on_accent).bg(app.theme.alert));
+            frame.render_widget(bar, area);
+            return;
+        }
+    }
+
+    if app.shows_function_keys() {
+        let mut spans = Vec::new();
+        for (key, label, _) in crate::app::function_key_bar(&app.keymap, area.width) {
+            spans.push(Span::styled(
+                format!("F{}", key),
+                Style::default().fg(app.theme.menu_bg).bg(app.theme.menu_fg),
+            ));
+            spans.push(Span::styled(
+                label,
+                Style::default()
+                    .fg(app.theme.on_accent)
+                    .bg(app.theme.accent),
+            ));
+        }
+        frame.render_widget(Paragraph::new(Line::from(spans)), area);
+        return;
+    }
+
     let help = match app.screen {
-        crate::app::Screen::PortSelect => "↑↓ Navigate  Enter Select  r Refresh  Esc/q Quit",
-        crate::app::Screen::BaudSelect => "↑↓ Navigate  Enter Select  Esc Back",
-        crate::app::Screen::DataBitsSelect => "↑↓ Navigate  Enter Select  Esc Back",
-        crate::app::Screen::ParitySelect => "↑↓ Navigate  Enter Select  Esc Back",
-        crate::app::Screen::StopBitsSelect => "↑↓ Navigate  Enter Select  Esc Back",
-        crate::app::Screen::DisplayModeSelect => "↑↓ Navigate  Enter Connect  Esc Back",
+        crate::app::Screen::ProfileSelect => "hint.profile_select",
+        crate::app::Screen::PortSelect => "hint.port_select",
+        crate::app::Screen::BaudSelect => "hint.list_select",
+        crate::app::Screen::DataBitsSelect => "hint.list_select",
+        crate::app::Screen::ParitySelect => "hint.list_select",
+        crate::app::Screen::StopBitsSelect => "hint.list_select",
+        crate::app::Screen::DisplayModeSelect => "hint.list_select",
+        crate::app::Screen::PacingSelect => "hint.list_connect",
         crate::app::Screen::Connected => {
             if app.is_pending_active() {
                 match app.pending_connection {
-                    Some(crate::app::PendingScreen::PortSelect) => {
-                        "↑↓ Navigate  Enter Select  r Refresh  Tab Switch  Esc Cancel"
-                    }
-                    Some(crate::app::PendingScreen::BaudSelect) => {
-                        "↑↓ Navigate  Enter Select  Tab Switch  Esc Back"
-                    }
-                    Some(crate::app::PendingScreen::DataBitsSelect) => {
-                        "↑↓ Navigate  Enter Select  Tab Switch  Esc Back"
-                    }
-                    Some(crate::app::PendingScreen::ParitySelect) => {
-                        "↑↓ Navigate  Enter Select  Tab Switch  Esc Back"
-                    }
-                    Some(crate::app::PendingScreen::StopBitsSelect) => {
-                        "↑↓ Navigate  Enter Select  Tab Switch  Esc Back"
-                    }
-                    Some(crate::app::PendingScreen::DisplayModeSelect) => {
-                        "↑↓ Navigate  Enter Connect  Tab Switch  Esc Back"
-                    }
+                    Some(crate::app::PendingScreen::PortSelect) => "hint.pending_port",
+                    Some(crate::app::PendingScreen::PacingSelect) => "hint.pending_connect",
+                    Some(_) => "hint.pending_select",
                     None => "",
                 }
+            } else if app.is_passthrough_active() {
+                "hint.passthrough"
+            } else if app.hex_input {
+                "hint.hex_input"
+            } else if app.view_mode == crate::app::ViewMode::Split {
+                "hint.split"
+            } else if app.search.is_some() {
+                "hint.search"
             } else {
-                "Tab Switch  Ctrl+N New  Ctrl+W Close  Ctrl+E Export  Ctrl+G Grid  ↑↓/PgUp/Dn/Wheel Scroll  Ctrl+Q Quit"
+                "hint.connected"
             }
         }
     };
 
-    let bar =
-        Paragraph::new(Line::raw(help)).style(Style::default().fg(Color::Black).bg(Color::White));
+    let bar = Paragraph::new(Line::raw(app.keymap.fill(i18n::t(help)))).style(app.theme.menu());
     frame.render_widget(bar, area);
 }
+
+/// Columns taken by the counters, filter and modem line indicators at the
+/// right, which leave the rest of the bar to hints or function keys.
+pub fn indicators_width(app: &App) -> u16 {
+    if app.screen != crate::app::Screen::Connected || app.is_pending_active() {
+        return 0;
+    }
+    app.connections
+        .get(app.active_connection)
+        .map_or(0, |conn| {
+            let filter = filter_indicator(conn).map_or(0, |text| Span::raw(&text).width());
+            let modem = modem_indicator(app, conn).map_or(0, |lines| lines.width());
+            (counters(conn).len() + filter + modem) as u16
+        })
+}
+
+fn filter_indicator(conn: &Connection) -> Option<String> {
+    let filter = conn.filter.as_ref()?;
+    let key = if filter.hide {
+        "hint.filter_hiding"
+    } else {
+        "hint.filter_only"
+    };
+    Some(i18n::tf(key, &[&filter.pattern]))
+}
+
+/// CTS, DSR, DCD and RI, the asserted ones highlighted.
+fn modem_indicator(app: &App, conn: &Connection) -> Option<Line<'static>> {
+    let lines = conn.modem_lines?;
+    let off = Style::default()
+        .fg(app.theme.menu_open_fg)
+        .bg(app.theme.menu_open_bg);
+    let on = Style::default()
+        .fg(app.theme.on_accent)
+        .bg(app.theme.success);
+    let mut spans = vec![Span::styled(" ", off)];
+    for (name, asserted) in [
+        ("CTS", lines.cts),
+        ("DSR", lines.dsr),
+        ("DCD", lines.dcd),
+        ("RI", lines.ri),
+    ] {
+        spans.push(Span::styled(name, if asserted { on } else { off }));
+        spans.push(Span::styled(" ", off));
+    }
+    Some(Line::from(spans))
+}
+
+fn counters(conn: &Connection) -> String {
+    format!(
+        " RX {} ({}/s)  TX {} ({}/s) ",
+        format_bytes(conn.rx_bytes as f64),
+        format_bytes(conn.rx_rate),
+        format_bytes(conn.tx_bytes as f64),
+        format_bytes(conn.tx_rate),
+    )
+}
+
+fn format_bytes(n: f64) -> String {
+    if n < 1024.0 {
+        format!("{} B", n.round())
+    } else if n < 1024.0 * 1024.0 {
+        format!("{:.1} KB", n / 1024.0)
+    } else {
+        format!("{:.1} MB", n / (1024.0 * 1024.0))
+    }
+}
@@ -1,13 +1,21 @@
+use std::ops::Range;
+use std::time::Duration;
+
 use ratatui::layout::{Constraint, Layout, Rect};
-use ratatui::style::{Color, Modifier, Style};
+use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{
     Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap,
 };
 use ratatui::Frame;
 
-use crate::app::{App, PendingScreen, ViewMode};
-use crate::serial::Connection;
+use crate::ansi;
+use crate::app::{App, PendingScreen, SplitDirection, ViewMode};
+use crate::hex;
+use crate::i18n;
+use crate::keymap::Action;
+use crate::serial::{Connection, DisplayMode};
+use crate::theme::Theme;
 
 pub fn render(app: &App, frame: &mut Frame, area: Rect) {
     if app.connections.is_empty() && app.pending_connection.is_none() {
@@ -16,7 +24,7 @@ pub fn render(app: &App, frame: &mut Frame, area: Rect) {
 
     let [main_area, input_area, status_area] = Layout::vertical([
         Constraint::Min(1),
-        Constraint::Length(3),
+        Constraint::Length(app.input_height),
         Constraint::Length(1),
     ])
     .areas(area);
@@ -24,16 +32,109 @@ pub fn render(app: &App, frame: &mut Frame, area: Rect) {
     match app.view_mode {
         ViewMode::Tabs => render_tabs(app, frame, main_area),
         ViewMode::Grid => render_grid(app, frame, main_area),
+        ViewMode::Split => render_split(app, frame, main_area),
     }
 
-    // Input bar
-    let input = Paragraph::new(Line::raw(format!("> {}", app.input_buffer)))
-        .block(Block::default().title(" Send ").borders(Borders::ALL));
+    // Input bar (replaced by the job state while the active connection is busy)
+    let input = match app.active_busy_text() {
+        Some(text) => Paragraph::new(Line::raw(i18n::tf("status.busy", &[&text])))
+            .style(Style::default().fg(app.theme.alert))
+            .block(
+                Block::default()
+                    .title(i18n::t("terminal.send_locked"))
+                    .borders(Borders::ALL),
+            ),
+        None if app.is_passthrough_active() => Paragraph::new(Line::raw(i18n::tf(
+            "terminal.passthrough_banner",
+            &[&app.keymap.label(Action::Passthrough).unwrap_or_default()],
+        )))
+        .style(Style::default().fg(app.theme.passthrough))
+        .block(
+            Block::default()
+                .title(i18n::t("terminal.passthrough"))
+                .borders(Borders::ALL),
+        ),
+        None if app.hex_input => Paragraph::new(input_line(
+            hex_input_spans(&app.theme, &app.input_buffer),
+            app.input_cursor,
+        ))
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .title(i18n::t("terminal.send_hex"))
+                .borders(Borders::ALL),
+        ),
+        None => Paragraph::new(input_line(
+            vec![Span::raw(app.input_buffer.as_str())],
+            app.input_cursor,
+        ))
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .title(if app.literal_input {
+                    i18n::t("terminal.send_literal")
+                } else {
+                    i18n::t("terminal.send")
+                })
+                .borders(Borders::ALL),
+        ),
+    };
     frame.render_widget(input, input_area);
 
     super::status_bar::render(app, frame, status_area);
 }
 
+/// The Send bar: a prompt, then `spans` with the cursor shown as a reversed
+/// character at byte `cursor` of their text, or a reversed space at the end.
+fn input_line(spans: Vec<Span<'_>>, cursor: usize) -> Line<'static> {
+    let mut line = vec![Span::raw("> ")];
+    let mut pos = 0;
+    let mut placed = false;
+    for span in spans {
+        let text = span.content.as_ref();
+        let end = pos + text.len();
+        if !placed && (pos..end).contains(&cursor) {
+            let at = cursor - pos;
+            let len = text[at..].chars().next().map_or(0, char::len_utf8);
+            line.push(Span::styled(text[..at].to_string(), span.style));
+            line.push(Span::styled(
+                text[at..at + len].to_string(),
+                span.style.add_modifier(Modifier::REVERSED),
+            ));
+            line.push(Span::styled(text[at + len..].to_string(), span.style));
+            placed = true;
+        } else {
+            line.push(Span::styled(text.to_string(), span.style));
+        }
+        pos = end;
+    }
+    if !placed {
+        line.push(Span::styled(
+            " ",
+            Style::default().add_modifier(Modifier::REVERSED),
+        ));
+    }
+    Line::from(line)
+}
+
+/// Send bar contents in hex mode, with malformed tokens highlighted.
+fn hex_input_spans<'a>(theme: &Theme, input: &'a str) -> Vec<Span<'a>> {
+    let mut spans = Vec::new();
+    let mut pos = 0;
+    for token in hex::tokens(input) {
+        spans.push(Span::raw(&input[pos..token.range.start]));
+        let style = if token.valid {
+            Style::default().fg(theme.accent)
+        } else {
+            Style::default().fg(theme.text).bg(theme.error)
+        };
+        pos = token.range.end;
+        spans.push(Span::styled(&input[token.range], style));
+    }
+    spans.push(Span::raw(&input[pos..]));
+    spans
+}
+
 fn render_tabs(app: &App, frame: &mut Frame, area: Rect) {
     let [tab_bar, content_area] =
         Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).areas(area);
@@ -44,17 +145,23 @@ fn render_tabs(app: &App, frame: &mut Frame, area: Rect) {
         .iter()
         .enumerate()
         .map(|(i, conn)| {
-            let label = format!(" {} ", conn.label());
+            let label = conn.tab_title();
             if i == app.active_connection {
+                Span::styled(label, app.theme.selected())
+            } else if conn.alert {
                 Span::styled(
                     label,
                     Style::default()
-                        .fg(Color::Black)
-                        .bg(Color::Cyan)
-                        .add_modifier(Modifier::BOLD),
+                        .fg(app.theme.on_accent)
+                        .bg(app.theme.alert)
+                        .add_modifier(Modifier::BOLD | Modifier::SLOW_BLINK),
                 )
             } else {
-                let color = if conn.alive { Color::White } else { Color::Red };
+                let color = if conn.alive {
+                    app.theme.text
+                } else {
+                    app.theme.error
+                };
                 Span::styled(label, Style::default().fg(color))
             }
         })
@@ -65,15 +172,18 @@ fn render_tabs(app: &App, frame: &mut Frame, area: Rect) {
         let pending_idx = app.connections.len();
         let style = if app.active_connection == pending_idx {
             Style::default()
-                .fg(Color::Black)
-                .bg(Color::Yellow)
+                .fg(app.theme.on_accent)
+                .bg(app.theme.alert)
                 .add_modifier(Modifier::BOLD)
         } else {
-            Style::default().fg(Color::Yellow)
+            Style::default().fg(app.theme.alert)
         };
         all_spans.push(Span::styled(" New ", style));
     } else {
-        all_spans.push(Span::styled(" [+] ", Style::default().fg(Color::Green)));
+        all_spans.push(Span::styled(
+            " [+] ",
+            Style::default().fg(app.theme.success),
+        ));
     }
 
     frame.render_widget(Paragraph::new(Line::from(all_spans)), tab_bar);
@@ -87,6 +197,7 @@ fn render_tabs(app: &App, frame: &mut Frame, area: Rect) {
             frame,
             content_area,
             true,
+            app,
         );
     }
 }
@@ -102,8 +213,7 @@ fn render_grid(app: &App, frame: &mut Frame, area: Rect) {
         return;
     }
 
-    let cols = (total as f64).sqrt().ceil() as usize;
-    let rows = total.div_ceil(cols);
+    let (cols, rows) = app.grid_shape.dims(total);
 
     let row_constraints: Vec<Constraint> = (0..rows)
         .map(|_| Constraint::Ratio(1, rows as u32))
@@ -123,7 +233,7 @@ fn render_grid(app: &App, frame: &mut Frame, area: Rect) {
             }
             if idx < app.connections.len() {
                 let is_active = idx == app.active_connection;
-                render_scrollback(&app.connections[idx], frame, col_areas[col], is_active);
+                render_scrollback(&app.connections[idx], frame, col_areas[col], is_active, app);
             } else {
                 let is_active = app.active_connection == app.connections.len();
                 render_pending_cell(app, frame, col_areas[col], is_active);
@@ -132,17 +242,147 @@ fn render_grid(app: &App, frame: &mut Frame, area: Rect) {
     }
 }
 
-fn render_scrollback(conn: &Connection, frame: &mut Frame, area: Rect, is_active: bool) {
+fn render_split(app: &App, frame: &mut Frame, area: Rect) {
+    let secondary = app.split_secondary();
+    let panes = if secondary.is_some() {
+        let constraints = [Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)];
+        match app.split_direction {
+            SplitDirection::Horizontal => Layout::horizontal(constraints).split(area),
+            SplitDirection::Vertical => Layout::vertical(constraints).split(area),
+        }
+    } else {
+        Layout::vertical([Constraint::Min(1)]).split(area)
+    };
+
+    if app.is_pending_active() {
+        render_pending_cell(app, frame, panes[0], true);
+    } else if app.active_connection < app.connections.len() {
+        render_scrollback(
+            &app.connections[app.active_connection],
+            frame,
+            panes[0],
+            true,
+            app,
+        );
+    }
+    if let Some(idx) = secondary {
+        render_scrollback(&app.connections[idx], frame, panes[1], false, app);
+    }
+}
+
+/// Where each connection's scrollback is drawn in the main area, as
+/// (connection index, area), following the layouts above.
+pub fn scrollback_areas(app: &App, area: Rect) -> Vec<(usize, Rect)> {
+    let mut areas = Vec::new();
+    match app.view_mode {
+        ViewMode::Tabs => {
+            let [_, content_area] =
+                Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).areas(area);
+            if !app.is_pending_active() && app.active_connection < app.connections.len() {
+                areas.push((app.active_connection, content_area));
+            }
+        }
+        ViewMode::Grid => {
+            let total = app.connections.len() + usize::from(app.pending_connection.is_some());
+            if total == 0 {
+                return areas;
+            }
+            let (cols, rows) = app.grid_shape.dims(total);
+            let row_constraints: Vec<Constraint> = (0..rows)
+                .map(|_| Constraint::Ratio(1, rows as u32))
+                .collect();
+            let col_constraints: Vec<Constraint> = (0..cols)
+                .map(|_| Constraint::Ratio(1, cols as u32))
+                .collect();
+            let row_areas = Layout::vertical(row_constraints).split(area);
+            for (row, row_area) in row_areas.iter().enumerate() {
+                let col_areas = Layout::horizontal(col_constraints.clone()).split(*row_area);
+                for (col, cell) in col_areas.iter().enumerate() {
+                    let idx = row * cols + col;
+                    if idx < app.connections.len() {
+                        areas.push((idx, *cell));
+                    }
+                }
+            }
+        }
+        ViewMode::Split => {
+            let secondary = app.split_secondary();
+            let constraints = [Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)];
+            let panes = match (secondary, app.split_direction) {
+                (None, _) => Layout::vertical([Constraint::Min(1)]).split(area),
+                (Some(_), SplitDirection::Horizontal) => {
+                    Layout::horizontal(constraints).split(area)
+                }
+                (Some(_), SplitDirection::Vertical) => Layout::vertical(constraints).split(area),
+            };
+            if !app.is_pending_active() && app.active_connection < app.connections.len() {
+                areas.push((app.active_connection, panes[0]));
+            }
+            if let Some(idx) = secondary {
+                areas.push((idx, panes[1]));
+            }
+        }
+    }
+    areas
+}
+
+// Partial lines idle for longer than this are shown as stalled
+const PARTIAL_STALL_AFTER: Duration = Duration::from_secs(2);
+
+fn render_scrollback(conn: &Connection, frame: &mut Frame, area: Rect, is_active: bool, app: &App) {
     let border_color = if !conn.alive {
-        Color::Red
+        app.theme.error
     } else if is_active {
-        Color::Cyan
+        app.theme.accent
     } else {
-        Color::DarkGray
+        app.theme.dim
     };
 
     let status = if conn.alive { "" } else { " [DISCONNECTED]" };
-    let title = format!(" {}{} ", conn.label(), status);
+    let streaming = if conn.live_stream_path().is_some() {
+        " [LIVE]"
+    } else {
+        ""
+    };
+    let busy = conn
+        .busy_text()
+        .map(|t| format!(" [{}]", t))
+        .unwrap_or_default();
+    let raw = if conn.passthrough { " [RAW]" } else { "" };
+    let plot = if conn.plot.is_some() {
+        " [PLOT]"
+    } else if conn.fields.is_some() {
+        " [TABLE]"
+    } else {
+        ""
+    };
+    let capture = match conn.armed.as_ref().map(|armed| armed.recorded()) {
+        None => String::new(),
+        Some(None) => " [ARMED]".to_string(),
+        Some(Some(lines)) => format!(" [CAPTURING {}]", lines),
+    };
+    let dry_run = if conn.dry_run { " [DRY RUN]" } else { "" };
+    let scroll = if conn.follow { "" } else { " [SCROLL]" };
+    let muted = if conn.muted { " 🔇" } else { "" };
+    let wrap = match (conn.wraps(), conn.scroll_column) {
+        (true, _) => String::new(),
+        (false, 0) => " [NO WRAP]".to_string(),
+        (false, column) => format!(" [COL {}]", column + 1),
+    };
+    let title = format!(
+        " {}{}{}{}{}{}{}{}{}{}{} ",
+        conn.label(),
+        plot,
+        capture,
+        muted,
+        scroll,
+        wrap,
+        dry_run,
+        raw,
+        streaming,
+        busy,
+        status
+    );
 
     let block = Block::default()
         .title(title)
@@ -152,29 +392,137 @@ fn render_scrollback(conn: &Connection, frame: &mut Frame, area: Rect, is_active
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
+    if let Some(plot) = &conn.plot {
+        super::plot::render(app, frame, inner, plot);
+        return;
+    }
+    if let Some(fields) = &conn.fields {
+        super::fields::render(app, frame, inner, fields);
+        return;
+    }
+
+    // A paused view gives up its bottom row to a banner
+    let inner = match conn.paused_new_lines() {
+        Some(new) if inner.height > 1 => {
+            let [rest, banner] =
+                Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(inner);
+            let text = app.keymap.fill(&i18n::tf("terminal.paused", &[&new]));
+            let bar = Paragraph::new(Line::raw(text))
+                .style(Style::default().fg(app.theme.on_accent).bg(app.theme.alert));
+            frame.render_widget(bar, banner);
+            rest
+        }
+        _ => inner,
+    };
+
     let visible_height = inner.height as usize;
     if visible_height == 0 {
         return;
     }
 
     let lines: Vec<&str> = conn.scrollback_with_partial().collect();
-    let total = lines.len();
+    let shown = conn.shown_lines();
+    let total = shown.len();
+
+    let Range { start, end } = conn.view_window(total, visible_height);
+    let offset = total - end;
+
+    let mut visible_lines: Vec<Line> = match &app.search {
+        Some(search) => {
+            let current = search.current_line(conn);
+            shown[start..end]
+                .iter()
+                .map(|&i| {
+                    highlight_matches(
+                        styled_spans(lines[i], app),
+                        lines[i],
+                        &search.query,
+                        current == Some(i),
+                        &app.theme,
+                    )
+                })
+                .collect()
+        }
+        None => shown[start..end]
+            .iter()
+            .map(|&i| Line::from(styled_spans(lines[i], app)))
+            .collect(),
+    };
 
-    // Clamp offset so the top of scrollback always fills the visible area
-    let max_offset = total.saturating_sub(visible_height);
-    let offset = conn.scroll_offset.min(max_offset);
+    // Each line's bytes beside it; the rows only line up unwrapped
+    let hex_lines: Option<Vec<Line>> = (conn.hex_pane && conn.display_mode == DisplayMode::Text)
+        .then(|| {
+            shown[start..end]
+                .iter()
+                .map(|&i| {
+                    Line::raw(
+                        conn.line_bytes(i)
+                            .map(|b| hex::format(&b))
+                            .unwrap_or_default(),
+                    )
+                })
+                .collect()
+        });
+
+    // The line cursor
+    if let Some(line) = app.cursor_line(conn) {
+        if let Some(pos) = shown[start..end].iter().position(|&i| i == line) {
+            let line = &mut visible_lines[pos];
+            line.style = line.style.bg(app.theme.dim);
+        }
+    }
 
-    let start = if total > visible_height + offset {
-        total - visible_height - offset
-    } else {
-        0
-    };
-    let end = total.saturating_sub(offset);
+    // A partial line that stopped growing: dim it and show how long it's been idle
+    if let Some(age) = conn.partial_age().filter(|age| *age >= PARTIAL_STALL_AFTER) {
+        if end == total && shown.last() == Some(&(lines.len() - 1)) {
+            if let Some(line) = visible_lines.last_mut() {
+                line.style = line.style.fg(app.theme.dim).add_modifier(Modifier::ITALIC);
+                line.spans.push(Span::styled(
+                    format!("  ⏸ {}s", age.as_secs()),
+                    Style::default().fg(app.theme.dim),
+                ));
+            }
+        }
+    }
 
-    let visible_lines: Vec<Line> = lines[start..end].iter().map(|s| Line::raw(*s)).collect();
+    let column = conn.scroll_column.min(u16::MAX as usize / 3) as u16;
+    let text_area = match hex_lines {
+        Some(mut hex_lines) => {
+            let [text_area, divider, hex_area] = Layout::horizontal([
+                Constraint::Fill(1),
+                Constraint::Length(1),
+                Constraint::Fill(1),
+            ])
+            .areas(inner);
+            if let Some(line) = app.cursor_line(conn) {
+                if let Some(pos) = shown[start..end].iter().position(|&i| i == line) {
+                    let line = &mut hex_lines[pos];
+                    line.style = line.style.bg(app.theme.dim);
+                }
+            }
+            let rule = vec![Line::raw("│"); hex_area.height as usize];
+            frame.render_widget(
+                Paragraph::new(rule).style(Style::default().fg(app.theme.dim)),
+                divider,
+            );
+            // Three columns per byte, so sideways scrolling keeps ASCII
+            // text and its hex roughly together
+            frame.render_widget(
+                Paragraph::new(hex_lines).scroll((0, column.saturating_mul(3))),
+                hex_area,
+            );
+            text_area
+        }
+        None => inner,
+    };
 
-    let content = Paragraph::new(visible_lines).wrap(Wrap { trim: false });
-    frame.render_widget(content, inner);
+    let content = Paragraph::new(visible_lines);
+    let content = if conn.wraps() {
+        content.wrap(Wrap { trim: false })
+    } else {
+        content.scroll((0, column))
+    };
+    frame.render_widget(content, text_area);
 
     // Scrollbar — use scrollable range so the thumb reaches the bottom
     if total > visible_height {
@@ -186,6 +534,89 @@ fn render_scrollback(conn: &Connection, frame: &mut Frame, area: Rect, is_active
     }
 }
 
+/// A line's ANSI colors with the config's highlight rules laid over them.
+fn styled_spans<'a>(line: &'a str, app: &App) -> Vec<Span<'a>> {
+    let mut spans = ansi::spans(line);
+    if !app.highlighter.is_empty() {
+        for (ranges, style) in app.highlighter.matches(&ansi::strip(line)) {
+            spans = overlay(spans, &ranges, style);
+        }
+    }
+    spans
+}
+
+/// Highlight every case-insensitive occurrence of `query` in the visible
+/// (escape-stripped) text of `line`, already rendered as `spans`.
+fn highlight_matches<'a>(
+    spans: Vec<Span<'a>>,
+    line: &str,
+    query: &str,
+    is_current: bool,
+    theme: &Theme,
+) -> Line<'a> {
+    let hit_style = if is_current {
+        Style::default()
+            .fg(theme.on_accent)
+            .bg(theme.current_match)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(theme.on_accent).bg(theme.matched)
+    };
+
+    let haystack = ansi::strip(line).to_ascii_lowercase();
+    let needle = query.to_ascii_lowercase();
+    let mut hits = Vec::new();
+    if !needle.is_empty() {
+        let mut pos = 0;
+        while let Some(found) = haystack[pos..].find(&needle) {
+            let hit_start = pos + found;
+            pos = hit_start + needle.len();
+            hits.push((hit_start, pos));
+        }
+    }
+    Line::from(overlay(spans, &hits, hit_style))
+}
+
+/// Patch `style` onto the byte ranges (in the concatenated span text) listed in `ranges`,
+/// splitting spans where a range starts or ends. `ranges` must be sorted and disjoint.
+fn overlay<'a>(spans: Vec<Span<'a>>, ranges: &[(usize, usize)], style: Style) -> Vec<Span<'a>> {
+    if ranges.is_empty() {
+        return spans;
+    }
+    let mut out = Vec::with_capacity(spans.len() + ranges.len() * 2);
+    let mut offset = 0;
+    for span in spans {
+        let text = span.content.as_ref();
+        let span_end = offset + text.len();
+        let mut pos = offset;
+        for &(start, end) in ranges {
+            if end <= pos || start >= span_end {
+                continue;
+            }
+            let start = start.max(pos);
+            let end = end.min(span_end);
+            if start > pos {
+                out.push(Span::styled(
+                    text[pos - offset..start - offset].to_string(),
+                    span.style,
+                ));
+            }
+            out.push(Span::styled(
+                text[start - offset..end - offset].to_string(),
+                span.style.patch(style),
+            ));
+            pos = end;
+        }
+        if pos == offset {
+            out.push(span);
+        } else if pos < span_end {
+            out.push(Span::styled(text[pos - offset..].to_string(), span.style));
+        }
+        offset = span_end;
+    }
+    out
+}
+
 fn render_pending_cell(app: &App, frame: &mut Frame, area: Rect, is_active: bool) {
     let pending = match app.pending_connection {
         Some(p) => p,
@@ -193,18 +624,22 @@ fn render_pending_cell(app: &App, frame: &mut Frame, area: Rect, is_active: bool
     };
 
     let border_color = if is_active {
-        Color::Yellow
+        app.theme.alert
     } else {
-        Color::DarkGray
+        app.theme.dim
     };
 
     let title = match pending {
-        PendingScreen::PortSelect => " Select Port ",
-        PendingScreen::BaudSelect => " Select Baud ",
-        PendingScreen::DataBitsSelect => " Select Data Bits ",
-        PendingScreen::ParitySelect => " Select Parity ",
-        PendingScreen::StopBitsSelect => " Select Stop Bits ",
-        PendingScreen::DisplayModeSelect => " Select Display Mode ",
+        PendingScreen::PortSelect if !app.port_filter.is_empty() => {
+            format!(" Select Port [{}] ", app.port_filter)
+        }
+        PendingScreen::PortSelect => " Select Port ".to_string(),
+        PendingScreen::BaudSelect => " Select Baud ".to_string(),
+        PendingScreen::DataBitsSelect => " Select Data Bits ".to_string(),
+        PendingScreen::ParitySelect => " Select Parity ".to_string(),
+        PendingScreen::StopBitsSelect => " Select Stop Bits ".to_string(),
+        PendingScreen::DisplayModeSelect => " Select Display Mode ".to_string(),
+        PendingScreen::PacingSelect => " Select Pacing ".to_string(),
     };
 
     let block = Block::default()
@@ -234,5 +669,8 @@ fn render_pending_cell(app: &App, frame: &mut Frame, area: Rect, is_active: bool
         PendingScreen::DisplayModeSelect => {
             super::display_mode_select::render_content(app, frame, inner);
         }
+        PendingScreen::PacingSelect => {
+            super::pacing_select::render_content(app, frame, inner);
+        }
     }
 }